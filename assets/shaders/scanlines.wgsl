@@ -0,0 +1,18 @@
+// Darkens every other line of the view, used by the render_targets example.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct ScanlinesSettings {
+    lines: f32,
+    strength: f32,
+}
+@group(0) @binding(2) var<uniform> settings: ScanlinesSettings;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    // The lines follow the UV, so they keep their count whatever the size of the target
+    let line = 0.5 + 0.5 * cos(in.uv.y * settings.lines * 6.2831853);
+    return vec4(color.rgb * (1.0 - settings.strength * line), color.a);
+}
@@ -0,0 +1,133 @@
+//! Runs an effect on cameras rendering to the primary window, to a second window and to an image,
+//! like the preview viewport of an editor. Each camera has its own settings.
+use bevy::camera::RenderTarget;
+use bevy::core_pipeline::FullscreenShader;
+use bevy::render::render_graph::RenderLabel;
+use bevy::window::WindowRef;
+use bevy::{
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_resource::*},
+};
+use bevy_post_process_util::PostProcessPlugin;
+
+const SHADER_ASSET_PATH: &str = "shaders/scanlines.wgsl";
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct ScanlinesLabel;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins)
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate);
+    let fullscreen_shader = app.world_mut().get_resource_or_init::<FullscreenShader>();
+    let vertex_state = fullscreen_shader.to_vertex_state();
+
+    app.add_plugins(PostProcessPlugin::<ScanlinesSettings, ScanlinesLabel>::new(
+        SHADER_ASSET_PATH,
+        ScanlinesLabel,
+        Some("scanlines_pipeline"),
+        "scanlines_bind_group_layout",
+        vertex_state,
+    ));
+
+    app.run();
+}
+
+#[derive(Component, Default, Clone, Copy, ExtractComponent, ShaderType)]
+struct ScanlinesSettings {
+    // The number of lines over the height of the view
+    lines: f32,
+    strength: f32,
+}
+
+#[derive(Component)]
+struct Rotating;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::default())),
+        MeshMaterial3d(materials.add(Color::srgb(0.8, 0.7, 0.6))),
+        Rotating,
+    ));
+    commands.spawn((
+        DirectionalLight::default(),
+        Transform::from_xyz(1.0, 2.0, 3.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // The primary window
+    commands.spawn((
+        Camera3d::default(),
+        IsDefaultUiCamera,
+        Transform::from_xyz(0.0, 1.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ScanlinesSettings {
+            lines: 200.0,
+            strength: 0.3,
+        },
+    ));
+
+    // A second window, with its own size and scale factor
+    let window = commands
+        .spawn(Window {
+            title: "Second window".into(),
+            resolution: (640, 360).into(),
+            ..default()
+        })
+        .id();
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            target: RenderTarget::Window(WindowRef::Entity(window)),
+            ..default()
+        },
+        Transform::from_xyz(3.0, 2.0, 3.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ScanlinesSettings {
+            lines: 90.0,
+            strength: 0.6,
+        },
+    ));
+
+    // An image, shown in the corner of the primary window like an editor preview
+    let preview = images.add(Image::new_target_texture(
+        320,
+        240,
+        TextureFormat::bevy_default(),
+    ));
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            target: RenderTarget::Image(preview.clone().into()),
+            // Render the preview before the window showing it
+            order: -1,
+            clear_color: Color::srgb(0.1, 0.1, 0.2).into(),
+            ..default()
+        },
+        Transform::from_xyz(0.0, 4.0, 0.1).looking_at(Vec3::ZERO, Vec3::Y),
+        ScanlinesSettings {
+            lines: 60.0,
+            strength: 0.8,
+        },
+    ));
+    commands.spawn((
+        ImageNode::new(preview),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            width: Val::Px(320.0),
+            height: Val::Px(240.0),
+            ..default()
+        },
+    ));
+}
+
+fn rotate(time: Res<Time>, mut query: Query<&mut Transform, With<Rotating>>) {
+    for mut transform in &mut query {
+        transform.rotate_y(time.delta_secs() * 0.5);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{
+    global::{self, GlobalEffect},
+    mask::MaskCamera,
+    PostProcessBypass, PostProcessLayers, PostProcessState,
+};
+use bevy::{
+    platform::collections::HashSet,
+    prelude::*,
+    render::render_graph::{InternedRenderLabel, RenderLabel},
+};
+use std::marker::PhantomData;
+
+/// Written when an effect starts rendering on a camera.
+///
+/// This happens once the pipelines of the effect are ready and the camera runs it: it has the
+/// settings of the effect, shares a layer with it, is active and doesn't bypass it. The run
+/// condition of the effect is evaluated in the render world and isn't taken into account.
+#[derive(Message, Clone, Debug)]
+pub struct PostProcessActivated {
+    /// The camera the effect renders on.
+    pub camera: Entity,
+    /// The label of the effect.
+    pub label: InternedRenderLabel,
+}
+
+/// Written when an effect stops rendering on a camera, including when the camera is despawned.
+#[derive(Message, Clone, Debug)]
+pub struct PostProcessDeactivated {
+    /// The camera the effect rendered on.
+    pub camera: Entity,
+    /// The label of the effect.
+    pub label: InternedRenderLabel,
+}
+
+// The cameras an effect was active on last frame
+#[derive(Resource)]
+pub(crate) struct ActiveCameras<U, R> {
+    label: InternedRenderLabel,
+    layers: PostProcessLayers,
+    cameras: HashSet<Entity>,
+    _marker: PhantomData<(U, R)>,
+}
+
+impl<U, R: RenderLabel> ActiveCameras<U, R> {
+    pub(crate) fn new(label: R, layers: PostProcessLayers) -> Self {
+        Self {
+            label: label.intern(),
+            layers,
+            cameras: HashSet::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    #[cfg(feature = "animation")]
+    pub(crate) fn label(&self) -> InternedRenderLabel {
+        self.label
+    }
+}
+
+// The cameras an effect may run on, with what decides whether it does
+type TrackedCameras<U> = (
+    Entity,
+    &'static Camera,
+    Has<U>,
+    Option<&'static PostProcessLayers>,
+    Option<&'static PostProcessBypass>,
+);
+
+/// Compare the cameras the effect runs on with those of last frame.
+pub(crate) fn track_active_cameras<U: Component, R: Send + Sync + 'static>(
+    mut active_cameras: ResMut<ActiveCameras<U, R>>,
+    state: Res<PostProcessState>,
+    cameras: Query<TrackedCameras<U>, Without<MaskCamera>>,
+    global: Option<Res<GlobalEffect<U>>>,
+    mut activated: MessageWriter<PostProcessActivated>,
+    mut deactivated: MessageWriter<PostProcessDeactivated>,
+) {
+    let active_cameras = &mut *active_cameras;
+    let label = active_cameras.label;
+    let ready = state.ready(label);
+
+    let active: HashSet<Entity> = cameras
+        .iter()
+        .filter(|(_, camera, has_settings, layers, bypass)| {
+            ready
+                && camera.is_active
+                && global::runs_effect(*has_settings, &global)
+                && active_cameras
+                    .layers
+                    .intersects(layers.unwrap_or(&PostProcessLayers::default()))
+                && bypass.is_none_or(|bypass| !bypass.is_bypassed(label))
+        })
+        .map(|(entity, ..)| entity)
+        .collect();
+
+    for &camera in active.difference(&active_cameras.cameras) {
+        activated.write(PostProcessActivated { camera, label });
+    }
+    for &camera in active_cameras.cameras.difference(&active) {
+        deactivated.write(PostProcessDeactivated { camera, label });
+    }
+    active_cameras.cameras = active;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{self, PostProcessReady, SharedPipelineReadiness};
+
+    #[derive(Component, Resource, Clone)]
+    struct TestSettings;
+
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+    struct TestEffect;
+
+    #[test]
+    fn removing_global_settings_deactivates_cameras() {
+        let mut app = App::new();
+        app.add_message::<PostProcessActivated>()
+            .add_message::<PostProcessDeactivated>()
+            .add_message::<PostProcessReady>()
+            .init_resource::<PostProcessState>()
+            .init_resource::<SharedPipelineReadiness>()
+            .insert_resource(ActiveCameras::<TestSettings, TestEffect>::new(
+                TestEffect,
+                PostProcessLayers::default(),
+            ))
+            .insert_resource(TestSettings)
+            .add_systems(
+                Update,
+                (
+                    state::sync_post_process_state,
+                    track_active_cameras::<TestSettings, TestEffect>,
+                )
+                    .chain(),
+            );
+        global::add_global_settings::<TestSettings>(&mut app);
+        app.world()
+            .resource::<SharedPipelineReadiness>()
+            .track(TestEffect.intern(), true);
+        let camera = app.world_mut().spawn(Camera::default()).id();
+
+        app.update();
+        let active_cameras = app
+            .world()
+            .resource::<ActiveCameras<TestSettings, TestEffect>>();
+        assert!(active_cameras.cameras.contains(&camera));
+
+        app.world_mut().remove_resource::<TestSettings>();
+        app.update();
+        let active_cameras = app
+            .world()
+            .resource::<ActiveCameras<TestSettings, TestEffect>>();
+        assert!(active_cameras.cameras.is_empty());
+        let deactivated = app.world().resource::<Messages<PostProcessDeactivated>>();
+        assert_eq!(deactivated.len(), 1);
+    }
+}
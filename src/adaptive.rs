@@ -0,0 +1,149 @@
+use crate::{PostProcessQuality, PostProcessQualityTier, PostProcessRegistry};
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_graph::{InternedRenderLabel, RenderLabel},
+};
+
+/// Lowers the quality tier of the effects when the frame time goes over a target, and restores
+/// it once there is headroom again.
+///
+/// The least important effect is lowered by one tier at a time, and the most important one is
+/// restored first, see [`PostProcessAdaptiveQuality::set_priority`]. Combined with the resolution
+/// scales of [`PostProcessQuality`], this is dynamic resolution for the effects. The plugin adds
+/// the [`FrameTimeDiagnosticsPlugin`] and a [`PostProcessQuality`] resource if needed.
+pub struct PostProcessAdaptiveQualityPlugin {
+    /// The frame time to stay under, in milliseconds.
+    pub target_frame_time_ms: f64,
+}
+
+impl Default for PostProcessAdaptiveQualityPlugin {
+    fn default() -> Self {
+        Self {
+            target_frame_time_ms: 1000.0 / 60.0,
+        }
+    }
+}
+
+impl Plugin for PostProcessAdaptiveQualityPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+        }
+        if !app.world().contains_resource::<PostProcessQuality>() {
+            app.init_resource::<PostProcessQuality>();
+        }
+        app.insert_resource(PostProcessAdaptiveQuality::new(self.target_frame_time_ms))
+            .add_systems(Update, adapt_quality);
+    }
+}
+
+/// The state of the [`PostProcessAdaptiveQualityPlugin`].
+#[derive(Resource, Debug)]
+pub struct PostProcessAdaptiveQuality {
+    /// Whether the quality is adapted. Turning it off keeps the current tiers.
+    pub enabled: bool,
+    /// The frame time to stay under, in milliseconds.
+    pub target_frame_time_ms: f64,
+    /// The fraction of the target the frame time must fall under before restoring an effect, so
+    /// the quality doesn't bounce between two tiers.
+    pub headroom: f64,
+    /// The number of seconds between two changes, giving the frame time time to settle.
+    pub interval: f64,
+    /// The lowest tier effects are lowered to.
+    pub min_tier: PostProcessQualityTier,
+    priorities: HashMap<InternedRenderLabel, i32>,
+    // The override of the lowered effects before they were first lowered
+    lowered: HashMap<InternedRenderLabel, Option<PostProcessQualityTier>>,
+    last_change: f64,
+}
+
+impl PostProcessAdaptiveQuality {
+    pub(crate) fn new(target_frame_time_ms: f64) -> Self {
+        Self {
+            enabled: true,
+            target_frame_time_ms,
+            headroom: 0.8,
+            interval: 1.0,
+            min_tier: PostProcessQualityTier::Low,
+            priorities: HashMap::default(),
+            lowered: HashMap::default(),
+            last_change: 0.0,
+        }
+    }
+
+    /// Set how important the effect with this label is. Effects default to 0, and the effects
+    /// with the lowest priority are lowered first.
+    pub fn set_priority(&mut self, label: impl RenderLabel, priority: i32) -> &mut Self {
+        self.priorities.insert(label.intern(), priority);
+        self
+    }
+
+    /// Whether the effect with this label currently runs below its tier.
+    pub fn is_lowered(&self, label: impl RenderLabel) -> bool {
+        self.lowered.contains_key(&label.intern())
+    }
+
+    fn priority(&self, label: InternedRenderLabel) -> i32 {
+        self.priorities.get(&label).copied().unwrap_or(0)
+    }
+}
+
+fn adapt_quality(
+    time: Res<Time<Real>>,
+    diagnostics: Res<DiagnosticsStore>,
+    registry: Res<PostProcessRegistry>,
+    mut adaptive: ResMut<PostProcessAdaptiveQuality>,
+    mut quality: ResMut<PostProcessQuality>,
+) {
+    let now = time.elapsed_secs_f64();
+    if !adaptive.enabled || now - adaptive.last_change < adaptive.interval {
+        return;
+    }
+    let Some(frame_time) = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|diagnostic| diagnostic.smoothed())
+    else {
+        return;
+    };
+
+    // From the least to the most important effect
+    let mut labels: Vec<_> = registry.effects().map(|effect| effect.label).collect();
+    labels.sort_by_key(|&label| adaptive.priority(label));
+
+    let adaptive = &mut *adaptive;
+    if frame_time > adaptive.target_frame_time_ms {
+        let lowered = labels.iter().find_map(|&label| {
+            let tier = quality.tier_of(label).lower()?;
+            (tier >= adaptive.min_tier).then_some((label, tier))
+        });
+        if let Some((label, tier)) = lowered {
+            let original = quality.override_of(label);
+            adaptive.lowered.entry(label).or_insert(original);
+            quality.set_override(label, Some(tier));
+            adaptive.last_change = now;
+        }
+    } else if frame_time < adaptive.target_frame_time_ms * adaptive.headroom {
+        let Some(&label) = labels
+            .iter()
+            .rev()
+            .find(|label| adaptive.lowered.contains_key(*label))
+        else {
+            return;
+        };
+        let original = adaptive.lowered[&label];
+        let restored = quality
+            .tier_of(label)
+            .higher()
+            .filter(|&tier| tier < original.unwrap_or(quality.tier));
+        match restored {
+            Some(tier) => quality.set_override(label, Some(tier)),
+            None => {
+                quality.set_override(label, original);
+                adaptive.lowered.remove(&label);
+            }
+        }
+        adaptive.last_change = now;
+    }
+}
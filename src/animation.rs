@@ -0,0 +1,105 @@
+use crate::{activity::ActiveCameras, PostProcessBypass};
+use bevy::{
+    animation::animation_curves::{AnimatableProperty, AnimatedField},
+    prelude::*,
+    reflect::TypePath,
+    render::render_graph::RenderLabel,
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// Turns the effect whose settings are `U` on or off, so it can be animated with the rest of a
+/// scene.
+///
+/// The settings fields themselves are animated like any other component, as long as the settings
+/// derive [`Reflect`]. Target them with `animated_field!` in an [`AnimationClip`] played by the
+/// [`AnimationPlayer`] of the camera, which also needs an
+/// [`AnimationTarget`](bevy::animation::AnimationTarget):
+///
+/// ```ignore
+/// let mut clip = AnimationClip::default();
+/// clip.add_curve_to_target(
+///     camera_target_id,
+///     AnimatableCurve::new(
+///         animated_field!(VignetteSettings::intensity),
+///         AnimatableKeyframeCurve::new([(0.0, 0.0), (2.0, 0.6)])?,
+///     ),
+/// );
+/// clip.add_curve_to_target(
+///     camera_target_id,
+///     AnimatableCurve::new(
+///         PostProcessEnabled::<GrainSettings>::property(),
+///         AnimatableKeyframeCurve::new([(0.0, false), (3.5, true)])?,
+///     ),
+/// );
+/// ```
+///
+/// The component writes [`PostProcessBypass`] when it changes, so it can also be set by hand.
+#[derive(Component, Reflect)]
+pub struct PostProcessEnabled<U> {
+    /// Whether the effect renders.
+    pub enabled: bool,
+    #[reflect(ignore)]
+    _marker: PhantomData<U>,
+}
+
+impl<U> PostProcessEnabled<U> {
+    /// Turn the effect on or off.
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<U> Default for PostProcessEnabled<U> {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl<U: Component + TypePath> PostProcessEnabled<U> {
+    /// The property animating [`PostProcessEnabled::enabled`], for an [`AnimatableCurve`].
+    pub fn property() -> impl AnimatableProperty<Property = bool> {
+        AnimatedField::new_unchecked("enabled", |enabled: &mut Self| &mut enabled.enabled)
+    }
+}
+
+// The cameras with their enabled state and bypass
+type EnabledCameras<U> = (
+    Entity,
+    &'static PostProcessEnabled<U>,
+    Option<&'static mut PostProcessBypass>,
+);
+
+/// Bypass the effect on the cameras whose [`PostProcessEnabled`] changed.
+pub(crate) fn sync_enabled<
+    U: Component,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    mut commands: Commands,
+    active_cameras: Res<ActiveCameras<U, R>>,
+    mut cameras: Query<EnabledCameras<U>, Changed<PostProcessEnabled<U>>>,
+) {
+    let label = active_cameras.label();
+    for (camera, enabled, bypass) in &mut cameras {
+        match bypass {
+            // Only touch the bypass when the state differs, to keep its change detection quiet
+            Some(mut bypass) if bypass.is_bypassed(label) == enabled.enabled => {
+                if enabled.enabled {
+                    bypass.enable(label);
+                } else {
+                    bypass.bypass(label);
+                }
+            }
+            None if !enabled.enabled => {
+                let mut bypass = PostProcessBypass::default();
+                bypass.bypass(label);
+                commands.entity(camera).insert(bypass);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{
+            binding_types::uniform_buffer, BindGroupLayoutEntryBuilder, BindingResource, ShaderType,
+            UniformBuffer,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        Extract, ExtractSchedule, Render, RenderApp, RenderSystems,
+    },
+};
+use std::f32::consts::TAU;
+
+/// The number of frequency bands of the audio feed.
+pub const AUDIO_BANDS: usize = 8;
+
+// The center frequencies of the bands, one per octave
+const BAND_FREQUENCIES: [f32; AUDIO_BANDS] =
+    [63.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0];
+
+/// Feeds the [`PostProcessAudio`] resource to the effects using
+/// [`PostProcessPlugin::with_audio`](crate::PostProcessPlugin::with_audio).
+pub struct PostProcessAudioPlugin;
+
+impl Plugin for PostProcessAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PostProcessAudio>();
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<AudioUniformBuffer>()
+            .add_systems(ExtractSchedule, extract_audio)
+            .add_systems(
+                Render,
+                prepare_audio_uniform.in_set(RenderSystems::PrepareResources),
+            );
+    }
+}
+
+/// The loudness of the audio being played, bound to effect shaders as a uniform:
+///
+/// ```wgsl
+/// struct PostProcessAudio {
+///     amplitude: f32,
+///     // The 8 bands, from 63 Hz to 8 kHz
+///     bands: array<vec4<f32>, 2>,
+/// }
+/// ```
+///
+/// Bevy's audio output can't be tapped, so the samples are pushed with
+/// [`PostProcessAudio::push_samples`] from wherever they are available, like a kira effect or a
+/// custom audio source. The values can also be set directly, from a precomputed analysis.
+#[derive(Resource, Clone, Debug)]
+pub struct PostProcessAudio {
+    /// The root mean square of the last samples.
+    pub amplitude: f32,
+    /// The magnitude of each octave band, from 63 Hz to 8 kHz.
+    pub bands: [f32; AUDIO_BANDS],
+    /// How much of the previous values is kept when new samples are pushed, from 0 to 1.
+    /// Higher values make the feed smoother but slower to react.
+    pub smoothing: f32,
+}
+
+impl Default for PostProcessAudio {
+    fn default() -> Self {
+        Self {
+            amplitude: 0.0,
+            bands: [0.0; AUDIO_BANDS],
+            smoothing: 0.5,
+        }
+    }
+}
+
+impl PostProcessAudio {
+    /// Analyze mono samples played at `sample_rate`, usually those of the last frame.
+    pub fn push_samples(&mut self, samples: &[f32], sample_rate: u32) {
+        if samples.is_empty() || sample_rate == 0 {
+            return;
+        }
+
+        let len = samples.len() as f32;
+        let amplitude = (samples.iter().map(|sample| sample * sample).sum::<f32>() / len).sqrt();
+        let smoothing = self.smoothing;
+        let smooth = |previous: f32, value: f32| previous * smoothing + value * (1.0 - smoothing);
+        self.amplitude = smooth(self.amplitude, amplitude);
+
+        // The Goertzel algorithm computes a single frequency bin, which is cheaper than a full
+        // FFT for a handful of bands
+        for (band, frequency) in self.bands.iter_mut().zip(BAND_FREQUENCIES) {
+            let coefficient = 2.0 * (TAU * frequency / sample_rate as f32).cos();
+            let (mut previous, mut before_previous) = (0.0, 0.0);
+            for sample in samples {
+                let current = sample + coefficient * previous - before_previous;
+                before_previous = previous;
+                previous = current;
+            }
+            let power = previous * previous + before_previous * before_previous
+                - coefficient * previous * before_previous;
+            let magnitude = 2.0 * power.max(0.0).sqrt() / len;
+            *band = smooth(*band, magnitude);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, ShaderType)]
+struct AudioUniform {
+    amplitude: f32,
+    bands: [Vec4; 2],
+}
+
+#[derive(Resource, Default)]
+struct AudioUniformBuffer(UniformBuffer<AudioUniform>);
+
+fn extract_audio(mut buffer: ResMut<AudioUniformBuffer>, audio: Extract<Res<PostProcessAudio>>) {
+    let [b0, b1, b2, b3, b4, b5, b6, b7] = audio.bands;
+    buffer.0.set(AudioUniform {
+        amplitude: audio.amplitude,
+        bands: [Vec4::new(b0, b1, b2, b3), Vec4::new(b4, b5, b6, b7)],
+    });
+}
+
+fn prepare_audio_uniform(
+    mut buffer: ResMut<AudioUniformBuffer>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    buffer.0.write_buffer(&render_device, &render_queue);
+}
+
+/// The layout entry of the audio uniform.
+pub(crate) fn layout_entry() -> BindGroupLayoutEntryBuilder {
+    uniform_buffer::<AudioUniform>(false)
+}
+
+/// The binding of the audio uniform, once it has been written.
+pub(crate) fn binding(world: &World) -> Option<BindingResource<'_>> {
+    world.get_resource::<AudioUniformBuffer>()?.0.binding()
+}
@@ -0,0 +1,56 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{BindGroup, BindGroupLayout},
+};
+use std::marker::PhantomData;
+
+/// The layout of the additional bind group of an effect, created from the function given to
+/// [`PostProcessPlugin::with_bind_group`](crate::PostProcessPlugin::with_bind_group).
+///
+/// This is a render world resource, used to create the [`PostProcessBindGroup`] of the views.
+#[derive(Resource)]
+pub struct PostProcessBindGroupLayout<U> {
+    /// The layout of group 1 of the effect shader.
+    pub layout: BindGroupLayout,
+    _marker: PhantomData<U>,
+}
+
+impl<U> PostProcessBindGroupLayout<U> {
+    pub(crate) fn new(layout: BindGroupLayout) -> Self {
+        Self {
+            layout,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// The additional bind group of an effect, set at index 1 when the effect runs on the view it is
+/// inserted on.
+///
+/// Inserted on the views in the render world by the system given to
+/// [`PostProcessPlugin::with_bind_group`](crate::PostProcessPlugin::with_bind_group).
+/// The effect skips the views without one.
+#[derive(Component)]
+pub struct PostProcessBindGroup<U> {
+    /// The bind group, created with the [`PostProcessBindGroupLayout`] of the effect.
+    pub bind_group: BindGroup,
+    /// The dynamic offsets of the bind group, ordered by binding index.
+    pub dynamic_offsets: Vec<u32>,
+    _marker: PhantomData<U>,
+}
+
+impl<U> PostProcessBindGroup<U> {
+    /// Wrap a bind group without dynamic offsets.
+    pub fn new(bind_group: BindGroup) -> Self {
+        Self::with_dynamic_offsets(bind_group, Vec::new())
+    }
+
+    /// Wrap a bind group with dynamic offsets.
+    pub fn with_dynamic_offsets(bind_group: BindGroup, dynamic_offsets: Vec<u32>) -> Self {
+        Self {
+            bind_group,
+            dynamic_offsets,
+            _marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Binding indices of the resources bound in group 0 of every effect shader.
+//!
+//! The indices are fixed, so a shader only has to declare the bindings of the features it uses.
+//! Bindings of features that are not enabled on an effect are simply left out of the layout.
+//!
+//! Every effect shader can also import the helpers of `bevy_post_process::utils`, for UV and pixel
+//! coordinates, depth linearization, position reconstruction from depth, luminance and HSV, and
+//! the color conversions of `bevy_post_process::color_space`:
+//!
+//! ```wgsl
+//! #import bevy_post_process::utils::{linearize_depth, luminance}
+//! ```
+
+/// The screen texture, `texture_2d<f32>`. It isn't filterable when the
+/// [`SCREEN_TEXTURE_NON_FILTERABLE`](crate::SCREEN_TEXTURE_NON_FILTERABLE) shader def is set.
+pub const SCREEN_TEXTURE: u32 = 0;
+/// The sampler used to sample the screen texture.
+pub const SAMPLER: u32 = 1;
+/// The settings uniform of the effect. Not bound for effects created with
+/// [`PostProcessPlugin::new_marker`](crate::PostProcessPlugin::new_marker).
+pub const SETTINGS: u32 = 2;
+/// The view uniform, `bevy_render::view::View`. Not bound for effects using
+/// [`PostProcessPlugin::without_view`](crate::PostProcessPlugin::without_view).
+pub const VIEW: u32 = 3;
+/// The depth texture of the view, bound with [`PostProcessPlugin::with_depth`](crate::PostProcessPlugin::with_depth).
+/// It is a `texture_depth_2d`, or a `texture_depth_multisampled_2d` when the `MULTISAMPLED` shader def is set.
+pub const DEPTH: u32 = 4;
+/// The mask texture set with [`PostProcessPlugin::with_mask`](crate::PostProcessPlugin::with_mask),
+/// `texture_2d<f32>`. By convention its red channel is the weight of the effect, from 0 to 1.
+pub const MASK: u32 = 5;
+/// The entity mask set with [`PostProcessPlugin::with_entity_mask`](crate::PostProcessPlugin::with_entity_mask),
+/// `texture_2d<f32>`. Meshes with a [`PostProcessMask`](crate::PostProcessMask) are rendered into it with their color.
+pub const ENTITY_MASK: u32 = 6;
+/// A linear sampler clamping to the edge, bound with
+/// [`PostProcessPlugin::with_standard_samplers`](crate::PostProcessPlugin::with_standard_samplers).
+pub const LINEAR_CLAMP_SAMPLER: u32 = 7;
+/// A nearest sampler clamping to the edge, to point-sample the screen for pixelation effects.
+pub const NEAREST_CLAMP_SAMPLER: u32 = 8;
+/// A linear sampler repeating the texture, to tile noise textures.
+pub const LINEAR_REPEAT_SAMPLER: u32 = 9;
+/// The instances gathered with [`PostProcessPlugin::with_instances`](crate::PostProcessPlugin::with_instances),
+/// a read-only `array<T>` storage buffer. It always holds at least one element, use the count.
+pub const INSTANCES: u32 = 10;
+/// The number of instances, `u32`.
+pub const INSTANCE_COUNT: u32 = 11;
+/// The audio feed bound with `PostProcessPlugin::with_audio`, behind the `audio` feature.
+pub const AUDIO: u32 = 12;
+/// The size and the exposure of the view bound with
+/// [`PostProcessPlugin::with_view_info`](crate::PostProcessPlugin::with_view_info).
+pub const VIEW_INFO: u32 = 13;
+/// The tonemapping LUT of the view bound with
+/// [`PostProcessPlugin::with_tonemapping_lut`](crate::PostProcessPlugin::with_tonemapping_lut),
+/// `texture_3d<f32>`.
+pub const TONEMAPPING_LUT: u32 = 14;
+/// The sampler of the tonemapping LUT.
+pub const TONEMAPPING_LUT_SAMPLER: u32 = 15;
+/// The first texture declared with [`PostProcessPlugin::with_input`](crate::PostProcessPlugin::with_input).
+/// The following inputs are bound at the following indices.
+pub const FIRST_INPUT: u32 = 16;
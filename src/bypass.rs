@@ -0,0 +1,33 @@
+use bevy::{
+    platform::collections::HashSet,
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        render_graph::{InternedRenderLabel, RenderLabel},
+    },
+};
+
+/// Turns off effects on the camera it is added to, without removing their settings component.
+///
+/// A bypassed effect doesn't render at all, so the view target is passed on unchanged.
+#[derive(Component, ExtractComponent, Clone, Debug, Default)]
+pub struct PostProcessBypass {
+    labels: HashSet<InternedRenderLabel>,
+}
+
+impl PostProcessBypass {
+    /// Turn off the effect with this label.
+    pub fn bypass(&mut self, label: impl RenderLabel) {
+        self.labels.insert(label.intern());
+    }
+
+    /// Turn the effect with this label back on.
+    pub fn enable(&mut self, label: impl RenderLabel) {
+        self.labels.remove(&label.intern());
+    }
+
+    /// Whether the effect with this label is turned off.
+    pub fn is_bypassed(&self, label: impl RenderLabel) -> bool {
+        self.labels.contains(&label.intern())
+    }
+}
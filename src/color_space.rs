@@ -0,0 +1,51 @@
+use bevy::shader::ShaderDefVal;
+
+/// The color space an effect works in.
+///
+/// The view textures always hold linear colors, which go above 1 on HDR cameras. Effects written
+/// for another space, like color grading tuned on sRGB values, convert the colors they sample
+/// with `to_working_space` and convert their result back with `from_working_space`:
+///
+/// ```wgsl
+/// #import bevy_post_process::color_space::{to_working_space, from_working_space}
+///
+/// @fragment
+/// fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+///     let color = textureSample(screen_texture, texture_sampler, in.uv);
+///     var graded = to_working_space(color.rgb);
+///     graded = (graded - 0.5) * settings.contrast + 0.5;
+///     return vec4(from_working_space(graded), color.a);
+/// }
+/// ```
+///
+/// The effect is compiled with one of the `WORKING_SPACE_LINEAR`, `WORKING_SPACE_SRGB`,
+/// `WORKING_SPACE_LOG` or `WORKING_SPACE_OKLAB` shader defs. The conversions between every space
+/// and linear are also available by name, like `linear_to_oklab`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PostProcessColorSpace {
+    /// The colors of the view, without conversion.
+    #[default]
+    Linear,
+    /// Gamma encoded with the sRGB transfer function. Values above 1 are encoded along the same
+    /// curve, so HDR colors survive the round trip.
+    Srgb,
+    /// A log2 encoding, mapping `2^-10` to 0 and `2^6.5` to 1. Suits grading HDR colors, like the
+    /// log spaces of film.
+    Log,
+    /// Oklab, a perceptual space where `x` is the lightness and `y` and `z` the chroma. Suits hue
+    /// and saturation changes.
+    Oklab,
+}
+
+impl PostProcessColorSpace {
+    /// The shader defs selecting the conversions of `bevy_post_process::color_space`.
+    pub fn shader_defs(self) -> Vec<ShaderDefVal> {
+        let name = match self {
+            Self::Linear => "WORKING_SPACE_LINEAR",
+            Self::Srgb => "WORKING_SPACE_SRGB",
+            Self::Log => "WORKING_SPACE_LOG",
+            Self::Oklab => "WORKING_SPACE_OKLAB",
+        };
+        vec![name.into()]
+    }
+}
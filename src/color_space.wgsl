@@ -0,0 +1,87 @@
+#define_import_path bevy_post_process::color_space
+
+// The view textures hold linear colors, HDR ones can go above 1. The working space of the effect
+// is selected by one of the WORKING_SPACE_* shader defs, see `PostProcessColorSpace`.
+
+// The sRGB transfer function, extended above 1 so HDR colors survive a round trip
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let c = max(color, vec3(0.0));
+    let low = c * 12.92;
+    let high = 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055;
+    return select(high, low, c <= vec3(0.0031308));
+}
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let c = max(color, vec3(0.0));
+    let low = c / 12.92;
+    let high = pow((c + 0.055) / 1.055, vec3(2.4));
+    return select(high, low, c <= vec3(0.04045));
+}
+
+// A log2 encoding mapping 2^-10 to 0 and 2^6.5 to 1, 18% gray landing around 0.45
+const LOG_MIN_EXPOSURE: f32 = -10.0;
+const LOG_EXPOSURE_RANGE: f32 = 16.5;
+
+fn linear_to_log(color: vec3<f32>) -> vec3<f32> {
+    let exposure = log2(max(color, vec3(exp2(LOG_MIN_EXPOSURE))));
+    return (exposure - LOG_MIN_EXPOSURE) / LOG_EXPOSURE_RANGE;
+}
+
+fn log_to_linear(color: vec3<f32>) -> vec3<f32> {
+    return exp2(color * LOG_EXPOSURE_RANGE + LOG_MIN_EXPOSURE);
+}
+
+// Oklab, from https://bottosson.github.io/posts/oklab/. L, a and b are in x, y and z
+fn linear_to_oklab(color: vec3<f32>) -> vec3<f32> {
+    let lms = mat3x3<f32>(
+        0.4122214708, 0.2119034982, 0.0883024619,
+        0.5363325363, 0.6806995451, 0.2817188376,
+        0.0514459929, 0.1073969566, 0.6299787005,
+    ) * color;
+    let lms_ = sign(lms) * pow(abs(lms), vec3(1.0 / 3.0));
+    return mat3x3<f32>(
+        0.2104542553, 1.9779984951, 0.0259040371,
+        0.7936177850, -2.4285922050, 0.7827717662,
+        -0.0040720468, 0.4505937099, -0.8086757660,
+    ) * lms_;
+}
+
+fn oklab_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let lms_ = mat3x3<f32>(
+        1.0, 1.0, 1.0,
+        0.3963377774, -0.1055613458, -0.0894841775,
+        0.2158037573, -0.0638541728, -1.2914855480,
+    ) * color;
+    let lms = lms_ * lms_ * lms_;
+    return mat3x3<f32>(
+        4.0767416621, -1.2684380046, -0.0041960863,
+        -3.3077115913, 2.6097574011, -0.7034186147,
+        0.2309699292, -0.3413193965, 1.7076147010,
+    ) * lms;
+}
+
+// Convert a color sampled from the view into the working space of the effect
+fn to_working_space(color: vec3<f32>) -> vec3<f32> {
+#ifdef WORKING_SPACE_SRGB
+    return linear_to_srgb(color);
+#else ifdef WORKING_SPACE_LOG
+    return linear_to_log(color);
+#else ifdef WORKING_SPACE_OKLAB
+    return linear_to_oklab(color);
+#else
+    return color;
+#endif
+}
+
+// Convert a color of the working space back before writing it to the view
+fn from_working_space(color: vec3<f32>) -> vec3<f32> {
+#ifdef WORKING_SPACE_SRGB
+    return srgb_to_linear(color);
+#else ifdef WORKING_SPACE_LOG
+    return log_to_linear(color);
+#else ifdef WORKING_SPACE_OKLAB
+    return oklab_to_linear(color);
+#else
+    return color;
+#endif
+}
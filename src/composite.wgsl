@@ -0,0 +1,70 @@
+// Upsamples the reduced resolution output of an effect back onto the view target.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+
+// Bilinear sampling, done manually when the adapter can't filter the texture
+fn sample_source(uv: vec2<f32>) -> vec4<f32> {
+#ifdef SCREEN_TEXTURE_NON_FILTERABLE
+    let texture_size = vec2<i32>(textureDimensions(source_texture));
+    let position = uv * vec2<f32>(texture_size) - 0.5;
+    let base = vec2<i32>(floor(position));
+    let f = fract(position);
+    let max_texel = texture_size - 1;
+    let t00 = textureLoad(source_texture, clamp(base, vec2(0), max_texel), 0);
+    let t10 = textureLoad(source_texture, clamp(base + vec2(1, 0), vec2(0), max_texel), 0);
+    let t01 = textureLoad(source_texture, clamp(base + vec2(0, 1), vec2(0), max_texel), 0);
+    let t11 = textureLoad(source_texture, clamp(base + vec2(1, 1), vec2(0), max_texel), 0);
+    return mix(mix(t00, t10, f.x), mix(t01, t11, f.x), f.y);
+#else
+    return textureSample(source_texture, source_sampler, uv);
+#endif
+}
+
+#ifdef BICUBIC
+// Catmull-Rom filtering using 9 bilinear samples instead of 16 point samples.
+// See https://gist.github.com/TheRealMJP/c83b8c0f46b63f3a88a5986f4fa982b1
+fn sample_bicubic(uv: vec2<f32>) -> vec4<f32> {
+    let texture_size = vec2<f32>(textureDimensions(source_texture));
+    let sample_position = uv * texture_size;
+    let texel_position = floor(sample_position - 0.5) + 0.5;
+    let f = sample_position - texel_position;
+
+    let w0 = f * (-0.5 + f * (1.0 - 0.5 * f));
+    let w1 = 1.0 + f * f * (-2.5 + 1.5 * f);
+    let w2 = f * (0.5 + f * (2.0 - 1.5 * f));
+    let w3 = f * f * (-0.5 + 0.5 * f);
+
+    let w12 = w1 + w2;
+    let offset12 = w2 / w12;
+
+    let texel_0 = (texel_position - 1.0) / texture_size;
+    let texel_3 = (texel_position + 2.0) / texture_size;
+    let texel_12 = (texel_position + offset12) / texture_size;
+
+    var result = vec4(0.0);
+    result += sample_source(vec2(texel_0.x, texel_0.y)) * w0.x * w0.y;
+    result += sample_source(vec2(texel_12.x, texel_0.y)) * w12.x * w0.y;
+    result += sample_source(vec2(texel_3.x, texel_0.y)) * w3.x * w0.y;
+
+    result += sample_source(vec2(texel_0.x, texel_12.y)) * w0.x * w12.y;
+    result += sample_source(vec2(texel_12.x, texel_12.y)) * w12.x * w12.y;
+    result += sample_source(vec2(texel_3.x, texel_12.y)) * w3.x * w12.y;
+
+    result += sample_source(vec2(texel_0.x, texel_3.y)) * w0.x * w3.y;
+    result += sample_source(vec2(texel_12.x, texel_3.y)) * w12.x * w3.y;
+    result += sample_source(vec2(texel_3.x, texel_3.y)) * w3.x * w3.y;
+
+    return max(result, vec4(0.0));
+}
+#endif
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+#ifdef BICUBIC
+    return sample_bicubic(in.uv);
+#else
+    return sample_source(in.uv);
+#endif
+}
@@ -0,0 +1,88 @@
+use crate::global::{self, EffectCameraFilter, GlobalEffect};
+use bevy::{
+    ecs::system::{BoxedReadOnlySystem, ReadOnlySystem},
+    platform::collections::HashSet,
+    prelude::*,
+    render::{sync_world::RenderEntity, Extract, MainWorld},
+};
+use std::marker::PhantomData;
+
+/// The run condition of an effect, evaluated for every camera running it during extraction.
+#[derive(Resource)]
+pub(crate) struct RunCondition<U, R> {
+    system: BoxedReadOnlySystem<In<Entity>, bool>,
+    // The render entities of the views the condition failed on this frame
+    skipped: HashSet<Entity>,
+    _marker: PhantomData<(U, R)>,
+}
+
+impl<U, R> RunCondition<U, R> {
+    /// Wrap a condition, initializing it on the main world.
+    pub(crate) fn new(
+        mut system: BoxedReadOnlySystem<In<Entity>, bool>,
+        main_world: &mut World,
+    ) -> Self {
+        system.initialize(main_world);
+        Self {
+            system,
+            skipped: HashSet::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Whether the effect runs on this view.
+    pub(crate) fn passes(&self, view: Entity) -> bool {
+        !self.skipped.contains(&view)
+    }
+
+    // Evaluate the condition for these cameras and their render entities
+    fn evaluate(&mut self, cameras: impl Iterator<Item = (Entity, Entity)>, main_world: &World) {
+        self.skipped.clear();
+        for (entity, render_entity) in cameras {
+            // A condition whose parameters aren't available doesn't pass
+            let passes = self
+                .system
+                .run_readonly(entity, main_world)
+                .unwrap_or(false);
+            if !passes {
+                self.skipped.insert(render_entity);
+            }
+        }
+    }
+}
+
+/// Box a condition, making sure it can run on a world it can't modify.
+pub(crate) fn boxed_condition<M, C>(condition: C) -> BoxedReadOnlySystem<In<Entity>, bool>
+where
+    C: IntoSystem<In<Entity>, bool, M>,
+    C::System: ReadOnlySystem,
+{
+    Box::new(IntoSystem::into_system(condition))
+}
+
+// The cameras that may run the effect, and whether they have its settings
+type EffectCameras<U> = (Entity, RenderEntity, Has<U>);
+
+/// Evaluate the run condition of an effect for every camera running it.
+pub(crate) fn extract_run_conditions<U: Component, R: Send + Sync + 'static>(
+    main_world: Res<MainWorld>,
+    mut run_condition: ResMut<RunCondition<U, R>>,
+    cameras: Extract<Query<EffectCameras<U>, EffectCameraFilter>>,
+    global: Extract<Option<Res<GlobalEffect<U>>>>,
+) {
+    let cameras = cameras
+        .iter()
+        .filter(|(_, _, has_settings)| global::runs_effect(*has_settings, &global))
+        .map(|(entity, render_entity, _)| (entity, render_entity));
+    run_condition.evaluate(cameras, &main_world);
+}
+
+/// Evaluate the run condition of a fused stack for every camera, whichever effects of the stack
+/// they run.
+pub(crate) fn extract_stack_run_conditions<R: Send + Sync + 'static>(
+    main_world: Res<MainWorld>,
+    mut run_condition: ResMut<RunCondition<(), R>>,
+    cameras: Extract<Query<(Entity, RenderEntity), EffectCameraFilter>>,
+) {
+    run_condition.evaluate(cameras.iter(), &main_world);
+}
@@ -0,0 +1,243 @@
+use crate::{
+    global::{self, GlobalEffect},
+    mask::MaskCamera,
+    PostProcessDestination, PostProcessPluginSettings, PostProcessStencilTest,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::FullscreenShader,
+    prelude::*,
+    render::{
+        render_asset::RenderAssets,
+        render_graph::RenderLabel,
+        render_resource::{
+            binding_types::{texture_depth_2d, texture_depth_2d_multisampled, uniform_buffer},
+            *,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        texture::GpuImage,
+        view::{Msaa, ViewTarget, ViewUniform},
+        RenderApp,
+    },
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+// Registers everything needed by effects restricted to a depth range. Added once by the first one.
+pub(crate) struct PostProcessDepthRangePlugin;
+
+impl Plugin for PostProcessDepthRangePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "depth_range.wgsl");
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<DepthRangePipeline>()
+            .init_resource::<SpecializedRenderPipelines<DepthRangePipeline>>();
+    }
+}
+
+/// By default the depth textures of the cameras can't be bound, make sure they can for the cameras
+/// running an effect reading the depth.
+pub(crate) fn configure_depth_texture_usages<U: Component>(
+    mut cameras: Query<(&mut Camera3d, Has<U>), Without<MaskCamera>>,
+    global: Option<Res<GlobalEffect<U>>>,
+) {
+    for (mut camera_3d, has_settings) in &mut cameras {
+        if !global::runs_effect(has_settings, &global) {
+            continue;
+        }
+        let usages = TextureUsages::from(camera_3d.depth_texture_usages);
+        if !usages.contains(TextureUsages::TEXTURE_BINDING) {
+            camera_3d.depth_texture_usages = (usages | TextureUsages::TEXTURE_BINDING).into();
+        }
+    }
+}
+
+/// The layout entry of the depth texture, which is multisampled when the view uses MSAA.
+pub(crate) fn depth_texture_entry(multisampled: bool) -> BindGroupLayoutEntryBuilder {
+    if multisampled {
+        texture_depth_2d_multisampled()
+    } else {
+        texture_depth_2d()
+    }
+}
+
+#[derive(Clone, Copy, Debug, ShaderType)]
+pub(crate) struct DepthRange {
+    near: f32,
+    far: f32,
+}
+
+/// The depth range of an effect, uploaded once.
+#[derive(Resource)]
+pub(crate) struct DepthRangeUniform<U, R> {
+    pub(crate) buffer: UniformBuffer<DepthRange>,
+    _marker: PhantomData<(U, R)>,
+}
+
+impl<U: Clone + Send + Sync + 'static, R: Hash + Eq + Clone + RenderLabel> FromWorld
+    for DepthRangeUniform<U, R>
+{
+    fn from_world(world: &mut World) -> Self {
+        let range = world
+            .resource::<PostProcessPluginSettings<U, R>>()
+            .depth_range
+            .clone()
+            .unwrap_or(0.0..f32::INFINITY);
+
+        let mut buffer = UniformBuffer::from(DepthRange {
+            near: range.start,
+            far: range.end,
+        });
+        buffer.set_label(Some("post_process_depth_range_uniform"));
+        buffer.write_buffer(
+            world.resource::<RenderDevice>(),
+            world.resource::<RenderQueue>(),
+        );
+
+        Self {
+            buffer,
+            _marker: PhantomData,
+        }
+    }
+}
+
+// Clears the stencil of the pixels outside of the depth range of an effect
+#[derive(Resource)]
+pub(crate) struct DepthRangePipeline {
+    layout: BindGroupLayout,
+    multisampled_layout: BindGroupLayout,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+}
+
+impl DepthRangePipeline {
+    pub(crate) fn layout(&self, multisampled: bool) -> &BindGroupLayout {
+        if multisampled {
+            &self.multisampled_layout
+        } else {
+            &self.layout
+        }
+    }
+}
+
+impl FromWorld for DepthRangePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let create_layout = |multisampled| {
+            render_device.create_bind_group_layout(
+                "post_process_depth_range_bind_group_layout",
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::FRAGMENT,
+                    (
+                        uniform_buffer::<ViewUniform>(true),
+                        depth_texture_entry(multisampled),
+                        uniform_buffer::<DepthRange>(false),
+                    ),
+                ),
+            )
+        };
+
+        Self {
+            layout: create_layout(false),
+            multisampled_layout: create_layout(true),
+            shader: load_embedded_asset!(world, "depth_range.wgsl"),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct DepthRangePipelineKey {
+    /// The format of the color attachment of the pass, which must match even if it isn't written
+    texture_format: TextureFormat,
+    multisampled: bool,
+}
+
+impl SpecializedRenderPipeline for DepthRangePipeline {
+    type Key = DepthRangePipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if key.multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+        }
+
+        RenderPipelineDescriptor {
+            label: Some("post_process_depth_range_pipeline".into()),
+            layout: vec![self.layout(key.multisampled).clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some("fragment".into()),
+                targets: vec![Some(ColorTargetState {
+                    format: key.texture_format,
+                    blend: None,
+                    write_mask: ColorWrites::empty(),
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: Some(PostProcessStencilTest::stencil_write_state()),
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+/// The depth range pipeline specialized for a view.
+#[derive(Component)]
+pub(crate) struct ViewPostProcessDepthRange<U: Send + Sync + 'static, R: Send + Sync + 'static> {
+    pub(crate) pipeline_id: CachedRenderPipelineId,
+    _marker: PhantomData<(U, R)>,
+}
+
+/// Specialize the depth range pipeline of an effect for every view it runs on.
+pub(crate) fn prepare_depth_range_pipelines<
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    mut commands: Commands,
+    plugin_settings: Res<PostProcessPluginSettings<U, R>>,
+    views: Query<(Entity, &ViewTarget, &Msaa), With<U>>,
+    pipeline_cache: Res<PipelineCache>,
+    depth_range_pipeline: Res<DepthRangePipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<DepthRangePipeline>>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+) {
+    for (entity, view_target, msaa) in &views {
+        let texture_format = match &plugin_settings.destination {
+            PostProcessDestination::ViewTarget => view_target.main_texture_format(),
+            PostProcessDestination::Image(image) => {
+                let Some(gpu_image) = gpu_images.get(image) else {
+                    continue;
+                };
+                gpu_image.texture_format
+            }
+        };
+
+        let pipeline_id = pipelines.specialize(
+            &pipeline_cache,
+            &depth_range_pipeline,
+            DepthRangePipelineKey {
+                texture_format,
+                multisampled: msaa.samples() > 1,
+            },
+        );
+
+        commands
+            .entity(entity)
+            .insert(ViewPostProcessDepthRange::<U, R> {
+                pipeline_id,
+                _marker: PhantomData,
+            });
+    }
+}
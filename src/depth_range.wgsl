@@ -0,0 +1,36 @@
+// Clears the stencil of the pixels outside of the depth range of an effect.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::{View, depth_ndc_to_view_z}
+
+struct DepthRange {
+    near: f32,
+    far: f32,
+}
+
+@group(0) @binding(0) var<uniform> view: View;
+#ifdef MULTISAMPLED
+@group(0) @binding(1) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(1) var depth_texture: texture_depth_2d;
+#endif
+@group(0) @binding(2) var<uniform> depth_range: DepthRange;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // The pass might run at a reduced resolution, so the depth is looked up from the uv
+    let coords = vec2<i32>(in.uv * vec2<f32>(textureDimensions(depth_texture)));
+    let depth = textureLoad(depth_texture, coords, 0);
+
+    // Distance along the view direction, the background is infinitely far away
+    var distance = 1e38;
+    if (depth > 0.0) {
+        distance = -depth_ndc_to_view_z(depth, view.clip_from_view, view.view_from_clip);
+    }
+
+    // Only the fragments that aren't discarded clear the stencil.
+    // The color output is masked out by the pipeline.
+    if (distance >= depth_range.near && distance <= depth_range.far) {
+        discard;
+    }
+    return vec4(0.0);
+}
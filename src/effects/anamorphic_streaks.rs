@@ -0,0 +1,430 @@
+use crate::{
+    effects, filtering, resolution::scaled_size, PostProcessBindGroup, PostProcessBindGroupLayout,
+    PostProcessPlacement, PostProcessPlugin, PostProcessTexturePool,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::{core_3d::graph::Core3d, FullscreenShader},
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{ComponentUniforms, DynamicUniformIndex, ExtractComponent},
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::CachedTexture,
+        view::ViewTarget,
+        Render, RenderApp, RenderSystems,
+    },
+    shader::ShaderDefVal,
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/anamorphic_streaks.wgsl";
+
+/// The most blur passes the streaks go through.
+const MAX_ITERATIONS: u32 = 6;
+
+/// The format of the streaks. It has no alpha, the effect keeps the one of the view.
+const STREAKS_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rg11b10Ufloat;
+
+/// Horizontal streaks across the highlights, like the flares of anamorphic lenses, run on the
+/// cameras with [`AnamorphicStreaksSettings`].
+///
+/// The [`AnamorphicStreaksBlur`] node keeps the parts of the view brighter than the threshold at
+/// half resolution, then blurs them horizontally a few times, every pass reaching four times as
+/// far as the previous one. The [`AnamorphicStreaks`] effect adds the tinted streaks to the view.
+/// Runs on the HDR colors by default, so only the real highlights streak. Placing it after the
+/// upscaling isn't supported.
+#[derive(Default)]
+pub struct AnamorphicStreaksPlugin {
+    /// Where the streaks are added.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`AnamorphicStreaksPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct AnamorphicStreaksSettings {
+    /// The color of the streaks, the blue of anamorphic lenses by default.
+    pub tint: LinearRgba,
+    /// The brightness above which the view streaks.
+    pub threshold: f32,
+    /// The brightness of the streaks added to the view.
+    pub intensity: f32,
+    /// How far the streaks reach, scaling the distance between the samples of every pass.
+    pub length: f32,
+    /// How many blur passes the streaks go through, from 1 to 6. Every pass makes them about
+    /// four times longer.
+    pub iterations: u32,
+}
+
+impl Default for AnamorphicStreaksSettings {
+    fn default() -> Self {
+        Self {
+            tint: LinearRgba::rgb(0.3, 0.5, 1.0),
+            threshold: 1.0,
+            intensity: 0.5,
+            length: 1.0,
+            iterations: 4,
+        }
+    }
+}
+
+/// The render graph label of the effect pass of the [`AnamorphicStreaksPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct AnamorphicStreaks;
+
+/// The render graph label of the node rendering the streaks of the [`AnamorphicStreaksPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct AnamorphicStreaksBlur;
+
+impl Plugin for AnamorphicStreaksPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "anamorphic_streaks.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<AnamorphicStreaksSettings>(
+            app,
+            &[
+                ("threshold", 0.0..=10.0),
+                ("intensity", 0.0..=2.0),
+                ("length", 0.0..=4.0),
+            ],
+        );
+        app.register_type::<AnamorphicStreaksSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<AnamorphicStreaksSettings, _>::new(
+                SHADER_PATH,
+                AnamorphicStreaks,
+                Some("anamorphic_streaks"),
+                "anamorphic_streaks_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view()
+            .with_bind_group(result_layout, prepare_result_bind_groups),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (before, _) = self.placement.edges();
+        render_app
+            .init_resource::<SpecializedRenderPipelines<AnamorphicStreaksBlurPipeline>>()
+            .add_systems(Render, prepare_streaks.in_set(RenderSystems::PrepareResources))
+            .add_render_graph_node::<ViewNodeRunner<AnamorphicStreaksBlurNode>>(
+                Core3d,
+                AnamorphicStreaksBlur,
+            )
+            .add_render_graph_edges(Core3d, (before, AnamorphicStreaksBlur, AnamorphicStreaks));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<AnamorphicStreaksBlurPipeline>();
+    }
+}
+
+// The pipeline of the bright pass and of the blur passes
+#[derive(Resource)]
+struct AnamorphicStreaksBlurPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    // Samples the streaks in the effect pass
+    result_sampler: Sampler,
+    filterable: bool,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+}
+
+impl FromWorld for AnamorphicStreaksBlurPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        // The bright pass reads the view, which some adapters can't filter
+        let filterable = filtering::screen_filterable(world);
+        let [source_texture, source_sampler] = filtering::screen_entries(filterable);
+        let layout = render_device.create_bind_group_layout(
+            "anamorphic_streaks_blur_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    source_texture,
+                    source_sampler,
+                    uniform_buffer::<AnamorphicStreaksSettings>(true),
+                ),
+            ),
+        );
+        let descriptor = SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        };
+
+        Self {
+            layout,
+            sampler: filtering::screen_sampler(render_device, filterable, descriptor.clone()),
+            result_sampler: render_device.create_sampler(&descriptor),
+            filterable,
+            shader: load_embedded_asset!(world, "anamorphic_streaks.wgsl"),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum AnamorphicStreaksPass {
+    BrightPass,
+    // The index of the pass, which sets how far apart its samples are
+    Blur(u32),
+}
+
+impl SpecializedRenderPipeline for AnamorphicStreaksBlurPipeline {
+    type Key = AnamorphicStreaksPass;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if !self.filterable {
+            shader_defs.push(filtering::SCREEN_TEXTURE_NON_FILTERABLE.into());
+        }
+        let entry_point = match key {
+            AnamorphicStreaksPass::BrightPass => "bright_pass",
+            AnamorphicStreaksPass::Blur(iteration) => {
+                shader_defs.push(ShaderDefVal::UInt("ITERATION".into(), iteration));
+                "blur"
+            }
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("anamorphic_streaks_blur_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some(entry_point.into()),
+                targets: vec![Some(ColorTargetState {
+                    format: STREAKS_TEXTURE_FORMAT,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+// The two half resolution textures the blur passes go back and forth between, and the pipelines
+// of the passes of a view
+#[derive(Component)]
+struct ViewAnamorphicStreaks {
+    textures: [CachedTexture; 2],
+    bright_pass_pipeline_id: CachedRenderPipelineId,
+    blur_pipeline_ids: Vec<CachedRenderPipelineId>,
+}
+
+impl ViewAnamorphicStreaks {
+    // The texture the last blur pass writes into
+    fn result(&self) -> &CachedTexture {
+        &self.textures[self.blur_pipeline_ids.len() % 2]
+    }
+}
+
+fn prepare_streaks(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewTarget, &AnamorphicStreaksSettings)>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    blur_pipeline: Res<AnamorphicStreaksBlurPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<AnamorphicStreaksBlurPipeline>>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+) {
+    let bright_pass_pipeline_id = pipelines.specialize(
+        &pipeline_cache,
+        &blur_pipeline,
+        AnamorphicStreaksPass::BrightPass,
+    );
+
+    for (entity, view_target, settings) in &views {
+        let size = view_target.main_texture().size();
+        let textures = [0, 1].map(|index| {
+            texture_pool.get_indexed(
+                &render_device,
+                entity,
+                AnamorphicStreaksBlur,
+                "streaks",
+                index,
+                &TextureDescriptor {
+                    label: Some("anamorphic_streaks_texture"),
+                    size: scaled_size(size, 0.5),
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: STREAKS_TEXTURE_FORMAT,
+                    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                },
+            )
+        });
+        let blur_pipeline_ids = (0..settings.iterations.clamp(1, MAX_ITERATIONS))
+            .map(|iteration| {
+                pipelines.specialize(
+                    &pipeline_cache,
+                    &blur_pipeline,
+                    AnamorphicStreaksPass::Blur(iteration),
+                )
+            })
+            .collect();
+        commands.entity(entity).insert(ViewAnamorphicStreaks {
+            textures,
+            bright_pass_pipeline_id,
+            blur_pipeline_ids,
+        });
+    }
+}
+
+// The layout of the group 1 of the effect, the streaks and their sampler
+fn result_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "anamorphic_streaks_result_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+            ),
+        ),
+    )
+}
+
+// The effect skips the views without the bind group, so it doesn't read the streaks until all
+// the passes are compiled
+fn prepare_result_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewAnamorphicStreaks), With<AnamorphicStreaksSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    layout: Res<PostProcessBindGroupLayout<AnamorphicStreaksSettings>>,
+    blur_pipeline: Res<AnamorphicStreaksBlurPipeline>,
+) {
+    for (entity, streaks) in &views {
+        let compiled = std::iter::once(&streaks.bright_pass_pipeline_id)
+            .chain(&streaks.blur_pipeline_ids)
+            .all(|id| pipeline_cache.get_render_pipeline(*id).is_some());
+        if !compiled {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<AnamorphicStreaksSettings>>();
+            continue;
+        }
+        let bind_group = render_device.create_bind_group(
+            "anamorphic_streaks_result_bind_group",
+            &layout.layout,
+            &BindGroupEntries::sequential((
+                &streaks.result().default_view,
+                &blur_pipeline.result_sampler,
+            )),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<AnamorphicStreaksSettings>::new(bind_group));
+    }
+}
+
+#[derive(Default)]
+struct AnamorphicStreaksBlurNode;
+
+impl ViewNode for AnamorphicStreaksBlurNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        // Only runs on the cameras with the streaks
+        &'static AnamorphicStreaksSettings,
+        &'static ViewAnamorphicStreaks,
+        &'static DynamicUniformIndex<AnamorphicStreaksSettings>,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, _settings, streaks, settings_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // The effect doesn't read the streaks on the views it skips
+        let view = graph.view_entity();
+        if effects::skip_intermediate::<AnamorphicStreaksSettings, AnamorphicStreaks>(world, view)
+        {
+            return Ok(());
+        }
+
+        let blur_pipeline = world.resource::<AnamorphicStreaksBlurPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let Some(settings_binding) = world
+            .resource::<ComponentUniforms<AnamorphicStreaksSettings>>()
+            .uniforms()
+            .binding()
+        else {
+            return Ok(());
+        };
+        let Some(pipelines) = std::iter::once(&streaks.bright_pass_pipeline_id)
+            .chain(&streaks.blur_pipeline_ids)
+            .map(|id| pipeline_cache.get_render_pipeline(*id))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(());
+        };
+
+        // The bright pass writes the first texture, then the blur passes go back and forth
+        let [first, second] = &streaks.textures;
+        let passes = pipelines.into_iter().enumerate().map(|(pass, pipeline)| {
+            let (source, destination) = match pass {
+                0 => (view_target.main_texture_view(), &first.default_view),
+                _ if pass % 2 == 1 => (&first.default_view, &second.default_view),
+                _ => (&second.default_view, &first.default_view),
+            };
+            (pipeline, source, destination)
+        });
+
+        for (pipeline, source, destination) in passes {
+            let bind_group = render_context.render_device().create_bind_group(
+                "anamorphic_streaks_blur_bind_group",
+                &blur_pipeline.layout,
+                &BindGroupEntries::sequential((
+                    source,
+                    &blur_pipeline.sampler,
+                    settings_binding.clone(),
+                )),
+            );
+
+            let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("anamorphic_streaks_blur_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: destination,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+// Anamorphic streaks. The bright pass keeps the highlights of the view at half resolution, the
+// blur passes spread them horizontally, every pass reaching four times as far as the previous
+// one, then the effect pass adds them to the view.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::utils::luminance
+
+// The texture read by the bright pass and the blur passes, or the view in the effect pass
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+struct AnamorphicStreaksSettings {
+    tint: vec4<f32>,
+    threshold: f32,
+    intensity: f32,
+    length: f32,
+    iterations: u32,
+}
+@group(0) @binding(2) var<uniform> settings: AnamorphicStreaksSettings;
+// The streaks, only bound to the effect pass
+@group(1) @binding(0) var streaks_texture: texture_2d<f32>;
+@group(1) @binding(1) var streaks_sampler: sampler;
+
+// The samples on each side of a pixel in a blur pass
+const BLUR_TAPS: i32 = 4;
+// How much of the light every sample keeps from the one closer to the pixel
+const BLUR_FALLOFF: f32 = 0.85;
+
+// Bilinear sampling, done manually when the adapter can't filter the view texture
+fn sample_bilinear(uv: vec2<f32>) -> vec3<f32> {
+#ifdef SCREEN_TEXTURE_NON_FILTERABLE
+    let texture_size = vec2<i32>(textureDimensions(source_texture));
+    let position = uv * vec2<f32>(texture_size) - 0.5;
+    let base = vec2<i32>(floor(position));
+    let f = fract(position);
+    let max_texel = texture_size - 1;
+    let t00 = textureLoad(source_texture, clamp(base, vec2(0), max_texel), 0).rgb;
+    let t10 = textureLoad(source_texture, clamp(base + vec2(1, 0), vec2(0), max_texel), 0).rgb;
+    let t01 = textureLoad(source_texture, clamp(base + vec2(0, 1), vec2(0), max_texel), 0).rgb;
+    let t11 = textureLoad(source_texture, clamp(base + vec2(1, 1), vec2(0), max_texel), 0).rgb;
+    return mix(mix(t00, t10, f.x), mix(t01, t11, f.x), f.y);
+#else
+    return textureSampleLevel(source_texture, source_sampler, uv, 0.0).rgb;
+#endif
+}
+
+// The part of the view brighter than the threshold, keeping its hue. Every pixel of the half
+// resolution texture covers four of the view, the bilinear sample at its center averages them
+@fragment
+fn bright_pass(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = max(sample_bilinear(in.uv), vec3(0.0));
+    let luma = luminance(color);
+    let bright = color * max(luma - settings.threshold, 0.0) / max(luma, 1.0e-5);
+    return vec4(bright, 1.0);
+}
+
+#ifdef ITERATION
+@fragment
+fn blur(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / f32(textureDimensions(source_texture).x);
+    let spacing = max(settings.length, 0.0) * pow(4.0, f32(#{ITERATION})) * texel;
+
+    var color = vec3(0.0);
+    var total_weight = 0.0;
+    for (var i = -BLUR_TAPS; i <= BLUR_TAPS; i++) {
+        let weight = pow(BLUR_FALLOFF, f32(abs(i)));
+        color += sample_bilinear(in.uv + vec2(f32(i) * spacing, 0.0)) * weight;
+        total_weight += weight;
+    }
+    return vec4(color / total_weight, 1.0);
+}
+#endif
+
+// The effect pass. The streaks have no alpha, so the one of the view is kept
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureLoad(source_texture, vec2<i32>(in.position.xy), 0);
+    let streaks = textureSampleLevel(streaks_texture, streaks_sampler, in.uv, 0.0).rgb;
+    return vec4(color.rgb + streaks * settings.tint.rgb * settings.intensity, color.a);
+}
@@ -0,0 +1,139 @@
+use crate::{effects, PostProcessInput, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::{embedded_asset, uuid_handle, RenderAssetUsages},
+    image::ImageSampler,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/ascii.wgsl";
+
+/// A text mode rendering, run on the cameras with [`AsciiSettings`].
+///
+/// Splits the view into a grid of character cells and draws every cell as the glyph of the font
+/// atlas matching its luminance, optionally tinted with its color. Runs after the tonemapping by
+/// default.
+pub struct AsciiPlugin {
+    /// Where the effect runs.
+    pub placement: PostProcessPlacement,
+    /// The glyphs, side by side in a single row of square cells, from the darkest to the
+    /// brightest. Their coverage is read from the red channel. Defaults to
+    /// [`AsciiPlugin::DEFAULT_FONT`].
+    pub font: Handle<Image>,
+}
+
+impl AsciiPlugin {
+    /// The ten 8x8 glyphs ` .:-=+*#%@`, generated by the plugin.
+    pub const DEFAULT_FONT: Handle<Image> = uuid_handle!("1f7c3e9a-6d2b-4a8f-9e5c-3b8d1a7f2e64");
+}
+
+impl Default for AsciiPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+            font: Self::DEFAULT_FONT,
+        }
+    }
+}
+
+/// The settings of the [`AsciiPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct AsciiSettings {
+    /// The size of the character cells, in pixels. Multiples of the glyph size keep them crisp.
+    pub cell_size: f32,
+    /// The color of the glyphs.
+    pub foreground: LinearRgba,
+    /// The color behind the glyphs.
+    pub background: LinearRgba,
+    /// How much the glyphs take the color of their cell instead of the foreground, from 0 to 1.
+    pub colorize: f32,
+}
+
+impl Default for AsciiSettings {
+    fn default() -> Self {
+        Self {
+            cell_size: 8.0,
+            foreground: LinearRgba::WHITE,
+            background: LinearRgba::BLACK,
+            colorize: 1.0,
+        }
+    }
+}
+
+/// The render graph label of the [`AsciiPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Ascii;
+
+// The rows of the default glyphs, the most significant bit being the leftmost pixel
+const DEFAULT_GLYPHS: [[u8; 8]; 10] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00],
+    [0x00, 0x18, 0x18, 0x00, 0x00, 0x18, 0x18, 0x00],
+    [0x00, 0x00, 0x00, 0x7e, 0x00, 0x00, 0x00, 0x00],
+    [0x00, 0x00, 0x7e, 0x00, 0x7e, 0x00, 0x00, 0x00],
+    [0x00, 0x18, 0x18, 0x7e, 0x18, 0x18, 0x00, 0x00],
+    [0x00, 0x66, 0x3c, 0xff, 0x3c, 0x66, 0x00, 0x00],
+    [0x36, 0x36, 0x7f, 0x36, 0x7f, 0x36, 0x36, 0x00],
+    [0x00, 0xc6, 0xcc, 0x18, 0x30, 0x66, 0xc6, 0x00],
+    [0x3c, 0x66, 0x6e, 0x6e, 0x60, 0x62, 0x3c, 0x00],
+];
+
+fn default_font() -> Image {
+    let width = DEFAULT_GLYPHS.len() * 8;
+    let mut data = vec![0; width * 8];
+    for (glyph, rows) in DEFAULT_GLYPHS.iter().enumerate() {
+        for (y, row) in rows.iter().enumerate() {
+            for x in 0..8 {
+                if row & (0x80 >> x) != 0 {
+                    data[y * width + glyph * 8 + x] = 255;
+                }
+            }
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: width as u32,
+            height: 8,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+impl Plugin for AsciiPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "ascii.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<AsciiSettings>(
+            app,
+            &[("cell_size", 4.0..=32.0), ("colorize", 0.0..=1.0)],
+        );
+        app.register_type::<AsciiSettings>();
+        app.world_mut()
+            .resource_mut::<Assets<Image>>()
+            .insert(&Self::DEFAULT_FONT, default_font())
+            .expect("the default font handle is a uuid handle");
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<AsciiSettings, _>::new(
+                SHADER_PATH,
+                Ascii,
+                Some("ascii"),
+                "ascii_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_input(PostProcessInput::Image(self.font.clone()))
+            .with_standard_samplers()
+            .without_view(),
+        );
+    }
+}
@@ -0,0 +1,49 @@
+// Draws every cell of a character grid as the glyph matching the luminance of the cell.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::linear_to_srgb
+#import bevy_post_process::utils::luminance
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct AsciiSettings {
+    cell_size: f32,
+    foreground: vec4<f32>,
+    background: vec4<f32>,
+    colorize: f32,
+}
+@group(0) @binding(2) var<uniform> settings: AsciiSettings;
+@group(0) @binding(7) var linear_sampler: sampler;
+@group(0) @binding(16) var font: texture_2d<f32>;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    let cell_size = max(settings.cell_size, 1.0);
+    let cell = floor(in.position.xy / cell_size);
+
+    // Four bilinear taps around the center of the cell average most of its pixels
+    let center = (cell + 0.5) * cell_size;
+    let quarter = cell_size * 0.25;
+    var color = vec3(0.0);
+    for (var i = 0u; i < 4u; i++) {
+        let x = select(-quarter, quarter, (i & 1u) != 0u);
+        let offset = vec2(x, select(-quarter, quarter, i > 1u));
+        let uv = clamp((center + offset) / size, vec2(0.0), vec2(1.0));
+        color += textureSampleLevel(screen_texture, linear_sampler, uv, 0.0).rgb;
+    }
+    color *= 0.25;
+
+    // The glyphs are picked by perceived brightness, so the ramp spreads evenly
+    let font_size = textureDimensions(font);
+    let glyph_size = font_size.y;
+    let glyph_count = max(font_size.x / glyph_size, 1u);
+    let brightness = clamp(linear_to_srgb(vec3(luminance(max(color, vec3(0.0))))).x, 0.0, 1.0);
+    let glyph = min(u32(brightness * f32(glyph_count)), glyph_count - 1u);
+
+    let in_cell = (in.position.xy - cell * cell_size) / cell_size;
+    let texel = min(vec2<u32>(in_cell * f32(glyph_size)), vec2(glyph_size - 1u));
+    let coverage = textureLoad(font, vec2(glyph * glyph_size + texel.x, texel.y), 0).r;
+
+    let ink = mix(settings.foreground.rgb, color, settings.colorize);
+    return vec4(mix(settings.background.rgb, ink, coverage), 1.0);
+}
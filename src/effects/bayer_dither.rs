@@ -0,0 +1,79 @@
+use crate::{effects, PostProcessColorSpace, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/bayer_dither.wgsl";
+
+/// An ordered dithering, run on the cameras with [`BayerDitherSettings`].
+///
+/// Quantizes every channel to a few bits, offsetting the rounding of every pixel by a Bayer matrix
+/// so the gradients turn into regular patterns instead of bands. The matrices are computed in the
+/// shader, so the effect doesn't need the
+/// [`PostProcessNoisePlugin`](crate::PostProcessNoisePlugin). The colors are quantized in sRGB,
+/// and the effect runs after the tonemapping by default, when they fit between 0 and 1.
+pub struct BayerDitherPlugin {
+    /// Where the dithering runs.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for BayerDitherPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+        }
+    }
+}
+
+/// The settings of the [`BayerDitherPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct BayerDitherSettings {
+    /// The size of the Bayer matrix, 2, 4 or 8. Larger matrices make finer patterns with more
+    /// intermediate shades.
+    pub matrix_size: u32,
+    /// The bit depth of the red, green and blue channels, from 1 to 8, like `(5, 6, 5)` for a 16
+    /// bit display.
+    pub bits: UVec3,
+    /// The size of the pattern cells, in pixels. Larger cells suit a pixelated view.
+    pub scale: f32,
+}
+
+impl Default for BayerDitherSettings {
+    fn default() -> Self {
+        Self {
+            matrix_size: 4,
+            bits: UVec3::splat(3),
+            scale: 1.0,
+        }
+    }
+}
+
+/// The render graph label of the [`BayerDitherPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct BayerDither;
+
+impl Plugin for BayerDitherPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "bayer_dither.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<BayerDitherSettings>(app, &[("scale", 1.0..=8.0)]);
+        app.register_type::<BayerDitherSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<BayerDitherSettings, _>::new(
+                SHADER_PATH,
+                BayerDither,
+                Some("bayer_dither"),
+                "bayer_dither_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_color_space(PostProcessColorSpace::Srgb)
+            .without_view(),
+        );
+    }
+}
@@ -0,0 +1,45 @@
+// Quantizes every channel, offsetting the rounding by the threshold of a Bayer matrix.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::{to_working_space, from_working_space}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct BayerDitherSettings {
+    matrix_size: u32,
+    bits: vec3<u32>,
+    scale: f32,
+}
+@group(0) @binding(2) var<uniform> settings: BayerDitherSettings;
+
+// The threshold of a cell of the Bayer matrix of size `2^order`, between 0 and 1. Every level of
+// the recursive construction interleaves one bit of each coordinate, the lowest bits weighing most
+fn bayer(cell: vec2<u32>, order: u32) -> f32 {
+    var index = 0u;
+    for (var i = 0u; i < order; i++) {
+        let x = (cell.x >> i) & 1u;
+        let y = (cell.y >> i) & 1u;
+        index = index * 4u + ((x ^ y) << 1u) + y;
+    }
+    let count = 1u << (2u * order);
+    return (f32(index) + 0.5) / f32(count);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let encoded = to_working_space(color.rgb);
+
+    var order = 3u;
+    if settings.matrix_size <= 2u {
+        order = 1u;
+    } else if settings.matrix_size <= 4u {
+        order = 2u;
+    }
+    let cell = vec2<u32>(in.position.xy / max(settings.scale, 1.0));
+    let threshold = bayer(cell, order);
+
+    let bits = clamp(settings.bits, vec3(1u), vec3(8u));
+    let levels = vec3<f32>((vec3(1u) << bits) - 1u);
+    let quantized = floor(encoded * levels + threshold) / levels;
+    return vec4(from_working_space(quantized), color.a);
+}
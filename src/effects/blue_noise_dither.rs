@@ -0,0 +1,99 @@
+use crate::{
+    effects, noise, PostProcessColorSpace, PostProcessInput, PostProcessNoisePlugin,
+    PostProcessPlacement, PostProcessPlugin,
+};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/blue_noise_dither.wgsl";
+
+/// A blue noise dithering removing the banding of smooth gradients, run on the cameras with
+/// [`BlueNoiseDitherSettings`].
+///
+/// Adds less than one step of the output precision to every pixel, following the blue noise
+/// texture of the [`noise`] module, so the rounding to the output format spreads into a fine grain
+/// instead of bands. Meant for the dark skies and fog of HDR scenes rather than as a look: the
+/// noise changes every frame, averaging away for the eye. Runs after the tonemapping by default,
+/// when the colors are about to be stored with the precision of the output.
+///
+/// Adds the [`PostProcessNoisePlugin`] when it isn't already.
+pub struct BlueNoiseDitherPlugin {
+    /// Where the dithering runs.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for BlueNoiseDitherPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+        }
+    }
+}
+
+/// The settings of the [`BlueNoiseDitherPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct BlueNoiseDitherSettings {
+    /// The bit depth of the output the noise hides the steps of, like 8 for a standard display or
+    /// 10 for an HDR one.
+    pub bit_depth: f32,
+    /// The amplitude of the noise, in steps of the output. 1 hides the banding, more makes a
+    /// visible grain.
+    pub strength: f32,
+    /// The index of the frame, offsetting the noise. Advanced by the plugin every frame.
+    pub frame: u32,
+}
+
+impl Default for BlueNoiseDitherSettings {
+    fn default() -> Self {
+        Self {
+            bit_depth: 8.0,
+            strength: 1.0,
+            frame: 0,
+        }
+    }
+}
+
+/// The render graph label of the [`BlueNoiseDitherPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct BlueNoiseDither;
+
+impl Plugin for BlueNoiseDitherPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "blue_noise_dither.wgsl");
+        if !app.is_plugin_added::<PostProcessNoisePlugin>() {
+            app.add_plugins(PostProcessNoisePlugin);
+        }
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<BlueNoiseDitherSettings>(
+            app,
+            &[("bit_depth", 1.0..=16.0), ("strength", 0.0..=4.0)],
+        );
+        app.register_type::<BlueNoiseDitherSettings>()
+            .add_systems(Update, advance_frame);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<BlueNoiseDitherSettings, _>::new(
+                SHADER_PATH,
+                BlueNoiseDither,
+                Some("blue_noise_dither"),
+                "blue_noise_dither_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_color_space(PostProcessColorSpace::Srgb)
+            .with_input(PostProcessInput::Image(noise::BLUE_NOISE))
+            .without_view(),
+        );
+    }
+}
+
+fn advance_frame(mut settings: Query<&mut BlueNoiseDitherSettings>) {
+    for mut settings in &mut settings {
+        settings.frame = settings.frame.wrapping_add(1);
+    }
+}
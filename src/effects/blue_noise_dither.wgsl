@@ -0,0 +1,41 @@
+// Adds a triangular distributed blue noise of about one step of the output precision, so the
+// rounding to the output format turns bands into grain.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::{to_working_space, from_working_space}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct BlueNoiseDitherSettings {
+    bit_depth: f32,
+    strength: f32,
+    frame: u32,
+}
+@group(0) @binding(2) var<uniform> settings: BlueNoiseDitherSettings;
+@group(0) @binding(16) var blue_noise: texture_2d<f32>;
+
+const GOLDEN_RATIO_FRACT: f32 = 0.618034;
+
+// Remaps a uniform value in [0, 1] to a triangular distribution in [-1, 1], which makes the
+// strength of the grain independent of the color
+fn uniform_to_triangular(value: f32) -> f32 {
+    let centered = value * 2.0 - 1.0;
+    let remapped = centered * inverseSqrt(max(abs(centered), 1.0e-6));
+    return max(-1.0, remapped) - sign(centered);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+
+    // Every frame shifts the tile and the values of the noise, so it averages out over time
+    let size = textureDimensions(blue_noise);
+    let shift = vec2(settings.frame * 17u, settings.frame * 29u);
+    let pixel = (vec2<u32>(in.position.xy) + shift) % size;
+    let value = textureLoad(blue_noise, pixel, 0).r;
+    let offset = f32(settings.frame % 64u) * GOLDEN_RATIO_FRACT;
+    let noise = uniform_to_triangular(fract(value + offset));
+
+    let step = 1.0 / (exp2(clamp(settings.bit_depth, 1.0, 16.0)) - 1.0);
+    let dithered = to_working_space(color.rgb) + noise * step * settings.strength;
+    return vec4(from_working_space(dithered), color.a);
+}
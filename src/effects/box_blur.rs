@@ -0,0 +1,304 @@
+use crate::{
+    effects, filtering, PostProcessPlacement, PostProcessPlugin, PostProcessPlugins,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::{core_3d::graph::Core3d, FullscreenShader},
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{ComponentUniforms, DynamicUniformIndex, ExtractComponent},
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{binding_types::uniform_buffer, *},
+        renderer::{RenderContext, RenderDevice},
+        view::ViewTarget,
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/box_blur.wgsl";
+
+/// The most iterations of the blur.
+const MAX_ITERATIONS: u32 = 8;
+
+/// An iterated box blur, run on the cameras with [`BoxBlurSettings`].
+///
+/// Each iteration is a horizontal and a vertical pass averaging the pixels of the kernel with the
+/// same weight. A single iteration looks blocky, but a few of them get close to a Gaussian blur
+/// for less samples, which makes it a cheap fallback on low end hardware.
+///
+/// The last iteration is the [`BoxBlurHorizontal`] and [`BoxBlurVertical`] effects, the previous
+/// ones run in the [`BoxBlurIterations`] node right before them, flipping the view target back and
+/// forth. Placing the blur after the upscaling isn't supported.
+#[derive(Default)]
+pub struct BoxBlurPlugin {
+    /// Where the blur runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`BoxBlurPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct BoxBlurSettings {
+    /// The width of the kernel in pixels, in each pass. Even sizes are rounded up to the next odd
+    /// size, so the kernel stays centered on the pixel. Capped at 129.
+    pub kernel_size: u32,
+    /// How many times the blur is applied, from 1 to 8.
+    pub iterations: u32,
+}
+
+impl Default for BoxBlurSettings {
+    fn default() -> Self {
+        Self {
+            kernel_size: 5,
+            iterations: 3,
+        }
+    }
+}
+
+/// The render graph label of the horizontal pass of the [`BoxBlurPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct BoxBlurHorizontal;
+
+/// The render graph label of the vertical pass of the [`BoxBlurPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct BoxBlurVertical;
+
+/// The render graph label of the node running every iteration of the [`BoxBlurPlugin`] but the
+/// last.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct BoxBlurIterations;
+
+impl Plugin for BoxBlurPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "box_blur.wgsl");
+        app.register_type::<BoxBlurSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        let horizontal = PostProcessPlugin::<BoxBlurSettings, _>::new(
+            SHADER_PATH,
+            BoxBlurHorizontal,
+            Some("box_blur_horizontal"),
+            "box_blur_bind_group_layout",
+            vertex_state.clone(),
+        )
+        .with_placement(self.placement)
+        .without_view();
+        let vertical = PostProcessPlugin::<BoxBlurSettings, _>::new(
+            SHADER_PATH,
+            BoxBlurVertical,
+            Some("box_blur_vertical"),
+            "box_blur_bind_group_layout",
+            vertex_state,
+        )
+        .with_shader_def("VERTICAL")
+        .without_view();
+        app.add_plugins(PostProcessPlugins::default().with(horizontal).with(vertical));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (before, _) = self.placement.edges();
+        render_app
+            .init_resource::<SpecializedRenderPipelines<BoxBlurIterationPipeline>>()
+            .add_systems(
+                Render,
+                prepare_iteration_pipelines.in_set(RenderSystems::Prepare),
+            )
+            .add_render_graph_node::<ViewNodeRunner<BoxBlurIterationsNode>>(
+                Core3d,
+                BoxBlurIterations,
+            )
+            .add_render_graph_edges(Core3d, (before, BoxBlurIterations, BoxBlurHorizontal));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<BoxBlurIterationPipeline>();
+    }
+}
+
+// The pipeline of the iterations before the last. It uses the shader and the layout of the
+// effect passes, in the format of each view
+#[derive(Resource)]
+struct BoxBlurIterationPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    filterable: bool,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+}
+
+impl FromWorld for BoxBlurIterationPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let filterable = filtering::screen_filterable(world);
+        let [screen_texture, screen_sampler] = filtering::screen_entries(filterable);
+        let layout = render_device.create_bind_group_layout(
+            "box_blur_iteration_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    screen_texture,
+                    screen_sampler,
+                    uniform_buffer::<BoxBlurSettings>(true),
+                ),
+            ),
+        );
+
+        Self {
+            layout,
+            sampler: filtering::screen_sampler(render_device, filterable, default()),
+            filterable,
+            shader: load_embedded_asset!(world, "box_blur.wgsl"),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct BoxBlurIterationPipelineKey {
+    texture_format: TextureFormat,
+    vertical: bool,
+}
+
+impl SpecializedRenderPipeline for BoxBlurIterationPipeline {
+    type Key = BoxBlurIterationPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if !self.filterable {
+            shader_defs.push(filtering::SCREEN_TEXTURE_NON_FILTERABLE.into());
+        }
+        if key.vertical {
+            shader_defs.push("VERTICAL".into());
+        }
+
+        RenderPipelineDescriptor {
+            label: Some("box_blur_iteration_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some("fragment".into()),
+                targets: vec![Some(ColorTargetState {
+                    format: key.texture_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+// The horizontal and vertical iteration pipelines of a view
+#[derive(Component)]
+struct ViewBoxBlurIterationPipelines([CachedRenderPipelineId; 2]);
+
+fn prepare_iteration_pipelines(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewTarget), With<BoxBlurSettings>>,
+    pipeline_cache: Res<PipelineCache>,
+    iteration_pipeline: Res<BoxBlurIterationPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<BoxBlurIterationPipeline>>,
+) {
+    for (entity, view_target) in &views {
+        let pipeline_ids = [false, true].map(|vertical| {
+            pipelines.specialize(
+                &pipeline_cache,
+                &iteration_pipeline,
+                BoxBlurIterationPipelineKey {
+                    texture_format: view_target.main_texture_format(),
+                    vertical,
+                },
+            )
+        });
+        commands
+            .entity(entity)
+            .insert(ViewBoxBlurIterationPipelines(pipeline_ids));
+    }
+}
+
+#[derive(Default)]
+struct BoxBlurIterationsNode;
+
+impl ViewNode for BoxBlurIterationsNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static BoxBlurSettings,
+        &'static ViewBoxBlurIterationPipelines,
+        &'static DynamicUniformIndex<BoxBlurSettings>,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, settings, view_pipelines, settings_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // The iterations only add to the last one, run by the effect passes
+        let view = graph.view_entity();
+        if effects::skip_intermediate::<BoxBlurSettings, BoxBlurHorizontal>(world, view)
+            || effects::skip_intermediate::<BoxBlurSettings, BoxBlurVertical>(world, view)
+        {
+            return Ok(());
+        }
+
+        let iteration_pipeline = world.resource::<BoxBlurIterationPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (Some(horizontal), Some(vertical), Some(settings_binding)) = (
+            pipeline_cache.get_render_pipeline(view_pipelines.0[0]),
+            pipeline_cache.get_render_pipeline(view_pipelines.0[1]),
+            world
+                .resource::<ComponentUniforms<BoxBlurSettings>>()
+                .uniforms()
+                .binding(),
+        ) else {
+            return Ok(());
+        };
+
+        let iterations = settings.iterations.clamp(1, MAX_ITERATIONS) - 1;
+        for pipeline in (0..iterations).flat_map(|_| [horizontal, vertical]) {
+            let post_process = view_target.post_process_write();
+            let bind_group = render_context.render_device().create_bind_group(
+                "box_blur_iteration_bind_group",
+                &iteration_pipeline.layout,
+                &BindGroupEntries::sequential((
+                    post_process.source,
+                    &iteration_pipeline.sampler,
+                    settings_binding.clone(),
+                )),
+            );
+
+            let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("box_blur_iteration_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: post_process.destination,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+// One pass of the box blur, horizontal unless VERTICAL is set.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct BoxBlurSettings {
+    kernel_size: u32,
+    iterations: u32,
+}
+@group(0) @binding(2) var<uniform> settings: BoxBlurSettings;
+
+const MAX_RADIUS: i32 = 64;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+#ifdef VERTICAL
+    let direction = vec2(0, 1);
+#else
+    let direction = vec2(1, 0);
+#endif
+    // Every pixel has the same weight, so the pixels are loaded rather than sampled
+    let size = vec2<i32>(textureDimensions(screen_texture));
+    let pixel = vec2<i32>(in.position.xy);
+    let radius = min(i32(settings.kernel_size / 2u), MAX_RADIUS);
+
+    var color = vec4(0.0);
+    for (var i = -radius; i <= radius; i++) {
+        let position = clamp(pixel + direction * i, vec2(0), size - 1);
+        color += textureLoad(screen_texture, position, 0);
+    }
+    return color / f32(2 * radius + 1);
+}
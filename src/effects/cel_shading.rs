@@ -0,0 +1,92 @@
+use crate::{effects, PostProcessColorSpace, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/cel_shading.wgsl";
+
+/// A cel shading of the view, quantizing its luminance into a few flat bands, run on the cameras
+/// with [`CelShadingSettings`].
+///
+/// Pairs with the [`ToonOutlinePlugin`](crate::effects::ToonOutlinePlugin) for a full toon look.
+/// Runs after the tonemapping by default, so the bands split the shades that reach the screen.
+pub struct CelShadingPlugin {
+    /// Where the cel shading runs.
+    pub placement: PostProcessPlacement,
+    /// The color space the bands are spread in. The default, sRGB, spreads them evenly for the
+    /// eye. Linear crowds the dark tones into the lowest band.
+    pub color_space: PostProcessColorSpace,
+}
+
+impl Default for CelShadingPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+            color_space: PostProcessColorSpace::Srgb,
+        }
+    }
+}
+
+/// The settings of the [`CelShadingPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct CelShadingSettings {
+    /// How many bands the luminance is reduced to, from 2 to 16.
+    pub bands: u32,
+    /// Where the edges between the bands fall. 1 spreads them evenly, lower values move them
+    /// toward the shadows, giving more bands to the dark tones, higher values toward the
+    /// highlights.
+    pub band_bias: f32,
+    /// The width of the transition between two bands, as a fraction of a band. 0 makes hard
+    /// edges.
+    pub softness: f32,
+    /// How much of the hue and saturation of the view the bands keep, 0 leaving flat gray bands.
+    pub color_preservation: f32,
+}
+
+impl Default for CelShadingSettings {
+    fn default() -> Self {
+        Self {
+            bands: 4,
+            band_bias: 1.0,
+            softness: 0.05,
+            color_preservation: 1.0,
+        }
+    }
+}
+
+/// The render graph label of the [`CelShadingPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct CelShading;
+
+impl Plugin for CelShadingPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "cel_shading.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<CelShadingSettings>(
+            app,
+            &[
+                ("band_bias", 0.25..=4.0),
+                ("softness", 0.0..=1.0),
+                ("color_preservation", 0.0..=1.0),
+            ],
+        );
+        app.register_type::<CelShadingSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<CelShadingSettings, _>::new(
+                SHADER_PATH,
+                CelShading,
+                Some("cel_shading"),
+                "cel_shading_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_color_space(self.color_space)
+            .without_view(),
+        );
+    }
+}
@@ -0,0 +1,34 @@
+// Quantizes the luminance into a few bands with soft edges, keeping or dropping the color.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::{to_working_space, from_working_space}
+#import bevy_post_process::utils::luminance
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct CelShadingSettings {
+    bands: u32,
+    band_bias: f32,
+    softness: f32,
+    color_preservation: f32,
+}
+@group(0) @binding(2) var<uniform> settings: CelShadingSettings;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let encoded = to_working_space(color.rgb);
+    let luma = luminance(encoded);
+
+    // The bias bends the luminance before the bands are cut evenly, then bends them back, so the
+    // edges move while the shade of the darkest and brightest bands stays
+    let bias = max(settings.band_bias, 1.0e-3);
+    let steps = f32(clamp(settings.bands, 2u, 16u) - 1u);
+    let position = pow(clamp(luma, 0.0, 1.0), bias) * steps;
+    let edge = max(settings.softness * 0.5, 1.0e-4);
+    let blend = smoothstep(0.5 - edge, 0.5 + edge, fract(position));
+    let banded = pow((floor(position) + blend) / steps, 1.0 / bias);
+
+    let colored = encoded * banded / max(luma, 1.0e-5);
+    let shaded = mix(vec3(banded), colored, clamp(settings.color_preservation, 0.0, 1.0));
+    return vec4(from_working_space(shaded), color.a);
+}
@@ -0,0 +1,81 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/channel_mixer.wgsl";
+
+/// A channel mixer, run on the cameras with [`ChannelMixerSettings`].
+///
+/// Every output channel is a weighted sum of the input channels plus an offset, which makes
+/// custom monochrome conversions, infrared looks and channel swaps.
+#[derive(Default)]
+pub struct ChannelMixerPlugin {
+    /// Where the mixer runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`ChannelMixerPlugin`] on a camera. The default leaves the colors
+/// unchanged.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct ChannelMixerSettings {
+    /// The weights of the red, green and blue input channels in the red output channel.
+    pub red: Vec3,
+    /// The weights of the input channels in the green output channel.
+    pub green: Vec3,
+    /// The weights of the input channels in the blue output channel.
+    pub blue: Vec3,
+    /// Added to the red, green and blue output channels.
+    pub offset: Vec3,
+}
+
+impl Default for ChannelMixerSettings {
+    fn default() -> Self {
+        Self {
+            red: Vec3::X,
+            green: Vec3::Y,
+            blue: Vec3::Z,
+            offset: Vec3::ZERO,
+        }
+    }
+}
+
+impl ChannelMixerSettings {
+    /// A monochrome conversion, every output channel being the input channels weighted by
+    /// `weights`. Weights summing to 1 keep the brightness of white.
+    pub fn monochrome(weights: Vec3) -> Self {
+        Self {
+            red: weights,
+            green: weights,
+            blue: weights,
+            offset: Vec3::ZERO,
+        }
+    }
+}
+
+/// The render graph label of the [`ChannelMixerPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ChannelMixer;
+
+impl Plugin for ChannelMixerPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "channel_mixer.wgsl");
+        app.register_type::<ChannelMixerSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<ChannelMixerSettings, _>::new(
+                SHADER_PATH,
+                ChannelMixer,
+                Some("channel_mixer"),
+                "channel_mixer_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view(),
+        );
+    }
+}
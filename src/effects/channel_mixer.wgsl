@@ -0,0 +1,23 @@
+// Every output channel is a weighted sum of the input channels plus an offset.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct ChannelMixerSettings {
+    red: vec3<f32>,
+    green: vec3<f32>,
+    blue: vec3<f32>,
+    offset: vec3<f32>,
+}
+@group(0) @binding(2) var<uniform> settings: ChannelMixerSettings;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let mixed = vec3(
+        dot(color.rgb, settings.red),
+        dot(color.rgb, settings.green),
+        dot(color.rgb, settings.blue),
+    ) + settings.offset;
+    return vec4(max(mixed, vec3(0.0)), color.a);
+}
@@ -0,0 +1,91 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/color_adjustments.wgsl";
+
+/// Brightness, contrast, saturation, hue and vibrance adjustments, run on the cameras with
+/// [`ColorAdjustmentsSettings`].
+///
+/// The contrast is applied in log space around a pivot, so it never pushes a channel below zero
+/// and works on HDR colors. The saturation, hue and vibrance are applied in Oklab, keeping the
+/// lightness of the colors. Every field blends smoothly between values, so the settings can be
+/// animated like any other reflected component.
+#[derive(Default)]
+pub struct ColorAdjustmentsPlugin {
+    /// Where the adjustments run.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`ColorAdjustmentsPlugin`] on a camera. The defaults leave the colors
+/// unchanged.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct ColorAdjustmentsSettings {
+    /// The change of brightness in stops. 1 doubles the brightness, -1 halves it.
+    pub brightness: f32,
+    /// The contrast, 1 leaving the colors unchanged. Above 1, the channels brighter than the
+    /// pivot get brighter and the darker ones darker.
+    pub contrast: f32,
+    /// The linear value the contrast pivots around, middle gray by default.
+    pub contrast_pivot: f32,
+    /// The saturation, from 0 for grayscale to 1 for the original colors and above.
+    pub saturation: f32,
+    /// The rotation of the hues, as a fraction of a full turn.
+    pub hue_shift: f32,
+    /// Saturates the dull colors more than the already saturated ones, which keeps skin tones
+    /// natural. 0 leaves the colors unchanged, negative values desaturate the dull colors.
+    pub vibrance: f32,
+}
+
+impl Default for ColorAdjustmentsSettings {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 1.0,
+            contrast_pivot: 0.18,
+            saturation: 1.0,
+            hue_shift: 0.0,
+            vibrance: 0.0,
+        }
+    }
+}
+
+/// The render graph label of the [`ColorAdjustmentsPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ColorAdjustments;
+
+impl Plugin for ColorAdjustmentsPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "color_adjustments.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<ColorAdjustmentsSettings>(
+            app,
+            &[
+                ("brightness", -4.0..=4.0),
+                ("contrast", 0.0..=2.0),
+                ("contrast_pivot", 0.0..=1.0),
+                ("saturation", 0.0..=2.0),
+                ("hue_shift", -0.5..=0.5),
+                ("vibrance", -1.0..=1.0),
+            ],
+        );
+        app.register_type::<ColorAdjustmentsSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<ColorAdjustmentsSettings, _>::new(
+                SHADER_PATH,
+                ColorAdjustments,
+                Some("color_adjustments"),
+                "color_adjustments_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view(),
+        );
+    }
+}
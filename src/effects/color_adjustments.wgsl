@@ -0,0 +1,42 @@
+// Brightness and contrast on the linear channels, then saturation, hue and vibrance in Oklab.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::{linear_to_oklab, oklab_to_linear}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct ColorAdjustmentsSettings {
+    brightness: f32,
+    contrast: f32,
+    contrast_pivot: f32,
+    saturation: f32,
+    hue_shift: f32,
+    vibrance: f32,
+}
+@group(0) @binding(2) var<uniform> settings: ColorAdjustmentsSettings;
+
+const TAU: f32 = 6.28318531;
+// The chroma in Oklab above which a color counts as fully saturated for the vibrance
+const VIBRANCE_CHROMA: f32 = 0.25;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    var rgb = max(color.rgb, vec3(0.0)) * exp2(settings.brightness);
+
+    // A power curve around the pivot, a straight line in log space, stays positive
+    let pivot = max(settings.contrast_pivot, 1.0e-4);
+    rgb = pivot * pow(rgb / pivot, vec3(settings.contrast));
+
+    var lab = linear_to_oklab(rgb);
+    let angle = settings.hue_shift * TAU;
+    let rotation = mat2x2<f32>(cos(angle), sin(angle), -sin(angle), cos(angle));
+    var chroma_vector = rotation * lab.yz;
+
+    let chroma = length(chroma_vector);
+    let dullness = 1.0 - clamp(chroma / VIBRANCE_CHROMA, 0.0, 1.0);
+    let vibrance = max(1.0 + settings.vibrance * dullness, 0.0);
+    chroma_vector *= max(settings.saturation, 0.0) * vibrance;
+
+    lab = vec3(lab.x, chroma_vector);
+    return vec4(max(oklab_to_linear(lab), vec3(0.0)), color.a);
+}
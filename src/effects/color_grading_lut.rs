@@ -0,0 +1,350 @@
+use crate::{
+    effects, PostProcessBindGroup, PostProcessBindGroupLayout, PostProcessColorSpace,
+    PostProcessPlacement, PostProcessPlugin,
+};
+use bevy::{
+    asset::{embedded_asset, io::Reader, AssetLoader, LoadContext, RenderAssetUsages},
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_asset::RenderAssets,
+        render_graph::RenderLabel,
+        render_resource::{binding_types::texture_3d, *},
+        renderer::RenderDevice,
+        texture::GpuImage,
+    },
+};
+use std::fmt::{self, Display};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/color_grading_lut.wgsl";
+
+/// Color grading with a 3D lookup table, run on the cameras with [`ColorGradingLutSettings`] and
+/// a [`ColorGradingLutTexture`].
+///
+/// Also registers a loader for the `.cube` format, which Resolve, Photoshop and most grading
+/// tools export, so grades load like any other image:
+///
+/// ```ignore
+/// commands.spawn((
+///     Camera3d::default(),
+///     ColorGradingLutTexture(asset_server.load("grades/teal_orange.cube")),
+///     ColorGradingLutSettings::default(),
+/// ));
+/// ```
+pub struct ColorGradingLutPlugin {
+    /// Where the grading runs.
+    pub placement: PostProcessPlacement,
+    /// The color space the LUT was authored for. LUTs exported by grading tools usually expect
+    /// sRGB encoded colors, the default.
+    pub color_space: PostProcessColorSpace,
+}
+
+impl Default for ColorGradingLutPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::default(),
+            color_space: PostProcessColorSpace::Srgb,
+        }
+    }
+}
+
+/// The settings of the [`ColorGradingLutPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct ColorGradingLutSettings {
+    /// How much of the grade is applied, from 0 for the original colors to 1 for the graded ones.
+    pub intensity: f32,
+}
+
+impl Default for ColorGradingLutSettings {
+    fn default() -> Self {
+        Self { intensity: 1.0 }
+    }
+}
+
+/// The 3D lookup table a camera is graded with by the [`ColorGradingLutPlugin`].
+///
+/// The image must be a 3D `Rgba32Float` texture indexed by red, green and blue, like the ones
+/// loaded from `.cube` files. The grading is skipped while it is loading.
+#[derive(Component, Reflect, Clone, Debug, ExtractComponent)]
+#[reflect(Component)]
+pub struct ColorGradingLutTexture(pub Handle<Image>);
+
+/// The render graph label of the [`ColorGradingLutPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ColorGradingLut;
+
+impl Plugin for ColorGradingLutPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "color_grading_lut.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<ColorGradingLutSettings>(
+            app,
+            &[("intensity", 0.0..=1.0)],
+        );
+        app.register_type::<ColorGradingLutSettings>()
+            .register_type::<ColorGradingLutTexture>()
+            .init_asset_loader::<CubeLutLoader>()
+            .add_plugins(ExtractComponentPlugin::<ColorGradingLutTexture>::default());
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<ColorGradingLutSettings, _>::new(
+                SHADER_PATH,
+                ColorGradingLut,
+                Some("color_grading_lut"),
+                "color_grading_lut_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_color_space(self.color_space)
+            .without_view()
+            .with_bind_group(lut_layout, prepare_lut_bind_groups),
+        );
+    }
+}
+
+// The layout of the group 1 of the effect, the LUT. It is filtered by the shader, as not every
+// adapter can filter 32 bit floats
+fn lut_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "color_grading_lut_texture_bind_group_layout",
+        &BindGroupLayoutEntries::single(
+            ShaderStages::FRAGMENT,
+            texture_3d(TextureSampleType::Float { filterable: false }),
+        ),
+    )
+}
+
+// The effect skips the views without the bind group, so the views whose LUT is still loading
+// are left ungraded
+fn prepare_lut_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &ColorGradingLutTexture), With<ColorGradingLutSettings>>,
+    render_device: Res<RenderDevice>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    layout: Res<PostProcessBindGroupLayout<ColorGradingLutSettings>>,
+) {
+    for (entity, lut) in &views {
+        let Some(gpu_image) = gpu_images.get(&lut.0) else {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<ColorGradingLutSettings>>();
+            continue;
+        };
+
+        let bind_group = render_device.create_bind_group(
+            "color_grading_lut_texture_bind_group",
+            &layout.layout,
+            &BindGroupEntries::single(&gpu_image.texture_view),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<ColorGradingLutSettings>::new(bind_group));
+    }
+}
+
+/// Loads the 3D LUTs of `.cube` files as 3D `Rgba32Float` images, for [`ColorGradingLutTexture`].
+///
+/// Only 3D tables over the default `0` to `1` domain are supported.
+#[derive(Default)]
+pub struct CubeLutLoader;
+
+/// The errors of the [`CubeLutLoader`].
+#[derive(Debug)]
+pub enum CubeLutError {
+    /// The file couldn't be read.
+    Io(std::io::Error),
+    /// The file isn't valid UTF-8 text.
+    NotText,
+    /// A line of the file couldn't be parsed, with its number starting at 1.
+    InvalidLine(usize),
+    /// The file has no `LUT_3D_SIZE`, or is a 1D table.
+    MissingSize,
+    /// The table has a `DOMAIN_MIN` or `DOMAIN_MAX` other than `0` and `1`.
+    UnsupportedDomain,
+    /// The number of entries doesn't match `LUT_3D_SIZE`, with the expected and found numbers.
+    WrongEntryCount(usize, usize),
+}
+
+impl Display for CubeLutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "couldn't read the LUT: {error}"),
+            Self::NotText => write!(f, "the LUT isn't a text file"),
+            Self::InvalidLine(line) => write!(f, "invalid line {line} in the LUT"),
+            Self::MissingSize => write!(f, "the LUT has no LUT_3D_SIZE"),
+            Self::UnsupportedDomain => write!(f, "only LUTs over the 0 to 1 domain are supported"),
+            Self::WrongEntryCount(expected, found) => {
+                write!(f, "the LUT should have {expected} entries, found {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CubeLutError {}
+
+impl AssetLoader for CubeLutLoader {
+    type Asset = Image;
+    type Settings = ();
+    type Error = CubeLutError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Image, CubeLutError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await.map_err(CubeLutError::Io)?;
+        let text = std::str::from_utf8(&bytes).map_err(|_| CubeLutError::NotText)?;
+        parse_cube(text)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cube"]
+    }
+}
+
+// The largest `LUT_3D_SIZE` accepted, well within the 3D texture limits of every adapter
+const MAX_CUBE_SIZE: u32 = 256;
+
+fn parse_cube(text: &str) -> Result<Image, CubeLutError> {
+    let mut size = None;
+    let mut data = Vec::new();
+    let mut entries = 0;
+    for (index, line) in text.lines().enumerate() {
+        let invalid = || CubeLutError::InvalidLine(index + 1);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("TITLE") {
+            continue;
+        }
+
+        let mut words = line.split_whitespace();
+        let Some(first) = words.next() else {
+            continue;
+        };
+        match first {
+            "LUT_3D_SIZE" => {
+                let value = words.next().and_then(|word| word.parse::<u32>().ok());
+                // Real tables stay far below the cap, larger ones are hostile or broken files
+                let valid = |size: &u32| (2..=MAX_CUBE_SIZE).contains(size);
+                size = Some((value.filter(valid).ok_or_else(invalid)?, index + 1));
+            }
+            "LUT_1D_SIZE" => return Err(CubeLutError::MissingSize),
+            "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                let expected = if first == "DOMAIN_MIN" { 0.0 } else { 1.0 };
+                for word in words {
+                    if word.parse::<f32>().map_err(|_| invalid())? != expected {
+                        return Err(CubeLutError::UnsupportedDomain);
+                    }
+                }
+            }
+            // Keywords of other tools, like LUT_3D_INPUT_RANGE, are ignored
+            keyword if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {}
+            _ => {
+                if line.split_whitespace().count() != 3 {
+                    return Err(invalid());
+                }
+                let mut rgb = [0.0f32; 3];
+                for (channel, word) in rgb.iter_mut().zip(line.split_whitespace()) {
+                    *channel = word.parse().map_err(|_| invalid())?;
+                }
+                // Red varies fastest, then green then blue, the layout of a 3D texture indexed
+                // by red, green and blue
+                for value in [rgb[0], rgb[1], rgb[2], 1.0] {
+                    data.extend_from_slice(&value.to_le_bytes());
+                }
+                entries += 1;
+            }
+        }
+    }
+
+    let (size, size_line) = size.ok_or(CubeLutError::MissingSize)?;
+    let expected = (size as usize)
+        .checked_mul(size as usize)
+        .and_then(|area| area.checked_mul(size as usize))
+        .ok_or(CubeLutError::InvalidLine(size_line))?;
+    if entries != expected {
+        return Err(CubeLutError::WrongEntryCount(expected, entries));
+    }
+
+    Ok(Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: size,
+        },
+        TextureDimension::D3,
+        data,
+        TextureFormat::Rgba32Float,
+        RenderAssetUsages::RENDER_WORLD,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTITY_2: &str = "TITLE \"identity\"
+LUT_3D_SIZE 2
+DOMAIN_MIN 0 0 0
+DOMAIN_MAX 1 1 1
+0 0 0
+1 0 0
+0 1 0
+1 1 0
+0 0 1
+1 0 1
+0 1 1
+1 1 1
+";
+
+    #[test]
+    fn parses_a_3d_lut() {
+        let image = parse_cube(IDENTITY_2).unwrap();
+        let size = image.texture_descriptor.size;
+        assert_eq!(
+            (size.width, size.height, size.depth_or_array_layers),
+            (2, 2, 2)
+        );
+        assert_eq!(image.texture_descriptor.dimension, TextureDimension::D3);
+        // Eight RGBA entries of four bytes per channel
+        assert_eq!(image.data.as_ref().map(Vec::len), Some(8 * 4 * 4));
+    }
+
+    #[test]
+    fn rejects_a_wrong_entry_count() {
+        let text = IDENTITY_2.replace("1 1 1\n", "");
+        assert!(matches!(
+            parse_cube(&text),
+            Err(CubeLutError::WrongEntryCount(8, 7))
+        ));
+    }
+
+    #[test]
+    fn rejects_an_unsupported_domain() {
+        let text = IDENTITY_2.replace("DOMAIN_MAX 1 1 1", "DOMAIN_MAX 2 2 2");
+        assert!(matches!(
+            parse_cube(&text),
+            Err(CubeLutError::UnsupportedDomain)
+        ));
+    }
+
+    #[test]
+    fn rejects_a_1d_lut() {
+        let text = "LUT_1D_SIZE 2\n0 0 0\n1 1 1\n";
+        assert!(matches!(parse_cube(text), Err(CubeLutError::MissingSize)));
+    }
+
+    #[test]
+    fn rejects_an_oversized_lut() {
+        for size in ["257", "1626", "4294967295"] {
+            let text = format!("LUT_3D_SIZE {size}\n0 0 0\n");
+            assert!(matches!(
+                parse_cube(&text),
+                Err(CubeLutError::InvalidLine(1))
+            ));
+        }
+    }
+}
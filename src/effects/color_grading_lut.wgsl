@@ -0,0 +1,43 @@
+// Grades the view with a 3D lookup table, indexed by the color in the working space of the LUT.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::{to_working_space, from_working_space}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct ColorGradingLutSettings {
+    intensity: f32,
+}
+@group(0) @binding(2) var<uniform> settings: ColorGradingLutSettings;
+
+@group(1) @binding(0) var lut_texture: texture_3d<f32>;
+
+// Trilinear filtering of the LUT by hand, as not every adapter can filter its format
+fn sample_lut(color: vec3<f32>) -> vec3<f32> {
+    let size = vec3<i32>(textureDimensions(lut_texture));
+    // The entries are at the corners of the domain, not at the centers of texels
+    let position = clamp(color, vec3(0.0), vec3(1.0)) * vec3<f32>(size - 1);
+    let base = min(vec3<i32>(floor(position)), size - 2);
+    let f = position - vec3<f32>(base);
+
+    let c000 = textureLoad(lut_texture, base, 0).rgb;
+    let c100 = textureLoad(lut_texture, base + vec3(1, 0, 0), 0).rgb;
+    let c010 = textureLoad(lut_texture, base + vec3(0, 1, 0), 0).rgb;
+    let c110 = textureLoad(lut_texture, base + vec3(1, 1, 0), 0).rgb;
+    let c001 = textureLoad(lut_texture, base + vec3(0, 0, 1), 0).rgb;
+    let c101 = textureLoad(lut_texture, base + vec3(1, 0, 1), 0).rgb;
+    let c011 = textureLoad(lut_texture, base + vec3(0, 1, 1), 0).rgb;
+    let c111 = textureLoad(lut_texture, base + vec3(1, 1, 1), 0).rgb;
+
+    let c00 = mix(c000, c100, f.x);
+    let c10 = mix(c010, c110, f.x);
+    let c01 = mix(c001, c101, f.x);
+    let c11 = mix(c011, c111, f.x);
+    return mix(mix(c00, c10, f.y), mix(c01, c11, f.y), f.z);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let graded = from_working_space(sample_lut(to_working_space(color.rgb)));
+    return vec4(mix(color.rgb, graded, settings.intensity), color.a);
+}
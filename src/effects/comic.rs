@@ -0,0 +1,118 @@
+use crate::{PostProcessFusedPlugin, PostProcessPlacement};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+    shader::load_shader_library,
+};
+
+const INK_SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/comic_ink.wgsl";
+const POSTERIZE_SHADER_PATH: &str =
+    "embedded://bevy_post_process_util/effects/comic_posterize.wgsl";
+const HALFTONE_SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/comic_halftone.wgsl";
+const PAPER_SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/comic_paper.wgsl";
+
+/// A comic book look, run on the cameras with [`ComicSettings`].
+///
+/// Inks the edges, flattens the colors, lays halftone dots over the shadows and prints the result
+/// on grainy paper, in a single pass: the four steps are chained by a
+/// [`PostProcessFusedPlugin`], all reading the same settings. Since a fused step only sees its own
+/// pixel, the edges are found from the change of luminance between the pixels the GPU shades
+/// together, which is cheaper but coarser than the
+/// [`EdgeDetectionPlugin`](crate::effects::EdgeDetectionPlugin). Runs after the tonemapping by
+/// default.
+pub struct ComicPlugin {
+    /// Where the effect runs.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for ComicPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+        }
+    }
+}
+
+/// The settings of the [`ComicPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct ComicSettings {
+    /// The color of the outlines and of the halftone dots, its alpha being their opacity.
+    pub ink: LinearRgba,
+    /// The color of the paper, multiplying the image.
+    pub paper: LinearRgba,
+    /// How many levels every channel is flattened to, at least 2.
+    pub levels: u32,
+    /// The relative change of luminance between two pixels inked as an edge. Lower values ink
+    /// more edges.
+    pub outline_threshold: f32,
+    /// The luminance below which the halftone dots appear, growing as the shadows darken.
+    pub shadow_threshold: f32,
+    /// The distance between the halftone dots, in pixels.
+    pub dot_spacing: f32,
+    /// The rotation of the screen of halftone dots, in radians.
+    pub dot_angle: f32,
+    /// How much the paper grain darkens and lightens the image, 0 leaving a smooth paper.
+    pub grain: f32,
+    /// The size of the paper grain, in pixels.
+    pub grain_size: f32,
+}
+
+impl Default for ComicSettings {
+    fn default() -> Self {
+        Self {
+            ink: LinearRgba::BLACK,
+            paper: LinearRgba::rgb(1.0, 0.95, 0.85),
+            levels: 5,
+            outline_threshold: 0.3,
+            shadow_threshold: 0.2,
+            dot_spacing: 5.0,
+            dot_angle: 45f32.to_radians(),
+            grain: 0.15,
+            grain_size: 2.0,
+        }
+    }
+}
+
+/// The render graph label of the [`ComicPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Comic;
+
+impl Plugin for ComicPlugin {
+    fn build(&self, app: &mut App) {
+        load_shader_library!(app, "comic.wgsl");
+        embedded_asset!(app, "comic_ink.wgsl");
+        embedded_asset!(app, "comic_posterize.wgsl");
+        embedded_asset!(app, "comic_halftone.wgsl");
+        embedded_asset!(app, "comic_paper.wgsl");
+        #[cfg(feature = "inspector")]
+        crate::effects::register_inspector_ranges::<ComicSettings>(
+            app,
+            &[
+                ("outline_threshold", 0.0..=1.0),
+                ("shadow_threshold", 0.0..=1.0),
+                ("dot_spacing", 2.0..=32.0),
+                ("grain", 0.0..=1.0),
+                ("grain_size", 0.5..=8.0),
+            ],
+        );
+        app.register_type::<ComicSettings>();
+
+        // The edges are found on the colors of the scene, before the posterization adds its own
+        app.add_plugins(
+            PostProcessFusedPlugin::new(Comic)
+                .with_effect::<ComicSettings>(INK_SHADER_PATH, "bevy_post_process::comic_ink")
+                .with_effect::<ComicSettings>(
+                    POSTERIZE_SHADER_PATH,
+                    "bevy_post_process::comic_posterize",
+                )
+                .with_effect::<ComicSettings>(
+                    HALFTONE_SHADER_PATH,
+                    "bevy_post_process::comic_halftone",
+                )
+                .with_effect::<ComicSettings>(PAPER_SHADER_PATH, "bevy_post_process::comic_paper")
+                .with_placement(self.placement),
+        );
+    }
+}
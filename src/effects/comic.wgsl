@@ -0,0 +1,36 @@
+#define_import_path bevy_post_process::comic
+
+// The settings shared by the steps of the comic stack, each binding them at its own index
+struct ComicSettings {
+    ink: vec4<f32>,
+    paper: vec4<f32>,
+    levels: u32,
+    outline_threshold: f32,
+    shadow_threshold: f32,
+    dot_spacing: f32,
+    dot_angle: f32,
+    grain: f32,
+    grain_size: f32,
+}
+
+// The pixel of a UV. The steps don't see the screen texture, but the UV moves by one pixel between
+// neighboring fragments
+fn uv_to_pixel(uv: vec2<f32>) -> vec2<f32> {
+    return uv / max(fwidth(uv), vec2(1.0e-6));
+}
+
+fn hash(cell: vec2<f32>) -> f32 {
+    return fract(sin(dot(cell, vec2(127.1, 311.7))) * 43758.5453);
+}
+
+// Smooth noise from 0 to 1, varying over one unit of `position`
+fn value_noise(position: vec2<f32>) -> f32 {
+    let cell = floor(position);
+    let f = fract(position);
+    let t = f * f * (3.0 - 2.0 * f);
+    let a = hash(cell);
+    let b = hash(cell + vec2(1.0, 0.0));
+    let c = hash(cell + vec2(0.0, 1.0));
+    let d = hash(cell + vec2(1.0, 1.0));
+    return mix(mix(a, b, t.x), mix(c, d, t.x), t.y);
+}
@@ -0,0 +1,28 @@
+#define_import_path bevy_post_process::comic_halftone
+
+// Lays a screen of ink dots over the shadows, the dots growing as the shadows darken.
+#import bevy_post_process::comic::{ComicSettings, uv_to_pixel}
+#import bevy_post_process::utils::luminance
+
+@group(0) @binding(#{BEVY_POST_PROCESS_COMIC_HALFTONE_SETTINGS_BINDING}) var<uniform> settings: ComicSettings;
+
+const PI: f32 = 3.14159265;
+
+fn apply(color: vec4<f32>, uv: vec2<f32>) -> vec4<f32> {
+    let luma = luminance(max(color.rgb, vec3(0.0)));
+    let threshold = max(settings.shadow_threshold, 1.0e-4);
+    let coverage = clamp(1.0 - luma / threshold, 0.0, 1.0);
+
+    let c = cos(settings.dot_angle);
+    let s = sin(settings.dot_angle);
+    let position = mat2x2(c, s, -s, c) * uv_to_pixel(uv) / max(settings.dot_spacing, 1.0);
+    let distance_to_center = length(fract(position) - 0.5);
+
+    // A dot covering `coverage` of its cell, in cell units
+    let radius = sqrt(coverage / PI);
+    let edge = max(fwidth(distance_to_center), 1.0e-4);
+    let inked = 1.0 - smoothstep(radius - edge, radius + edge, distance_to_center);
+    // Keeps the antialiased edge from leaving specks where there is no shadow
+    let ink = inked * step(1.0e-4, coverage) * settings.ink.a;
+    return vec4(mix(color.rgb, settings.ink.rgb, ink), color.a);
+}
@@ -0,0 +1,16 @@
+#define_import_path bevy_post_process::comic_ink
+
+// Inks the edges of the scene, found from the change of luminance between the fragments shaded
+// together, before the other steps flatten the colors.
+#import bevy_post_process::comic::ComicSettings
+#import bevy_post_process::utils::luminance
+
+@group(0) @binding(#{BEVY_POST_PROCESS_COMIC_INK_SETTINGS_BINDING}) var<uniform> settings: ComicSettings;
+
+fn apply(color: vec4<f32>, uv: vec2<f32>) -> vec4<f32> {
+    let luma = luminance(max(color.rgb, vec3(0.0)));
+    let gradient = fwidth(luma) / max(luma, 0.05);
+    let threshold = max(settings.outline_threshold, 1.0e-4);
+    let ink = smoothstep(threshold, threshold * 1.5, gradient) * settings.ink.a;
+    return vec4(mix(color.rgb, settings.ink.rgb, ink), color.a);
+}
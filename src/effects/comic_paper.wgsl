@@ -0,0 +1,14 @@
+#define_import_path bevy_post_process::comic_paper
+
+// Prints the image on tinted, grainy paper.
+#import bevy_post_process::comic::{ComicSettings, uv_to_pixel, value_noise}
+
+@group(0) @binding(#{BEVY_POST_PROCESS_COMIC_PAPER_SETTINGS_BINDING}) var<uniform> settings: ComicSettings;
+
+fn apply(color: vec4<f32>, uv: vec2<f32>) -> vec4<f32> {
+    let position = uv_to_pixel(uv) / max(settings.grain_size, 1.0);
+    // Two octaves, so the fibers of the paper don't line up on the noise cells
+    let noise = value_noise(position) * 0.65 + value_noise(position * 2.7 + 17.0) * 0.35;
+    let grain = 1.0 + settings.grain * (noise - 0.5);
+    return vec4(color.rgb * settings.paper.rgb * grain, color.a);
+}
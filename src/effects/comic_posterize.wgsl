@@ -0,0 +1,13 @@
+#define_import_path bevy_post_process::comic_posterize
+
+// Flattens the colors to a few levels per channel of sRGB.
+#import bevy_post_process::comic::ComicSettings
+#import bevy_post_process::color_space::{linear_to_srgb, srgb_to_linear}
+
+@group(0) @binding(#{BEVY_POST_PROCESS_COMIC_POSTERIZE_SETTINGS_BINDING}) var<uniform> settings: ComicSettings;
+
+fn apply(color: vec4<f32>, uv: vec2<f32>) -> vec4<f32> {
+    let steps = f32(max(settings.levels, 2u) - 1u);
+    let encoded = linear_to_srgb(max(color.rgb, vec3(0.0)));
+    return vec4(srgb_to_linear(round(encoded * steps) / steps), color.a);
+}
@@ -0,0 +1,73 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str =
+    "embedded://bevy_post_process_util/effects/contrast_adaptive_sharpening.wgsl";
+
+/// Contrast adaptive sharpening in the style of AMD FidelityFX CAS, run on the cameras with
+/// [`ContrastAdaptiveSharpeningSettings`].
+///
+/// Every pixel is sharpened less the more contrast there already is around it, which restores
+/// the details lost to a low render resolution or TAA without the halos of a fixed kernel. Runs
+/// after the upscaling by default, sharpening the image at the resolution of the output, UI
+/// included. It expects colors between 0 and 1, so placing it before the tonemapping isn't
+/// supported.
+pub struct ContrastAdaptiveSharpeningPlugin {
+    /// Where the sharpening runs.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for ContrastAdaptiveSharpeningPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterUpscaling,
+        }
+    }
+}
+
+/// The settings of the [`ContrastAdaptiveSharpeningPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct ContrastAdaptiveSharpeningSettings {
+    /// How much the image is sharpened, from 0 to 1.
+    pub sharpness: f32,
+}
+
+impl Default for ContrastAdaptiveSharpeningSettings {
+    fn default() -> Self {
+        Self { sharpness: 0.6 }
+    }
+}
+
+/// The render graph label of the [`ContrastAdaptiveSharpeningPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ContrastAdaptiveSharpening;
+
+impl Plugin for ContrastAdaptiveSharpeningPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "contrast_adaptive_sharpening.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<ContrastAdaptiveSharpeningSettings>(
+            app,
+            &[("sharpness", 0.0..=1.0)],
+        );
+        app.register_type::<ContrastAdaptiveSharpeningSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<ContrastAdaptiveSharpeningSettings, _>::new(
+                SHADER_PATH,
+                ContrastAdaptiveSharpening,
+                Some("contrast_adaptive_sharpening"),
+                "contrast_adaptive_sharpening_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view(),
+        );
+    }
+}
@@ -0,0 +1,49 @@
+// Contrast adaptive sharpening, after AMD FidelityFX CAS. A cross shaped kernel whose negative
+// lobe shrinks where the 3x3 neighborhood already has a lot of contrast.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct ContrastAdaptiveSharpeningSettings {
+    sharpness: f32,
+}
+@group(0) @binding(2) var<uniform> settings: ContrastAdaptiveSharpeningSettings;
+
+fn load(pixel: vec2<i32>, offset: vec2<i32>) -> vec3<f32> {
+    let size = vec2<i32>(textureDimensions(screen_texture));
+    let color = textureLoad(screen_texture, clamp(pixel + offset, vec2(0), size - 1), 0).rgb;
+    return clamp(color, vec3(0.0), vec3(1.0));
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(in.position.xy);
+    // a b c
+    // d e f
+    // g h i
+    let a = load(pixel, vec2(-1, -1));
+    let b = load(pixel, vec2(0, -1));
+    let c = load(pixel, vec2(1, -1));
+    let d = load(pixel, vec2(-1, 0));
+    let e = textureLoad(screen_texture, pixel, 0);
+    let f = load(pixel, vec2(1, 0));
+    let g = load(pixel, vec2(-1, 1));
+    let h = load(pixel, vec2(0, 1));
+    let i = load(pixel, vec2(1, 1));
+    let center = clamp(e.rgb, vec3(0.0), vec3(1.0));
+
+    // Soft minimum and maximum, the cross plus the whole neighborhood
+    let cross_min = min(min(min(b, d), min(center, f)), h);
+    let cross_max = max(max(max(b, d), max(center, f)), h);
+    let soft_min = cross_min + min(cross_min, min(min(a, c), min(g, i)));
+    let soft_max = cross_max + max(cross_max, max(max(a, c), max(g, i)));
+
+    // How far the neighborhood is from clipping, 0 where it already spans the whole range
+    let headroom = min(soft_min, 2.0 - soft_max) / max(soft_max, vec3(1.0e-5));
+    let amplitude = sqrt(clamp(headroom, vec3(0.0), vec3(1.0)));
+
+    let peak = -1.0 / mix(8.0, 5.0, clamp(settings.sharpness, 0.0, 1.0));
+    let weight = amplitude * peak;
+    let sharpened = ((b + d + f + h) * weight + center) / (1.0 + 4.0 * weight);
+    return vec4(clamp(sharpened, vec3(0.0), vec3(1.0)), e.a);
+}
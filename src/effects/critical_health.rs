@@ -0,0 +1,114 @@
+use crate::{effects, PostProcessColorSpace, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/critical_health.wgsl";
+
+/// The look of a player close to death, run on the cameras with [`CriticalHealthSettings`].
+///
+/// Everything is driven by the severity, from 0 at full health to 1 on the brink: the colors
+/// drain, the shadows and highlights are crushed, a dark vignette beats like a heart racing
+/// faster, and the edges of the screen blur. Gameplay only has to write the severity, the other
+/// settings tune how far each part goes at a severity of 1. Runs after the tonemapping by
+/// default, on sRGB colors so the contrast is the one seen on the screen.
+pub struct CriticalHealthPlugin {
+    /// Where the effect runs.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for CriticalHealthPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+        }
+    }
+}
+
+/// The settings of the [`CriticalHealthPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct CriticalHealthSettings {
+    /// The color of the vignette, its alpha scaling how much it covers the view.
+    pub vignette_color: LinearRgba,
+    /// How critical the health is, from 0 for no effect to 1 for the full effect.
+    pub severity: f32,
+    /// How much the colors drain at full severity, from 0 to 1 for black and white.
+    pub desaturation: f32,
+    /// How much the contrast rises at full severity, 0 keeping it.
+    pub contrast: f32,
+    /// The heart rate at the lowest severity, in beats per minute.
+    pub min_heart_rate: f32,
+    /// The heart rate at full severity, in beats per minute.
+    pub max_heart_rate: f32,
+    /// How far the edges of the screen blur at full severity, in pixels. 0 turns the blur off.
+    pub edge_blur: f32,
+    /// How far the current heartbeat has gone, from 0 to 1. Advanced every frame at the heart
+    /// rate, so changing the severity doesn't skip beats.
+    pub heartbeat_phase: f32,
+}
+
+impl Default for CriticalHealthSettings {
+    fn default() -> Self {
+        Self {
+            vignette_color: LinearRgba::rgb(0.15, 0.0, 0.0),
+            severity: 0.0,
+            desaturation: 0.85,
+            contrast: 0.4,
+            min_heart_rate: 70.0,
+            max_heart_rate: 140.0,
+            edge_blur: 4.0,
+            heartbeat_phase: 0.0,
+        }
+    }
+}
+
+/// The render graph label of the [`CriticalHealthPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct CriticalHealth;
+
+impl Plugin for CriticalHealthPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "critical_health.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<CriticalHealthSettings>(
+            app,
+            &[
+                ("severity", 0.0..=1.0),
+                ("desaturation", 0.0..=1.0),
+                ("contrast", 0.0..=2.0),
+                ("min_heart_rate", 30.0..=200.0),
+                ("max_heart_rate", 30.0..=200.0),
+                ("edge_blur", 0.0..=32.0),
+            ],
+        );
+        app.register_type::<CriticalHealthSettings>()
+            .add_systems(Update, beat_heart);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<CriticalHealthSettings, _>::new(
+                SHADER_PATH,
+                CriticalHealth,
+                Some("critical_health"),
+                "critical_health_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_color_space(PostProcessColorSpace::Srgb)
+            .without_view(),
+        );
+    }
+}
+
+fn beat_heart(mut settings: Query<&mut CriticalHealthSettings>, time: Res<Time>) {
+    for mut settings in &mut settings {
+        let severity = settings.severity.clamp(0.0, 1.0);
+        let heart_rate = settings.min_heart_rate.lerp(settings.max_heart_rate, severity);
+        // Counted in beats and wrapped to one, so the phase keeps its precision
+        settings.heartbeat_phase =
+            (settings.heartbeat_phase + heart_rate / 60.0 * time.delta_secs()).rem_euclid(1.0);
+    }
+}
@@ -0,0 +1,64 @@
+// Critical health. The severity drains the colors, raises the contrast, blurs the edges of the
+// screen and darkens them with a vignette beating like a heart.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::utils::saturate_color
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct CriticalHealthSettings {
+    vignette_color: vec4<f32>,
+    severity: f32,
+    desaturation: f32,
+    contrast: f32,
+    min_heart_rate: f32,
+    max_heart_rate: f32,
+    edge_blur: f32,
+    heartbeat_phase: f32,
+}
+@group(0) @binding(2) var<uniform> settings: CriticalHealthSettings;
+
+const TAU: f32 = 6.28318530718;
+const BLUR_SAMPLES: u32 = 8u;
+
+// The two thumps of a heartbeat over its phase, a strong one followed by a weaker one
+fn heartbeat(phase: f32) -> f32 {
+    let first = exp(-pow((phase - 0.08) / 0.05, 2.0));
+    let second = exp(-pow((phase - 0.28) / 0.06, 2.0)) * 0.6;
+    return first + second;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let severity = clamp(settings.severity, 0.0, 1.0);
+    if severity <= 0.0 {
+        return color;
+    }
+
+    // From 0 in the center to 1 in the corners
+    let edge = clamp(length(in.uv * 2.0 - 1.0) / sqrt(2.0), 0.0, 1.0);
+
+    // A ring of samples, widening toward the edges
+    var rgb = color.rgb;
+    let radius = settings.edge_blur * severity * edge * edge;
+    if radius > 0.5 {
+        let texel = 1.0 / vec2<f32>(textureDimensions(screen_texture));
+        for (var i = 0u; i < BLUR_SAMPLES; i++) {
+            let angle = f32(i) / f32(BLUR_SAMPLES) * TAU;
+            let offset = vec2(cos(angle), sin(angle)) * radius * texel;
+            rgb += textureSampleLevel(screen_texture, texture_sampler, in.uv + offset, 0.0).rgb;
+        }
+        rgb /= f32(BLUR_SAMPLES + 1u);
+    }
+
+    rgb = saturate_color(rgb, 1.0 - clamp(settings.desaturation, 0.0, 1.0) * severity);
+    // The contrast pivots around the middle gray of the screen
+    rgb = clamp((rgb - 0.5) * (1.0 + settings.contrast * severity) + 0.5, vec3(0.0), vec3(1.0));
+
+    // The vignette closes in with the severity and swells on every beat
+    let beat = heartbeat(settings.heartbeat_phase);
+    let reach = mix(1.0, 0.35, severity) - beat * 0.1 * severity;
+    let vignette = smoothstep(reach, reach + 0.5, edge * 1.2) * severity;
+    let amount = clamp(vignette * (0.7 + 0.3 * beat) * settings.vignette_color.a, 0.0, 1.0);
+    return vec4(mix(rgb, settings.vignette_color.rgb, amount), color.a);
+}
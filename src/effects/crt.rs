@@ -0,0 +1,116 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/crt.wgsl";
+
+/// A CRT monitor look, run on the cameras with [`CrtSettings`].
+///
+/// Bends the view like the glass of a tube, then adds scanlines, the phosphor pattern of a shadow
+/// mask or an aperture grille, a glow around the bright areas and a slight bleed of the red and
+/// blue channels. Runs after the upscaling by default, so the scanlines and the phosphors stay
+/// crisp at the resolution of the output and cover the UI like the rest of the screen. Use
+/// [`PostProcessPlacement::AfterUi`] to render them at the resolution of the scene instead.
+pub struct CrtPlugin {
+    /// Where the effect runs.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for CrtPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterUpscaling,
+        }
+    }
+}
+
+/// The settings of the [`CrtPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct CrtSettings {
+    /// How much the screen bulges, 0 being flat. The corners outside the tube are black.
+    pub curvature: f32,
+    /// How many scanlines cover the height of the view, like 240 for a console of the 90s.
+    pub scanline_count: f32,
+    /// How dark the gaps between the scanlines are, from 0 to 1.
+    pub scanline_intensity: f32,
+    /// The phosphor pattern, one of [`CrtSettings::MASK_NONE`], [`CrtSettings::SHADOW_MASK`] or
+    /// [`CrtSettings::APERTURE_GRILLE`].
+    pub mask: u32,
+    /// How strongly the phosphor pattern tints the view, from 0 to 1.
+    pub mask_intensity: f32,
+    /// The width of a triad of phosphors, in pixels of the output.
+    pub mask_size: f32,
+    /// The strength of the glow around the bright areas, 0 turning it off.
+    pub halation: f32,
+    /// The radius of the glow, in pixels.
+    pub halation_radius: f32,
+    /// How far the red and blue channels bleed sideways, in pixels.
+    pub chroma_bleed: f32,
+}
+
+impl CrtSettings {
+    /// No phosphor pattern.
+    pub const MASK_NONE: u32 = 0;
+    /// Triads of round phosphors, every other row shifted by half a triad, like most TVs.
+    pub const SHADOW_MASK: u32 = 1;
+    /// Vertical stripes of phosphors, like Trinitron monitors.
+    pub const APERTURE_GRILLE: u32 = 2;
+}
+
+impl Default for CrtSettings {
+    fn default() -> Self {
+        Self {
+            curvature: 0.1,
+            scanline_count: 240.0,
+            scanline_intensity: 0.4,
+            mask: Self::SHADOW_MASK,
+            mask_intensity: 0.3,
+            mask_size: 3.0,
+            halation: 0.15,
+            halation_radius: 6.0,
+            chroma_bleed: 1.0,
+        }
+    }
+}
+
+/// The render graph label of the [`CrtPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Crt;
+
+impl Plugin for CrtPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "crt.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<CrtSettings>(
+            app,
+            &[
+                ("curvature", 0.0..=0.5),
+                ("scanline_count", 60.0..=1080.0),
+                ("scanline_intensity", 0.0..=1.0),
+                ("mask_intensity", 0.0..=1.0),
+                ("mask_size", 1.0..=12.0),
+                ("halation", 0.0..=2.0),
+                ("halation_radius", 0.0..=16.0),
+                ("chroma_bleed", 0.0..=8.0),
+            ],
+        );
+        app.register_type::<CrtSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<CrtSettings, _>::new(
+                SHADER_PATH,
+                Crt,
+                Some("crt"),
+                "crt_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view(),
+        );
+    }
+}
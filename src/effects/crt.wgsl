@@ -0,0 +1,77 @@
+// A CRT monitor: barrel curvature, chroma bleed, halation, scanlines and a phosphor mask.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct CrtSettings {
+    curvature: f32,
+    scanline_count: f32,
+    scanline_intensity: f32,
+    mask: u32,
+    mask_intensity: f32,
+    mask_size: f32,
+    halation: f32,
+    halation_radius: f32,
+    chroma_bleed: f32,
+}
+@group(0) @binding(2) var<uniform> settings: CrtSettings;
+
+const PI: f32 = 3.14159265;
+const MASK_SHADOW: u32 = 1u;
+const MASK_APERTURE_GRILLE: u32 = 2u;
+const HALATION_SAMPLES: u32 = 12u;
+
+fn sample(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(screen_texture, texture_sampler, uv, 0.0).rgb;
+}
+
+// Pushes the UV outward the further it is from the center, like the bulge of the tube
+fn curve(uv: vec2<f32>) -> vec2<f32> {
+    var centered = uv * 2.0 - 1.0;
+    centered *= 1.0 + settings.curvature * centered.yx * centered.yx;
+    return centered * 0.5 + 0.5;
+}
+
+// The tint of the phosphor under this pixel of the output
+fn phosphor_mask(position: vec2<f32>) -> vec3<f32> {
+    var cell = position / max(settings.mask_size / 3.0, 1.0);
+    if settings.mask == MASK_SHADOW {
+        // Every other row of triads is shifted by half a triad
+        let row = floor(cell.y / 3.0);
+        cell.x += select(0.0, 1.5, row % 2.0 == 1.0);
+    } else if settings.mask != MASK_APERTURE_GRILLE {
+        return vec3(1.0);
+    }
+    let channel = u32(floor(cell.x)) % 3u;
+    var mask = vec3(0.0);
+    mask[channel] = 1.0;
+    return mix(vec3(1.0), mask * 3.0, settings.mask_intensity / 3.0);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let uv = curve(in.uv);
+    if any(uv < vec2(0.0)) || any(uv > vec2(1.0)) {
+        return vec4(0.0, 0.0, 0.0, 1.0);
+    }
+    let texel = 1.0 / vec2<f32>(textureDimensions(screen_texture));
+
+    // The electron beams of the red and blue guns land slightly off
+    let bleed = vec2(settings.chroma_bleed * texel.x, 0.0);
+    var color = vec3(sample(uv + bleed).r, sample(uv).g, sample(uv - bleed).b);
+
+    // The light scattered in the glass glows around the bright areas
+    var glow = vec3(0.0);
+    for (var i = 0u; i < HALATION_SAMPLES; i++) {
+        let angle = f32(i) / f32(HALATION_SAMPLES) * 2.0 * PI;
+        glow += sample(uv + vec2(cos(angle), sin(angle)) * settings.halation_radius * texel);
+    }
+    color += glow / f32(HALATION_SAMPLES) * settings.halation;
+
+    // Dark gaps between the lines of the beam, brightest at the middle of a line
+    let scanline = 0.5 - 0.5 * cos(uv.y * settings.scanline_count * 2.0 * PI);
+    color *= mix(1.0, scanline, settings.scanline_intensity);
+
+    color *= phosphor_mask(in.position.xy);
+    return vec4(color, 1.0);
+}
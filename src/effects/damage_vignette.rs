@@ -0,0 +1,153 @@
+use crate::{effects, PostProcessColorSpace, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/damage_vignette.wgsl";
+
+/// A red vignette showing where the damage comes from, run on the cameras with
+/// [`DamageVignetteSettings`].
+///
+/// Gameplay calls [`DamageVignetteSettings::hit`] or [`DamageVignetteSettings::hit_from`] when the
+/// player is hurt, which raises the intensity and turns the vignette toward the edge of the
+/// screen the hit came from. The vignette pulses while it shows, and the plugin lets the
+/// intensity decay every frame, so it fades on its own. The decay is built in because the crate
+/// has no tweening subsystem, and the animation clips of the `animation` feature play fixed
+/// curves, which can't restart from the intensity a hit left. Runs after the tonemapping by
+/// default, on sRGB colors so the color is the one seen on the screen.
+pub struct DamageVignettePlugin {
+    /// Where the vignette runs.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for DamageVignettePlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+        }
+    }
+}
+
+/// The settings of the [`DamageVignettePlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct DamageVignetteSettings {
+    /// The color of the vignette, its alpha scaling how much it covers the view.
+    pub color: LinearRgba,
+    /// The direction of the edge of the screen the damage comes from, with +X to the right and
+    /// +Y to the bottom. Zero darkens every edge alike.
+    pub direction: Vec2,
+    /// How strong the vignette is, from 0 for invisible to 1. Raised by the hits and decayed by
+    /// the plugin every frame.
+    pub intensity: f32,
+    /// How fast the intensity decays, the fraction of it lost every second being
+    /// `1 - exp(-decay)`.
+    pub decay: f32,
+    /// How much the vignette gathers on the edge of the direction, from 0 for every edge alike to
+    /// 1 for that edge only.
+    pub directionality: f32,
+    /// The distance from the center of the screen the vignette starts at, 1 being the middle of
+    /// the edges.
+    pub radius: f32,
+    /// The distance over which the vignette grows from its radius to its full strength.
+    pub softness: f32,
+    /// How many times per second the vignette pulses.
+    pub pulse_frequency: f32,
+    /// How much the pulses dim the vignette between two beats, from 0 for steady to 1.
+    pub pulse_amount: f32,
+    /// The time the pulses are animated with, in seconds. Written by the plugin every frame,
+    /// wrapping like [`Time::elapsed_secs_wrapped`].
+    pub time: f32,
+}
+
+impl DamageVignetteSettings {
+    /// Add a hit of `amount` coming from the edge of the screen in `direction`, with +X to the
+    /// right and +Y to the bottom.
+    ///
+    /// The direction is blended with the one of the previous hits, weighted by their remaining
+    /// intensity, so the vignette turns smoothly between the hits.
+    pub fn hit(&mut self, direction: Vec2, amount: f32) {
+        let amount = amount.max(0.0);
+        let blended = self.direction * self.intensity + direction.normalize_or_zero() * amount;
+        self.direction = blended.normalize_or_zero();
+        self.intensity = (self.intensity + amount).min(1.0);
+    }
+
+    /// Add a hit of `amount` coming from `source` in world space, for the camera at `camera`.
+    ///
+    /// The hits from in front of the camera come from the top of the screen, the ones from
+    /// behind from the bottom, and the ones from the sides from the sides.
+    pub fn hit_from(&mut self, camera: &GlobalTransform, source: Vec3, amount: f32) {
+        let local = camera.affine().inverse().transform_point3(source);
+        self.hit(Vec2::new(local.x, local.z), amount);
+    }
+}
+
+impl Default for DamageVignetteSettings {
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::rgb(0.45, 0.0, 0.0),
+            direction: Vec2::ZERO,
+            intensity: 0.0,
+            decay: 1.5,
+            directionality: 0.7,
+            radius: 0.6,
+            softness: 0.6,
+            pulse_frequency: 1.5,
+            pulse_amount: 0.3,
+            time: 0.0,
+        }
+    }
+}
+
+/// The render graph label of the [`DamageVignettePlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct DamageVignette;
+
+impl Plugin for DamageVignettePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "damage_vignette.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<DamageVignetteSettings>(
+            app,
+            &[
+                ("intensity", 0.0..=1.0),
+                ("decay", 0.0..=10.0),
+                ("directionality", 0.0..=1.0),
+                ("radius", 0.0..=1.5),
+                ("softness", 0.0..=1.0),
+                ("pulse_frequency", 0.0..=10.0),
+                ("pulse_amount", 0.0..=1.0),
+            ],
+        );
+        app.register_type::<DamageVignetteSettings>()
+            .add_systems(Update, decay_damage);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<DamageVignetteSettings, _>::new(
+                SHADER_PATH,
+                DamageVignette,
+                Some("damage_vignette"),
+                "damage_vignette_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_color_space(PostProcessColorSpace::Srgb)
+            .without_view(),
+        );
+    }
+}
+
+fn decay_damage(mut settings: Query<&mut DamageVignetteSettings>, time: Res<Time>) {
+    for mut settings in &mut settings {
+        settings.time = time.elapsed_secs_wrapped();
+        if settings.intensity > 0.0 {
+            let decayed = settings.intensity * (-settings.decay * time.delta_secs()).exp();
+            // Snapped to 0 once invisible, so the pulses end
+            settings.intensity = if decayed < 1.0e-3 { 0.0 } else { decayed };
+        }
+    }
+}
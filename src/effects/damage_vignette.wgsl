@@ -0,0 +1,49 @@
+// A vignette of the damage color, gathered toward the edge of the screen the damage comes from
+// and pulsing with the time.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct DamageVignetteSettings {
+    color: vec4<f32>,
+    direction: vec2<f32>,
+    intensity: f32,
+    decay: f32,
+    directionality: f32,
+    radius: f32,
+    softness: f32,
+    pulse_frequency: f32,
+    pulse_amount: f32,
+    time: f32,
+}
+@group(0) @binding(2) var<uniform> settings: DamageVignetteSettings;
+
+const TAU: f32 = 6.28318530718;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    if settings.intensity <= 0.0 {
+        return color;
+    }
+
+    // From -1 to 1 on both axes, the corners being the farthest
+    let position = in.uv * 2.0 - 1.0;
+    let vignette = smoothstep(
+        settings.radius,
+        settings.radius + max(settings.softness, 1.0e-3),
+        length(position),
+    );
+
+    // The edges facing the direction keep the vignette, the opposite ones lose it
+    var facing = 1.0;
+    if any(settings.direction != vec2(0.0)) {
+        let alignment = dot(normalize(position + 1.0e-5), normalize(settings.direction));
+        facing = mix(1.0, max(alignment, 0.0), clamp(settings.directionality, 0.0, 1.0));
+    }
+
+    let beat = 0.5 + 0.5 * cos(settings.time * settings.pulse_frequency * TAU);
+    let pulse = 1.0 - clamp(settings.pulse_amount, 0.0, 1.0) * (1.0 - beat);
+    let amount = vignette * facing * pulse * min(settings.intensity, 1.0) * settings.color.a;
+    return vec4(mix(color.rgb, settings.color.rgb, clamp(amount, 0.0, 1.0)), color.a);
+}
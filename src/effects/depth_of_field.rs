@@ -0,0 +1,405 @@
+use crate::{
+    depth::depth_texture_entry, effects, filtering, resolution::scaled_size, PostProcessBindGroup,
+    PostProcessBindGroupLayout, PostProcessPlacement, PostProcessPlugin, PostProcessTexturePool,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::{core_3d::graph::Core3d, FullscreenShader},
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{ComponentUniforms, DynamicUniformIndex, ExtractComponent},
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::CachedTexture,
+        view::{Msaa, ViewDepthTexture, ViewTarget, ViewUniform, ViewUniformOffset, ViewUniforms},
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/depth_of_field.wgsl";
+
+/// The format of the half resolution gather, the blurred color and the coverage of the near field.
+const GATHER_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// A depth of field with shaped bokeh, run on the cameras with [`DepthOfFieldSettings`].
+///
+/// The circle of confusion of every pixel comes from its depth and the lens of the settings. The
+/// [`DepthOfFieldGather`] node blurs the view at half resolution, gathering the samples inside
+/// the bokeh shape, then the [`DepthOfField`] effect blends the sharp view with the blurred one
+/// by the circle of confusion. Placing it after the upscaling isn't supported.
+#[derive(Default)]
+pub struct DepthOfFieldPlugin {
+    /// Where the depth of field runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`DepthOfFieldPlugin`] on a camera.
+///
+/// The lens follows the thin lens model, like a physical camera: the longer the focal length and
+/// the lower the f-number, the shallower the depth of field.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct DepthOfFieldSettings {
+    /// The distance to the plane in focus, in world units.
+    pub focus_distance: f32,
+    /// The f-number of the aperture.
+    pub aperture_f_stops: f32,
+    /// The focal length of the lens, in meters. 0.05 is a 50 mm lens.
+    pub focal_length: f32,
+    /// The height of the sensor, in meters. Defaults to Super 35, 18.66 mm.
+    pub sensor_height: f32,
+    /// The largest radius of the bokeh, in pixels of the view.
+    pub max_coc_radius: f32,
+    /// The number of blades of the aperture, which gives the shape of the bokeh. 0 makes round
+    /// bokeh, 6 hexagonal ones.
+    pub blades: u32,
+}
+
+impl Default for DepthOfFieldSettings {
+    fn default() -> Self {
+        Self {
+            focus_distance: 10.0,
+            aperture_f_stops: 1.8,
+            focal_length: 0.05,
+            sensor_height: 0.01866,
+            max_coc_radius: 24.0,
+            blades: 0,
+        }
+    }
+}
+
+/// The render graph label of the effect pass of the [`DepthOfFieldPlugin`], blending the blurred
+/// view onto the sharp one.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct DepthOfField;
+
+/// The render graph label of the node gathering the bokeh of the [`DepthOfFieldPlugin`] at half
+/// resolution.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct DepthOfFieldGather;
+
+impl Plugin for DepthOfFieldPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "depth_of_field.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<DepthOfFieldSettings>(
+            app,
+            &[
+                ("focus_distance", 0.1..=100.0),
+                ("aperture_f_stops", 0.5..=22.0),
+                ("focal_length", 0.01..=0.3),
+                ("sensor_height", 0.005..=0.05),
+                ("max_coc_radius", 0.0..=32.0),
+            ],
+        );
+        app.register_type::<DepthOfFieldSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<DepthOfFieldSettings, _>::new(
+                SHADER_PATH,
+                DepthOfField,
+                Some("depth_of_field"),
+                "depth_of_field_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_depth()
+            .with_bind_group(gather_result_layout, prepare_gather_result_bind_groups),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (before, _) = self.placement.edges();
+        render_app
+            .init_resource::<SpecializedRenderPipelines<DepthOfFieldGatherPipeline>>()
+            .add_systems(Render, prepare_gathers.in_set(RenderSystems::PrepareResources))
+            .add_render_graph_node::<ViewNodeRunner<DepthOfFieldGatherNode>>(
+                Core3d,
+                DepthOfFieldGather,
+            )
+            .add_render_graph_edges(Core3d, (before, DepthOfFieldGather, DepthOfField));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<DepthOfFieldGatherPipeline>();
+    }
+}
+
+// The pipeline of the half resolution gather. Its group 0 has the layout of the effect, so both
+// passes share the declarations of the shader
+#[derive(Resource)]
+struct DepthOfFieldGatherPipeline {
+    layout: BindGroupLayout,
+    multisampled_layout: BindGroupLayout,
+    sampler: Sampler,
+    filterable: bool,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+}
+
+impl FromWorld for DepthOfFieldGatherPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let filterable = filtering::screen_filterable(world);
+        let [screen_texture, screen_sampler] = filtering::screen_entries(filterable);
+        let layout = |label: &'static str, multisampled: bool| {
+            render_device.create_bind_group_layout(
+                label,
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::FRAGMENT,
+                    (
+                        screen_texture,
+                        screen_sampler,
+                        uniform_buffer::<DepthOfFieldSettings>(true),
+                        uniform_buffer::<ViewUniform>(true),
+                        depth_texture_entry(multisampled),
+                    ),
+                ),
+            )
+        };
+
+        Self {
+            layout: layout("depth_of_field_gather_bind_group_layout", false),
+            multisampled_layout: layout(
+                "depth_of_field_gather_multisampled_bind_group_layout",
+                true,
+            ),
+            sampler: filtering::screen_sampler(
+                render_device,
+                filterable,
+                SamplerDescriptor {
+                    mag_filter: FilterMode::Linear,
+                    min_filter: FilterMode::Linear,
+                    ..default()
+                },
+            ),
+            filterable,
+            shader: load_embedded_asset!(world, "depth_of_field.wgsl"),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for DepthOfFieldGatherPipeline {
+    // Whether the view uses MSAA, which makes its depth texture multisampled
+    type Key = bool;
+
+    fn specialize(&self, multisampled: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+        }
+        if !self.filterable {
+            shader_defs.push(filtering::SCREEN_TEXTURE_NON_FILTERABLE.into());
+        }
+        let layout = match multisampled {
+            true => &self.multisampled_layout,
+            false => &self.layout,
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("depth_of_field_gather_pipeline".into()),
+            layout: vec![layout.clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some("gather".into()),
+                targets: vec![Some(ColorTargetState {
+                    format: GATHER_TEXTURE_FORMAT,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+// The half resolution gather of a view
+#[derive(Component)]
+struct ViewDepthOfFieldGather {
+    texture: CachedTexture,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+fn prepare_gathers(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewTarget, &Msaa), With<DepthOfFieldSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    gather_pipeline: Res<DepthOfFieldGatherPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<DepthOfFieldGatherPipeline>>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+) {
+    for (entity, view_target, msaa) in &views {
+        let texture = texture_pool.get(
+            &render_device,
+            entity,
+            DepthOfFieldGather,
+            "gather",
+            &TextureDescriptor {
+                label: Some("depth_of_field_gather_texture"),
+                size: scaled_size(view_target.main_texture().size(), 0.5),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: GATHER_TEXTURE_FORMAT,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+        let pipeline_id =
+            pipelines.specialize(&pipeline_cache, &gather_pipeline, msaa.samples() > 1);
+        commands.entity(entity).insert(ViewDepthOfFieldGather {
+            texture,
+            pipeline_id,
+        });
+    }
+}
+
+// The layout of the group 1 of the effect, the half resolution gather. It is filtered by the
+// shader, as not every adapter can filter its format
+fn gather_result_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "depth_of_field_gather_result_bind_group_layout",
+        &BindGroupLayoutEntries::single(
+            ShaderStages::FRAGMENT,
+            texture_2d(TextureSampleType::Float { filterable: false }),
+        ),
+    )
+}
+
+// The effect skips the views without the bind group, so it doesn't read the gather until the
+// gather pipeline is compiled
+fn prepare_gather_result_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewDepthOfFieldGather), With<DepthOfFieldSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    layout: Res<PostProcessBindGroupLayout<DepthOfFieldSettings>>,
+) {
+    for (entity, gather) in &views {
+        if pipeline_cache.get_render_pipeline(gather.pipeline_id).is_none() {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<DepthOfFieldSettings>>();
+            continue;
+        }
+
+        let bind_group = render_device.create_bind_group(
+            "depth_of_field_gather_result_bind_group",
+            &layout.layout,
+            &BindGroupEntries::single(&gather.texture.default_view),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<DepthOfFieldSettings>::new(bind_group));
+    }
+}
+
+#[derive(Default)]
+struct DepthOfFieldGatherNode;
+
+impl ViewNode for DepthOfFieldGatherNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        // Only runs on the cameras with the depth of field
+        &'static DepthOfFieldSettings,
+        &'static ViewDepthOfFieldGather,
+        &'static DynamicUniformIndex<DepthOfFieldSettings>,
+        &'static ViewUniformOffset,
+        &'static ViewDepthTexture,
+        &'static Msaa,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (
+            view_target,
+            _settings,
+            gather,
+            settings_index,
+            view_uniform_offset,
+            view_depth,
+            msaa,
+        ): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // The effect doesn't read the gather on the views it skips
+        let view = graph.view_entity();
+        if effects::skip_intermediate::<DepthOfFieldSettings, DepthOfField>(world, view) {
+            return Ok(());
+        }
+
+        let gather_pipeline = world.resource::<DepthOfFieldGatherPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (Some(pipeline), Some(settings_binding), Some(view_binding)) = (
+            pipeline_cache.get_render_pipeline(gather.pipeline_id),
+            world
+                .resource::<ComponentUniforms<DepthOfFieldSettings>>()
+                .uniforms()
+                .binding(),
+            world.resource::<ViewUniforms>().uniforms.binding(),
+        ) else {
+            return Ok(());
+        };
+
+        let layout = match msaa.samples() > 1 {
+            true => &gather_pipeline.multisampled_layout,
+            false => &gather_pipeline.layout,
+        };
+        let bind_group = render_context.render_device().create_bind_group(
+            "depth_of_field_gather_bind_group",
+            layout,
+            &BindGroupEntries::sequential((
+                view_target.main_texture_view(),
+                &gather_pipeline.sampler,
+                settings_binding,
+                view_binding,
+                view_depth.view(),
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("depth_of_field_gather_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &gather.texture.default_view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(
+            0,
+            &bind_group,
+            &[settings_index.index(), view_uniform_offset.offset],
+        );
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
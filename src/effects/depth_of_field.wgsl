@@ -0,0 +1,122 @@
+// Depth of field with shaped bokeh. The `gather` entry point blurs the view at half resolution,
+// then `fragment` blends the sharp view with the blur by the circle of confusion of every pixel.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_post_process::utils::linearize_depth
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct DepthOfFieldSettings {
+    focus_distance: f32,
+    aperture_f_stops: f32,
+    focal_length: f32,
+    sensor_height: f32,
+    max_coc_radius: f32,
+    blades: u32,
+}
+@group(0) @binding(2) var<uniform> settings: DepthOfFieldSettings;
+@group(0) @binding(3) var<uniform> view: View;
+#ifdef MULTISAMPLED
+@group(0) @binding(4) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(4) var depth_texture: texture_depth_2d;
+#endif
+
+// The blurred view at half resolution, with the coverage of the near field in alpha
+@group(1) @binding(0) var gather_texture: texture_2d<f32>;
+
+const PI: f32 = 3.14159265;
+const GOLDEN_ANGLE: f32 = 2.39996323;
+const GATHER_SAMPLES: u32 = 64u;
+
+// The signed radius of the circle of confusion in pixels of the view, negative in front of the
+// focus plane. Follows the thin lens model
+fn coc_at(pixel: vec2<i32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(screen_texture));
+    let depth = textureLoad(depth_texture, clamp(pixel, vec2(0), size - 1), 0);
+    let distance = max(linearize_depth(depth, view.view_from_clip), 1.0e-4);
+
+    let focal_length = settings.focal_length;
+    let aperture = focal_length / max(settings.aperture_f_stops, 1.0e-4);
+    let focus = max(settings.focus_distance, focal_length + 1.0e-4);
+    let coc = aperture * focal_length * (distance - focus) / (distance * (focus - focal_length));
+    let coc_pixels = coc / settings.sensor_height * view.viewport.w;
+    return clamp(coc_pixels, -settings.max_coc_radius, settings.max_coc_radius);
+}
+
+// Scales a radius of the circle to the edge of a polygon with one side per blade, so the bokeh
+// take the shape of the aperture. Fewer than 3 blades is a circle
+fn polygon_scale(angle: f32) -> f32 {
+    if settings.blades < 3u {
+        return 1.0;
+    }
+    let segment = 2.0 * PI / f32(settings.blades);
+    let local = angle - segment * floor(angle / segment);
+    return cos(segment * 0.5) / cos(local - segment * 0.5);
+}
+
+@fragment
+fn gather(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    let center_pixel = in.uv * size;
+    let center_coc = abs(coc_at(vec2<i32>(center_pixel)));
+
+    var color = vec3(0.0);
+    var total_weight = 0.0;
+    var near_coverage = 0.0;
+    for (var i = 0u; i < GATHER_SAMPLES; i += 1u) {
+        // A golden angle spiral spreads the samples evenly over the disk
+        let t = (f32(i) + 0.5) / f32(GATHER_SAMPLES);
+        let angle = f32(i) * GOLDEN_ANGLE;
+        let radius = settings.max_coc_radius * sqrt(t) * polygon_scale(angle);
+        let offset = vec2(cos(angle), sin(angle)) * radius;
+        let sample_pixel = center_pixel + offset;
+
+        let coc = coc_at(vec2<i32>(sample_pixel));
+        // The samples behind the center can't spread wider than the center is blurred, or the
+        // background would bleed over the sharp surfaces in front of it
+        var spread = abs(coc);
+        if coc > 0.0 {
+            spread = min(spread, center_coc);
+        }
+        let weight = clamp(spread - radius + 1.0, 0.0, 1.0) / max(spread * spread, 1.0);
+
+        let sample_uv = sample_pixel / size;
+        let sample_color = textureSampleLevel(screen_texture, texture_sampler, sample_uv, 0.0);
+        color += sample_color.rgb * weight;
+        total_weight += weight;
+        if coc < 0.0 {
+            near_coverage += clamp(-coc - radius + 1.0, 0.0, 1.0);
+        }
+    }
+
+    let center_color = textureSampleLevel(screen_texture, texture_sampler, in.uv, 0.0).rgb;
+    let blurred = select(center_color, color / total_weight, total_weight > 0.0);
+    let coverage = clamp(near_coverage / f32(GATHER_SAMPLES) * 4.0, 0.0, 1.0);
+    return vec4(blurred, coverage);
+}
+
+// Bilinear filtering of the gather by hand, as not every adapter can filter its format
+fn sample_gather(uv: vec2<f32>) -> vec4<f32> {
+    let size = vec2<i32>(textureDimensions(gather_texture));
+    let position = uv * vec2<f32>(size) - 0.5;
+    let base = vec2<i32>(floor(position));
+    let f = fract(position);
+    let top_left = textureLoad(gather_texture, clamp(base, vec2(0), size - 1), 0);
+    let top_right = textureLoad(gather_texture, clamp(base + vec2(1, 0), vec2(0), size - 1), 0);
+    let bottom_left = textureLoad(gather_texture, clamp(base + vec2(0, 1), vec2(0), size - 1), 0);
+    let bottom_right = textureLoad(gather_texture, clamp(base + vec2(1), vec2(0), size - 1), 0);
+    return mix(mix(top_left, top_right, f.x), mix(bottom_left, bottom_right, f.x), f.y);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let sharp = textureSample(screen_texture, texture_sampler, in.uv);
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    let coc = abs(coc_at(vec2<i32>(in.uv * size)));
+    let blurred = sample_gather(in.uv);
+
+    // Blurred near surfaces spill over the sharp ones behind them
+    let blend = max(smoothstep(0.5, 2.0, coc), blurred.a);
+    return vec4(mix(sharp.rgb, blurred.rgb, blend), sharp.a);
+}
@@ -0,0 +1,212 @@
+use crate::{
+    effects, PostProcessBindGroup, PostProcessBindGroupLayout, PostProcessPlacement,
+    PostProcessPlugin,
+};
+use bevy::{
+    asset::embedded_asset,
+    camera::ClearColorConfig,
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_asset::RenderAssets,
+        render_graph::RenderLabel,
+        render_resource::{
+            binding_types::{sampler, texture_cube},
+            *,
+        },
+        renderer::RenderDevice,
+        texture::{FallbackImage, GpuImage},
+    },
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/distance_fog.wgsl";
+
+/// A fog thickening with the distance from the camera, run on the cameras with
+/// [`DistanceFogSettings`].
+///
+/// The distance of every pixel comes from the depth of the view, the sky being infinitely far
+/// away. The fog takes the color of the settings, the clear color of the camera, or the horizon of
+/// a [`DistanceFogEnvironment`] cubemap, which blends the distant geometry into the skybox. Runs
+/// on the HDR colors by default, so the fog is lit like the scene. Placing it after the upscaling
+/// isn't supported.
+#[derive(Default)]
+pub struct DistanceFogPlugin {
+    /// Where the fog runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`DistanceFogPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct DistanceFogSettings {
+    /// The color of the fog, tinting the environment with
+    /// [`DistanceFogSettings::SOURCE_ENVIRONMENT`]. Its alpha scales the amount of fog with every
+    /// source.
+    pub color: LinearRgba,
+    /// The clear color of the camera, for [`DistanceFogSettings::SOURCE_CLEAR_COLOR`]. Written by
+    /// the plugin every frame.
+    pub clear_color: LinearRgba,
+    /// How the fog thickens with the distance, one of [`DistanceFogSettings::FALLOFF_LINEAR`],
+    /// [`DistanceFogSettings::FALLOFF_EXPONENTIAL`] or
+    /// [`DistanceFogSettings::FALLOFF_EXPONENTIAL_SQUARED`].
+    pub falloff: u32,
+    /// Where the color of the fog comes from, one of [`DistanceFogSettings::SOURCE_COLOR`],
+    /// [`DistanceFogSettings::SOURCE_CLEAR_COLOR`] or [`DistanceFogSettings::SOURCE_ENVIRONMENT`].
+    pub source: u32,
+    /// How quickly the exponential falloffs thicken, per world unit.
+    pub density: f32,
+    /// The distance from the camera where the fog starts, in world units.
+    pub start: f32,
+    /// The distance from the camera where the linear falloff reaches full fog, in world units.
+    pub end: f32,
+    /// The most the fog covers, so the sky or the distant geometry can still show through.
+    pub max_opacity: f32,
+    /// The factor applied to the samples of the environment, like the intensity of an
+    /// `EnvironmentMapLight`.
+    pub environment_intensity: f32,
+    /// The mip level the environment is sampled at, higher levels giving a smoother horizon.
+    pub environment_mip_level: f32,
+}
+
+impl DistanceFogSettings {
+    /// The fog grows linearly from [`DistanceFogSettings::start`] to [`DistanceFogSettings::end`].
+    pub const FALLOFF_LINEAR: u32 = 0;
+    /// The light is absorbed at a constant rate past [`DistanceFogSettings::start`], like a
+    /// uniform medium.
+    pub const FALLOFF_EXPONENTIAL: u32 = 1;
+    /// Like the exponential falloff, with a clearer area near the camera and a steeper wall
+    /// further.
+    pub const FALLOFF_EXPONENTIAL_SQUARED: u32 = 2;
+
+    /// The fog takes [`DistanceFogSettings::color`].
+    pub const SOURCE_COLOR: u32 = 0;
+    /// The fog takes the clear color of the camera, so the geometry fades into the background.
+    pub const SOURCE_CLEAR_COLOR: u32 = 1;
+    /// The fog takes the color of the [`DistanceFogEnvironment`] at the horizon, in the direction
+    /// of every pixel, tinted by [`DistanceFogSettings::color`]. Without an environment, it takes
+    /// the color alone.
+    pub const SOURCE_ENVIRONMENT: u32 = 2;
+}
+
+impl Default for DistanceFogSettings {
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::rgb(0.5, 0.6, 0.7),
+            clear_color: LinearRgba::BLACK,
+            falloff: Self::FALLOFF_EXPONENTIAL,
+            source: Self::SOURCE_COLOR,
+            density: 0.02,
+            start: 0.0,
+            end: 100.0,
+            max_opacity: 1.0,
+            environment_intensity: 1.0,
+            environment_mip_level: 0.0,
+        }
+    }
+}
+
+/// The cubemap the fog of a camera takes its color from with
+/// [`DistanceFogSettings::SOURCE_ENVIRONMENT`], like the image of its `Skybox`.
+///
+/// The image must be viewed as a cube, like the images of a `Skybox` or an `EnvironmentMapLight`.
+#[derive(Component, Reflect, Clone, Debug, ExtractComponent)]
+#[reflect(Component)]
+pub struct DistanceFogEnvironment(pub Handle<Image>);
+
+/// The render graph label of the [`DistanceFogPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct DistanceFog;
+
+impl Plugin for DistanceFogPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "distance_fog.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<DistanceFogSettings>(
+            app,
+            &[
+                ("density", 0.0..=1.0),
+                ("start", 0.0..=1000.0),
+                ("end", 0.0..=1000.0),
+                ("max_opacity", 0.0..=1.0),
+                ("environment_intensity", 0.0..=4.0),
+                ("environment_mip_level", 0.0..=12.0),
+            ],
+        );
+        app.register_type::<DistanceFogSettings>()
+            .register_type::<DistanceFogEnvironment>()
+            .add_plugins(ExtractComponentPlugin::<DistanceFogEnvironment>::default())
+            .add_systems(Update, update_clear_colors);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<DistanceFogSettings, _>::new(
+                SHADER_PATH,
+                DistanceFog,
+                Some("distance_fog"),
+                "distance_fog_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_depth()
+            .with_bind_group(environment_layout, prepare_environment_bind_groups),
+        );
+    }
+}
+
+// The cameras without a clear color of their own clear to the one of the app. Those that don't
+// clear keep the last one
+fn update_clear_colors(
+    mut cameras: Query<(&Camera, &mut DistanceFogSettings)>,
+    clear_color: Res<ClearColor>,
+) {
+    for (camera, mut settings) in &mut cameras {
+        let color = match camera.clear_color {
+            ClearColorConfig::Default => clear_color.0,
+            ClearColorConfig::Custom(color) => color,
+            ClearColorConfig::None => continue,
+        };
+        settings.clear_color = color.into();
+    }
+}
+
+// The layout of the group 1 of the effect, the environment and its sampler
+fn environment_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "distance_fog_environment_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_cube(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+            ),
+        ),
+    )
+}
+
+// The views without an environment, or whose environment is still loading, bind a white cubemap
+// so the fog takes the color of the settings
+fn prepare_environment_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, Option<&DistanceFogEnvironment>), With<DistanceFogSettings>>,
+    render_device: Res<RenderDevice>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    fallback_image: Res<FallbackImage>,
+    layout: Res<PostProcessBindGroupLayout<DistanceFogSettings>>,
+) {
+    for (entity, environment) in &views {
+        let environment = environment.and_then(|environment| gpu_images.get(&environment.0));
+        let (texture_view, sampler) = match environment {
+            Some(gpu_image) => (&gpu_image.texture_view, &gpu_image.sampler),
+            None => (&fallback_image.cube.texture_view, &fallback_image.cube.sampler),
+        };
+
+        let bind_group = render_device.create_bind_group(
+            "distance_fog_environment_bind_group",
+            &layout.layout,
+            &BindGroupEntries::sequential((texture_view, sampler)),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<DistanceFogSettings>::new(bind_group));
+    }
+}
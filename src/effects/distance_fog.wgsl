@@ -0,0 +1,82 @@
+// Blends every pixel toward the color of the fog by its distance from the camera, the fog color
+// coming from the settings, the clear color or the horizon of an environment cubemap.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_post_process::utils::position_world_from_depth
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct DistanceFogSettings {
+    color: vec4<f32>,
+    clear_color: vec4<f32>,
+    falloff: u32,
+    source: u32,
+    density: f32,
+    start: f32,
+    end: f32,
+    max_opacity: f32,
+    environment_intensity: f32,
+    environment_mip_level: f32,
+}
+@group(0) @binding(2) var<uniform> settings: DistanceFogSettings;
+@group(0) @binding(3) var<uniform> view: View;
+#ifdef MULTISAMPLED
+@group(0) @binding(4) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(4) var depth_texture: texture_depth_2d;
+#endif
+
+@group(1) @binding(0) var environment_texture: texture_cube<f32>;
+@group(1) @binding(1) var environment_sampler: sampler;
+
+const FALLOFF_EXPONENTIAL: u32 = 1u;
+const FALLOFF_EXPONENTIAL_SQUARED: u32 = 2u;
+const SOURCE_CLEAR_COLOR: u32 = 1u;
+const SOURCE_ENVIRONMENT: u32 = 2u;
+
+fn fog_amount(distance: f32) -> f32 {
+    let traveled = max(distance - settings.start, 0.0);
+    if settings.falloff == FALLOFF_EXPONENTIAL {
+        return 1.0 - exp(-settings.density * traveled);
+    }
+    if settings.falloff == FALLOFF_EXPONENTIAL_SQUARED {
+        let optical_depth = settings.density * traveled;
+        return 1.0 - exp(-optical_depth * optical_depth);
+    }
+    return clamp(traveled / max(settings.end - settings.start, 1.0e-4), 0.0, 1.0);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let depth = textureLoad(depth_texture, vec2<i32>(in.position.xy), 0);
+
+    // Two points along the ray of the pixel give its direction with both projections. The sky is
+    // cleared to a depth of 0, infinitely far away
+    let near = position_world_from_depth(in.uv, 1.0, view.world_from_clip);
+    let middle = position_world_from_depth(in.uv, 0.5, view.world_from_clip);
+    let direction = normalize(middle - near);
+    var amount = 1.0;
+    if depth > 0.0 {
+        let position = position_world_from_depth(in.uv, depth, view.world_from_clip);
+        amount = fog_amount(distance(position, view.world_position));
+    }
+    amount = min(amount, settings.max_opacity) * settings.color.a;
+
+    var fog = settings.color.rgb;
+    if settings.source == SOURCE_CLEAR_COLOR {
+        fog = settings.clear_color.rgb;
+    } else if settings.source == SOURCE_ENVIRONMENT {
+        // The horizon in the direction of the pixel, so the geometry blends into the sky behind it
+        let horizon = normalize(vec3(direction.x, 0.0, direction.z) + vec3(0.0, 0.0, 1.0e-5));
+        let environment = textureSampleLevel(
+            environment_texture,
+            environment_sampler,
+            horizon,
+            settings.environment_mip_level,
+        ).rgb;
+        fog *= environment * settings.environment_intensity;
+    }
+
+    return vec4(mix(color.rgb, fog, amount), color.a);
+}
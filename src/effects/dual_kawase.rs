@@ -0,0 +1,395 @@
+use crate::{
+    effects, filtering, resolution::scaled_size, PostProcessBindGroup, PostProcessBindGroupLayout,
+    PostProcessPlacement, PostProcessPlugin, PostProcessTexturePool,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::{core_3d::graph::Core3d, FullscreenShader},
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{ComponentUniforms, DynamicUniformIndex, ExtractComponent},
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::CachedTexture,
+        view::ViewTarget,
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/dual_kawase.wgsl";
+
+/// The most levels the chain goes down.
+const MAX_ITERATIONS: u32 = 8;
+
+/// The format of the levels of the chain. It has no alpha, the effect keeps the one of the view.
+const CHAIN_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rg11b10Ufloat;
+
+/// A dual Kawase blur, run on the cameras with [`DualKawaseSettings`].
+///
+/// The view is halved down a chain of textures, then upsampled back one level at a time, with a
+/// few bilinear samples per pass. This gives large and soft blurs, like the background of a pause
+/// menu, for far less than a Gaussian blur of the same size.
+///
+/// The chain is rendered by the [`DualKawaseChain`] node right before the [`DualKawase`] effect,
+/// which upsamples the first level of the chain onto the view. Placing the blur after the upscaling
+/// isn't supported, the chain always reads the main texture of the view.
+#[derive(Default)]
+pub struct DualKawasePlugin {
+    /// Where the blur runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`DualKawasePlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct DualKawaseSettings {
+    /// How many times the view is halved, from 1 to 8. Each iteration doubles the size of the blur.
+    pub iterations: u32,
+    /// How far apart the samples are, in texels of the level they read. Values above 1 widen the
+    /// blur a bit more, at the cost of some blockiness.
+    pub offset: f32,
+}
+
+impl Default for DualKawaseSettings {
+    fn default() -> Self {
+        Self {
+            iterations: 4,
+            offset: 1.0,
+        }
+    }
+}
+
+/// The render graph label of the effect pass of the [`DualKawasePlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct DualKawase;
+
+/// The render graph label of the node rendering the chain of the [`DualKawasePlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct DualKawaseChain;
+
+impl Plugin for DualKawasePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "dual_kawase.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<DualKawaseSettings>(app, &[("offset", 0.5..=4.0)]);
+        app.register_type::<DualKawaseSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<DualKawaseSettings, _>::new(
+                SHADER_PATH,
+                DualKawase,
+                Some("dual_kawase"),
+                "dual_kawase_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view()
+            .with_bind_group(result_layout, prepare_result_bind_groups),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (before, _) = self.placement.edges();
+        render_app
+            .init_resource::<SpecializedRenderPipelines<DualKawaseChainPipeline>>()
+            .add_systems(Render, prepare_chains.in_set(RenderSystems::PrepareResources))
+            .add_render_graph_node::<ViewNodeRunner<DualKawaseChainNode>>(Core3d, DualKawaseChain)
+            .add_render_graph_edges(Core3d, (before, DualKawaseChain, DualKawase));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<DualKawaseChainPipeline>();
+    }
+}
+
+// The pipeline of the passes going down and up the chain
+#[derive(Resource)]
+struct DualKawaseChainPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    // Samples the first level of the chain in the effect pass
+    result_sampler: Sampler,
+    filterable: bool,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+}
+
+impl FromWorld for DualKawaseChainPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        // The first pass reads the view, which some adapters can't filter
+        let filterable = filtering::screen_filterable(world);
+        let [source_texture, source_sampler] = filtering::screen_entries(filterable);
+        let layout = render_device.create_bind_group_layout(
+            "dual_kawase_chain_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    source_texture,
+                    source_sampler,
+                    uniform_buffer::<DualKawaseSettings>(true),
+                ),
+            ),
+        );
+        let descriptor = SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        };
+
+        Self {
+            layout,
+            sampler: filtering::screen_sampler(render_device, filterable, descriptor.clone()),
+            result_sampler: render_device.create_sampler(&descriptor),
+            filterable,
+            shader: load_embedded_asset!(world, "dual_kawase.wgsl"),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum DualKawasePass {
+    Downsample,
+    Upsample,
+}
+
+impl SpecializedRenderPipeline for DualKawaseChainPipeline {
+    type Key = DualKawasePass;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if !self.filterable {
+            shader_defs.push(filtering::SCREEN_TEXTURE_NON_FILTERABLE.into());
+        }
+        let entry_point = match key {
+            DualKawasePass::Downsample => "downsample",
+            DualKawasePass::Upsample => "upsample",
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("dual_kawase_chain_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some(entry_point.into()),
+                targets: vec![Some(ColorTargetState {
+                    format: CHAIN_TEXTURE_FORMAT,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+// The levels of the chain of a view, the first one being half the size of the view
+#[derive(Component)]
+struct ViewDualKawaseChain {
+    levels: Vec<CachedTexture>,
+    downsample_pipeline_id: CachedRenderPipelineId,
+    upsample_pipeline_id: CachedRenderPipelineId,
+}
+
+fn prepare_chains(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewTarget, &DualKawaseSettings)>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    chain_pipeline: Res<DualKawaseChainPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<DualKawaseChainPipeline>>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+) {
+    let downsample_pipeline_id =
+        pipelines.specialize(&pipeline_cache, &chain_pipeline, DualKawasePass::Downsample);
+    let upsample_pipeline_id =
+        pipelines.specialize(&pipeline_cache, &chain_pipeline, DualKawasePass::Upsample);
+
+    for (entity, view_target, settings) in &views {
+        let size = view_target.main_texture().size();
+        let levels = (0..settings.iterations.clamp(1, MAX_ITERATIONS))
+            .map(|level| {
+                texture_pool.get_indexed(
+                    &render_device,
+                    entity,
+                    DualKawaseChain,
+                    "level",
+                    level,
+                    &TextureDescriptor {
+                        label: Some("dual_kawase_chain_texture"),
+                        size: scaled_size(size, 0.5f32.powi(level as i32 + 1)),
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: TextureDimension::D2,
+                        format: CHAIN_TEXTURE_FORMAT,
+                        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                        view_formats: &[],
+                    },
+                )
+            })
+            .collect();
+        commands.entity(entity).insert(ViewDualKawaseChain {
+            levels,
+            downsample_pipeline_id,
+            upsample_pipeline_id,
+        });
+    }
+}
+
+// The layout of the group 1 of the effect, the first level of the chain and its sampler
+fn result_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "dual_kawase_result_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+            ),
+        ),
+    )
+}
+
+// The effect skips the views without the bind group, so it doesn't read the chain until the
+// chain pipelines are compiled
+fn prepare_result_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewDualKawaseChain), With<DualKawaseSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    layout: Res<PostProcessBindGroupLayout<DualKawaseSettings>>,
+    chain_pipeline: Res<DualKawaseChainPipeline>,
+) {
+    for (entity, chain) in &views {
+        if pipeline_cache
+            .get_render_pipeline(chain.downsample_pipeline_id)
+            .is_none()
+            || pipeline_cache
+                .get_render_pipeline(chain.upsample_pipeline_id)
+                .is_none()
+        {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<DualKawaseSettings>>();
+            continue;
+        }
+        let bind_group = render_device.create_bind_group(
+            "dual_kawase_result_bind_group",
+            &layout.layout,
+            &BindGroupEntries::sequential((
+                &chain.levels[0].default_view,
+                &chain_pipeline.result_sampler,
+            )),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<DualKawaseSettings>::new(bind_group));
+    }
+}
+
+#[derive(Default)]
+struct DualKawaseChainNode;
+
+impl ViewNode for DualKawaseChainNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        // Only runs on the cameras with the blur
+        &'static DualKawaseSettings,
+        &'static ViewDualKawaseChain,
+        &'static DynamicUniformIndex<DualKawaseSettings>,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, _settings, chain, settings_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // The effect doesn't read the chain on the views it skips
+        let view = graph.view_entity();
+        if effects::skip_intermediate::<DualKawaseSettings, DualKawase>(world, view) {
+            return Ok(());
+        }
+
+        let chain_pipeline = world.resource::<DualKawaseChainPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (Some(downsample_pipeline), Some(upsample_pipeline), Some(settings_binding)) = (
+            pipeline_cache.get_render_pipeline(chain.downsample_pipeline_id),
+            pipeline_cache.get_render_pipeline(chain.upsample_pipeline_id),
+            world
+                .resource::<ComponentUniforms<DualKawaseSettings>>()
+                .uniforms()
+                .binding(),
+        ) else {
+            return Ok(());
+        };
+
+        // Go down from the view to the last level, then back up to the first one
+        let levels = &chain.levels;
+        let downsample_passes = (0..levels.len()).map(|level| {
+            let source = match level {
+                0 => view_target.main_texture_view(),
+                _ => &levels[level - 1].default_view,
+            };
+            (downsample_pipeline, source, &levels[level].default_view)
+        });
+        let upsample_passes = (1..levels.len()).rev().map(|level| {
+            (
+                upsample_pipeline,
+                &levels[level].default_view,
+                &levels[level - 1].default_view,
+            )
+        });
+
+        for (pipeline, source, destination) in downsample_passes.chain(upsample_passes) {
+            let bind_group = render_context.render_device().create_bind_group(
+                "dual_kawase_chain_bind_group",
+                &chain_pipeline.layout,
+                &BindGroupEntries::sequential((
+                    source,
+                    &chain_pipeline.sampler,
+                    settings_binding.clone(),
+                )),
+            );
+
+            let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("dual_kawase_chain_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: destination,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        Ok(())
+    }
+}
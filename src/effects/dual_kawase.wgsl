@@ -0,0 +1,73 @@
+// The dual Kawase blur. The chain passes halve the view down a chain of textures and upsample it
+// back to the first level, then the effect pass upsamples the first level onto the view.
+// See "Bandwidth-Efficient Rendering", Marius Bjørge, SIGGRAPH 2015.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+// The level read by the chain passes, or the view in the effect pass
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+struct DualKawaseSettings {
+    iterations: u32,
+    offset: f32,
+}
+@group(0) @binding(2) var<uniform> settings: DualKawaseSettings;
+// The first level of the chain, only bound to the effect pass
+@group(1) @binding(0) var chain_texture: texture_2d<f32>;
+@group(1) @binding(1) var chain_sampler: sampler;
+
+// Bilinear sampling, done manually when the adapter can't filter the view texture
+fn sample_bilinear(level: texture_2d<f32>, level_sampler: sampler, uv: vec2<f32>) -> vec4<f32> {
+#ifdef SCREEN_TEXTURE_NON_FILTERABLE
+    let texture_size = vec2<i32>(textureDimensions(level));
+    let position = uv * vec2<f32>(texture_size) - 0.5;
+    let base = vec2<i32>(floor(position));
+    let f = fract(position);
+    let max_texel = texture_size - 1;
+    let t00 = textureLoad(level, clamp(base, vec2(0), max_texel), 0);
+    let t10 = textureLoad(level, clamp(base + vec2(1, 0), vec2(0), max_texel), 0);
+    let t01 = textureLoad(level, clamp(base + vec2(0, 1), vec2(0), max_texel), 0);
+    let t11 = textureLoad(level, clamp(base + vec2(1, 1), vec2(0), max_texel), 0);
+    return mix(mix(t00, t10, f.x), mix(t01, t11, f.x), f.y);
+#else
+    return textureSampleLevel(level, level_sampler, uv, 0.0);
+#endif
+}
+
+// The center and the four diagonals, a texel of the source away
+@fragment
+fn downsample(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let o = settings.offset / vec2<f32>(textureDimensions(source_texture));
+    var color = sample_bilinear(source_texture, source_sampler, in.uv) * 4.0;
+    color += sample_bilinear(source_texture, source_sampler, in.uv - o);
+    color += sample_bilinear(source_texture, source_sampler, in.uv + o);
+    color += sample_bilinear(source_texture, source_sampler, in.uv + vec2(o.x, -o.y));
+    color += sample_bilinear(source_texture, source_sampler, in.uv + vec2(-o.x, o.y));
+    return color / 8.0;
+}
+
+// A tent of eight samples around the pixel, half a texel of the source away on the diagonals
+fn upsample_from(level: texture_2d<f32>, level_sampler: sampler, uv: vec2<f32>) -> vec4<f32> {
+    let h = settings.offset * 0.5 / vec2<f32>(textureDimensions(level));
+    var color = sample_bilinear(level, level_sampler, uv + vec2(-h.x * 2.0, 0.0));
+    color += sample_bilinear(level, level_sampler, uv + vec2(h.x * 2.0, 0.0));
+    color += sample_bilinear(level, level_sampler, uv + vec2(0.0, -h.y * 2.0));
+    color += sample_bilinear(level, level_sampler, uv + vec2(0.0, h.y * 2.0));
+    color += sample_bilinear(level, level_sampler, uv + vec2(-h.x, h.y)) * 2.0;
+    color += sample_bilinear(level, level_sampler, uv + vec2(h.x, h.y)) * 2.0;
+    color += sample_bilinear(level, level_sampler, uv + vec2(h.x, -h.y)) * 2.0;
+    color += sample_bilinear(level, level_sampler, uv + vec2(-h.x, -h.y)) * 2.0;
+    return color / 12.0;
+}
+
+@fragment
+fn upsample(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    return upsample_from(source_texture, source_sampler, in.uv);
+}
+
+// The effect pass. The chain has no alpha, so the one of the view is kept
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = upsample_from(chain_texture, chain_sampler, in.uv);
+    let alpha = textureLoad(source_texture, vec2<i32>(in.position.xy), 0).a;
+    return vec4(color.rgb, alpha);
+}
@@ -0,0 +1,96 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/edge_detection.wgsl";
+
+/// Outlines drawn along the luminance edges of the view, run on the cameras with
+/// [`EdgeDetectionSettings`].
+///
+/// Finds the edges with a Sobel or Scharr operator and draws them over the scene. Hiding the scene
+/// with [`EdgeDetectionSettings::scene_opacity`] shows the edges alone, to check what the operator
+/// sees. Runs after the tonemapping by default, so the threshold applies to displayed colors.
+pub struct EdgeDetectionPlugin {
+    /// Where the effect runs.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for EdgeDetectionPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+        }
+    }
+}
+
+/// The settings of the [`EdgeDetectionPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct EdgeDetectionSettings {
+    /// The kernel of the operator, [`EdgeDetectionSettings::KERNEL_SOBEL`] or
+    /// [`EdgeDetectionSettings::KERNEL_SCHARR`].
+    pub kernel: u32,
+    /// The gradient of luminance below which no edge is drawn.
+    pub threshold: f32,
+    /// How far apart the operator reads its pixels, in pixels. Thicker lines for larger values.
+    pub thickness: f32,
+    /// The color of the edges, its alpha being their opacity.
+    pub color: LinearRgba,
+    /// How much of the scene shows under the edges, 0 leaving them over black.
+    pub scene_opacity: f32,
+}
+
+impl EdgeDetectionSettings {
+    /// The Sobel operator, the most common one.
+    pub const KERNEL_SOBEL: u32 = 0;
+    /// The Scharr operator, treating the diagonal edges more evenly with the straight ones.
+    pub const KERNEL_SCHARR: u32 = 1;
+}
+
+impl Default for EdgeDetectionSettings {
+    fn default() -> Self {
+        Self {
+            kernel: Self::KERNEL_SOBEL,
+            threshold: 0.2,
+            thickness: 1.0,
+            color: LinearRgba::BLACK,
+            scene_opacity: 1.0,
+        }
+    }
+}
+
+/// The render graph label of the [`EdgeDetectionPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct EdgeDetection;
+
+impl Plugin for EdgeDetectionPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "edge_detection.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<EdgeDetectionSettings>(
+            app,
+            &[
+                ("threshold", 0.0..=1.0),
+                ("thickness", 0.5..=4.0),
+                ("scene_opacity", 0.0..=1.0),
+            ],
+        );
+        app.register_type::<EdgeDetectionSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<EdgeDetectionSettings, _>::new(
+                SHADER_PATH,
+                EdgeDetection,
+                Some("edge_detection"),
+                "edge_detection_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view(),
+        );
+    }
+}
@@ -0,0 +1,57 @@
+// Draws the edges found by a Sobel or Scharr operator on the luminance over the scene.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::linear_to_srgb
+#import bevy_post_process::utils::luminance
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct EdgeDetectionSettings {
+    kernel: u32,
+    threshold: f32,
+    thickness: f32,
+    color: vec4<f32>,
+    scene_opacity: f32,
+}
+@group(0) @binding(2) var<uniform> settings: EdgeDetectionSettings;
+
+const KERNEL_SCHARR: u32 = 1u;
+
+// The perceived brightness of a neighbor, so the threshold behaves the same in dark and bright
+// areas
+fn brightness(uv: vec2<f32>, offset: vec2<f32>, texel: vec2<f32>) -> f32 {
+    let neighbor = clamp(uv + offset * texel, vec2(0.0), vec2(1.0));
+    let color = textureSampleLevel(screen_texture, texture_sampler, neighbor, 0.0).rgb;
+    return linear_to_srgb(vec3(luminance(max(color, vec3(0.0))))).x;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let texel = max(settings.thickness, 1.0) / vec2<f32>(textureDimensions(screen_texture));
+    let top_left = brightness(in.uv, vec2(-1.0, -1.0), texel);
+    let top = brightness(in.uv, vec2(0.0, -1.0), texel);
+    let top_right = brightness(in.uv, vec2(1.0, -1.0), texel);
+    let left = brightness(in.uv, vec2(-1.0, 0.0), texel);
+    let right = brightness(in.uv, vec2(1.0, 0.0), texel);
+    let bottom_left = brightness(in.uv, vec2(-1.0, 1.0), texel);
+    let bottom = brightness(in.uv, vec2(0.0, 1.0), texel);
+    let bottom_right = brightness(in.uv, vec2(1.0, 1.0), texel);
+
+    // The corner and middle weights of the kernels, normalized so both peak at the same gradient
+    var weights = vec2(1.0, 2.0) / 4.0;
+    if settings.kernel == KERNEL_SCHARR {
+        weights = vec2(3.0, 10.0) / 16.0;
+    }
+    let gradient_x = weights.x * (top_right + bottom_right - top_left - bottom_left)
+        + weights.y * (right - left);
+    let gradient_y = weights.x * (bottom_left + bottom_right - top_left - top_right)
+        + weights.y * (bottom - top);
+    let gradient = length(vec2(gradient_x, gradient_y));
+
+    // A soft threshold keeps the lines antialiased
+    let threshold = settings.threshold;
+    let edge = smoothstep(threshold, threshold * 1.5 + 1.0e-3, gradient) * settings.color.a;
+
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let scene = color.rgb * settings.scene_opacity;
+    return vec4(mix(scene, settings.color.rgb, edge), color.a);
+}
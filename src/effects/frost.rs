@@ -0,0 +1,114 @@
+use crate::{
+    effects, noise, PostProcessColorSpace, PostProcessInput, PostProcessNoisePlugin,
+    PostProcessPlacement, PostProcessPlugin,
+};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/frost.wgsl";
+
+/// Ice growing over the screen from its edges, run on the cameras with [`FrostSettings`].
+///
+/// As the freeze goes from 0 to 1, a front of ice crystals spreads from the edges toward the
+/// center, its border broken up by the value noise of the [`noise`] module. The view under the
+/// ice is refracted by the crystals and loses its colors. The plugin can take a `mask` to grow
+/// the ice in another shape. Runs after the tonemapping by default, on sRGB colors so the ice
+/// color is the one seen on the screen.
+///
+/// Adds the [`PostProcessNoisePlugin`] when it isn't already.
+pub struct FrostPlugin {
+    /// Where the frost runs.
+    pub placement: PostProcessPlacement,
+    /// The shape the ice grows in, stretched over the view. Its red channel is the freeze at
+    /// which the ice reaches every pixel, 0 for the first pixels to freeze. Without one, the ice
+    /// grows from the edges of the screen.
+    pub mask: Option<Handle<Image>>,
+}
+
+impl Default for FrostPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+            mask: None,
+        }
+    }
+}
+
+/// The settings of the [`FrostPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct FrostSettings {
+    /// The color of the ice, its alpha scaling how much the ice covers the view.
+    pub color: LinearRgba,
+    /// How far the ice has grown, from 0 for none to 1 for the whole screen.
+    pub freeze: f32,
+    /// The width of the border of the ice, where it thins out, as a fraction of the freeze.
+    pub softness: f32,
+    /// How far the crystals shift the view under them, in pixels.
+    pub refraction: f32,
+    /// How much the view under the ice loses its colors, from 0 to 1.
+    pub desaturation: f32,
+    /// The size of the crystals, in pixels.
+    pub crystal_size: f32,
+}
+
+impl Default for FrostSettings {
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::rgb(0.85, 0.95, 1.0),
+            freeze: 0.0,
+            softness: 0.15,
+            refraction: 6.0,
+            desaturation: 0.6,
+            crystal_size: 96.0,
+        }
+    }
+}
+
+/// The render graph label of the [`FrostPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Frost;
+
+impl Plugin for FrostPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "frost.wgsl");
+        if !app.is_plugin_added::<PostProcessNoisePlugin>() {
+            app.add_plugins(PostProcessNoisePlugin);
+        }
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<FrostSettings>(
+            app,
+            &[
+                ("freeze", 0.0..=1.0),
+                ("softness", 0.0..=1.0),
+                ("refraction", 0.0..=16.0),
+                ("desaturation", 0.0..=1.0),
+                ("crystal_size", 8.0..=256.0),
+            ],
+        );
+        app.register_type::<FrostSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        let mut plugin = PostProcessPlugin::<FrostSettings, _>::new(
+            SHADER_PATH,
+            Frost,
+            Some("frost"),
+            "frost_bind_group_layout",
+            vertex_state,
+        )
+        .with_placement(self.placement)
+        .with_color_space(PostProcessColorSpace::Srgb)
+        .with_input(PostProcessInput::Image(noise::VALUE_NOISE))
+        .with_standard_samplers()
+        .without_view();
+        if let Some(mask) = &self.mask {
+            plugin = plugin
+                .with_input(PostProcessInput::Image(mask.clone()))
+                .with_shader_def("FROST_MASK");
+        }
+        app.add_plugins(plugin);
+    }
+}
@@ -0,0 +1,69 @@
+// Frost growing from the edges of the screen. Every pixel freezes once the freeze passes the
+// value of the mask there, offset by a value noise so the front of the ice is ragged. The
+// crystals are the ridges of the noise, refracting the view along its gradient.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::utils::saturate_color
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct FrostSettings {
+    color: vec4<f32>,
+    freeze: f32,
+    softness: f32,
+    refraction: f32,
+    desaturation: f32,
+    crystal_size: f32,
+}
+@group(0) @binding(2) var<uniform> settings: FrostSettings;
+@group(0) @binding(7) var clamp_sampler: sampler;
+@group(0) @binding(9) var repeat_sampler: sampler;
+@group(0) @binding(16) var value_noise: texture_2d<f32>;
+#ifdef FROST_MASK
+@group(0) @binding(17) var mask_texture: texture_2d<f32>;
+#endif
+
+fn noise_at(uv: vec2<f32>) -> f32 {
+    return textureSampleLevel(value_noise, repeat_sampler, uv, 0.0).r;
+}
+
+// The freeze at which the ice reaches a pixel
+fn growth_at(uv: vec2<f32>) -> f32 {
+#ifdef FROST_MASK
+    return textureSampleLevel(mask_texture, clamp_sampler, uv, 0.0).r;
+#else
+    // A rounded rectangle, 0 on the edges and 1 in the center, so the corners freeze first
+    let edge = pow(abs(uv * 2.0 - 1.0), vec2(4.0));
+    return 1.0 - pow(edge.x + edge.y, 0.25);
+#endif
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let crystal_uv = in.position.xy / max(settings.crystal_size, 1.0);
+
+    // The noise breaks up the front, so the ice reaches out in fingers
+    let front = mix(growth_at(in.uv), noise_at(crystal_uv * 0.5), 0.2);
+    let softness = max(settings.softness, 1.0e-3);
+    let ice = smoothstep(front, front + softness, settings.freeze * (1.0 + softness));
+    if ice <= 0.0 {
+        return color;
+    }
+
+    // The ridges of a finer noise are the crystals, their slopes bending the light. The noise
+    // texture is 128 texels wide, the slopes are measured over one of them
+    let fine = crystal_uv * 2.0;
+    let delta = 1.0 / 128.0;
+    let center = noise_at(fine);
+    let slope = vec2(noise_at(fine + vec2(delta, 0.0)), noise_at(fine + vec2(0.0, delta)))
+        - center;
+    let tilt = clamp(slope * 8.0, vec2(-1.0), vec2(1.0));
+    let ridge = pow(1.0 - abs(center * 2.0 - 1.0), 4.0);
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    let uv = in.uv + tilt * settings.refraction * ice / size;
+    let refracted = textureSampleLevel(screen_texture, clamp_sampler, uv, 0.0).rgb;
+
+    let under = saturate_color(refracted, 1.0 - settings.desaturation * ice);
+    let layer = ice * mix(0.35, 1.0, ridge) * settings.color.a;
+    return vec4(mix(under, settings.color.rgb, layer), color.a);
+}
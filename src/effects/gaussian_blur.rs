@@ -0,0 +1,86 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin, PostProcessPlugins};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/gaussian_blur.wgsl";
+
+/// A separable Gaussian blur, run on the cameras with [`GaussianBlurSettings`].
+///
+/// The blur is two passes, a horizontal one then a vertical one, so its cost grows with the
+/// radius rather than with its square.
+#[derive(Default)]
+pub struct GaussianBlurPlugin {
+    /// Where the blur runs. The vertical pass always runs right after the horizontal one.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`GaussianBlurPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct GaussianBlurSettings {
+    /// The standard deviation of the blur, in pixels.
+    pub sigma: f32,
+    /// How many pixels are sampled on each side of a pixel, in each pass. Capped at 64.
+    ///
+    /// Three times the sigma covers nearly all the weight of the kernel, so a lower radius cuts
+    /// the blur off and a higher one only costs samples.
+    pub radius: u32,
+}
+
+impl GaussianBlurSettings {
+    /// A blur of this sigma, sampling three sigmas on each side.
+    pub fn new(sigma: f32) -> Self {
+        Self {
+            sigma,
+            radius: (sigma * 3.0).ceil() as u32,
+        }
+    }
+}
+
+impl Default for GaussianBlurSettings {
+    fn default() -> Self {
+        Self::new(4.0)
+    }
+}
+
+/// The render graph label of the horizontal pass of the [`GaussianBlurPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct GaussianBlurHorizontal;
+
+/// The render graph label of the vertical pass of the [`GaussianBlurPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct GaussianBlurVertical;
+
+impl Plugin for GaussianBlurPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "gaussian_blur.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<GaussianBlurSettings>(app, &[("sigma", 0.0..=32.0)]);
+        app.register_type::<GaussianBlurSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        let horizontal = PostProcessPlugin::<GaussianBlurSettings, _>::new(
+            SHADER_PATH,
+            GaussianBlurHorizontal,
+            Some("gaussian_blur_horizontal"),
+            "gaussian_blur_bind_group_layout",
+            vertex_state.clone(),
+        )
+        .with_placement(self.placement)
+        .without_view();
+        let vertical = PostProcessPlugin::<GaussianBlurSettings, _>::new(
+            SHADER_PATH,
+            GaussianBlurVertical,
+            Some("gaussian_blur_vertical"),
+            "gaussian_blur_bind_group_layout",
+            vertex_state,
+        )
+        .with_shader_def("VERTICAL")
+        .without_view();
+
+        app.add_plugins(PostProcessPlugins::default().with(horizontal).with(vertical));
+    }
+}
@@ -0,0 +1,38 @@
+// One pass of the separable Gaussian blur, horizontal unless VERTICAL is set.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct GaussianBlurSettings {
+    sigma: f32,
+    radius: u32,
+}
+@group(0) @binding(2) var<uniform> settings: GaussianBlurSettings;
+
+const MAX_RADIUS: u32 = 64u;
+
+fn sample(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(screen_texture, texture_sampler, uv, 0.0);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+#ifdef VERTICAL
+    let direction = vec2(0.0, 1.0);
+#else
+    let direction = vec2(1.0, 0.0);
+#endif
+    let step = direction / vec2<f32>(textureDimensions(screen_texture));
+    let radius = min(settings.radius, MAX_RADIUS);
+    let sigma = max(settings.sigma, 0.0001);
+
+    var color = sample(in.uv);
+    var total = 1.0;
+    for (var i = 1u; i <= radius; i++) {
+        let offset = f32(i);
+        let weight = exp(-offset * offset / (2.0 * sigma * sigma));
+        color += weight * (sample(in.uv + step * offset) + sample(in.uv - step * offset));
+        total += 2.0 * weight;
+    }
+    return color / total;
+}
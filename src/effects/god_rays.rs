@@ -0,0 +1,452 @@
+use crate::{
+    depth::depth_texture_entry, effects, filtering, resolution::scaled_size, PostProcessBindGroup,
+    PostProcessBindGroupLayout, PostProcessPlacement, PostProcessPlugin, PostProcessTexturePool,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::{core_3d::graph::Core3d, FullscreenShader},
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{ComponentUniforms, DynamicUniformIndex, ExtractComponent},
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::CachedTexture,
+        view::{Msaa, ViewDepthTexture, ViewTarget, ViewUniform, ViewUniformOffset, ViewUniforms},
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/god_rays.wgsl";
+
+/// The format of the reduced resolution light shafts.
+const SHAFTS_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// Light shafts streaming from the sun past the geometry, run on the cameras with
+/// [`GodRaysSettings`].
+///
+/// The [`GodRaysScatter`] node marches every pixel toward the position of the main directional
+/// light on the screen at a reduced resolution, gathering the sky the depth shows along the way
+/// and letting it decay with every step, after "Volumetric Light Scattering as a Post-Process" of
+/// GPU Gems 3. The [`GodRays`] effect then adds the shafts to the view. The main light is the
+/// directional light with a [`GodRaysLight`], or the brightest one when none has it. Placing the
+/// effect after the upscaling isn't supported.
+pub struct GodRaysPlugin {
+    /// Where the shafts are added.
+    pub placement: PostProcessPlacement,
+    /// The fraction of the view resolution the shafts are gathered at. They are smooth, so a low
+    /// resolution barely shows.
+    pub resolution_scale: f32,
+}
+
+impl Default for GodRaysPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::default(),
+            resolution_scale: 0.5,
+        }
+    }
+}
+
+/// The settings of the [`GodRaysPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct GodRaysSettings {
+    /// The tint of the shafts, multiplying the color of the light.
+    pub color: LinearRgba,
+    /// The color of the main light. Written by the plugin every frame, transparent when there is
+    /// no directional light.
+    pub light_color: LinearRgba,
+    /// The direction from the scene toward the main light, in world space. Written by the plugin
+    /// every frame.
+    pub light_direction: Vec3,
+    /// How far toward the light the march goes, as a fraction of the distance between the pixel
+    /// and the light on the screen. Longer shafts for higher values.
+    pub density: f32,
+    /// How much of the light every step keeps from the previous one, from 0 to 1. Values close to
+    /// 1 keep the shafts bright far away from the light.
+    pub decay: f32,
+    /// The light gathered by every step.
+    pub weight: f32,
+    /// The brightness of the shafts added to the view.
+    pub exposure: f32,
+    /// How many steps the march takes toward the light, capped at 128.
+    pub samples: u32,
+}
+
+impl Default for GodRaysSettings {
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::WHITE,
+            light_color: LinearRgba::NONE,
+            light_direction: Vec3::Y,
+            density: 0.9,
+            decay: 0.97,
+            weight: 0.05,
+            exposure: 0.3,
+            samples: 64,
+        }
+    }
+}
+
+/// The directional light the [`GodRaysPlugin`] streams the shafts from, when the scene has
+/// several.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default)]
+#[reflect(Component, Default)]
+pub struct GodRaysLight;
+
+/// The render graph label of the effect pass of the [`GodRaysPlugin`], adding the shafts to the
+/// view.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct GodRays;
+
+/// The render graph label of the node gathering the shafts of the [`GodRaysPlugin`] at a reduced
+/// resolution.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct GodRaysScatter;
+
+impl Plugin for GodRaysPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "god_rays.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<GodRaysSettings>(
+            app,
+            &[
+                ("density", 0.0..=2.0),
+                ("decay", 0.0..=1.0),
+                ("weight", 0.0..=1.0),
+                ("exposure", 0.0..=2.0),
+            ],
+        );
+        app.register_type::<GodRaysSettings>()
+            .register_type::<GodRaysLight>()
+            .add_systems(Update, update_lights);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<GodRaysSettings, _>::new(
+                SHADER_PATH,
+                GodRays,
+                Some("god_rays"),
+                "god_rays_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_bind_group(shafts_layout, prepare_shafts_bind_groups),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (before, _) = self.placement.edges();
+        render_app
+            .insert_resource(GodRaysResolution(self.resolution_scale.clamp(0.05, 1.0)))
+            .init_resource::<SpecializedRenderPipelines<GodRaysScatterPipeline>>()
+            .add_systems(Render, prepare_scatters.in_set(RenderSystems::PrepareResources))
+            .add_render_graph_node::<ViewNodeRunner<GodRaysScatterNode>>(Core3d, GodRaysScatter)
+            .add_render_graph_edges(Core3d, (before, GodRaysScatter, GodRays));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<GodRaysScatterPipeline>();
+    }
+}
+
+// The main light is the one marked with `GodRaysLight`, or the brightest one
+fn update_lights(
+    lights: Query<(&DirectionalLight, &GlobalTransform, Has<GodRaysLight>)>,
+    mut settings: Query<&mut GodRaysSettings>,
+) {
+    let main_light = lights.iter().max_by(|(a, _, a_marked), (b, _, b_marked)| {
+        a_marked
+            .cmp(b_marked)
+            .then(a.illuminance.total_cmp(&b.illuminance))
+    });
+    for mut settings in &mut settings {
+        let Some((light, transform, _)) = main_light else {
+            settings.light_color = LinearRgba::NONE;
+            continue;
+        };
+        // Directional lights shine along their forward direction
+        settings.light_direction = transform.back().into();
+        settings.light_color = light.color.into();
+    }
+}
+
+// The fraction of the view resolution the shafts are gathered at
+#[derive(Resource)]
+struct GodRaysResolution(f32);
+
+// The pipeline of the reduced resolution scatter. Its group 0 has the layout of the effect, so
+// both passes share the declarations of the shader
+#[derive(Resource)]
+struct GodRaysScatterPipeline {
+    layout: BindGroupLayout,
+    multisampled_layout: BindGroupLayout,
+    sampler: Sampler,
+    filterable: bool,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+}
+
+impl FromWorld for GodRaysScatterPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let filterable = filtering::screen_filterable(world);
+        let [screen_texture, screen_sampler] = filtering::screen_entries(filterable);
+        let layout = |label: &'static str, multisampled: bool| {
+            render_device.create_bind_group_layout(
+                label,
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::FRAGMENT,
+                    (
+                        screen_texture,
+                        screen_sampler,
+                        uniform_buffer::<GodRaysSettings>(true),
+                        uniform_buffer::<ViewUniform>(true),
+                        depth_texture_entry(multisampled),
+                    ),
+                ),
+            )
+        };
+
+        Self {
+            layout: layout("god_rays_scatter_bind_group_layout", false),
+            multisampled_layout: layout("god_rays_scatter_multisampled_bind_group_layout", true),
+            sampler: filtering::screen_sampler(
+                render_device,
+                filterable,
+                SamplerDescriptor {
+                    mag_filter: FilterMode::Linear,
+                    min_filter: FilterMode::Linear,
+                    ..default()
+                },
+            ),
+            filterable,
+            shader: load_embedded_asset!(world, "god_rays.wgsl"),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for GodRaysScatterPipeline {
+    // Whether the view uses MSAA, which makes its depth texture multisampled
+    type Key = bool;
+
+    fn specialize(&self, multisampled: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+        }
+        if !self.filterable {
+            shader_defs.push(filtering::SCREEN_TEXTURE_NON_FILTERABLE.into());
+        }
+        let layout = match multisampled {
+            true => &self.multisampled_layout,
+            false => &self.layout,
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("god_rays_scatter_pipeline".into()),
+            layout: vec![layout.clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some("scatter".into()),
+                targets: vec![Some(ColorTargetState {
+                    format: SHAFTS_TEXTURE_FORMAT,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+// The reduced resolution shafts of a view
+#[derive(Component)]
+struct ViewGodRaysScatter {
+    texture: CachedTexture,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+fn prepare_scatters(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewTarget, &Msaa), With<GodRaysSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    scatter_pipeline: Res<GodRaysScatterPipeline>,
+    resolution: Res<GodRaysResolution>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<GodRaysScatterPipeline>>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+) {
+    for (entity, view_target, msaa) in &views {
+        let texture = texture_pool.get(
+            &render_device,
+            entity,
+            GodRaysScatter,
+            "shafts",
+            &TextureDescriptor {
+                label: Some("god_rays_shafts_texture"),
+                size: scaled_size(view_target.main_texture().size(), resolution.0),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: SHAFTS_TEXTURE_FORMAT,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+        let pipeline_id =
+            pipelines.specialize(&pipeline_cache, &scatter_pipeline, msaa.samples() > 1);
+        commands.entity(entity).insert(ViewGodRaysScatter {
+            texture,
+            pipeline_id,
+        });
+    }
+}
+
+// The layout of the group 1 of the effect, the reduced resolution shafts. They are filtered by
+// the shader, as not every adapter can filter their format
+fn shafts_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "god_rays_shafts_bind_group_layout",
+        &BindGroupLayoutEntries::single(
+            ShaderStages::FRAGMENT,
+            texture_2d(TextureSampleType::Float { filterable: false }),
+        ),
+    )
+}
+
+// The effect skips the views without the bind group, so it doesn't read the shafts until the
+// scatter pipeline is compiled
+fn prepare_shafts_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewGodRaysScatter), With<GodRaysSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    layout: Res<PostProcessBindGroupLayout<GodRaysSettings>>,
+) {
+    for (entity, scatter) in &views {
+        if pipeline_cache.get_render_pipeline(scatter.pipeline_id).is_none() {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<GodRaysSettings>>();
+            continue;
+        }
+
+        let bind_group = render_device.create_bind_group(
+            "god_rays_shafts_bind_group",
+            &layout.layout,
+            &BindGroupEntries::single(&scatter.texture.default_view),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<GodRaysSettings>::new(bind_group));
+    }
+}
+
+#[derive(Default)]
+struct GodRaysScatterNode;
+
+impl ViewNode for GodRaysScatterNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        // Only runs on the cameras with the god rays
+        &'static GodRaysSettings,
+        &'static ViewGodRaysScatter,
+        &'static DynamicUniformIndex<GodRaysSettings>,
+        &'static ViewUniformOffset,
+        &'static ViewDepthTexture,
+        &'static Msaa,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (
+            view_target,
+            _settings,
+            scatter,
+            settings_index,
+            view_uniform_offset,
+            view_depth,
+            msaa,
+        ): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // The effect doesn't read the shafts on the views it skips
+        let view = graph.view_entity();
+        if effects::skip_intermediate::<GodRaysSettings, GodRays>(world, view) {
+            return Ok(());
+        }
+
+        let scatter_pipeline = world.resource::<GodRaysScatterPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (Some(pipeline), Some(settings_binding), Some(view_binding)) = (
+            pipeline_cache.get_render_pipeline(scatter.pipeline_id),
+            world
+                .resource::<ComponentUniforms<GodRaysSettings>>()
+                .uniforms()
+                .binding(),
+            world.resource::<ViewUniforms>().uniforms.binding(),
+        ) else {
+            return Ok(());
+        };
+
+        let layout = match msaa.samples() > 1 {
+            true => &scatter_pipeline.multisampled_layout,
+            false => &scatter_pipeline.layout,
+        };
+        let bind_group = render_context.render_device().create_bind_group(
+            "god_rays_scatter_bind_group",
+            layout,
+            &BindGroupEntries::sequential((
+                view_target.main_texture_view(),
+                &scatter_pipeline.sampler,
+                settings_binding,
+                view_binding,
+                view_depth.view(),
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("god_rays_scatter_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &scatter.texture.default_view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(
+            0,
+            &bind_group,
+            &[settings_index.index(), view_uniform_offset.offset],
+        );
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
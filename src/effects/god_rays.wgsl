@@ -0,0 +1,90 @@
+// Light shafts after "Volumetric Light Scattering as a Post-Process", GPU Gems 3. The `scatter`
+// entry point marches every pixel toward the light on the screen at a reduced resolution,
+// gathering the sky seen along the way, then `fragment` adds the shafts to the view.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_post_process::utils::ndc_to_uv
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct GodRaysSettings {
+    color: vec4<f32>,
+    light_color: vec4<f32>,
+    light_direction: vec3<f32>,
+    density: f32,
+    decay: f32,
+    weight: f32,
+    exposure: f32,
+    samples: u32,
+}
+@group(0) @binding(2) var<uniform> settings: GodRaysSettings;
+@group(0) @binding(3) var<uniform> view: View;
+#ifdef MULTISAMPLED
+@group(0) @binding(4) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(4) var depth_texture: texture_depth_2d;
+#endif
+
+// The shafts at a reduced resolution
+@group(1) @binding(0) var shafts_texture: texture_2d<f32>;
+
+const MAX_SAMPLES: u32 = 128u;
+
+// The light reaching the screen at a UV, the sky behind the geometry. The sky is cleared to a
+// depth of 0
+fn light_at(uv: vec2<f32>) -> vec3<f32> {
+    if any(uv < vec2(0.0)) || any(uv > vec2(1.0)) {
+        return vec3(0.0);
+    }
+    let size = vec2<i32>(textureDimensions(depth_texture));
+    let pixel = clamp(vec2<i32>(uv * vec2<f32>(size)), vec2(0), size - 1);
+    if textureLoad(depth_texture, pixel, 0) > 0.0 {
+        return vec3(0.0);
+    }
+    return textureSampleLevel(screen_texture, texture_sampler, uv, 0.0).rgb;
+}
+
+@fragment
+fn scatter(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // The light is infinitely far away, and fades out as it leaves the field of view
+    let light_clip = view.clip_from_world * vec4(settings.light_direction, 0.0);
+    if light_clip.w <= 0.0 || settings.light_color.a <= 0.0 {
+        return vec4(0.0);
+    }
+    let light_uv = ndc_to_uv(light_clip.xy / light_clip.w);
+    let outside = max(max(-light_uv, light_uv - 1.0), vec2(0.0));
+    let facing = 1.0 - smoothstep(0.0, 0.5, length(outside));
+
+    let samples = clamp(settings.samples, 1u, MAX_SAMPLES);
+    let delta = (in.uv - light_uv) * settings.density / f32(samples);
+    var uv = in.uv;
+    var illumination = 1.0;
+    var shafts = vec3(0.0);
+    for (var i = 0u; i < samples; i++) {
+        uv -= delta;
+        shafts += light_at(uv) * illumination * settings.weight;
+        illumination *= settings.decay;
+    }
+    return vec4(shafts * facing, 1.0);
+}
+
+// Bilinear filtering of the shafts by hand, as not every adapter can filter their format
+fn sample_shafts(uv: vec2<f32>) -> vec3<f32> {
+    let size = vec2<i32>(textureDimensions(shafts_texture));
+    let position = uv * vec2<f32>(size) - 0.5;
+    let base = vec2<i32>(floor(position));
+    let f = fract(position);
+    let top_left = textureLoad(shafts_texture, clamp(base, vec2(0), size - 1), 0).rgb;
+    let top_right = textureLoad(shafts_texture, clamp(base + vec2(1, 0), vec2(0), size - 1), 0).rgb;
+    let bottom_left = textureLoad(shafts_texture, clamp(base + vec2(0, 1), vec2(0), size - 1), 0).rgb;
+    let bottom_right = textureLoad(shafts_texture, clamp(base + vec2(1), vec2(0), size - 1), 0).rgb;
+    return mix(mix(top_left, top_right, f.x), mix(bottom_left, bottom_right, f.x), f.y);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let tint = settings.color.rgb * settings.light_color.rgb;
+    let shafts = sample_shafts(in.uv) * tint * settings.exposure;
+    return vec4(color.rgb + shafts, color.a);
+}
@@ -0,0 +1,88 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/halftone.wgsl";
+
+/// A halftone print, run on the cameras with [`HalftoneSettings`].
+///
+/// Redraws the view as grids of dots, one rotated grid per ink, whose dots grow with the amount
+/// of ink their area needs, like newspapers and comics. Runs after the tonemapping by default.
+pub struct HalftonePlugin {
+    /// Where the effect runs.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for HalftonePlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+        }
+    }
+}
+
+/// The settings of the [`HalftonePlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct HalftoneSettings {
+    /// The inks, [`HalftoneSettings::MODE_CMYK`] or [`HalftoneSettings::MODE_MONOCHROME`].
+    pub mode: u32,
+    /// The distance between the dots of a screen, in pixels.
+    pub dot_spacing: f32,
+    /// The rotations of the cyan, magenta, yellow and black screens, in radians. The monochrome
+    /// mode uses the black one.
+    pub screen_angles: Vec4,
+    /// The color of the paper.
+    pub paper: LinearRgba,
+    /// The color of the ink of the monochrome mode.
+    pub ink: LinearRgba,
+}
+
+impl HalftoneSettings {
+    /// Four screens of cyan, magenta, yellow and black ink, like color print.
+    pub const MODE_CMYK: u32 = 0;
+    /// A single screen of [`HalftoneSettings::ink`] following the luminance.
+    pub const MODE_MONOCHROME: u32 = 1;
+}
+
+impl Default for HalftoneSettings {
+    fn default() -> Self {
+        Self {
+            mode: Self::MODE_CMYK,
+            dot_spacing: 6.0,
+            // The traditional angles, keeping the screens from forming moiré patterns
+            screen_angles: Vec4::new(15.0, 75.0, 0.0, 45.0).map(f32::to_radians),
+            paper: LinearRgba::WHITE,
+            ink: LinearRgba::BLACK,
+        }
+    }
+}
+
+/// The render graph label of the [`HalftonePlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Halftone;
+
+impl Plugin for HalftonePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "halftone.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<HalftoneSettings>(app, &[("dot_spacing", 2.0..=32.0)]);
+        app.register_type::<HalftoneSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<HalftoneSettings, _>::new(
+                SHADER_PATH,
+                Halftone,
+                Some("halftone"),
+                "halftone_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view(),
+        );
+    }
+}
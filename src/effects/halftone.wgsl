@@ -0,0 +1,83 @@
+// Rotated screens of dots, one per ink, sized by the ink coverage at the center of every dot.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::{linear_to_srgb, srgb_to_linear}
+#import bevy_post_process::utils::luminance
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct HalftoneSettings {
+    mode: u32,
+    dot_spacing: f32,
+    screen_angles: vec4<f32>,
+    paper: vec4<f32>,
+    ink: vec4<f32>,
+}
+@group(0) @binding(2) var<uniform> settings: HalftoneSettings;
+
+const MODE_MONOCHROME: u32 = 1u;
+const PI: f32 = 3.14159265;
+
+fn rotation(angle: f32) -> mat2x2<f32> {
+    let c = cos(angle);
+    let s = sin(angle);
+    return mat2x2(c, s, -s, c);
+}
+
+// The sRGB encoded color at a pixel, as the inks are laid in print
+fn sample_encoded(pixel: vec2<f32>, size: vec2<f32>) -> vec3<f32> {
+    let uv = clamp(pixel / size, vec2(0.0), vec2(1.0));
+    let color = textureSampleLevel(screen_texture, texture_sampler, uv, 0.0).rgb;
+    return clamp(linear_to_srgb(max(color, vec3(0.0))), vec3(0.0), vec3(1.0));
+}
+
+fn cmyk(color: vec3<f32>) -> vec4<f32> {
+    let k = 1.0 - max(max(color.r, color.g), color.b);
+    let cmy = (1.0 - color - k) / max(1.0 - k, 1.0e-5);
+    return vec4(cmy, k);
+}
+
+// How much a screen inks a pixel, from 0 to 1, with antialiased dot edges. `channel` selects the
+// ink of the CMYK coverage sampled at the dot center, 4 meaning the inverted luminance
+fn screen(pixel: vec2<f32>, size: vec2<f32>, angle: f32, channel: u32) -> f32 {
+    let spacing = max(settings.dot_spacing, 1.0);
+    let to_screen = rotation(angle);
+    let position = to_screen * pixel / spacing;
+    let center = floor(position) + 0.5;
+
+    // The rotation is orthonormal, so its transpose brings the dot center back to the view
+    let center_pixel = transpose(to_screen) * center * spacing;
+    let color = sample_encoded(center_pixel, size);
+    var coverage: f32;
+    if channel == 4u {
+        coverage = 1.0 - luminance(color);
+    } else {
+        coverage = cmyk(color)[channel];
+    }
+
+    // A dot covering `coverage` of its cell, in cell units
+    let radius = sqrt(max(coverage, 0.0) / PI);
+    let distance_to_center = length(position - center);
+    let edge = max(fwidth(distance_to_center), 1.0e-4);
+    return 1.0 - smoothstep(radius - edge, radius + edge, distance_to_center);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    let pixel = in.position.xy;
+    let paper = linear_to_srgb(settings.paper.rgb);
+
+    if settings.mode == MODE_MONOCHROME {
+        let ink = screen(pixel, size, settings.screen_angles.w, 4u);
+        let color = mix(paper, linear_to_srgb(settings.ink.rgb), ink);
+        return vec4(srgb_to_linear(color), 1.0);
+    }
+
+    // The inks absorb the light the paper reflects
+    let c = screen(pixel, size, settings.screen_angles.x, 0u);
+    let m = screen(pixel, size, settings.screen_angles.y, 1u);
+    let y = screen(pixel, size, settings.screen_angles.z, 2u);
+    let k = screen(pixel, size, settings.screen_angles.w, 3u);
+    let color = paper * (1.0 - vec3(c, m, y)) * (1.0 - k);
+    return vec4(srgb_to_linear(color), 1.0);
+}
@@ -0,0 +1,161 @@
+use crate::{effects, PostProcessInput, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::{embedded_asset, uuid_handle, RenderAssetUsages},
+    image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor},
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/hatching.wgsl";
+
+/// A pencil or ink sketch, run on the cameras with [`HatchingSettings`].
+///
+/// Redraws the view with layers of strokes read from tonal art maps: six tiling hatching
+/// textures, each adding strokes to the previous one, blended by the luminance of the pixel.
+/// Runs after the tonemapping by default.
+pub struct HatchingPlugin {
+    /// Where the effect runs.
+    pub placement: PostProcessPlacement,
+    /// The six tones, from the lightest to the darkest, in the red, green and blue channels of
+    /// two tiling textures. 1 is paper and 0 is a stroke. Defaults to
+    /// [`HatchingPlugin::DEFAULT_TONAL_ART_MAPS`].
+    pub tonal_art_maps: [Handle<Image>; 2],
+}
+
+impl HatchingPlugin {
+    /// Straight crossing hatches of increasing density, generated by the plugin.
+    pub const DEFAULT_TONAL_ART_MAPS: [Handle<Image>; 2] = [
+        uuid_handle!("b3e8d2a6-5f1c-4b9e-a7d3-6c2f8e1b4a95"),
+        uuid_handle!("7a4f9c1e-3d6b-4e2a-8f5c-9b1d3e7a2c06"),
+    ];
+}
+
+impl Default for HatchingPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+            tonal_art_maps: Self::DEFAULT_TONAL_ART_MAPS,
+        }
+    }
+}
+
+/// The settings of the [`HatchingPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct HatchingSettings {
+    /// The size of a tile of the tonal art maps on screen, in pixels.
+    pub tile_size: f32,
+    /// The color of the strokes.
+    pub ink: LinearRgba,
+    /// The color of the paper.
+    pub paper: LinearRgba,
+    /// How much the paper takes the color of the view, from 0 for plain paper to 1 for colored
+    /// pencils.
+    pub tint: f32,
+}
+
+impl Default for HatchingSettings {
+    fn default() -> Self {
+        Self {
+            tile_size: 64.0,
+            ink: LinearRgba::rgb(0.02, 0.02, 0.03),
+            paper: LinearRgba::rgb(0.9, 0.87, 0.8),
+            tint: 0.0,
+        }
+    }
+}
+
+/// The render graph label of the [`HatchingPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Hatching;
+
+const TONAL_ART_MAP_SIZE: usize = 64;
+
+// The strokes every tone adds to the previous one, as the spacing and the direction of a family
+// of lines. The directions are 0 for horizontal, 1 for vertical and 2 and 3 for the diagonals
+const TONE_STROKES: [(usize, usize); 6] = [(8, 0), (8, 2), (4, 0), (8, 1), (4, 2), (4, 3)];
+
+fn tonal_art_maps() -> [Image; 2] {
+    let size = TONAL_ART_MAP_SIZE;
+    let mut tones = [[255u8; TONAL_ART_MAP_SIZE * TONAL_ART_MAP_SIZE]; 6];
+    for (tone, &(spacing, direction)) in TONE_STROKES.iter().enumerate() {
+        for y in 0..size {
+            for x in 0..size {
+                let coordinate = match direction {
+                    0 => y,
+                    1 => x,
+                    2 => x + y,
+                    _ => x + size - y,
+                };
+                if coordinate % spacing == 0 {
+                    // Every stroke of a tone stays in the darker ones
+                    for darker in &mut tones[tone..] {
+                        darker[y * size + x] = 0;
+                    }
+                }
+            }
+        }
+    }
+
+    [0, 3].map(|first| {
+        let data = (0..size * size)
+            .flat_map(|index| {
+                let [r, g, b] = [0, 1, 2].map(|channel| tones[first + channel][index]);
+                [r, g, b, 255]
+            })
+            .collect();
+        let mut image = Image::new(
+            Extent3d {
+                width: size as u32,
+                height: size as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8Unorm,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+            address_mode_u: ImageAddressMode::Repeat,
+            address_mode_v: ImageAddressMode::Repeat,
+            ..ImageSamplerDescriptor::linear()
+        });
+        image
+    })
+}
+
+impl Plugin for HatchingPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "hatching.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<HatchingSettings>(
+            app,
+            &[("tile_size", 16.0..=512.0), ("tint", 0.0..=1.0)],
+        );
+        app.register_type::<HatchingSettings>();
+
+        let mut images = app.world_mut().resource_mut::<Assets<Image>>();
+        for (handle, image) in Self::DEFAULT_TONAL_ART_MAPS.iter().zip(tonal_art_maps()) {
+            images
+                .insert(handle, image)
+                .expect("the tonal art map handles are uuid handles");
+        }
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        let [light_tones, dark_tones] = self.tonal_art_maps.clone();
+        app.add_plugins(
+            PostProcessPlugin::<HatchingSettings, _>::new(
+                SHADER_PATH,
+                Hatching,
+                Some("hatching"),
+                "hatching_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_input(PostProcessInput::Image(light_tones))
+            .with_input(PostProcessInput::Image(dark_tones))
+            .with_standard_samplers()
+            .without_view(),
+        );
+    }
+}
@@ -0,0 +1,39 @@
+// Blends the six tones of the tonal art maps by the luminance of the pixel, the tones nesting so
+// the strokes stay in place as the shading changes.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::linear_to_srgb
+#import bevy_post_process::utils::luminance
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct HatchingSettings {
+    tile_size: f32,
+    ink: vec4<f32>,
+    paper: vec4<f32>,
+    tint: f32,
+}
+@group(0) @binding(2) var<uniform> settings: HatchingSettings;
+@group(0) @binding(9) var repeat_sampler: sampler;
+@group(0) @binding(16) var light_tones: texture_2d<f32>;
+@group(0) @binding(17) var dark_tones: texture_2d<f32>;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let brightness = clamp(linear_to_srgb(vec3(luminance(max(color.rgb, vec3(0.0))))).x, 0.0, 1.0);
+
+    let tile_uv = in.position.xy / max(settings.tile_size, 1.0);
+    let light = textureSampleLevel(light_tones, repeat_sampler, tile_uv, 0.0).rgb;
+    let dark = textureSampleLevel(dark_tones, repeat_sampler, tile_uv, 0.0).rgb;
+
+    // Tone 0 is the bare paper, tone 6 the densest hatching. Every pixel blends the two tones
+    // around its shade
+    let shade = (1.0 - brightness) * 6.0;
+    let paper_weight = clamp(1.0 - shade, 0.0, 1.0);
+    let light_weights = clamp(1.0 - abs(shade - vec3(1.0, 2.0, 3.0)), vec3(0.0), vec3(1.0));
+    let dark_weights = clamp(1.0 - abs(shade - vec3(4.0, 5.0, 6.0)), vec3(0.0), vec3(1.0));
+    let hatching = paper_weight + dot(light, light_weights) + dot(dark, dark_weights);
+
+    let paper = mix(settings.paper.rgb, color.rgb, settings.tint);
+    return vec4(mix(settings.ink.rgb, paper, hatching), color.a);
+}
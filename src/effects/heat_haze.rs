@@ -0,0 +1,120 @@
+use crate::{
+    effects, noise, PostProcessInput, PostProcessNoisePlugin, PostProcessPlacement,
+    PostProcessPlugin,
+};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+use std::ops::Range;
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/heat_haze.wgsl";
+
+/// The shimmer of hot air over the distance, run on the cameras with [`HeatHazeSettings`].
+///
+/// Every pixel is displaced by a value noise rising up the screen, growing with the distance from
+/// the camera. The pixels closer than the start of the `depth_range` are left untouched by the
+/// depth range test of the crate, and a displaced pixel never picks up the color of geometry much
+/// closer than itself, so the foreground stays sharp. Runs on the HDR colors by default. Placing
+/// it after the upscaling isn't supported.
+///
+/// Adds the [`PostProcessNoisePlugin`] when it isn't already.
+pub struct HeatHazePlugin {
+    /// Where the haze runs.
+    pub placement: PostProcessPlacement,
+    /// The distances from the camera the haze applies to, in world units, see
+    /// [`PostProcessPlugin::with_depth_range`].
+    pub depth_range: Range<f32>,
+}
+
+impl Default for HeatHazePlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::default(),
+            depth_range: 5.0..f32::INFINITY,
+        }
+    }
+}
+
+/// The settings of the [`HeatHazePlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct HeatHazeSettings {
+    /// How far the pixels are displaced at full strength, in pixels.
+    pub strength: f32,
+    /// The size of the waves of the shimmer, in pixels.
+    pub scale: f32,
+    /// How fast the shimmer rises up the screen, in pixels per second.
+    pub rise_speed: f32,
+    /// How far the shimmer has risen, in pixels. Advanced every frame by the `rise_speed`.
+    pub rise_offset: f32,
+    /// The distance from the camera the haze reaches its full strength at, in world units. It
+    /// grows from nothing at the camera.
+    pub full_strength_distance: f32,
+}
+
+impl Default for HeatHazeSettings {
+    fn default() -> Self {
+        Self {
+            strength: 3.0,
+            scale: 48.0,
+            rise_speed: 40.0,
+            rise_offset: 0.0,
+            full_strength_distance: 50.0,
+        }
+    }
+}
+
+/// The render graph label of the [`HeatHazePlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct HeatHaze;
+
+impl Plugin for HeatHazePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "heat_haze.wgsl");
+        if !app.is_plugin_added::<PostProcessNoisePlugin>() {
+            app.add_plugins(PostProcessNoisePlugin);
+        }
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<HeatHazeSettings>(
+            app,
+            &[
+                ("strength", 0.0..=16.0),
+                ("scale", 4.0..=256.0),
+                ("rise_speed", 0.0..=200.0),
+                ("full_strength_distance", 0.0..=200.0),
+            ],
+        );
+        app.register_type::<HeatHazeSettings>()
+            .add_systems(Update, rise_haze);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<HeatHazeSettings, _>::new(
+                SHADER_PATH,
+                HeatHaze,
+                Some("heat_haze"),
+                "heat_haze_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_depth()
+            .with_depth_range(self.depth_range.clone())
+            .with_input(PostProcessInput::Image(noise::VALUE_NOISE))
+            .with_standard_samplers(),
+        );
+    }
+}
+
+fn rise_haze(mut settings: Query<&mut HeatHazeSettings>, time: Res<Time>) {
+    for mut settings in &mut settings {
+        if settings.rise_speed == 0.0 {
+            continue;
+        }
+        // Wrapped to one tile of the noise, so the offset keeps its precision
+        let scale = settings.scale.max(1.0);
+        settings.rise_offset =
+            (settings.rise_offset + settings.rise_speed * time.delta_secs()).rem_euclid(scale);
+    }
+}
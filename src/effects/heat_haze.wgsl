@@ -0,0 +1,66 @@
+// Heat haze. Every pixel is displaced by a value noise scrolling up the screen, scaled by its
+// distance from the camera, and keeps its own color when the displaced one comes from geometry
+// much closer to the camera.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_post_process::utils::linearize_depth
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct HeatHazeSettings {
+    strength: f32,
+    scale: f32,
+    rise_speed: f32,
+    rise_offset: f32,
+    full_strength_distance: f32,
+}
+@group(0) @binding(2) var<uniform> settings: HeatHazeSettings;
+@group(0) @binding(3) var<uniform> view: View;
+#ifdef MULTISAMPLED
+@group(0) @binding(4) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(4) var depth_texture: texture_depth_2d;
+#endif
+@group(0) @binding(7) var clamp_sampler: sampler;
+@group(0) @binding(9) var repeat_sampler: sampler;
+@group(0) @binding(16) var value_noise: texture_2d<f32>;
+
+// The sky is cleared to a depth of 0, infinitely far away
+const FAR_DISTANCE: f32 = 1.0e6;
+// How much closer than the pixel the displaced color can come from
+const OCCLUSION_TOLERANCE: f32 = 0.8;
+
+fn distance_at(pixel: vec2<i32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(depth_texture));
+    let depth = textureLoad(depth_texture, clamp(pixel, vec2(0), size - 1), 0);
+    if depth <= 0.0 {
+        return FAR_DISTANCE;
+    }
+    return linearize_depth(depth, view.view_from_clip);
+}
+
+fn noise_at(uv: vec2<f32>) -> f32 {
+    return textureSampleLevel(value_noise, repeat_sampler, uv, 0.0).r * 2.0 - 1.0;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    let center_distance = distance_at(vec2<i32>(in.position.xy));
+    let amount = clamp(center_distance / max(settings.full_strength_distance, 1.0e-3), 0.0, 1.0);
+
+    // The noise is read further down as time goes, so the waves rise. Hot air mostly bends the
+    // light sideways
+    let noise_uv = (in.position.xy + vec2(0.0, settings.rise_offset)) / max(settings.scale, 1.0);
+    let wave = vec2(noise_at(noise_uv), noise_at(noise_uv + vec2(0.37, 0.61)) * 0.5);
+    let offset = wave * settings.strength * amount;
+    let uv = in.uv + offset / size;
+
+    let displaced_distance = distance_at(vec2<i32>(in.position.xy + offset));
+    if displaced_distance < center_distance * OCCLUSION_TOLERANCE {
+        return color;
+    }
+    let displaced = textureSampleLevel(screen_texture, clamp_sampler, uv, 0.0);
+    return vec4(displaced.rgb, color.a);
+}
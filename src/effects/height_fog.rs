@@ -0,0 +1,126 @@
+use crate::{
+    effects, noise, PostProcessInput, PostProcessNoisePlugin, PostProcessPlacement,
+    PostProcessPlugin,
+};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/height_fog.wgsl";
+
+/// A fog pooling near the ground, run on the cameras with [`HeightFogSettings`].
+///
+/// The world position of every pixel is rebuilt from the depth, and the fog is integrated along
+/// the ray from the camera through a density thinning out exponentially with the height. The
+/// value noise of the [`noise`] module, scrolled by the wind, breaks the density into drifting
+/// patches of mist. Runs on the HDR colors by default. Placing it after the upscaling isn't
+/// supported.
+///
+/// Adds the [`PostProcessNoisePlugin`] when it isn't already.
+#[derive(Default)]
+pub struct HeightFogPlugin {
+    /// Where the fog runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`HeightFogPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct HeightFogSettings {
+    /// The color of the fog, its alpha scaling the amount of fog.
+    pub color: LinearRgba,
+    /// The speed the noise drifts with, in world units per second along the X and Z axes.
+    pub wind: Vec2,
+    /// How far the noise has drifted, in world units. Advanced every frame by the `wind`.
+    pub wind_offset: Vec2,
+    /// The height of the ground the fog lies on, in world units. The fog is densest below it.
+    pub base_height: f32,
+    /// The density of the fog at the base height, per world unit.
+    pub density: f32,
+    /// How quickly the fog thins out above the base height. Every `1 / falloff` world units
+    /// higher, the density is divided by e.
+    pub falloff: f32,
+    /// How far the fog is integrated along the rays, in world units. The sky counts as this far.
+    pub max_distance: f32,
+    /// The most the fog covers, so the sky or the distant geometry can still show through.
+    pub max_opacity: f32,
+    /// The size of a tile of the noise, in world units.
+    pub noise_size: f32,
+    /// How much the noise varies the density, from 0 for a uniform fog to 1 for patches of mist
+    /// between clear areas.
+    pub noise_intensity: f32,
+}
+
+impl Default for HeightFogSettings {
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::rgb(0.7, 0.75, 0.8),
+            wind: Vec2::new(0.5, 0.2),
+            wind_offset: Vec2::ZERO,
+            base_height: 0.0,
+            density: 0.1,
+            falloff: 0.5,
+            max_distance: 500.0,
+            max_opacity: 1.0,
+            noise_size: 30.0,
+            noise_intensity: 0.5,
+        }
+    }
+}
+
+/// The render graph label of the [`HeightFogPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct HeightFog;
+
+impl Plugin for HeightFogPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "height_fog.wgsl");
+        if !app.is_plugin_added::<PostProcessNoisePlugin>() {
+            app.add_plugins(PostProcessNoisePlugin);
+        }
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<HeightFogSettings>(
+            app,
+            &[
+                ("base_height", -100.0..=100.0),
+                ("density", 0.0..=1.0),
+                ("falloff", 0.0..=2.0),
+                ("max_distance", 0.0..=1000.0),
+                ("max_opacity", 0.0..=1.0),
+                ("noise_size", 1.0..=100.0),
+                ("noise_intensity", 0.0..=1.0),
+            ],
+        );
+        app.register_type::<HeightFogSettings>()
+            .add_systems(Update, blow_wind);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<HeightFogSettings, _>::new(
+                SHADER_PATH,
+                HeightFog,
+                Some("height_fog"),
+                "height_fog_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_depth()
+            .with_input(PostProcessInput::Image(noise::VALUE_NOISE))
+            .with_standard_samplers(),
+        );
+    }
+}
+
+fn blow_wind(mut settings: Query<&mut HeightFogSettings>, time: Res<Time>) {
+    for mut settings in &mut settings {
+        if settings.wind == Vec2::ZERO {
+            continue;
+        }
+        // Wrapped to one tile of the noise, so the offset keeps its precision
+        let size = settings.noise_size.max(1.0e-3);
+        settings.wind_offset = (settings.wind_offset + settings.wind * time.delta_secs())
+            .map(|offset| offset.rem_euclid(size));
+    }
+}
@@ -0,0 +1,83 @@
+// Integrates an exponential height fog along the ray from the camera to every pixel, its density
+// varied by a drifting value noise read at a few points along the ray.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_post_process::utils::position_world_from_depth
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct HeightFogSettings {
+    color: vec4<f32>,
+    wind: vec2<f32>,
+    wind_offset: vec2<f32>,
+    base_height: f32,
+    density: f32,
+    falloff: f32,
+    max_distance: f32,
+    max_opacity: f32,
+    noise_size: f32,
+    noise_intensity: f32,
+}
+@group(0) @binding(2) var<uniform> settings: HeightFogSettings;
+@group(0) @binding(3) var<uniform> view: View;
+#ifdef MULTISAMPLED
+@group(0) @binding(4) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(4) var depth_texture: texture_depth_2d;
+#endif
+@group(0) @binding(9) var repeat_sampler: sampler;
+@group(0) @binding(16) var value_noise: texture_2d<f32>;
+
+const NOISE_SAMPLES: u32 = 3u;
+
+// The average of (1 - exp(-x)) / x, the thinning of the density along a ray climbing by x over
+// the falloff, with its limit of 1 for flat rays
+fn climb_factor(x: f32) -> f32 {
+    if abs(x) < 1.0e-4 {
+        return 1.0;
+    }
+    return (1.0 - exp(-x)) / x;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let depth = textureLoad(depth_texture, vec2<i32>(in.position.xy), 0);
+
+    // The sky is cleared to a depth of 0, and is as far as the fog goes
+    let origin = view.world_position;
+    let near = position_world_from_depth(in.uv, 1.0, view.world_from_clip);
+    let middle = position_world_from_depth(in.uv, 0.5, view.world_from_clip);
+    let direction = normalize(middle - near);
+    var ray_length = settings.max_distance;
+    if depth > 0.0 {
+        let position = position_world_from_depth(in.uv, depth, view.world_from_clip);
+        ray_length = min(distance(position, origin), settings.max_distance);
+    }
+
+    // The density integrated analytically along the ray
+    let falloff = max(settings.falloff, 0.0);
+    let camera_height = origin.y - settings.base_height;
+    let climb = direction.y * ray_length;
+    let optical_depth = settings.density * exp(-falloff * camera_height) * ray_length
+        * climb_factor(falloff * climb);
+
+    // The noise is read where the fog is, weighted by the density at every point
+    var noise = 0.0;
+    var weights = 0.0;
+    for (var i = 0u; i < NOISE_SAMPLES; i++) {
+        let t = (f32(i) + 0.5) / f32(NOISE_SAMPLES);
+        let position = origin + direction * ray_length * t;
+        let weight = exp(-falloff * (position.y - settings.base_height)) + 1.0e-5;
+        // The height shifts the noise too, so the mist doesn't look extruded from the ground
+        let drifted = position.xz - settings.wind_offset + position.y * 0.37;
+        let noise_uv = drifted / max(settings.noise_size, 1.0e-3);
+        noise += textureSampleLevel(value_noise, repeat_sampler, noise_uv, 0.0).r * weight;
+        weights += weight;
+    }
+    let modulation = mix(1.0, noise / weights * 2.0, clamp(settings.noise_intensity, 0.0, 1.0));
+
+    let amount = 1.0 - exp(-max(optical_depth * modulation, 0.0));
+    let opacity = min(amount, settings.max_opacity) * settings.color.a;
+    return vec4(mix(color.rgb, settings.color.rgb, opacity), color.a);
+}
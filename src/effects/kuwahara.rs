@@ -0,0 +1,82 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/kuwahara.wgsl";
+
+/// A painterly filter, run on the cameras with [`KuwaharaSettings`].
+///
+/// The generalized Kuwahara filter: every pixel takes the average color of the least varied of
+/// eight overlapping sectors around it, flattening areas into strokes while keeping their edges.
+/// The kernel reads hundreds of pixels, so the effect renders at half resolution by default.
+/// Runs after the tonemapping by default.
+pub struct KuwaharaPlugin {
+    /// Where the effect runs.
+    pub placement: PostProcessPlacement,
+    /// The fraction of the view resolution the filter renders at, see
+    /// [`PostProcessPlugin::with_resolution_scale`].
+    pub resolution_scale: f32,
+}
+
+impl Default for KuwaharaPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+            resolution_scale: 0.5,
+        }
+    }
+}
+
+/// The settings of the [`KuwaharaPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct KuwaharaSettings {
+    /// The radius of the kernel, in pixels of the view, up to 12. Larger radii make broader
+    /// strokes.
+    pub radius: f32,
+    /// How strongly the least varied sectors win over the others. Low values blend the sectors
+    /// into a smoother paint, high values make crisper strokes.
+    pub sharpness: f32,
+}
+
+impl Default for KuwaharaSettings {
+    fn default() -> Self {
+        Self {
+            radius: 6.0,
+            sharpness: 8.0,
+        }
+    }
+}
+
+/// The render graph label of the [`KuwaharaPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Kuwahara;
+
+impl Plugin for KuwaharaPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "kuwahara.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<KuwaharaSettings>(
+            app,
+            &[("radius", 1.0..=12.0), ("sharpness", 0.0..=20.0)],
+        );
+        app.register_type::<KuwaharaSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<KuwaharaSettings, _>::new(
+                SHADER_PATH,
+                Kuwahara,
+                Some("kuwahara"),
+                "kuwahara_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_resolution_scale(self.resolution_scale)
+            .without_view(),
+        );
+    }
+}
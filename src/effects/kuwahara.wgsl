@@ -0,0 +1,80 @@
+// The generalized Kuwahara filter with the polynomial sector weights of Kyprianidis et al., "Image
+// and Video Abstraction by Anisotropic Kuwahara Filtering". Every pixel blends the means of eight
+// sectors of a disc, weighted by how little their colors vary.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct KuwaharaSettings {
+    radius: f32,
+    sharpness: f32,
+}
+@group(0) @binding(2) var<uniform> settings: KuwaharaSettings;
+
+const SECTORS: u32 = 8u;
+const MAX_RADIUS: f32 = 12.0;
+// cos(pi / 8) and sin(pi / 8) squared, for the sector polynomials
+const COS_HALF_SECTOR: f32 = 0.92387953;
+const SIN_HALF_SECTOR_SQUARED: f32 = 0.14644661;
+
+// The weights of the four sectors centered on the axes at the normalized offset `v`
+fn axis_weights(v: vec2<f32>, zeta: f32, eta: f32) -> vec4<f32> {
+    let vxx = zeta - eta * v.x * v.x;
+    let vyy = zeta - eta * v.y * v.y;
+    let w = max(vec4(v.y + vxx, -v.x + vyy, -v.y + vxx, v.x + vyy), vec4(0.0));
+    return w * w;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // The filter may render at a reduced resolution, so it works in pixels of the source
+    let size = vec2<i32>(textureDimensions(screen_texture));
+    let center = vec2<i32>(in.uv * vec2<f32>(size));
+    let alpha = textureLoad(screen_texture, clamp(center, vec2(0), size - 1), 0).a;
+
+    let radius = clamp(settings.radius, 1.0, MAX_RADIUS);
+    let extent = i32(ceil(radius));
+    let zeta = 2.0 / radius;
+    let eta = (zeta + COS_HALF_SECTOR) / SIN_HALF_SECTOR_SQUARED;
+
+    var means: array<vec4<f32>, SECTORS>;
+    var squares: array<vec3<f32>, SECTORS>;
+    for (var y = -extent; y <= extent; y++) {
+        for (var x = -extent; x <= extent; x++) {
+            let v = vec2(f32(x), f32(y)) / radius;
+            let distance_squared = dot(v, v);
+            if distance_squared > 1.0 {
+                continue;
+            }
+            let pixel = clamp(center + vec2(x, y), vec2(0), size - 1);
+            let color = textureLoad(screen_texture, pixel, 0).rgb;
+
+            // The diagonal sectors are the axis ones of the offset rotated by 45 degrees
+            let straight = axis_weights(v, zeta, eta);
+            let diagonal = axis_weights(0.70710678 * vec2(v.x - v.y, v.x + v.y), zeta, eta);
+            let falloff = exp(-3.125 * distance_squared);
+            let sum = dot(straight, vec4(1.0)) + dot(diagonal, vec4(1.0));
+            let scale = falloff / max(sum, 1.0e-6);
+
+            for (var k = 0u; k < 4u; k++) {
+                let straight_weight = straight[k] * scale;
+                let diagonal_weight = diagonal[k] * scale;
+                means[2u * k] += vec4(color * straight_weight, straight_weight);
+                squares[2u * k] += color * color * straight_weight;
+                means[2u * k + 1u] += vec4(color * diagonal_weight, diagonal_weight);
+                squares[2u * k + 1u] += color * color * diagonal_weight;
+            }
+        }
+    }
+
+    var result = vec4(0.0);
+    for (var k = 0u; k < SECTORS; k++) {
+        let weight_sum = max(means[k].w, 1.0e-6);
+        let mean = means[k].rgb / weight_sum;
+        let variance = abs(squares[k] / weight_sum - mean * mean);
+        let sigma_squared = variance.r + variance.g + variance.b;
+        let weight = 1.0 / (1.0 + pow(1000.0 * sigma_squared, 0.5 * settings.sharpness));
+        result += vec4(mean * weight, weight);
+    }
+    return vec4(result.rgb / max(result.w, 1.0e-6), alpha);
+}
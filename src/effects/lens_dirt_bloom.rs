@@ -0,0 +1,542 @@
+use crate::{
+    effects, filtering, resolution::scaled_size, PostProcessBindGroup, PostProcessBindGroupLayout,
+    PostProcessPlacement, PostProcessPlugin, PostProcessTexturePool,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::{core_3d::graph::Core3d, FullscreenShader},
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+        },
+        render_asset::RenderAssets,
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::{CachedTexture, FallbackImageZero, GpuImage},
+        view::ViewTarget,
+        Render, RenderApp, RenderSystems,
+    },
+    shader::ShaderDefVal,
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/lens_dirt_bloom.wgsl";
+
+/// The most levels the chain goes down.
+pub const MAX_BLOOM_LEVELS: usize = 8;
+
+/// The format of the levels of the chain. It has no alpha, the effect keeps the one of the view.
+const CHAIN_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rg11b10Ufloat;
+
+/// A bloom with a tint per level and a lens dirt overlay, run on the cameras with
+/// [`LensDirtBloomSettings`].
+///
+/// The highlights of the view are halved down a chain of textures, then added back up one level
+/// at a time, every level multiplied by its own tint, so the tight glow and the wide haze can
+/// take different colors. Where the bloom is bright, the [`LensDirtTexture`] of the camera shows
+/// up like dust and smudges on the lens. Unlike Bevy's bloom, it runs wherever the stack places
+/// it, before or after the other effects. Runs on the HDR colors by default.
+///
+/// The chain is rendered by the [`LensDirtBloomChain`] node right before the [`LensDirtBloom`]
+/// effect. Placing the bloom after the upscaling isn't supported.
+#[derive(Default)]
+pub struct LensDirtBloomPlugin {
+    /// Where the bloom runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`LensDirtBloomPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct LensDirtBloomSettings {
+    /// The color every level is multiplied by, from the first level at half the size of the view
+    /// to the last one. Brighter tints make stronger levels.
+    pub level_tints: [LinearRgba; MAX_BLOOM_LEVELS],
+    /// The brightness above which the view blooms.
+    pub threshold: f32,
+    /// How gradually the bloom fades in below the threshold, from 0 for a hard cut to 1 for a
+    /// fade starting at black.
+    pub soft_knee: f32,
+    /// The brightness of the bloom added to the view.
+    pub intensity: f32,
+    /// The brightness of the lens dirt, lit by the bloom.
+    pub dirt_intensity: f32,
+    /// How many levels the chain goes down, from 1 to 8. Each level doubles the size of the glow.
+    pub levels: u32,
+}
+
+impl Default for LensDirtBloomSettings {
+    fn default() -> Self {
+        Self {
+            level_tints: [LinearRgba::WHITE; MAX_BLOOM_LEVELS],
+            threshold: 1.0,
+            soft_knee: 0.5,
+            intensity: 0.15,
+            dirt_intensity: 1.0,
+            levels: 6,
+        }
+    }
+}
+
+/// The lens dirt of a camera running the [`LensDirtBloomPlugin`], stretched over the view.
+///
+/// Dark areas of the image stay clean, bright ones light up where the bloom is. Cameras without
+/// one have a clean lens.
+#[derive(Component, Reflect, Clone, Debug, ExtractComponent)]
+#[reflect(Component)]
+pub struct LensDirtTexture(pub Handle<Image>);
+
+/// The render graph label of the effect pass of the [`LensDirtBloomPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct LensDirtBloom;
+
+/// The render graph label of the node rendering the chain of the [`LensDirtBloomPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct LensDirtBloomChain;
+
+impl Plugin for LensDirtBloomPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "lens_dirt_bloom.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<LensDirtBloomSettings>(
+            app,
+            &[
+                ("threshold", 0.0..=10.0),
+                ("soft_knee", 0.0..=1.0),
+                ("intensity", 0.0..=2.0),
+                ("dirt_intensity", 0.0..=4.0),
+            ],
+        );
+        app.register_type::<LensDirtBloomSettings>()
+            .register_type::<LensDirtTexture>()
+            .add_plugins(ExtractComponentPlugin::<LensDirtTexture>::default());
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<LensDirtBloomSettings, _>::new(
+                SHADER_PATH,
+                LensDirtBloom,
+                Some("lens_dirt_bloom"),
+                "lens_dirt_bloom_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view()
+            .with_bind_group(result_layout, prepare_result_bind_groups),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (before, _) = self.placement.edges();
+        render_app
+            .init_resource::<SpecializedRenderPipelines<LensDirtBloomChainPipeline>>()
+            .add_systems(Render, prepare_chains.in_set(RenderSystems::PrepareResources))
+            .add_render_graph_node::<ViewNodeRunner<LensDirtBloomChainNode>>(
+                Core3d,
+                LensDirtBloomChain,
+            )
+            .add_render_graph_edges(Core3d, (before, LensDirtBloomChain, LensDirtBloom));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<LensDirtBloomChainPipeline>();
+    }
+}
+
+// The pipeline of the passes going down and up the chain. The passes going up also read the
+// level of the way down they add the glow to
+#[derive(Resource)]
+struct LensDirtBloomChainPipeline {
+    downsample_layout: BindGroupLayout,
+    upsample_layout: BindGroupLayout,
+    sampler: Sampler,
+    // Samples the first level of the chain and the lens dirt in the effect pass
+    result_sampler: Sampler,
+    filterable: bool,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+}
+
+impl FromWorld for LensDirtBloomChainPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        // The first pass reads the view, which some adapters can't filter
+        let filterable = filtering::screen_filterable(world);
+        let [source_texture, source_sampler] = filtering::screen_entries(filterable);
+        let [level_texture, _] = filtering::screen_entries(filterable);
+        let downsample_layout = render_device.create_bind_group_layout(
+            "lens_dirt_bloom_downsample_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    source_texture,
+                    source_sampler,
+                    uniform_buffer::<LensDirtBloomSettings>(true),
+                ),
+            ),
+        );
+        let upsample_layout = render_device.create_bind_group_layout(
+            "lens_dirt_bloom_upsample_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    source_texture,
+                    source_sampler,
+                    uniform_buffer::<LensDirtBloomSettings>(true),
+                    level_texture,
+                ),
+            ),
+        );
+        let descriptor = SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        };
+
+        Self {
+            downsample_layout,
+            upsample_layout,
+            sampler: filtering::screen_sampler(render_device, filterable, descriptor.clone()),
+            result_sampler: render_device.create_sampler(&descriptor),
+            filterable,
+            shader: load_embedded_asset!(world, "lens_dirt_bloom.wgsl"),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum LensDirtBloomPass {
+    // The first pass also keeps the highlights of the view
+    Downsample { first: bool },
+    // The level written, and whether it is the last one, which has no deeper glow to add
+    Upsample { level: u32, last: bool },
+}
+
+impl SpecializedRenderPipeline for LensDirtBloomChainPipeline {
+    type Key = LensDirtBloomPass;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs: Vec<ShaderDefVal> = Vec::new();
+        if !self.filterable {
+            shader_defs.push(filtering::SCREEN_TEXTURE_NON_FILTERABLE.into());
+        }
+        let (entry_point, layout) = match key {
+            LensDirtBloomPass::Downsample { first } => {
+                if first {
+                    shader_defs.push("FIRST_DOWNSAMPLE".into());
+                }
+                ("downsample", &self.downsample_layout)
+            }
+            LensDirtBloomPass::Upsample { level, last } => {
+                shader_defs.push(ShaderDefVal::UInt("LEVEL".into(), level));
+                if last {
+                    shader_defs.push("LAST_LEVEL".into());
+                }
+                ("upsample", &self.upsample_layout)
+            }
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("lens_dirt_bloom_chain_pipeline".into()),
+            layout: vec![layout.clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some(entry_point.into()),
+                targets: vec![Some(ColorTargetState {
+                    format: CHAIN_TEXTURE_FORMAT,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+// The levels of the way down and of the way up the chain of a view, the first ones being half
+// the size of the view, and the pipelines of their passes
+#[derive(Component)]
+struct ViewLensDirtBloomChain {
+    down_levels: Vec<CachedTexture>,
+    up_levels: Vec<CachedTexture>,
+    downsample_pipeline_ids: Vec<CachedRenderPipelineId>,
+    upsample_pipeline_ids: Vec<CachedRenderPipelineId>,
+}
+
+impl ViewLensDirtBloomChain {
+    fn pipeline_ids(&self) -> impl Iterator<Item = &CachedRenderPipelineId> {
+        self.downsample_pipeline_ids
+            .iter()
+            .chain(&self.upsample_pipeline_ids)
+    }
+}
+
+fn prepare_chains(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewTarget, &LensDirtBloomSettings)>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    chain_pipeline: Res<LensDirtBloomChainPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<LensDirtBloomChainPipeline>>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+) {
+    for (entity, view_target, settings) in &views {
+        let size = view_target.main_texture().size();
+        let count = settings.levels.clamp(1, MAX_BLOOM_LEVELS as u32);
+        let mut levels = |name: &'static str| -> Vec<CachedTexture> {
+            (0..count)
+                .map(|level| {
+                    texture_pool.get_indexed(
+                        &render_device,
+                        entity,
+                        LensDirtBloomChain,
+                        name,
+                        level,
+                        &TextureDescriptor {
+                            label: Some("lens_dirt_bloom_chain_texture"),
+                            size: scaled_size(size, 0.5f32.powi(level as i32 + 1)),
+                            mip_level_count: 1,
+                            sample_count: 1,
+                            dimension: TextureDimension::D2,
+                            format: CHAIN_TEXTURE_FORMAT,
+                            usage: TextureUsages::RENDER_ATTACHMENT
+                                | TextureUsages::TEXTURE_BINDING,
+                            view_formats: &[],
+                        },
+                    )
+                })
+                .collect()
+        };
+        let down_levels = levels("down");
+        let up_levels = levels("up");
+
+        let downsample_pipeline_ids = (0..count)
+            .map(|level| {
+                let key = LensDirtBloomPass::Downsample { first: level == 0 };
+                pipelines.specialize(&pipeline_cache, &chain_pipeline, key)
+            })
+            .collect();
+        let upsample_pipeline_ids = (0..count)
+            .map(|level| {
+                let key = LensDirtBloomPass::Upsample {
+                    level,
+                    last: level == count - 1,
+                };
+                pipelines.specialize(&pipeline_cache, &chain_pipeline, key)
+            })
+            .collect();
+        commands.entity(entity).insert(ViewLensDirtBloomChain {
+            down_levels,
+            up_levels,
+            downsample_pipeline_ids,
+            upsample_pipeline_ids,
+        });
+    }
+}
+
+// The layout of the group 1 of the effect, the first level of the way up the chain, the lens
+// dirt and their sampler
+fn result_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "lens_dirt_bloom_result_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+            ),
+        ),
+    )
+}
+
+// The effect skips the views without the bind group, so it doesn't read the chain until the
+// chain pipelines are compiled. The views without lens dirt, or whose dirt is still loading, bind
+// a black texture
+fn prepare_result_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewLensDirtBloomChain, Option<&LensDirtTexture>)>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    fallback_image: Res<FallbackImageZero>,
+    layout: Res<PostProcessBindGroupLayout<LensDirtBloomSettings>>,
+    chain_pipeline: Res<LensDirtBloomChainPipeline>,
+) {
+    for (entity, chain, dirt) in &views {
+        if chain
+            .pipeline_ids()
+            .any(|id| pipeline_cache.get_render_pipeline(*id).is_none())
+        {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<LensDirtBloomSettings>>();
+            continue;
+        }
+        let dirt = dirt
+            .and_then(|dirt| gpu_images.get(&dirt.0))
+            .map_or(&fallback_image.texture_view, |gpu_image| {
+                &gpu_image.texture_view
+            });
+        let bind_group = render_device.create_bind_group(
+            "lens_dirt_bloom_result_bind_group",
+            &layout.layout,
+            &BindGroupEntries::sequential((
+                &chain.up_levels[0].default_view,
+                dirt,
+                &chain_pipeline.result_sampler,
+            )),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<LensDirtBloomSettings>::new(bind_group));
+    }
+}
+
+#[derive(Default)]
+struct LensDirtBloomChainNode;
+
+impl ViewNode for LensDirtBloomChainNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        // Only runs on the cameras with the bloom
+        &'static LensDirtBloomSettings,
+        &'static ViewLensDirtBloomChain,
+        &'static DynamicUniformIndex<LensDirtBloomSettings>,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, _settings, chain, settings_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // The effect doesn't read the chain on the views it skips
+        let view = graph.view_entity();
+        if effects::skip_intermediate::<LensDirtBloomSettings, LensDirtBloom>(world, view) {
+            return Ok(());
+        }
+
+        let chain_pipeline = world.resource::<LensDirtBloomChainPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let Some(settings_binding) = world
+            .resource::<ComponentUniforms<LensDirtBloomSettings>>()
+            .uniforms()
+            .binding()
+        else {
+            return Ok(());
+        };
+        let (Some(downsample_pipelines), Some(upsample_pipelines)) = (
+            chain
+                .downsample_pipeline_ids
+                .iter()
+                .map(|id| pipeline_cache.get_render_pipeline(*id))
+                .collect::<Option<Vec<_>>>(),
+            chain
+                .upsample_pipeline_ids
+                .iter()
+                .map(|id| pipeline_cache.get_render_pipeline(*id))
+                .collect::<Option<Vec<_>>>(),
+        ) else {
+            return Ok(());
+        };
+
+        // Go down from the view to the last level
+        let down = &chain.down_levels;
+        for (level, pipeline) in downsample_pipelines.into_iter().enumerate() {
+            let source = match level {
+                0 => view_target.main_texture_view(),
+                _ => &down[level - 1].default_view,
+            };
+            let bind_group = render_context.render_device().create_bind_group(
+                "lens_dirt_bloom_downsample_bind_group",
+                &chain_pipeline.downsample_layout,
+                &BindGroupEntries::sequential((
+                    source,
+                    &chain_pipeline.sampler,
+                    settings_binding.clone(),
+                )),
+            );
+            draw_pass(
+                render_context,
+                pipeline,
+                &bind_group,
+                settings_index,
+                &down[level].default_view,
+            );
+        }
+
+        // Then back up, every level adding its tinted glow to the deeper ones. The last level
+        // only tints its own, it reads itself as the deeper glow without using it
+        let up = &chain.up_levels;
+        for (level, pipeline) in upsample_pipelines.into_iter().enumerate().rev() {
+            let deeper = match up.get(level + 1) {
+                Some(deeper) => &deeper.default_view,
+                None => &down[level].default_view,
+            };
+            let bind_group = render_context.render_device().create_bind_group(
+                "lens_dirt_bloom_upsample_bind_group",
+                &chain_pipeline.upsample_layout,
+                &BindGroupEntries::sequential((
+                    deeper,
+                    &chain_pipeline.sampler,
+                    settings_binding.clone(),
+                    &down[level].default_view,
+                )),
+            );
+            draw_pass(
+                render_context,
+                pipeline,
+                &bind_group,
+                settings_index,
+                &up[level].default_view,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn draw_pass(
+    render_context: &mut RenderContext,
+    pipeline: &RenderPipeline,
+    bind_group: &BindGroup,
+    settings_index: &DynamicUniformIndex<LensDirtBloomSettings>,
+    destination: &TextureView,
+) {
+    let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+        label: Some("lens_dirt_bloom_chain_pass"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view: destination,
+            depth_slice: None,
+            resolve_target: None,
+            ops: Operations::default(),
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    render_pass.set_render_pipeline(pipeline);
+    render_pass.set_bind_group(0, bind_group, &[settings_index.index()]);
+    render_pass.draw(0..3, 0..1);
+}
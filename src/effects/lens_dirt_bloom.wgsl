@@ -0,0 +1,111 @@
+// A bloom with a tint per level and lens dirt. The downsample passes halve the highlights of the
+// view down a chain of textures, the upsample passes add every level, multiplied by its tint, to
+// the glow of the deeper levels on the way back up, then the effect pass adds the first level and
+// the lens dirt it lights to the view.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::utils::luminance
+
+// The level read by the chain passes, or the view in the effect pass
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+struct LensDirtBloomSettings {
+    level_tints: array<vec4<f32>, 8>,
+    threshold: f32,
+    soft_knee: f32,
+    intensity: f32,
+    dirt_intensity: f32,
+    levels: u32,
+}
+@group(0) @binding(2) var<uniform> settings: LensDirtBloomSettings;
+// The level of the way down an upsample pass adds its glow to, at the size of the pass
+@group(0) @binding(3) var level_texture: texture_2d<f32>;
+// The first level of the way up the chain and the lens dirt, only bound to the effect pass
+@group(1) @binding(0) var bloom_texture: texture_2d<f32>;
+@group(1) @binding(1) var dirt_texture: texture_2d<f32>;
+@group(1) @binding(2) var bloom_sampler: sampler;
+
+// Bilinear sampling, done manually when the adapter can't filter the view texture
+fn sample_bilinear(uv: vec2<f32>) -> vec3<f32> {
+#ifdef SCREEN_TEXTURE_NON_FILTERABLE
+    let texture_size = vec2<i32>(textureDimensions(source_texture));
+    let position = uv * vec2<f32>(texture_size) - 0.5;
+    let base = vec2<i32>(floor(position));
+    let f = fract(position);
+    let max_texel = texture_size - 1;
+    let t00 = textureLoad(source_texture, clamp(base, vec2(0), max_texel), 0).rgb;
+    let t10 = textureLoad(source_texture, clamp(base + vec2(1, 0), vec2(0), max_texel), 0).rgb;
+    let t01 = textureLoad(source_texture, clamp(base + vec2(0, 1), vec2(0), max_texel), 0).rgb;
+    let t11 = textureLoad(source_texture, clamp(base + vec2(1, 1), vec2(0), max_texel), 0).rgb;
+    return mix(mix(t00, t10, f.x), mix(t01, t11, f.x), f.y);
+#else
+    return textureSampleLevel(source_texture, source_sampler, uv, 0.0).rgb;
+#endif
+}
+
+// The part of a color above the threshold, fading in over the knee below it, keeping its hue
+fn highlights(color: vec3<f32>) -> vec3<f32> {
+    let luma = luminance(color);
+    let knee = settings.threshold * clamp(settings.soft_knee, 0.0, 1.0);
+    var soft = clamp(luma - settings.threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 1.0e-5);
+    let contribution = max(soft, luma - settings.threshold) / max(luma, 1.0e-5);
+    return color * contribution;
+}
+
+// The center and the four diagonals, a texel of the source away. The first pass keeps the
+// highlights of every sample, so a single bright pixel doesn't flicker as it moves
+@fragment
+fn downsample(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let o = 1.0 / vec2<f32>(textureDimensions(source_texture));
+    var samples = array(
+        sample_bilinear(in.uv),
+        sample_bilinear(in.uv - o),
+        sample_bilinear(in.uv + o),
+        sample_bilinear(in.uv + vec2(o.x, -o.y)),
+        sample_bilinear(in.uv + vec2(-o.x, o.y)),
+    );
+    var weights = array(4.0, 1.0, 1.0, 1.0, 1.0);
+
+    var color = vec3(0.0);
+    for (var i = 0; i < 5; i++) {
+        var texel = max(samples[i], vec3(0.0));
+#ifdef FIRST_DOWNSAMPLE
+        texel = highlights(texel);
+#endif
+        color += texel * weights[i];
+    }
+    return vec4(color / 8.0, 1.0);
+}
+
+#ifdef LEVEL
+// The tinted level of the way down, plus a tent of eight samples of the deeper glow around the
+// pixel. The last level has no deeper glow
+@fragment
+fn upsample(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let tint = settings.level_tints[#{LEVEL}].rgb;
+    var color = textureLoad(level_texture, vec2<i32>(in.position.xy), 0).rgb * tint;
+#ifndef LAST_LEVEL
+    let h = 0.5 / vec2<f32>(textureDimensions(source_texture));
+    var deeper = sample_bilinear(in.uv + vec2(-h.x * 2.0, 0.0));
+    deeper += sample_bilinear(in.uv + vec2(h.x * 2.0, 0.0));
+    deeper += sample_bilinear(in.uv + vec2(0.0, -h.y * 2.0));
+    deeper += sample_bilinear(in.uv + vec2(0.0, h.y * 2.0));
+    deeper += sample_bilinear(in.uv + vec2(-h.x, h.y)) * 2.0;
+    deeper += sample_bilinear(in.uv + vec2(h.x, h.y)) * 2.0;
+    deeper += sample_bilinear(in.uv + vec2(h.x, -h.y)) * 2.0;
+    deeper += sample_bilinear(in.uv + vec2(-h.x, -h.y)) * 2.0;
+    color += deeper / 12.0;
+#endif
+    return vec4(color, 1.0);
+}
+#endif
+
+// The effect pass. The bloom has no alpha, so the one of the view is kept
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureLoad(source_texture, vec2<i32>(in.position.xy), 0);
+    let bloom = textureSampleLevel(bloom_texture, bloom_sampler, in.uv, 0.0).rgb;
+    let dirt = textureSampleLevel(dirt_texture, bloom_sampler, in.uv, 0.0).rgb;
+    let glow = bloom * (settings.intensity + dirt * settings.dirt_intensity);
+    return vec4(color.rgb + glow, color.a);
+}
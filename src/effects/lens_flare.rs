@@ -0,0 +1,145 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/lens_flare.wgsl";
+
+/// A lens flare streaming from the bright lights of the scene, run on the cameras with
+/// [`LensFlareSettings`].
+///
+/// Every entity with a [`LensFlareLight`] casts a flare when it is in view and not hidden by the
+/// geometry: ghosts mirrored through the center of the screen and a halo ring, both fringed by
+/// the dispersion of the lens. The flare scales with how much the view at the light is brighter
+/// than [`LensFlareSettings::threshold`], so lights dimmed by fog or hidden behind a translucent
+/// surface flare less. The lights are gathered in a single storage buffer, which WebGL doesn't
+/// support. Runs on the HDR colors by default. Placing it after the upscaling isn't supported.
+#[derive(Default)]
+pub struct LensFlarePlugin {
+    /// Where the flares are added.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`LensFlarePlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct LensFlareSettings {
+    /// The brightness of the view at a light under which it doesn't flare.
+    pub threshold: f32,
+    /// The brightness of the flares.
+    pub intensity: f32,
+    /// How many ghosts every light casts, up to 8.
+    pub ghosts: u32,
+    /// The distance between two ghosts, as a fraction of the distance from the light to its
+    /// mirror through the center of the screen.
+    pub ghost_spacing: f32,
+    /// The radius of the largest ghosts, as a fraction of the height of the view.
+    pub ghost_size: f32,
+    /// The radius of the halo ring, as a fraction of the height of the view.
+    pub halo_radius: f32,
+    /// The thickness of the halo ring, as a fraction of the height of the view.
+    pub halo_width: f32,
+    /// The brightness of the halo relative to the ghosts.
+    pub halo_intensity: f32,
+    /// How far apart the red, green and blue parts of the ghosts and of the halo spread, as a
+    /// fraction of their distance from the light.
+    pub chromatic_aberration: f32,
+}
+
+impl Default for LensFlareSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            intensity: 0.05,
+            ghosts: 5,
+            ghost_spacing: 0.4,
+            ghost_size: 0.06,
+            halo_radius: 0.45,
+            halo_width: 0.04,
+            halo_intensity: 0.5,
+            chromatic_aberration: 0.03,
+        }
+    }
+}
+
+/// A light casting a lens flare with the [`LensFlarePlugin`].
+///
+/// Add it to the entity of a point light, a spot light or a directional light, or to any entity
+/// that should flare, like the sun of a skybox. The position is written by the plugin every frame
+/// from the [`GlobalTransform`] of the entity.
+#[derive(Component, Reflect, Clone, Copy, Debug, ShaderType)]
+#[reflect(Component, Default)]
+pub struct LensFlareLight {
+    /// The color of the flare.
+    pub color: LinearRgba,
+    /// The position of the light in world space, or with a `w` of 0 the direction toward a
+    /// directional light. Written by the plugin every frame.
+    pub position: Vec4,
+    /// The brightness of the flare of this light, multiplying the one of the settings.
+    pub intensity: f32,
+}
+
+impl Default for LensFlareLight {
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::WHITE,
+            position: Vec4::ZERO,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// The render graph label of the [`LensFlarePlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct LensFlare;
+
+impl Plugin for LensFlarePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "lens_flare.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<LensFlareSettings>(
+            app,
+            &[
+                ("threshold", 0.0..=10.0),
+                ("intensity", 0.0..=2.0),
+                ("ghost_spacing", 0.0..=1.0),
+                ("ghost_size", 0.0..=0.5),
+                ("halo_radius", 0.0..=1.0),
+                ("halo_width", 0.0..=0.5),
+                ("halo_intensity", 0.0..=2.0),
+                ("chromatic_aberration", 0.0..=0.1),
+            ],
+        );
+        app.register_type::<LensFlareSettings>()
+            .register_type::<LensFlareLight>()
+            .add_systems(Update, update_light_positions);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<LensFlareSettings, _>::new(
+                SHADER_PATH,
+                LensFlare,
+                Some("lens_flare"),
+                "lens_flare_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_depth()
+            .with_instances::<LensFlareLight>(),
+        );
+    }
+}
+
+// Directional lights are infinitely far away, in the direction they shine from
+fn update_light_positions(
+    mut lights: Query<(&mut LensFlareLight, &GlobalTransform, Has<DirectionalLight>)>,
+) {
+    for (mut light, transform, directional) in &mut lights {
+        light.position = match directional {
+            true => transform.back().extend(0.0),
+            false => transform.translation().extend(1.0),
+        };
+    }
+}
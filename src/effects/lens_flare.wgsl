@@ -0,0 +1,116 @@
+// Adds the flare of every light in view: ghosts along the line from the light through the center
+// of the screen and a halo ring, each color channel offset along that line for the fringing.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_post_process::utils::{luminance, ndc_to_uv}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct LensFlareSettings {
+    threshold: f32,
+    intensity: f32,
+    ghosts: u32,
+    ghost_spacing: f32,
+    ghost_size: f32,
+    halo_radius: f32,
+    halo_width: f32,
+    halo_intensity: f32,
+    chromatic_aberration: f32,
+}
+@group(0) @binding(2) var<uniform> settings: LensFlareSettings;
+@group(0) @binding(3) var<uniform> view: View;
+#ifdef MULTISAMPLED
+@group(0) @binding(4) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(4) var depth_texture: texture_depth_2d;
+#endif
+struct LensFlareLight {
+    color: vec4<f32>,
+    position: vec4<f32>,
+    intensity: f32,
+}
+@group(0) @binding(10) var<storage, read> lights: array<LensFlareLight>;
+@group(0) @binding(11) var<uniform> light_count: u32;
+
+const MAX_GHOSTS: u32 = 8u;
+
+// From UV to a space where distances are fractions of the height of the view, centered on the
+// screen
+fn to_lens(uv: vec2<f32>) -> vec2<f32> {
+    let aspect_ratio = view.viewport.z / view.viewport.w;
+    return vec2((uv.x - 0.5) * aspect_ratio, uv.y - 0.5);
+}
+
+// How much the view at the light is brighter than the threshold, the light being hidden when
+// the depth of the scene is in front of it. The sky is cleared to a depth of 0
+fn brightness_at(light_uv: vec2<f32>, light_depth: f32) -> f32 {
+    let size = vec2<i32>(textureDimensions(depth_texture));
+    let pixel = clamp(vec2<i32>(light_uv * vec2<f32>(size)), vec2(0), size - 1);
+    if textureLoad(depth_texture, pixel, 0) > light_depth {
+        return 0.0;
+    }
+    let color = textureSampleLevel(screen_texture, texture_sampler, light_uv, 0.0).rgb;
+    return max(luminance(color) - settings.threshold, 0.0);
+}
+
+// A soft disk, brighter toward its rim like the ghosts of a real lens
+fn ghost_shape(offset: vec2<f32>, radius: f32) -> f32 {
+    let distance = length(offset) / radius;
+    return smoothstep(1.0, 0.85, distance) * mix(0.4, 1.0, distance * distance);
+}
+
+fn ring_shape(distance: f32, radius: f32) -> f32 {
+    let width = max(settings.halo_width, 1.0e-4);
+    return max(1.0 - abs(distance - radius) / width, 0.0);
+}
+
+fn flare(position: vec2<f32>, light: vec2<f32>) -> vec3<f32> {
+    // The ghosts lie on the line from the light through the center to its mirror
+    let axis = -light * 2.0;
+    let fringe = vec3(-1.0, 0.0, 1.0) * settings.chromatic_aberration;
+    var color = vec3(0.0);
+    for (var i = 0u; i < min(settings.ghosts, MAX_GHOSTS); i++) {
+        let t = settings.ghost_spacing * f32(i + 1u);
+        // The ghosts vary in size, and fade out away from the center
+        let radius = settings.ghost_size * (0.4 + 0.6 * fract(f32(i) * 0.618 + 0.3));
+        let falloff = 1.0 - smoothstep(0.0, 0.8, length(light + axis * t));
+        for (var c = 0u; c < 3u; c++) {
+            let center = light + axis * t * (1.0 + fringe[c]);
+            color[c] += ghost_shape(position - center, radius) * falloff;
+        }
+    }
+
+    // The halo faces the light, as if its ring were seen from the side of the lens
+    let facing = max(dot(normalize(position + 1.0e-5), normalize(light + 1.0e-5)), 0.0);
+    for (var c = 0u; c < 3u; c++) {
+        let radius = settings.halo_radius * (1.0 + fringe[c]);
+        color[c] += ring_shape(length(position), radius) * facing * settings.halo_intensity;
+    }
+    return color;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let position = to_lens(in.uv);
+
+    var flares = vec3(0.0);
+    for (var i = 0u; i < light_count; i++) {
+        let light = lights[i];
+        let clip = view.clip_from_world * light.position;
+        if clip.w <= 0.0 {
+            continue;
+        }
+        let light_uv = ndc_to_uv(clip.xy / clip.w);
+        if any(light_uv < vec2(0.0)) || any(light_uv > vec2(1.0)) {
+            continue;
+        }
+        let brightness = brightness_at(light_uv, clip.z / clip.w);
+        if brightness <= 0.0 {
+            continue;
+        }
+        let strength = brightness * light.intensity;
+        flares += flare(position, to_lens(light_uv)) * light.color.rgb * strength;
+    }
+    return vec4(color.rgb + flares * settings.intensity, color.a);
+}
@@ -0,0 +1,80 @@
+use crate::{effects, PostProcessColorSpace, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/lift_gamma_gain.wgsl";
+
+/// Three-way color grading of the shadows, midtones and highlights, run on the cameras with
+/// [`LiftGammaGainSettings`].
+///
+/// The lift tints the shadows, the gamma the midtones and the gain the highlights, like the color
+/// wheels of grading tools. Each is weighted by the luminance of the pixel, with smooth crossovers
+/// between the ranges. The grading works on sRGB encoded colors, so the ranges follow the
+/// perceived brightness.
+#[derive(Default)]
+pub struct LiftGammaGainPlugin {
+    /// Where the grading runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`LiftGammaGainPlugin`] on a camera. The defaults leave the colors
+/// unchanged.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct LiftGammaGainSettings {
+    /// Raises or lowers each channel of the shadows, pinning white. 0 is neutral.
+    pub lift: Vec3,
+    /// The gamma of each channel of the midtones. 1 is neutral, higher values brighten.
+    pub gamma: Vec3,
+    /// Scales each channel of the highlights, pinning black. 1 is neutral.
+    pub gain: Vec3,
+    /// The luminance, from 0 to 1, where the shadows have fully crossed over into the midtones.
+    pub shadows_end: f32,
+    /// The luminance, from 0 to 1, where the highlights start crossing over from the midtones.
+    pub highlights_start: f32,
+}
+
+impl Default for LiftGammaGainSettings {
+    fn default() -> Self {
+        Self {
+            lift: Vec3::ZERO,
+            gamma: Vec3::ONE,
+            gain: Vec3::ONE,
+            shadows_end: 0.3,
+            highlights_start: 0.55,
+        }
+    }
+}
+
+/// The render graph label of the [`LiftGammaGainPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct LiftGammaGain;
+
+impl Plugin for LiftGammaGainPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "lift_gamma_gain.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<LiftGammaGainSettings>(
+            app,
+            &[("shadows_end", 0.0..=1.0), ("highlights_start", 0.0..=1.0)],
+        );
+        app.register_type::<LiftGammaGainSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<LiftGammaGainSettings, _>::new(
+                SHADER_PATH,
+                LiftGammaGain,
+                Some("lift_gamma_gain"),
+                "lift_gamma_gain_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_color_space(PostProcessColorSpace::Srgb)
+            .without_view(),
+        );
+    }
+}
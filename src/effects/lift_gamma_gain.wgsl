@@ -0,0 +1,33 @@
+// Lift, gamma and gain, weighted by the shadows, midtones and highlights ranges of the pixel.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::{to_working_space, from_working_space}
+#import bevy_post_process::utils::luminance
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct LiftGammaGainSettings {
+    lift: vec3<f32>,
+    gamma: vec3<f32>,
+    gain: vec3<f32>,
+    shadows_end: f32,
+    highlights_start: f32,
+}
+@group(0) @binding(2) var<uniform> settings: LiftGammaGainSettings;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    var graded = max(to_working_space(color.rgb), vec3(0.0));
+
+    let luma = clamp(luminance(graded), 0.0, 1.0);
+    let shadows = 1.0 - smoothstep(0.0, settings.shadows_end, luma);
+    let highlights = smoothstep(settings.highlights_start, 1.0, luma);
+    let midtones = max(1.0 - shadows - highlights, 0.0);
+
+    graded += settings.lift * shadows * (1.0 - graded);
+    graded *= mix(vec3(1.0), settings.gain, highlights);
+    let gamma = max(mix(vec3(1.0), settings.gamma, midtones), vec3(1.0e-3));
+    graded = pow(max(graded, vec3(0.0)), 1.0 / gamma);
+
+    return vec4(from_working_space(graded), color.a);
+}
@@ -0,0 +1,166 @@
+//! Ready-made effects built on [`PostProcessPlugin`](crate::PostProcessPlugin), behind the
+//! `effects` feature.
+//!
+//! Every effect is a plugin paired with a settings component. Add the plugin once, then add the
+//! settings to the cameras the effect runs on. Effects made of several passes register one
+//! [`PostProcessPlugin`](crate::PostProcessPlugin) per pass, chained with
+//! [`PostProcessPlugins`](crate::PostProcessPlugins), so they all share one settings component.
+//! Passes rendering into textures of their own, like the chain of the dual Kawase blur, run in a
+//! node of the effect right before its last pass.
+
+pub mod anamorphic_streaks;
+pub mod ascii;
+pub mod bayer_dither;
+pub mod blue_noise_dither;
+pub mod box_blur;
+pub mod cel_shading;
+pub mod channel_mixer;
+pub mod color_adjustments;
+pub mod color_grading_lut;
+pub mod comic;
+pub mod contrast_adaptive_sharpening;
+pub mod critical_health;
+pub mod crt;
+pub mod damage_vignette;
+pub mod depth_of_field;
+pub mod distance_fog;
+pub mod dual_kawase;
+pub mod edge_detection;
+pub mod frost;
+pub mod gaussian_blur;
+pub mod god_rays;
+pub mod halftone;
+pub mod hatching;
+pub mod heat_haze;
+pub mod height_fog;
+pub mod kuwahara;
+pub mod lens_dirt_bloom;
+pub mod lens_flare;
+pub mod lift_gamma_gain;
+pub mod motion_blur;
+pub mod palette_map;
+pub mod pixelate;
+pub mod posterize;
+pub mod radial_blur;
+pub mod rain_droplets;
+pub mod scanlines;
+pub mod shockwave;
+pub mod smaa;
+pub mod split_toning;
+pub mod star_filter;
+pub mod tilt_shift;
+pub mod toon_outline;
+pub mod underwater;
+pub mod unsharp_mask;
+pub mod watercolor;
+pub mod white_balance;
+
+pub use anamorphic_streaks::{AnamorphicStreaksPlugin, AnamorphicStreaksSettings};
+pub use ascii::{AsciiPlugin, AsciiSettings};
+pub use bayer_dither::{BayerDitherPlugin, BayerDitherSettings};
+pub use blue_noise_dither::{BlueNoiseDitherPlugin, BlueNoiseDitherSettings};
+pub use box_blur::{BoxBlurPlugin, BoxBlurSettings};
+pub use cel_shading::{CelShadingPlugin, CelShadingSettings};
+pub use channel_mixer::{ChannelMixerPlugin, ChannelMixerSettings};
+pub use color_adjustments::{ColorAdjustmentsPlugin, ColorAdjustmentsSettings};
+pub use color_grading_lut::{
+    ColorGradingLutPlugin, ColorGradingLutSettings, ColorGradingLutTexture, CubeLutLoader,
+};
+pub use comic::{ComicPlugin, ComicSettings};
+pub use contrast_adaptive_sharpening::{
+    ContrastAdaptiveSharpeningPlugin, ContrastAdaptiveSharpeningSettings,
+};
+pub use critical_health::{CriticalHealthPlugin, CriticalHealthSettings};
+pub use crt::{CrtPlugin, CrtSettings};
+pub use damage_vignette::{DamageVignettePlugin, DamageVignetteSettings};
+pub use depth_of_field::{DepthOfFieldPlugin, DepthOfFieldSettings};
+pub use distance_fog::{DistanceFogEnvironment, DistanceFogPlugin, DistanceFogSettings};
+pub use dual_kawase::{DualKawasePlugin, DualKawaseSettings};
+pub use edge_detection::{EdgeDetectionPlugin, EdgeDetectionSettings};
+pub use frost::{FrostPlugin, FrostSettings};
+pub use gaussian_blur::{GaussianBlurPlugin, GaussianBlurSettings};
+pub use god_rays::{GodRaysLight, GodRaysPlugin, GodRaysSettings};
+pub use halftone::{HalftonePlugin, HalftoneSettings};
+pub use hatching::{HatchingPlugin, HatchingSettings};
+pub use heat_haze::{HeatHazePlugin, HeatHazeSettings};
+pub use height_fog::{HeightFogPlugin, HeightFogSettings};
+pub use kuwahara::{KuwaharaPlugin, KuwaharaSettings};
+pub use lens_dirt_bloom::{LensDirtBloomPlugin, LensDirtBloomSettings, LensDirtTexture};
+pub use lens_flare::{LensFlareLight, LensFlarePlugin, LensFlareSettings};
+pub use lift_gamma_gain::{LiftGammaGainPlugin, LiftGammaGainSettings};
+pub use motion_blur::{MotionBlurPlugin, MotionBlurSettings};
+pub use palette_map::{PaletteMapPlugin, PaletteMapSettings, PaletteMapTexture};
+pub use pixelate::{PixelatePlugin, PixelateSettings};
+pub use posterize::{PosterizePlugin, PosterizeSettings};
+pub use radial_blur::{RadialBlurPlugin, RadialBlurSettings};
+pub use rain_droplets::{RainDropletsPlugin, RainDropletsSettings};
+pub use scanlines::{ScanlinesPlugin, ScanlinesSettings};
+pub use shockwave::{Shockwave, ShockwavePlugin, ShockwaveSettings};
+pub use smaa::{SmaaPlugin, SmaaSettings};
+pub use split_toning::{SplitToningPlugin, SplitToningSettings};
+pub use star_filter::{StarFilterPlugin, StarFilterSettings};
+pub use tilt_shift::{TiltShiftPlugin, TiltShiftSettings};
+pub use toon_outline::{ToonOutlinePlugin, ToonOutlineSettings};
+pub use underwater::{UnderwaterPlugin, UnderwaterSettings};
+pub use unsharp_mask::{UnsharpMaskPlugin, UnsharpMaskSettings};
+pub use watercolor::{WatercolorPlugin, WatercolorSettings};
+pub use white_balance::{WhiteBalancePlugin, WhiteBalanceSettings};
+
+#[cfg(feature = "inspector")]
+use crate::PostProcessInspectorAppExt;
+use crate::{
+    condition::RunCondition, PostProcessBypass, PostProcessLayers, PostProcessPluginSettings,
+};
+#[cfg(feature = "inspector")]
+use bevy::reflect::GetTypeRegistration;
+use bevy::{
+    core_pipeline::FullscreenShader,
+    prelude::*,
+    render::{render_graph::RenderLabel, render_resource::VertexState},
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+#[cfg(feature = "inspector")]
+use std::ops::RangeInclusive;
+
+// The vertex state of the fullscreen triangle every built-in effect draws
+pub(crate) fn fullscreen_vertex_state(app: &mut App) -> VertexState {
+    app.world_mut()
+        .get_resource_or_init::<FullscreenShader>()
+        .to_vertex_state()
+}
+
+// Limit the numeric fields of the settings of a built-in effect in the inspector
+#[cfg(feature = "inspector")]
+pub(crate) fn register_inspector_ranges<U: Component + GetTypeRegistration>(
+    app: &mut App,
+    ranges: &[(&str, RangeInclusive<f32>)],
+) {
+    for (field, range) in ranges {
+        app.register_post_process_range::<U>(field, range.clone());
+    }
+}
+
+/// Whether the passes an effect renders into textures of its own skip a view.
+///
+/// They skip the views the effect node skips, which don't read their textures: the views off the
+/// layers of the effect, bypassing it or failing its run condition.
+pub(crate) fn skip_intermediate<U, R>(world: &World, view: Entity) -> bool
+where
+    U: Clone + Send + Sync + 'static,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+{
+    let Some(plugin_settings) = world.get_resource::<PostProcessPluginSettings<U, R>>() else {
+        return true;
+    };
+    let view_layers = world.get::<PostProcessLayers>(view);
+    !plugin_settings
+        .layers
+        .intersects(view_layers.unwrap_or(&PostProcessLayers::default()))
+        || world
+            .get::<PostProcessBypass>(view)
+            .is_some_and(|bypass| bypass.is_bypassed(plugin_settings.label.clone()))
+        || world
+            .get_resource::<RunCondition<U, R>>()
+            .is_some_and(|run_condition| !run_condition.passes(view))
+}
@@ -0,0 +1,488 @@
+use crate::{
+    effects, PostProcessBindGroup, PostProcessBindGroupLayout, PostProcessPlacement,
+    PostProcessPlugin, PostProcessTexturePool,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::{
+        core_3d::graph::Core3d,
+        prepass::{
+            PreviousViewData, PreviousViewUniformOffset, PreviousViewUniforms,
+            ViewPrepassTextures,
+        },
+    },
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{
+                texture_2d, texture_2d_multisampled, texture_storage_2d, uniform_buffer,
+            },
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::CachedTexture,
+        view::Msaa,
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/motion_blur.wgsl";
+
+/// The size in pixels of the tiles the motion is dilated over, matching the workgroups of the
+/// tile pass.
+const TILE_SIZE: u32 = 16;
+
+/// The format of the motion of every pixel and of the tiles, in pixels.
+const VELOCITY_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// A camera motion blur, run on the cameras with [`MotionBlurSettings`].
+///
+/// The motion of every pixel is reconstructed from the depth and the view of the previous frame,
+/// so the blur follows the movements and the rotations of the camera, and the scene is blurred
+/// the more the closer it is. Moving objects seen from a still camera aren't blurred.
+///
+/// The view of the previous frame is the one kept by the prepass plugin of `bevy_pbr`, which
+/// `DefaultPlugins` adds. The blur runs before the bloom by default, like the motion blur of Bevy,
+/// and can be placed anywhere in the stack of the crate.
+///
+/// With [`MotionBlurPlugin::object_motion`] the blur follows the motion vectors of the meshes
+/// instead, so fast moving characters blur against a still camera. The largest motion of every
+/// 16x16 tile is found by the [`MotionBlurTiles`] compute pass, and spread over the neighboring
+/// tiles, so the streaks of a moving object also cover the still background around it.
+pub struct MotionBlurPlugin {
+    /// Where the blur runs.
+    pub placement: PostProcessPlacement,
+    /// Blur along the motion vectors of the meshes rather than the motion of the camera.
+    ///
+    /// The cameras need the `MotionVectorPrepass` of `bevy_core_pipeline`, the cameras without it
+    /// are skipped. Placing the blur after the upscaling isn't supported.
+    pub object_motion: bool,
+}
+
+impl Default for MotionBlurPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::BeforeBloom,
+            object_motion: false,
+        }
+    }
+}
+
+/// The settings of the [`MotionBlurPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct MotionBlurSettings {
+    /// The fraction of the frame the shutter stays open, 0.5 being the 180 degree shutter of
+    /// films. The streaks are as long as the motion during that time.
+    pub shutter_angle: f32,
+    /// The longest streak, in pixels. Keeps quick turns of the camera from smearing the view.
+    pub max_radius: f32,
+    /// How many samples are taken along each streak, capped at 32.
+    pub samples: u32,
+}
+
+impl Default for MotionBlurSettings {
+    fn default() -> Self {
+        Self {
+            shutter_angle: 0.5,
+            max_radius: 32.0,
+            samples: 8,
+        }
+    }
+}
+
+/// The render graph label of the [`MotionBlurPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct MotionBlur;
+
+/// The render graph label of the compute pass finding the largest motion of every tile, when the
+/// [`MotionBlurPlugin`] follows the motion of the objects.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct MotionBlurTiles;
+
+impl Plugin for MotionBlurPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "motion_blur.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<MotionBlurSettings>(
+            app,
+            &[("shutter_angle", 0.0..=1.0), ("max_radius", 0.0..=64.0)],
+        );
+        app.register_type::<MotionBlurSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        let plugin = PostProcessPlugin::<MotionBlurSettings, _>::new(
+            SHADER_PATH,
+            MotionBlur,
+            Some("motion_blur"),
+            "motion_blur_bind_group_layout",
+            vertex_state,
+        )
+        .with_placement(self.placement)
+        .with_depth();
+
+        if !self.object_motion {
+            app.add_plugins(
+                plugin.with_bind_group(previous_view_layout, prepare_previous_view_bind_groups),
+            );
+            return;
+        }
+
+        embedded_asset!(app, "motion_blur_tiles.wgsl");
+        app.add_plugins(
+            plugin
+                .with_shader_def("OBJECT_MOTION")
+                .with_bind_group(velocity_layout, prepare_velocity_bind_groups),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (before, _) = self.placement.edges();
+        render_app
+            .init_resource::<SpecializedComputePipelines<MotionBlurTilesPipeline>>()
+            .add_systems(Render, prepare_tiles.in_set(RenderSystems::PrepareResources))
+            .add_render_graph_node::<ViewNodeRunner<MotionBlurTilesNode>>(Core3d, MotionBlurTiles)
+            .add_render_graph_edges(Core3d, (before, MotionBlurTiles, MotionBlur));
+    }
+
+    fn finish(&self, app: &mut App) {
+        if !self.object_motion {
+            return;
+        }
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<MotionBlurTilesPipeline>();
+    }
+}
+
+// The layout of the group 1 of the effect, the view of the previous frame
+fn previous_view_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "motion_blur_previous_view_bind_group_layout",
+        &BindGroupLayoutEntries::single(
+            ShaderStages::FRAGMENT,
+            uniform_buffer::<PreviousViewData>(true),
+        ),
+    )
+}
+
+fn prepare_previous_view_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &PreviousViewUniformOffset), With<MotionBlurSettings>>,
+    render_device: Res<RenderDevice>,
+    layout: Res<PostProcessBindGroupLayout<MotionBlurSettings>>,
+    previous_view_uniforms: Option<Res<PreviousViewUniforms>>,
+) {
+    let Some(binding) = previous_view_uniforms
+        .as_ref()
+        .and_then(|uniforms| uniforms.uniforms.binding())
+    else {
+        return;
+    };
+
+    for (entity, offset) in &views {
+        let bind_group = render_device.create_bind_group(
+            "motion_blur_previous_view_bind_group",
+            &layout.layout,
+            &BindGroupEntries::single(binding.clone()),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<MotionBlurSettings>::with_dynamic_offsets(
+                bind_group,
+                vec![offset.offset],
+            ));
+    }
+}
+
+// The pipelines of the tile pass, reading the motion vectors of the view, and of the neighbor
+// pass, spreading the motion of every tile over the tiles around it
+#[derive(Resource)]
+struct MotionBlurTilesPipeline {
+    tiles_layout: BindGroupLayout,
+    tiles_multisampled_layout: BindGroupLayout,
+    neighbor_layout: BindGroupLayout,
+    shader: Handle<Shader>,
+}
+
+impl FromWorld for MotionBlurTilesPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let motion_vectors_type = TextureSampleType::Float { filterable: false };
+        let storage = texture_storage_2d(VELOCITY_TEXTURE_FORMAT, StorageTextureAccess::WriteOnly);
+        let tiles_layout = |label: &'static str, motion_vectors: BindGroupLayoutEntryBuilder| {
+            render_device.create_bind_group_layout(
+                label,
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::COMPUTE,
+                    (motion_vectors, storage, storage),
+                ),
+            )
+        };
+
+        Self {
+            tiles_layout: tiles_layout(
+                "motion_blur_tiles_bind_group_layout",
+                texture_2d(motion_vectors_type),
+            ),
+            tiles_multisampled_layout: tiles_layout(
+                "motion_blur_tiles_multisampled_bind_group_layout",
+                texture_2d_multisampled(motion_vectors_type),
+            ),
+            neighbor_layout: render_device.create_bind_group_layout(
+                "motion_blur_neighbor_bind_group_layout",
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::COMPUTE,
+                    (texture_2d(motion_vectors_type), storage),
+                ),
+            ),
+            shader: load_embedded_asset!(world, "motion_blur_tiles.wgsl"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum MotionBlurTilesPass {
+    Tiles { multisampled: bool },
+    Neighbor,
+}
+
+impl SpecializedComputePipeline for MotionBlurTilesPipeline {
+    type Key = MotionBlurTilesPass;
+
+    fn specialize(&self, key: Self::Key) -> ComputePipelineDescriptor {
+        let (layout, shader_defs, entry_point) = match key {
+            MotionBlurTilesPass::Tiles { multisampled: false } => {
+                (&self.tiles_layout, vec![], "tile_max")
+            }
+            MotionBlurTilesPass::Tiles { multisampled: true } => (
+                &self.tiles_multisampled_layout,
+                vec!["MULTISAMPLED".into()],
+                "tile_max",
+            ),
+            MotionBlurTilesPass::Neighbor => (
+                &self.neighbor_layout,
+                vec!["NEIGHBOR_MAX".into()],
+                "neighbor_max",
+            ),
+        };
+
+        ComputePipelineDescriptor {
+            label: Some("motion_blur_tiles_pipeline".into()),
+            layout: vec![layout.clone()],
+            push_constant_ranges: vec![],
+            shader: self.shader.clone(),
+            shader_defs,
+            entry_point: Some(entry_point.into()),
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+// The motion of every pixel of a view, the largest motion of every tile and of its neighbors
+#[derive(Component)]
+struct ViewMotionBlurTiles {
+    velocity: CachedTexture,
+    tiles: CachedTexture,
+    neighbor_max: CachedTexture,
+    tiles_pipeline_id: CachedComputePipelineId,
+    neighbor_pipeline_id: CachedComputePipelineId,
+}
+
+fn prepare_tiles(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewPrepassTextures, &Msaa), With<MotionBlurSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    tiles_pipeline: Res<MotionBlurTilesPipeline>,
+    mut pipelines: ResMut<SpecializedComputePipelines<MotionBlurTilesPipeline>>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+) {
+    for (entity, prepass_textures, msaa) in &views {
+        if prepass_textures.motion_vectors.is_none() {
+            continue;
+        }
+
+        let size = prepass_textures.size;
+        let tiles_size = Extent3d {
+            width: size.width.div_ceil(TILE_SIZE),
+            height: size.height.div_ceil(TILE_SIZE),
+            depth_or_array_layers: 1,
+        };
+        let mut texture = |name: &'static str, size: Extent3d| {
+            texture_pool.get(
+                &render_device,
+                entity,
+                MotionBlurTiles,
+                name,
+                &TextureDescriptor {
+                    label: Some("motion_blur_velocity_texture"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: VELOCITY_TEXTURE_FORMAT,
+                    usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                },
+            )
+        };
+        let velocity = texture("velocity", size);
+        let tiles = texture("tiles", tiles_size);
+        let neighbor_max = texture("neighbor_max", tiles_size);
+
+        let tiles_pipeline_id = pipelines.specialize(
+            &pipeline_cache,
+            &tiles_pipeline,
+            MotionBlurTilesPass::Tiles {
+                multisampled: msaa.samples() > 1,
+            },
+        );
+        let neighbor_pipeline_id =
+            pipelines.specialize(&pipeline_cache, &tiles_pipeline, MotionBlurTilesPass::Neighbor);
+
+        commands.entity(entity).insert(ViewMotionBlurTiles {
+            velocity,
+            tiles,
+            neighbor_max,
+            tiles_pipeline_id,
+            neighbor_pipeline_id,
+        });
+    }
+}
+
+// The layout of the group 1 of the effect following the motion of the objects, the motion of
+// every pixel and the largest motion around its tile
+fn velocity_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    let velocity = texture_2d(TextureSampleType::Float { filterable: false });
+    render_device.create_bind_group_layout(
+        "motion_blur_velocity_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(ShaderStages::FRAGMENT, (velocity, velocity)),
+    )
+}
+
+// The effect skips the views without the bind group, so it doesn't read the tiles until the tile
+// pipelines are compiled
+fn prepare_velocity_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewMotionBlurTiles), With<MotionBlurSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    layout: Res<PostProcessBindGroupLayout<MotionBlurSettings>>,
+) {
+    for (entity, tiles) in &views {
+        if pipeline_cache
+            .get_compute_pipeline(tiles.tiles_pipeline_id)
+            .is_none()
+            || pipeline_cache
+                .get_compute_pipeline(tiles.neighbor_pipeline_id)
+                .is_none()
+        {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<MotionBlurSettings>>();
+            continue;
+        }
+
+        let bind_group = render_device.create_bind_group(
+            "motion_blur_velocity_bind_group",
+            &layout.layout,
+            &BindGroupEntries::sequential((
+                &tiles.velocity.default_view,
+                &tiles.neighbor_max.default_view,
+            )),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<MotionBlurSettings>::new(bind_group));
+    }
+}
+
+#[derive(Default)]
+struct MotionBlurTilesNode;
+
+impl ViewNode for MotionBlurTilesNode {
+    type ViewQuery = (
+        // Only runs on the cameras with the blur
+        &'static MotionBlurSettings,
+        &'static ViewPrepassTextures,
+        &'static ViewMotionBlurTiles,
+        &'static Msaa,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (_settings, prepass_textures, view_tiles, msaa): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // The effect doesn't read the tiles on the views it skips
+        let view = graph.view_entity();
+        if effects::skip_intermediate::<MotionBlurSettings, MotionBlur>(world, view) {
+            return Ok(());
+        }
+
+        let tiles_pipeline = world.resource::<MotionBlurTilesPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (Some(motion_vectors), Some(tiles_compute_pipeline), Some(neighbor_pipeline)) = (
+            prepass_textures.motion_vectors_view(),
+            pipeline_cache.get_compute_pipeline(view_tiles.tiles_pipeline_id),
+            pipeline_cache.get_compute_pipeline(view_tiles.neighbor_pipeline_id),
+        ) else {
+            return Ok(());
+        };
+
+        let tiles_layout = match msaa.samples() > 1 {
+            true => &tiles_pipeline.tiles_multisampled_layout,
+            false => &tiles_pipeline.tiles_layout,
+        };
+        let tiles_bind_group = render_context.render_device().create_bind_group(
+            "motion_blur_tiles_bind_group",
+            tiles_layout,
+            &BindGroupEntries::sequential((
+                motion_vectors,
+                &view_tiles.velocity.default_view,
+                &view_tiles.tiles.default_view,
+            )),
+        );
+        let neighbor_bind_group = render_context.render_device().create_bind_group(
+            "motion_blur_neighbor_bind_group",
+            &tiles_pipeline.neighbor_layout,
+            &BindGroupEntries::sequential((
+                &view_tiles.tiles.default_view,
+                &view_tiles.neighbor_max.default_view,
+            )),
+        );
+
+        let tiles_size = view_tiles.tiles.texture.size();
+        let mut compute_pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("motion_blur_tiles_pass"),
+                    timestamp_writes: None,
+                });
+        // One workgroup per tile
+        compute_pass.set_pipeline(tiles_compute_pipeline);
+        compute_pass.set_bind_group(0, &tiles_bind_group, &[]);
+        compute_pass.dispatch_workgroups(tiles_size.width, tiles_size.height, 1);
+        compute_pass.set_pipeline(neighbor_pipeline);
+        compute_pass.set_bind_group(0, &neighbor_bind_group, &[]);
+        compute_pass.dispatch_workgroups(
+            tiles_size.width.div_ceil(8),
+            tiles_size.height.div_ceil(8),
+            1,
+        );
+
+        Ok(())
+    }
+}
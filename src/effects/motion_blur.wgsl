@@ -0,0 +1,149 @@
+// Blurs every pixel along its motion since the previous frame. The motion is reconstructed from
+// the depth and the previous view, or read from the motion vectors of the objects when
+// OBJECT_MOTION is set.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_post_process::utils::{linearize_depth, ndc_to_uv, position_world_from_depth}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct MotionBlurSettings {
+    shutter_angle: f32,
+    max_radius: f32,
+    samples: u32,
+}
+@group(0) @binding(2) var<uniform> settings: MotionBlurSettings;
+@group(0) @binding(3) var<uniform> view: View;
+#ifdef MULTISAMPLED
+@group(0) @binding(4) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(4) var depth_texture: texture_depth_2d;
+#endif
+
+#ifdef OBJECT_MOTION
+// The motion of every pixel and the largest motion around every tile, in pixels
+@group(1) @binding(0) var velocity_texture: texture_2d<f32>;
+@group(1) @binding(1) var neighbor_max_texture: texture_2d<f32>;
+
+const TILE_SIZE: i32 = 16;
+// The depth difference, in world units, over which a sample goes from in front to behind
+const SOFT_DEPTH: f32 = 0.1;
+#else
+struct PreviousViewData {
+    view_from_world: mat4x4<f32>,
+    clip_from_world: mat4x4<f32>,
+    clip_from_view: mat4x4<f32>,
+    world_from_clip: mat4x4<f32>,
+    view_from_clip: mat4x4<f32>,
+}
+@group(1) @binding(0) var<uniform> previous_view: PreviousViewData;
+
+// The background is infinitely far away, it is moved to a finite depth so it still follows the
+// rotations of the camera
+const MIN_DEPTH: f32 = 1.0e-6;
+#endif
+
+const MAX_SAMPLES: u32 = 32u;
+
+// Offsets the samples of neighboring pixels, so too few samples show as noise rather than bands
+fn interleaved_gradient_noise(pixel: vec2<f32>) -> f32 {
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+// The streak of a motion in pixels, scaled by the shutter and capped to the longest streak
+fn streak_of(motion: vec2<f32>) -> vec2<f32> {
+    let streak = motion * settings.shutter_angle;
+    let streak_length = length(streak);
+    if streak_length > settings.max_radius {
+        return streak * settings.max_radius / streak_length;
+    }
+    return streak;
+}
+
+#ifdef OBJECT_MOTION
+fn velocity_at(pixel: vec2<i32>) -> vec2<f32> {
+    let size = vec2<i32>(textureDimensions(velocity_texture));
+    return streak_of(textureLoad(velocity_texture, clamp(pixel, vec2(0), size - 1), 0).xy);
+}
+
+fn depth_at(pixel: vec2<i32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(velocity_texture));
+    let depth = textureLoad(depth_texture, clamp(pixel, vec2(0), size - 1), 0);
+    return linearize_depth(depth, view.view_from_clip);
+}
+
+// How much a sample whose blur is `radius` long covers a pixel `distance` away
+fn cone(distance: f32, radius: f32) -> f32 {
+    return clamp(1.0 - distance / max(radius, 1.0e-4), 0.0, 1.0);
+}
+
+fn cylinder(distance: f32, radius: f32) -> f32 {
+    return 1.0 - smoothstep(0.95 * radius, 1.05 * radius, distance);
+}
+
+// Reconstruction filter of "A Reconstruction Filter for Plausible Motion Blur", McGuire et al.
+// 2012. The samples are spread along the largest motion around the tile, and each one counts
+// when it is in front and its own blur reaches the pixel, or when it is behind and the blur of
+// the pixel reaches it.
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(in.position.xy);
+    let center = textureSampleLevel(screen_texture, texture_sampler, in.uv, 0.0);
+    let largest = streak_of(textureLoad(neighbor_max_texture, pixel / TILE_SIZE, 0).xy);
+    if length(largest) < 0.5 {
+        return center;
+    }
+
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    let center_radius = length(velocity_at(pixel));
+    let center_depth = depth_at(pixel);
+
+    let samples = clamp(settings.samples, 1u, MAX_SAMPLES);
+    let noise = interleaved_gradient_noise(in.position.xy);
+    var total_weight = 1.0 / max(center_radius, 1.0);
+    var color = center * total_weight;
+    for (var i = 0u; i < samples; i++) {
+        let t = (f32(i) + noise) / f32(samples) - 0.5;
+        let offset = largest * t;
+        let sample_pixel = vec2<i32>(in.position.xy + offset);
+        let distance = length(offset);
+        let sample_radius = length(velocity_at(sample_pixel));
+        let sample_depth = depth_at(sample_pixel);
+
+        let in_front = clamp(1.0 - (sample_depth - center_depth) / SOFT_DEPTH, 0.0, 1.0);
+        let behind = clamp(1.0 - (center_depth - sample_depth) / SOFT_DEPTH, 0.0, 1.0);
+        let weight = in_front * cone(distance, sample_radius)
+            + behind * cone(distance, center_radius)
+            + 2.0 * cylinder(distance, sample_radius) * cylinder(distance, center_radius);
+
+        let uv = in.uv + offset / size;
+        color += weight * textureSampleLevel(screen_texture, texture_sampler, uv, 0.0);
+        total_weight += weight;
+    }
+    return color / total_weight;
+}
+#else
+// The distance the pixel moved on the screen since the previous frame, in pixels
+fn camera_motion(uv: vec2<f32>, pixel: vec2<i32>, size: vec2<f32>) -> vec2<f32> {
+    let depth = max(textureLoad(depth_texture, pixel, 0), MIN_DEPTH);
+    let world_position = position_world_from_depth(uv, depth, view.world_from_clip);
+    let previous_clip = previous_view.clip_from_world * vec4(world_position, 1.0);
+    return (uv - ndc_to_uv(previous_clip.xy / previous_clip.w)) * size;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    let streak = streak_of(camera_motion(in.uv, vec2<i32>(in.position.xy), size)) / size;
+
+    // The samples are spread over the streak, centered on the pixel
+    let samples = clamp(settings.samples, 1u, MAX_SAMPLES);
+    let noise = interleaved_gradient_noise(in.position.xy);
+    var color = vec4(0.0);
+    for (var i = 0u; i < samples; i++) {
+        let t = (f32(i) + noise) / f32(samples) - 0.5;
+        color += textureSampleLevel(screen_texture, texture_sampler, in.uv - streak * t, 0.0);
+    }
+    return color / f32(samples);
+}
+#endif
@@ -0,0 +1,74 @@
+// The tile passes of the motion blur following the motion of the objects. The tile pass converts
+// the motion vectors to pixels and keeps the largest motion of every 16x16 tile, the neighbor pass
+// spreads the largest motion of every tile over the tiles around it.
+
+#ifdef NEIGHBOR_MAX
+@group(0) @binding(0) var tiles: texture_2d<f32>;
+@group(0) @binding(1) var neighbor_max_texture: texture_storage_2d<rgba16float, write>;
+#else
+#ifdef MULTISAMPLED
+@group(0) @binding(0) var motion_vectors: texture_multisampled_2d<f32>;
+#else
+@group(0) @binding(0) var motion_vectors: texture_2d<f32>;
+#endif
+@group(0) @binding(1) var velocity: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(2) var tiles: texture_storage_2d<rgba16float, write>;
+
+// The motion of every pixel of the tile, reduced to the largest one
+var<workgroup> tile_motion: array<vec2<f32>, 256>;
+
+@compute @workgroup_size(16, 16, 1)
+fn tile_max(
+    @builtin(global_invocation_id) pixel: vec3<u32>,
+    @builtin(local_invocation_index) index: u32,
+    @builtin(workgroup_id) tile: vec3<u32>,
+) {
+    let size = textureDimensions(motion_vectors);
+    var motion = vec2(0.0);
+    if all(pixel.xy < size) {
+        // The motion vectors are in UV units, and the first sample stands for the pixel
+        motion = textureLoad(motion_vectors, pixel.xy, 0).xy * vec2<f32>(size);
+        textureStore(velocity, pixel.xy, vec4(motion, 0.0, 0.0));
+    }
+    tile_motion[index] = motion;
+    workgroupBarrier();
+
+    for (var stride = 128u; stride > 0u; stride >>= 1u) {
+        if index < stride {
+            let other = tile_motion[index + stride];
+            let current = tile_motion[index];
+            if dot(other, other) > dot(current, current) {
+                tile_motion[index] = other;
+            }
+        }
+        workgroupBarrier();
+    }
+
+    if index == 0u {
+        textureStore(tiles, tile.xy, vec4(tile_motion[0], 0.0, 0.0));
+    }
+}
+#endif
+
+#ifdef NEIGHBOR_MAX
+@compute @workgroup_size(8, 8, 1)
+fn neighbor_max(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = vec2<i32>(textureDimensions(tiles));
+    let tile = vec2<i32>(id.xy);
+    if any(tile >= size) {
+        return;
+    }
+
+    var largest = vec2(0.0);
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let neighbor = clamp(tile + vec2(x, y), vec2(0), size - 1);
+            let motion = textureLoad(tiles, neighbor, 0).xy;
+            if dot(motion, motion) > dot(largest, largest) {
+                largest = motion;
+            }
+        }
+    }
+    textureStore(neighbor_max_texture, tile, vec4(largest, 0.0, 0.0));
+}
+#endif
@@ -0,0 +1,218 @@
+use crate::{
+    effects, noise, PostProcessBindGroup, PostProcessBindGroupLayout, PostProcessInput,
+    PostProcessNoisePlugin, PostProcessPlacement, PostProcessPlugin,
+};
+use bevy::{
+    asset::{embedded_asset, uuid_handle, RenderAssetUsages},
+    color::ColorToPacked,
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_asset::RenderAssets,
+        render_graph::RenderLabel,
+        render_resource::{binding_types::texture_2d, *},
+        renderer::RenderDevice,
+        texture::GpuImage,
+    },
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/palette_map.wgsl";
+
+/// A reduction of the view to the colors of a palette, run on the cameras with
+/// [`PaletteMapSettings`] and a [`PaletteMapTexture`].
+///
+/// Every pixel takes the closest color of the palette, optionally dithered with the second closest
+/// one so gradients survive. The plugin registers a few classic palettes as constants of
+/// [`PaletteMapTexture`], and [`palette_image`] makes new ones:
+///
+/// ```ignore
+/// commands.spawn((
+///     Camera2d,
+///     PaletteMapTexture::PICO_8,
+///     PaletteMapSettings::default(),
+/// ));
+/// ```
+///
+/// Runs after the tonemapping by default. Adds the [`PostProcessNoisePlugin`] for the dithering
+/// when it isn't already.
+pub struct PaletteMapPlugin {
+    /// Where the palette mapping runs.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for PaletteMapPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+        }
+    }
+}
+
+/// The settings of the [`PaletteMapPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct PaletteMapSettings {
+    /// How the closest color is found, [`PaletteMapSettings::MATCH_RGB`] or
+    /// [`PaletteMapSettings::MATCH_OKLAB`].
+    pub matching: u32,
+    /// How much the two closest colors are dithered together, from 0 for flat areas of the
+    /// closest color to 1 for an ordered dithering following the distances to both.
+    pub dither: f32,
+    /// The size of the dithering pattern cells, in pixels.
+    pub dither_scale: f32,
+}
+
+impl PaletteMapSettings {
+    /// Compare the colors by their sRGB encoded channels, like most pixel art tools.
+    pub const MATCH_RGB: u32 = 0;
+    /// Compare the colors in Oklab, matching them as the eye does at the cost of a few more
+    /// instructions per palette entry.
+    pub const MATCH_OKLAB: u32 = 1;
+}
+
+impl Default for PaletteMapSettings {
+    fn default() -> Self {
+        Self {
+            matching: Self::MATCH_OKLAB,
+            dither: 0.0,
+            dither_scale: 1.0,
+        }
+    }
+}
+
+/// The palette a camera is reduced to by the [`PaletteMapPlugin`].
+///
+/// The colors are the pixels of the first row of the image, up to 256 of them, like the images
+/// made by [`palette_image`]. The mapping is skipped while the image is loading.
+#[derive(Component, Reflect, Clone, Debug, ExtractComponent)]
+#[reflect(Component)]
+pub struct PaletteMapTexture(pub Handle<Image>);
+
+impl PaletteMapTexture {
+    /// The four greens of the original Game Boy.
+    pub const GAME_BOY: Self = Self(uuid_handle!("9d2f6b1e-4c8a-4e3d-b7f5-1a6c3e9d2b47"));
+    /// The high intensity CGA palette 1: black, cyan, magenta and white.
+    pub const CGA: Self = Self(uuid_handle!("4a7e1c9f-2b6d-4f8e-a3c5-8d1b7f4e6a92"));
+    /// The 16 colors of the PICO-8 fantasy console.
+    pub const PICO_8: Self = Self(uuid_handle!("e6c3a8d2-7f1b-4d9e-8b4a-5c2e9f7d1a38"));
+}
+
+const GAME_BOY_COLORS: [u32; 4] = [0x0f380f, 0x306230, 0x8bac0f, 0x9bbc0f];
+const CGA_COLORS: [u32; 4] = [0x000000, 0x55ffff, 0xff55ff, 0xffffff];
+const PICO_8_COLORS: [u32; 16] = [
+    0x000000, 0x1d2b53, 0x7e2553, 0x008751, 0xab5236, 0x5f574f, 0xc2c3c7, 0xfff1e8, 0xff004d,
+    0xffa300, 0xffec27, 0x00e436, 0x29adff, 0x83769c, 0xff77a8, 0xffccaa,
+];
+
+/// Makes a palette for [`PaletteMapTexture`] out of a list of colors, at most 256 of them.
+pub fn palette_image(colors: &[Srgba]) -> Image {
+    let data = colors
+        .iter()
+        .flat_map(|color| color.to_u8_array())
+        .collect();
+    Image::new(
+        Extent3d {
+            width: colors.len() as u32,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+fn hex_palette_image(colors: &[u32]) -> Image {
+    let colors: Vec<_> = colors
+        .iter()
+        .map(|&hex| Srgba::rgb_u8((hex >> 16) as u8, (hex >> 8) as u8, hex as u8))
+        .collect();
+    palette_image(&colors)
+}
+
+/// The render graph label of the [`PaletteMapPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct PaletteMap;
+
+impl Plugin for PaletteMapPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "palette_map.wgsl");
+        if !app.is_plugin_added::<PostProcessNoisePlugin>() {
+            app.add_plugins(PostProcessNoisePlugin);
+        }
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<PaletteMapSettings>(
+            app,
+            &[("dither", 0.0..=1.0), ("dither_scale", 1.0..=8.0)],
+        );
+        app.register_type::<PaletteMapSettings>()
+            .register_type::<PaletteMapTexture>()
+            .add_plugins(ExtractComponentPlugin::<PaletteMapTexture>::default());
+
+        let mut images = app.world_mut().resource_mut::<Assets<Image>>();
+        for (palette, colors) in [
+            (PaletteMapTexture::GAME_BOY, &GAME_BOY_COLORS[..]),
+            (PaletteMapTexture::CGA, &CGA_COLORS[..]),
+            (PaletteMapTexture::PICO_8, &PICO_8_COLORS[..]),
+        ] {
+            images
+                .insert(&palette.0, hex_palette_image(colors))
+                .expect("the palette handles are uuid handles");
+        }
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<PaletteMapSettings, _>::new(
+                SHADER_PATH,
+                PaletteMap,
+                Some("palette_map"),
+                "palette_map_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_input(PostProcessInput::Image(noise::BAYER_8X8))
+            .without_view()
+            .with_bind_group(palette_layout, prepare_palette_bind_groups),
+        );
+    }
+}
+
+// The layout of the group 1 of the effect, the palette. It is only loaded, never filtered
+fn palette_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "palette_map_texture_bind_group_layout",
+        &BindGroupLayoutEntries::single(
+            ShaderStages::FRAGMENT,
+            texture_2d(TextureSampleType::Float { filterable: false }),
+        ),
+    )
+}
+
+// The effect skips the views without the bind group, so the views whose palette is still loading
+// keep their colors
+fn prepare_palette_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &PaletteMapTexture), With<PaletteMapSettings>>,
+    render_device: Res<RenderDevice>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    layout: Res<PostProcessBindGroupLayout<PaletteMapSettings>>,
+) {
+    for (entity, palette) in &views {
+        let Some(gpu_image) = gpu_images.get(&palette.0) else {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<PaletteMapSettings>>();
+            continue;
+        };
+
+        let bind_group = render_device.create_bind_group(
+            "palette_map_texture_bind_group",
+            &layout.layout,
+            &BindGroupEntries::single(&gpu_image.texture_view),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<PaletteMapSettings>::new(bind_group));
+    }
+}
@@ -0,0 +1,73 @@
+// Replaces every pixel by the closest color of the palette, or by an ordered dithering of the two
+// closest ones.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::{linear_to_srgb, linear_to_oklab}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct PaletteMapSettings {
+    matching: u32,
+    dither: f32,
+    dither_scale: f32,
+}
+@group(0) @binding(2) var<uniform> settings: PaletteMapSettings;
+@group(0) @binding(16) var bayer: texture_2d<f32>;
+
+@group(1) @binding(0) var palette: texture_2d<f32>;
+
+const MATCH_OKLAB: u32 = 1u;
+const MAX_COLORS: u32 = 256u;
+
+fn to_matching_space(color: vec3<f32>) -> vec3<f32> {
+    if settings.matching == MATCH_OKLAB {
+        return linear_to_oklab(color);
+    }
+    return linear_to_srgb(color);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let target_color = to_matching_space(clamp(color.rgb, vec3(0.0), vec3(1.0)));
+
+    // The two closest entries, in the matching space
+    var closest = vec3(0.0);
+    var closest_color = vec3(0.0);
+    var closest_distance = 1.0e9;
+    var second = vec3(0.0);
+    var second_color = vec3(0.0);
+    var second_distance = 1.0e9;
+    let count = min(textureDimensions(palette).x, MAX_COLORS);
+    for (var i = 0u; i < count; i++) {
+        let entry_color = textureLoad(palette, vec2(i, 0u), 0).rgb;
+        let entry = to_matching_space(entry_color);
+        let offset = entry - target_color;
+        let entry_distance = dot(offset, offset);
+        if entry_distance < closest_distance {
+            second = closest;
+            second_color = closest_color;
+            second_distance = closest_distance;
+            closest = entry;
+            closest_color = entry_color;
+            closest_distance = entry_distance;
+        } else if entry_distance < second_distance {
+            second = entry;
+            second_color = entry_color;
+            second_distance = entry_distance;
+        }
+    }
+
+    if settings.dither <= 0.0 || count < 2u {
+        return vec4(closest_color, color.a);
+    }
+
+    // How far the pixel lies from the closest entry toward the second one, at most a half, is the
+    // share of the pixels of the pattern taking the second entry
+    let segment = second - closest;
+    let along = dot(target_color - closest, segment) / max(dot(segment, segment), 1.0e-8);
+    let share = clamp(along, 0.0, 1.0) * settings.dither;
+
+    let cell = vec2<u32>(in.position.xy / max(settings.dither_scale, 1.0));
+    let threshold = textureLoad(bayer, cell % textureDimensions(bayer), 0).r;
+    return vec4(select(closest_color, second_color, share > threshold), color.a);
+}
@@ -0,0 +1,76 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/pixelate.wgsl";
+
+/// A pixelation into square blocks, run on the cameras with [`PixelateSettings`].
+///
+/// Every block takes the color of its center, point sampled with the nearest sampler of
+/// [`PostProcessPlugin::with_standard_samplers`], or the average of a grid of samples across it.
+/// Animating the block size makes a classic transition.
+#[derive(Default)]
+pub struct PixelatePlugin {
+    /// Where the pixelation runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`PixelatePlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct PixelateSettings {
+    /// The size of the blocks, in pixels. 1 or less leaves the view unchanged.
+    pub block_size: f32,
+    /// When above 0, the block size is given for a view of this height and scales with the actual
+    /// height, so the view has the same number of blocks at every resolution.
+    pub reference_height: f32,
+    /// How many samples are averaged along each side of a block, capped at 8. 1 takes the color
+    /// of the center of the block.
+    pub samples: u32,
+}
+
+impl Default for PixelateSettings {
+    fn default() -> Self {
+        Self {
+            block_size: 8.0,
+            reference_height: 0.0,
+            samples: 1,
+        }
+    }
+}
+
+/// The render graph label of the [`PixelatePlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Pixelate;
+
+impl Plugin for PixelatePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "pixelate.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<PixelateSettings>(
+            app,
+            &[
+                ("block_size", 1.0..=64.0),
+                ("reference_height", 0.0..=2160.0),
+            ],
+        );
+        app.register_type::<PixelateSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<PixelateSettings, _>::new(
+                SHADER_PATH,
+                Pixelate,
+                Some("pixelate"),
+                "pixelate_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view()
+            .with_standard_samplers(),
+        );
+    }
+}
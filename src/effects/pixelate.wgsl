@@ -0,0 +1,42 @@
+// Snaps every pixel to the block it falls in, taking the color of the block center or the average
+// of a grid of samples across the block.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct PixelateSettings {
+    block_size: f32,
+    reference_height: f32,
+    samples: u32,
+}
+@group(0) @binding(2) var<uniform> settings: PixelateSettings;
+@group(0) @binding(8) var nearest_sampler: sampler;
+
+const MAX_SAMPLES: u32 = 8u;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    var block_size = settings.block_size;
+    if settings.reference_height > 0.0 {
+        block_size *= size.y / settings.reference_height;
+    }
+    if block_size <= 1.0 {
+        return textureSampleLevel(screen_texture, nearest_sampler, in.uv, 0.0);
+    }
+
+    // Blocks are anchored at the center of the view, so resizing it grows them symmetrically
+    let pixel = in.uv * size - size * 0.5;
+    let block_start = floor(pixel / block_size) * block_size + size * 0.5;
+
+    let samples = clamp(settings.samples, 1u, MAX_SAMPLES);
+    var color = vec4(0.0);
+    for (var y = 0u; y < samples; y++) {
+        for (var x = 0u; x < samples; x++) {
+            let offset = (vec2(f32(x), f32(y)) + 0.5) / f32(samples) * block_size;
+            let uv = clamp((block_start + offset) / size, vec2(0.0), vec2(1.0));
+            color += textureSampleLevel(screen_texture, nearest_sampler, uv, 0.0);
+        }
+    }
+    return color / f32(samples * samples);
+}
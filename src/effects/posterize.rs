@@ -0,0 +1,80 @@
+use crate::{effects, PostProcessColorSpace, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/posterize.wgsl";
+
+/// A posterization to a few levels per channel, run on the cameras with [`PosterizeSettings`].
+///
+/// Runs after the tonemapping by default, so the levels split the colors that reach the screen.
+pub struct PosterizePlugin {
+    /// Where the posterization runs.
+    pub placement: PostProcessPlacement,
+    /// The color space the levels are evenly spread in. The default, sRGB, spreads them evenly
+    /// for the eye. Linear crowds the dark tones into the lowest levels.
+    pub color_space: PostProcessColorSpace,
+}
+
+impl Default for PosterizePlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+            color_space: PostProcessColorSpace::Srgb,
+        }
+    }
+}
+
+/// The settings of the [`PosterizePlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct PosterizeSettings {
+    /// How many levels every channel is reduced to, at least 2.
+    pub levels: u32,
+    /// What is quantized, [`PosterizeSettings::MODE_CHANNELS`] or
+    /// [`PosterizeSettings::MODE_LUMINANCE`].
+    pub mode: u32,
+}
+
+impl PosterizeSettings {
+    /// The red, green and blue channels are quantized separately, shifting the hues.
+    pub const MODE_CHANNELS: u32 = 0;
+    /// Only the luminance is quantized, keeping the hue and saturation of every pixel.
+    pub const MODE_LUMINANCE: u32 = 1;
+}
+
+impl Default for PosterizeSettings {
+    fn default() -> Self {
+        Self {
+            levels: 6,
+            mode: Self::MODE_CHANNELS,
+        }
+    }
+}
+
+/// The render graph label of the [`PosterizePlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Posterize;
+
+impl Plugin for PosterizePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "posterize.wgsl");
+        app.register_type::<PosterizeSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<PosterizeSettings, _>::new(
+                SHADER_PATH,
+                Posterize,
+                Some("posterize"),
+                "posterize_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_color_space(self.color_space)
+            .without_view(),
+        );
+    }
+}
@@ -0,0 +1,34 @@
+// Rounds every channel, or the luminance alone, to a few evenly spread levels of the working space.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::{to_working_space, from_working_space}
+#import bevy_post_process::utils::luminance
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct PosterizeSettings {
+    levels: u32,
+    mode: u32,
+}
+@group(0) @binding(2) var<uniform> settings: PosterizeSettings;
+
+const MODE_LUMINANCE: u32 = 1u;
+
+fn quantize(value: f32, steps: f32) -> f32 {
+    return round(value * steps) / steps;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let encoded = to_working_space(color.rgb);
+    let steps = f32(max(settings.levels, 2u) - 1u);
+
+    var posterized: vec3<f32>;
+    if settings.mode == MODE_LUMINANCE {
+        let luma = luminance(encoded);
+        posterized = encoded * quantize(luma, steps) / max(luma, 1.0e-5);
+    } else {
+        posterized = round(encoded * steps) / steps;
+    }
+    return vec4(from_working_space(posterized), color.a);
+}
@@ -0,0 +1,69 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/radial_blur.wgsl";
+
+/// A radial blur streaking the view outward from a point, run on the cameras with
+/// [`RadialBlurSettings`].
+///
+/// Suits speed boosts, explosions and warp jumps.
+#[derive(Default)]
+pub struct RadialBlurPlugin {
+    /// Where the blur runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`RadialBlurPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct RadialBlurSettings {
+    /// The point the blur streaks away from, in UV coordinates. `(0.5, 0.5)` is the center of the
+    /// view.
+    pub center: Vec2,
+    /// The length of the streaks, as a fraction of the distance to the center. 0 turns the blur
+    /// off.
+    pub strength: f32,
+    /// How many samples are taken along each streak, capped at 64. Long streaks need more samples
+    /// to stay smooth.
+    pub samples: u32,
+}
+
+impl Default for RadialBlurSettings {
+    fn default() -> Self {
+        Self {
+            center: Vec2::splat(0.5),
+            strength: 0.1,
+            samples: 16,
+        }
+    }
+}
+
+/// The render graph label of the [`RadialBlurPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct RadialBlur;
+
+impl Plugin for RadialBlurPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "radial_blur.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<RadialBlurSettings>(app, &[("strength", 0.0..=1.0)]);
+        app.register_type::<RadialBlurSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<RadialBlurSettings, _>::new(
+                SHADER_PATH,
+                RadialBlur,
+                Some("radial_blur"),
+                "radial_blur_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view(),
+        );
+    }
+}
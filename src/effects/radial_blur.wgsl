@@ -0,0 +1,27 @@
+// Averages the view along the line from each pixel to the center of the blur.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct RadialBlurSettings {
+    center: vec2<f32>,
+    strength: f32,
+    samples: u32,
+}
+@group(0) @binding(2) var<uniform> settings: RadialBlurSettings;
+
+const MAX_SAMPLES: u32 = 64u;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let samples = clamp(settings.samples, 1u, MAX_SAMPLES);
+    // The streak goes from the pixel toward the center, longer further away from it
+    let streak = (settings.center - in.uv) * settings.strength;
+
+    var color = vec4(0.0);
+    for (var i = 0u; i < samples; i++) {
+        let t = f32(i) / f32(samples);
+        color += textureSampleLevel(screen_texture, texture_sampler, in.uv + streak * t, 0.0);
+    }
+    return color / f32(samples);
+}
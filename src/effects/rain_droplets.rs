@@ -0,0 +1,112 @@
+use crate::{effects, PostProcessInput, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/rain_droplets.wgsl";
+
+/// Rain droplets on the lens, run on the cameras with [`RainDropletsSettings`].
+///
+/// Droplets scattered over the view refract the scene behind them, and now and then one runs
+/// down the lens, leaving a thin trail. The droplets are generated by the shader, or laid out by
+/// the `droplets` texture of the plugin. Ramp up the wetness during a storm to cover the lens, and
+/// back down to dry it. Runs after the tonemapping by default, like the rest of the lens.
+pub struct RainDropletsPlugin {
+    /// Where the droplets run.
+    pub placement: PostProcessPlacement,
+    /// The droplets on the lens, stretched over the view. Its red and green channels are the
+    /// normal of the droplets encoded in 0 to 1, and its blue channel the wetness from which every
+    /// droplet shows, 0 to show it on a barely wet lens. Without one, the droplets are generated.
+    pub droplets: Option<Handle<Image>>,
+}
+
+impl Default for RainDropletsPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+            droplets: None,
+        }
+    }
+}
+
+/// The settings of the [`RainDropletsPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct RainDropletsSettings {
+    /// How wet the lens is, from 0 for dry to 1 for covered in droplets. The drips run more often
+    /// on a wetter lens.
+    pub wetness: f32,
+    /// The size of the largest droplets, in pixels.
+    pub size: f32,
+    /// How far the droplets shift the scene behind them, in pixels.
+    pub refraction: f32,
+    /// How fast the drips run down the lens, in screen heights per second.
+    pub drip_speed: f32,
+    /// The fraction of the columns of droplets a drip runs down at a wetness of 1.
+    pub drips: f32,
+    /// The time the drips are animated with, in seconds. Written by the plugin every frame,
+    /// wrapping like [`Time::elapsed_secs_wrapped`].
+    pub time: f32,
+}
+
+impl Default for RainDropletsSettings {
+    fn default() -> Self {
+        Self {
+            wetness: 0.5,
+            size: 32.0,
+            refraction: 12.0,
+            drip_speed: 0.15,
+            drips: 0.2,
+            time: 0.0,
+        }
+    }
+}
+
+/// The render graph label of the [`RainDropletsPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct RainDroplets;
+
+impl Plugin for RainDropletsPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "rain_droplets.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<RainDropletsSettings>(
+            app,
+            &[
+                ("wetness", 0.0..=1.0),
+                ("size", 4.0..=128.0),
+                ("refraction", 0.0..=32.0),
+                ("drip_speed", 0.0..=2.0),
+                ("drips", 0.0..=1.0),
+            ],
+        );
+        app.register_type::<RainDropletsSettings>()
+            .add_systems(Update, update_time);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        let mut plugin = PostProcessPlugin::<RainDropletsSettings, _>::new(
+            SHADER_PATH,
+            RainDroplets,
+            Some("rain_droplets"),
+            "rain_droplets_bind_group_layout",
+            vertex_state,
+        )
+        .with_placement(self.placement)
+        .with_standard_samplers()
+        .without_view();
+        if let Some(droplets) = &self.droplets {
+            plugin = plugin
+                .with_input(PostProcessInput::Image(droplets.clone()))
+                .with_shader_def("DROPLET_TEXTURE");
+        }
+        app.add_plugins(plugin);
+    }
+}
+
+fn update_time(mut settings: Query<&mut RainDropletsSettings>, time: Res<Time>) {
+    for mut settings in &mut settings {
+        settings.time = time.elapsed_secs_wrapped();
+    }
+}
@@ -0,0 +1,126 @@
+// Rain droplets on the lens. Every droplet is a small lens with a normal, shifting the scene
+// behind it. The static droplets come from a texture or from a grid of randomly placed circles,
+// and the drips run down columns of the screen, each one leaving a trail.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct RainDropletsSettings {
+    wetness: f32,
+    size: f32,
+    refraction: f32,
+    drip_speed: f32,
+    drips: f32,
+    time: f32,
+}
+@group(0) @binding(2) var<uniform> settings: RainDropletsSettings;
+@group(0) @binding(7) var clamp_sampler: sampler;
+#ifdef DROPLET_TEXTURE
+@group(0) @binding(16) var droplet_texture: texture_2d<f32>;
+#endif
+
+// A droplet, its normal and how much of the pixel it covers
+struct Droplet {
+    normal: vec2<f32>,
+    coverage: f32,
+}
+
+fn hash(cell: vec2<f32>) -> f32 {
+    return fract(sin(dot(cell, vec2(127.1, 311.7))) * 43758.5453);
+}
+
+// The normal of a dome at an offset from its center, relative to its radius
+fn dome(offset: vec2<f32>, radius: f32) -> Droplet {
+    let d = offset / max(radius, 1.0e-5);
+    let distance_squared = dot(d, d);
+    if distance_squared >= 1.0 {
+        return Droplet(vec2(0.0), 0.0);
+    }
+    // Antialiased over about a pixel at the rim
+    let coverage = clamp((1.0 - sqrt(distance_squared)) * radius, 0.0, 1.0);
+    return Droplet(d, coverage);
+}
+
+// The droplet of the cell of a grid of cells of `size` pixels, placed and sized at random
+fn grid_droplet(pixel: vec2<f32>, size: f32, seed: f32) -> Droplet {
+    let cell = floor(pixel / size);
+    let presence = hash(cell + seed);
+    if presence > settings.wetness {
+        return Droplet(vec2(0.0), 0.0);
+    }
+    let radius = size * mix(0.15, 0.45, hash(cell + seed + 17.0));
+    // The droplet stays inside its cell
+    let room = size * 0.5 - radius;
+    let jitter = vec2(hash(cell + seed + 3.0), hash(cell + seed + 7.0)) * 2.0 - 1.0;
+    let center = (cell + 0.5) * size + jitter * room;
+    return dome(pixel - center, radius);
+}
+
+fn static_droplet(uv: vec2<f32>, pixel: vec2<f32>) -> Droplet {
+#ifdef DROPLET_TEXTURE
+    let texel = textureSampleLevel(droplet_texture, clamp_sampler, uv, 0.0);
+    let coverage = clamp((settings.wetness - texel.b) * 32.0, 0.0, 1.0);
+    return Droplet(texel.rg * 2.0 - 1.0, coverage);
+#else
+    // Large droplets over a layer of smaller ones
+    let large = grid_droplet(pixel, settings.size, 0.0);
+    if large.coverage > 0.0 {
+        return large;
+    }
+    return grid_droplet(pixel, settings.size * 0.5, 31.0);
+#endif
+}
+
+// The drip of the column of the pixel, if one runs down it, and its trail
+fn drip(pixel: vec2<f32>, height: f32) -> Droplet {
+    let width = settings.size * 2.0;
+    let column = floor(pixel.x / width);
+    if hash(vec2(column, 91.0)) > settings.drips * settings.wetness {
+        return Droplet(vec2(0.0), 0.0);
+    }
+
+    // Every drip runs at its own pace and starts at its own time, wrapping to the top
+    let pace = mix(0.6, 1.4, hash(vec2(column, 5.0)));
+    let run = fract(settings.time * settings.drip_speed * pace + hash(vec2(column, 13.0)));
+    let radius = settings.size * 0.35;
+    let head = vec2((column + 0.5) * width, run * (height + radius * 8.0) - radius * 4.0);
+    // The drip wiggles as it runs, and the trail follows it
+    let wiggle = sin(pixel.y / settings.size * 1.7 + column) * settings.size * 0.15;
+    let offset = pixel - head - vec2(wiggle, 0.0);
+
+    let body = dome(offset, radius);
+    if body.coverage > 0.0 {
+        return body;
+    }
+    // The trail narrows and dries up behind the drip
+    let behind = -offset.y;
+    if behind <= 0.0 {
+        return Droplet(vec2(0.0), 0.0);
+    }
+    let trail_radius = radius * 0.3 * exp(-behind / (settings.size * 6.0));
+    let trail = dome(vec2(offset.x, 0.0), trail_radius);
+    return Droplet(trail.normal * 0.5, trail.coverage);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    if settings.wetness <= 0.0 {
+        return color;
+    }
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    let pixel = in.position.xy;
+
+    var droplet = drip(pixel, size.y);
+    if droplet.coverage <= 0.0 {
+        droplet = static_droplet(in.uv, pixel);
+    }
+    if droplet.coverage <= 0.0 {
+        return color;
+    }
+
+    // The droplets flip what is behind them, like small lenses
+    let uv = in.uv - droplet.normal * settings.refraction / size;
+    let refracted = textureSampleLevel(screen_texture, clamp_sampler, uv, 0.0).rgb;
+    return vec4(mix(color.rgb, refracted, droplet.coverage), color.a);
+}
@@ -0,0 +1,101 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/scanlines.wgsl";
+
+/// Horizontal scanlines, run on the cameras with [`ScanlinesSettings`].
+///
+/// A lighter alternative to the [`CrtPlugin`](super::CrtPlugin) when only the lines are wanted.
+/// Runs after the upscaling by default, so the lines stay crisp at the resolution of the output.
+pub struct ScanlinesPlugin {
+    /// Where the scanlines run.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for ScanlinesPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterUpscaling,
+        }
+    }
+}
+
+/// The settings of the [`ScanlinesPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct ScanlinesSettings {
+    /// The distance between two lines, in pixels.
+    pub spacing: f32,
+    /// The fraction of the spacing covered by the dark part of a line, from 0 to 1.
+    pub thickness: f32,
+    /// How dark the lines are, from 0 for invisible to 1 for black.
+    pub darkness: f32,
+    /// How fast the lines roll down the screen, in pixels per second. Negative values roll them
+    /// up, 0 keeps them still.
+    pub roll_speed: f32,
+    /// How far the lines have rolled, in pixels. Advanced every frame by the `roll_speed`.
+    pub roll_offset: f32,
+}
+
+impl Default for ScanlinesSettings {
+    fn default() -> Self {
+        Self {
+            spacing: 3.0,
+            thickness: 0.5,
+            darkness: 0.3,
+            roll_speed: 0.0,
+            roll_offset: 0.0,
+        }
+    }
+}
+
+/// The render graph label of the [`ScanlinesPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Scanlines;
+
+impl Plugin for ScanlinesPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "scanlines.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<ScanlinesSettings>(
+            app,
+            &[
+                ("spacing", 1.0..=16.0),
+                ("thickness", 0.0..=1.0),
+                ("darkness", 0.0..=1.0),
+                ("roll_speed", -100.0..=100.0),
+            ],
+        );
+        app.register_type::<ScanlinesSettings>()
+            .add_systems(Update, roll_scanlines);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<ScanlinesSettings, _>::new(
+                SHADER_PATH,
+                Scanlines,
+                Some("scanlines"),
+                "scanlines_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view(),
+        );
+    }
+}
+
+fn roll_scanlines(mut settings: Query<&mut ScanlinesSettings>, time: Res<Time>) {
+    for mut settings in &mut settings {
+        if settings.roll_speed == 0.0 {
+            continue;
+        }
+        // Wrapped to one spacing, so the offset keeps its precision
+        let spacing = settings.spacing.max(1.0);
+        settings.roll_offset =
+            (settings.roll_offset + settings.roll_speed * time.delta_secs()).rem_euclid(spacing);
+    }
+}
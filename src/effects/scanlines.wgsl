@@ -0,0 +1,26 @@
+// Darkens a band at the bottom of every line of `spacing` pixels.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct ScanlinesSettings {
+    spacing: f32,
+    thickness: f32,
+    darkness: f32,
+    roll_speed: f32,
+    roll_offset: f32,
+}
+@group(0) @binding(2) var<uniform> settings: ScanlinesSettings;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let spacing = max(settings.spacing, 1.0);
+    let phase = fract((in.position.y - settings.roll_offset) / spacing);
+
+    // Antialiased over one pixel, so lines that don't fall on whole pixels don't alias
+    let edge = 1.0 - settings.thickness;
+    let half_pixel = 0.5 / spacing;
+    let line = smoothstep(edge - half_pixel, edge + half_pixel, phase);
+    return vec4(color.rgb * (1.0 - line * settings.darkness), color.a);
+}
@@ -0,0 +1,123 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/shockwave.wgsl";
+
+/// Rings of distortion spreading from explosions, run on the cameras with [`ShockwaveSettings`].
+///
+/// Every entity with a [`Shockwave`] is a ring growing from its position in the world, bending
+/// the view outward as it passes and fading as it ages. The plugin ages the shockwaves every
+/// frame and despawns them once they end, so an explosion only has to spawn one. All the
+/// shockwaves are gathered in a single storage buffer, which WebGL doesn't support. Runs on the
+/// HDR colors by default.
+#[derive(Default)]
+pub struct ShockwavePlugin {
+    /// Where the shockwaves run.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`ShockwavePlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct ShockwaveSettings {
+    /// How far the shockwaves bend the view, multiplying their amplitude.
+    pub intensity: f32,
+    /// How far apart the red, green and blue parts of the view are bent, as a fraction of the
+    /// bending.
+    pub chromatic_aberration: f32,
+}
+
+impl Default for ShockwaveSettings {
+    fn default() -> Self {
+        Self {
+            intensity: 1.0,
+            chromatic_aberration: 0.2,
+        }
+    }
+}
+
+/// A shockwave of the [`ShockwavePlugin`], spreading from a position in the world.
+///
+/// Spawn one on its own entity when something explodes. Its age is advanced by the plugin every
+/// frame, and the entity is despawned once it reaches the duration.
+#[derive(Component, Reflect, Clone, Copy, Debug, ShaderType)]
+#[reflect(Component, Default)]
+pub struct Shockwave {
+    /// The center of the shockwave, in world space.
+    pub position: Vec3,
+    /// The radius the ring reaches at the end of the shockwave, in world units. It grows fast at
+    /// first and slows down.
+    pub radius: f32,
+    /// The thickness of the ring, in world units.
+    pub thickness: f32,
+    /// How far the ring bends the view, in world units at the position of the shockwave.
+    pub amplitude: f32,
+    /// The time since the shockwave started, in seconds. Advanced every frame by the plugin.
+    pub age: f32,
+    /// How long the shockwave lasts, in seconds.
+    pub duration: f32,
+}
+
+impl Default for Shockwave {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            radius: 10.0,
+            thickness: 1.5,
+            amplitude: 0.3,
+            age: 0.0,
+            duration: 0.8,
+        }
+    }
+}
+
+/// The render graph label of the [`ShockwavePlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ShockwaveEffect;
+
+impl Plugin for ShockwavePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "shockwave.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<ShockwaveSettings>(
+            app,
+            &[
+                ("intensity", 0.0..=4.0),
+                ("chromatic_aberration", 0.0..=1.0),
+            ],
+        );
+        app.register_type::<ShockwaveSettings>()
+            .register_type::<Shockwave>()
+            .add_systems(Update, age_shockwaves);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<ShockwaveSettings, _>::new(
+                SHADER_PATH,
+                ShockwaveEffect,
+                Some("shockwave"),
+                "shockwave_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_instances::<Shockwave>(),
+        );
+    }
+}
+
+fn age_shockwaves(
+    mut commands: Commands,
+    mut shockwaves: Query<(Entity, &mut Shockwave)>,
+    time: Res<Time>,
+) {
+    for (entity, mut shockwave) in &mut shockwaves {
+        shockwave.age += time.delta_secs();
+        if shockwave.age >= shockwave.duration {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,85 @@
+// Bends the view around the ring of every shockwave in view. The rings are projected from the
+// world, so they shrink with the distance, and every color channel is bent a little differently.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_post_process::utils::ndc_to_uv
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct ShockwaveSettings {
+    intensity: f32,
+    chromatic_aberration: f32,
+}
+@group(0) @binding(2) var<uniform> settings: ShockwaveSettings;
+@group(0) @binding(3) var<uniform> view: View;
+struct Shockwave {
+    position: vec3<f32>,
+    radius: f32,
+    thickness: f32,
+    amplitude: f32,
+    age: f32,
+    duration: f32,
+}
+@group(0) @binding(10) var<storage, read> shockwaves: array<Shockwave>;
+@group(0) @binding(11) var<uniform> shockwave_count: u32;
+
+// From UV to a space where distances are fractions of the height of the view
+fn to_screen(uv: vec2<f32>) -> vec2<f32> {
+    let aspect_ratio = view.viewport.z / view.viewport.w;
+    return vec2(uv.x * aspect_ratio, uv.y);
+}
+
+fn to_uv(screen: vec2<f32>) -> vec2<f32> {
+    let aspect_ratio = view.viewport.z / view.viewport.w;
+    return vec2(screen.x / aspect_ratio, screen.y);
+}
+
+fn project(position: vec3<f32>) -> vec3<f32> {
+    let clip = view.clip_from_world * vec4(position, 1.0);
+    return vec3(to_screen(ndc_to_uv(clip.xy / clip.w)), clip.w);
+}
+
+// The offset of a pixel toward the outside of a ring, in the space of `to_screen`
+fn displacement(position: vec2<f32>, shockwave: Shockwave) -> vec2<f32> {
+    let duration = max(shockwave.duration, 1.0e-4);
+    let progress = clamp(shockwave.age / duration, 0.0, 1.0);
+    // Grows fast and slows down, fading out as it ends
+    let radius = shockwave.radius * (1.0 - (1.0 - progress) * (1.0 - progress));
+    let fade = 1.0 - progress * progress;
+
+    let center = project(shockwave.position);
+    if center.z <= 0.0 {
+        return vec2(0.0);
+    }
+    // The size of a world unit on the screen at the shockwave, from a point beside its center
+    let right = view.world_from_view[0].xyz;
+    let scale = distance(project(shockwave.position + right).xy, center.xy);
+
+    let offset = position - center.xy;
+    let ring = (length(offset) - radius * scale) / max(shockwave.thickness * scale, 1.0e-5);
+    // A bump pushing out on the front of the ring and pulling in behind it
+    let profile = -ring * exp(-4.0 * ring * ring) * 2.3;
+    let direction = offset / max(length(offset), 1.0e-5);
+    return direction * profile * shockwave.amplitude * scale * fade;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let position = to_screen(in.uv);
+
+    var offset = vec2(0.0);
+    for (var i = 0u; i < shockwave_count; i++) {
+        offset += displacement(position, shockwaves[i]);
+    }
+    if all(offset == vec2(0.0)) {
+        return color;
+    }
+    offset = to_uv(offset) * settings.intensity;
+
+    let fringe = 1.0 + vec3(-1.0, 0.0, 1.0) * settings.chromatic_aberration;
+    let r = textureSampleLevel(screen_texture, texture_sampler, in.uv - offset * fringe.r, 0.0).r;
+    let g = textureSampleLevel(screen_texture, texture_sampler, in.uv - offset * fringe.g, 0.0).g;
+    let b = textureSampleLevel(screen_texture, texture_sampler, in.uv - offset * fringe.b, 0.0).b;
+    return vec4(r, g, b, color.a);
+}
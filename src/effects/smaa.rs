@@ -0,0 +1,516 @@
+use crate::{
+    effects, filtering, PostProcessBindGroup, PostProcessBindGroupLayout, PostProcessPlacement,
+    PostProcessPlugin, PostProcessTexturePool,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset, RenderAssetUsages},
+    core_pipeline::{core_3d::graph::Core3d, FullscreenShader},
+    ecs::query::QueryItem,
+    image::ImageLoaderSettings,
+    prelude::*,
+    render::{
+        extract_component::{ComponentUniforms, DynamicUniformIndex, ExtractComponent},
+        render_asset::RenderAssets,
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::{CachedTexture, GpuImage},
+        view::ViewTarget,
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/smaa.wgsl";
+
+/// The format of the edges, on the left in red and on the top in green.
+const EDGES_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rg8Unorm;
+
+/// The format of the blending weights with the four neighbors of every pixel.
+const BLEND_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+
+/// Subpixel morphological antialiasing, run on the cameras with [`SmaaSettings`].
+///
+/// SMAA finds the edges of the view from its luma, measures the shape of the lines they form, then
+/// blends every pixel along them. It gives cleaner edges than FXAA without the ghosting of TAA.
+/// The [`SmaaWeights`] node renders the edge detection and the blending weight passes, using two
+/// precomputed textures embedded in the crate, then the [`SmaaBlending`] effect blends the view.
+///
+/// The precomputed textures are KTX2 files, which needs the `ktx2` and `zstd_rust` features of
+/// Bevy, both on by default. Runs after the tonemapping by default, as the edges are found on
+/// display colors. Placing it after the upscaling isn't supported, the edge detection always
+/// reads the main texture of the view.
+pub struct SmaaPlugin {
+    /// Where the antialiasing runs.
+    pub placement: PostProcessPlacement,
+}
+
+impl Default for SmaaPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+        }
+    }
+}
+
+/// The settings of the [`SmaaPlugin`] on a camera. Defaults to [`SmaaSettings::HIGH`].
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct SmaaSettings {
+    /// The smallest difference of luma between two pixels that makes an edge, from 0 to 0.5.
+    /// Lower values catch more edges, at the cost of blurring details.
+    pub threshold: f32,
+    /// How many steps the search for the ends of horizontal and vertical lines takes on each side
+    /// of a pixel, up to 112. Each step covers two pixels.
+    pub max_search_steps: u32,
+    /// How many steps the search for the ends of diagonal lines takes on each side of a pixel, up
+    /// to 20. Each step covers one pixel, 0 turns the diagonal detection off.
+    pub max_search_steps_diag: u32,
+    /// How much the sharp corners are rounded off, from 0 to 1. 1 turns the corner detection off.
+    pub corner_rounding: f32,
+}
+
+impl SmaaSettings {
+    /// About 60% of the quality of [`SmaaSettings::ULTRA`], without the diagonal and corner
+    /// detections.
+    pub const LOW: Self = Self {
+        threshold: 0.15,
+        max_search_steps: 4,
+        max_search_steps_diag: 0,
+        corner_rounding: 1.0,
+    };
+
+    /// About 80% of the quality, without the diagonal and corner detections.
+    pub const MEDIUM: Self = Self {
+        threshold: 0.1,
+        max_search_steps: 8,
+        max_search_steps_diag: 0,
+        corner_rounding: 1.0,
+    };
+
+    /// About 95% of the quality.
+    pub const HIGH: Self = Self {
+        threshold: 0.1,
+        max_search_steps: 16,
+        max_search_steps_diag: 8,
+        corner_rounding: 0.25,
+    };
+
+    /// The highest quality.
+    pub const ULTRA: Self = Self {
+        threshold: 0.05,
+        max_search_steps: 32,
+        max_search_steps_diag: 16,
+        corner_rounding: 0.25,
+    };
+}
+
+impl Default for SmaaSettings {
+    fn default() -> Self {
+        Self::HIGH
+    }
+}
+
+/// The render graph label of the effect pass of the [`SmaaPlugin`], blending every pixel with
+/// its neighbors.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct SmaaBlending;
+
+/// The render graph label of the node rendering the edges and the blending weights of the
+/// [`SmaaPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct SmaaWeights;
+
+// The precomputed textures of the blending weight pass
+#[derive(Resource)]
+struct SmaaLuts {
+    area: Handle<Image>,
+    search: Handle<Image>,
+}
+
+impl Plugin for SmaaPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "smaa.wgsl");
+        embedded_asset!(app, "smaa_area_lut.ktx2");
+        embedded_asset!(app, "smaa_search_lut.ktx2");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<SmaaSettings>(
+            app,
+            &[("threshold", 0.0..=0.5), ("corner_rounding", 0.0..=1.0)],
+        );
+        app.register_type::<SmaaSettings>();
+
+        let lut_settings = |settings: &mut ImageLoaderSettings| {
+            settings.is_srgb = false;
+            settings.asset_usage = RenderAssetUsages::RENDER_WORLD;
+        };
+        let luts = SmaaLuts {
+            area: load_embedded_asset!(app, "smaa_area_lut.ktx2", lut_settings),
+            search: load_embedded_asset!(app, "smaa_search_lut.ktx2", lut_settings),
+        };
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<SmaaSettings, _>::new(
+                SHADER_PATH,
+                SmaaBlending,
+                Some("smaa"),
+                "smaa_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view()
+            .with_bind_group(blend_layout, prepare_blend_bind_groups),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (before, _) = self.placement.edges();
+        render_app
+            .insert_resource(luts)
+            .init_resource::<SpecializedRenderPipelines<SmaaWeightsPipeline>>()
+            .add_systems(Render, prepare_weights.in_set(RenderSystems::PrepareResources))
+            .add_render_graph_node::<ViewNodeRunner<SmaaWeightsNode>>(Core3d, SmaaWeights)
+            .add_render_graph_edges(Core3d, (before, SmaaWeights, SmaaBlending));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<SmaaWeightsPipeline>();
+    }
+}
+
+// The pipelines of the edge detection and of the blending weight passes
+#[derive(Resource)]
+struct SmaaWeightsPipeline {
+    edge_detection_layout: BindGroupLayout,
+    blending_weights_layout: BindGroupLayout,
+    // Reads the view in the edge detection pass, which some adapters can't filter
+    screen_sampler: Sampler,
+    // Filters the edges and the precomputed textures, the searches rely on bilinear fetches
+    sampler: Sampler,
+    filterable: bool,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+}
+
+impl FromWorld for SmaaWeightsPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let filterable = filtering::screen_filterable(world);
+        let [screen_texture, screen_sampler] = filtering::screen_entries(filterable);
+        let edge_detection_layout = render_device.create_bind_group_layout(
+            "smaa_edge_detection_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    screen_texture,
+                    screen_sampler,
+                    uniform_buffer::<SmaaSettings>(true),
+                ),
+            ),
+        );
+        let blending_weights_layout = render_device.create_bind_group_layout(
+            "smaa_blending_weights_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<SmaaSettings>(true),
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                ),
+            ),
+        );
+        let descriptor = SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        };
+
+        Self {
+            edge_detection_layout,
+            blending_weights_layout,
+            screen_sampler: filtering::screen_sampler(
+                render_device,
+                filterable,
+                descriptor.clone(),
+            ),
+            sampler: render_device.create_sampler(&descriptor),
+            filterable,
+            shader: load_embedded_asset!(world, "smaa.wgsl"),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum SmaaPass {
+    EdgeDetection,
+    BlendingWeights,
+}
+
+impl SpecializedRenderPipeline for SmaaWeightsPipeline {
+    type Key = SmaaPass;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if !self.filterable {
+            shader_defs.push(filtering::SCREEN_TEXTURE_NON_FILTERABLE.into());
+        }
+        let (layout, entry_point, format) = match key {
+            SmaaPass::EdgeDetection => {
+                shader_defs.push("EDGE_DETECTION".into());
+                (
+                    &self.edge_detection_layout,
+                    "edge_detection",
+                    EDGES_TEXTURE_FORMAT,
+                )
+            }
+            SmaaPass::BlendingWeights => {
+                shader_defs.push("BLENDING_WEIGHTS".into());
+                (
+                    &self.blending_weights_layout,
+                    "blending_weights",
+                    BLEND_TEXTURE_FORMAT,
+                )
+            }
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("smaa_weights_pipeline".into()),
+            layout: vec![layout.clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some(entry_point.into()),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+// The edges and the blending weights of a view
+#[derive(Component)]
+struct ViewSmaaWeights {
+    edges: CachedTexture,
+    blend: CachedTexture,
+    edge_detection_pipeline_id: CachedRenderPipelineId,
+    blending_weights_pipeline_id: CachedRenderPipelineId,
+}
+
+fn prepare_weights(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewTarget), With<SmaaSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    weights_pipeline: Res<SmaaWeightsPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<SmaaWeightsPipeline>>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+) {
+    let edge_detection_pipeline_id =
+        pipelines.specialize(&pipeline_cache, &weights_pipeline, SmaaPass::EdgeDetection);
+    let blending_weights_pipeline_id =
+        pipelines.specialize(&pipeline_cache, &weights_pipeline, SmaaPass::BlendingWeights);
+
+    for (entity, view_target) in &views {
+        let mut texture = |name: &'static str, label: &'static str, format: TextureFormat| {
+            texture_pool.get(
+                &render_device,
+                entity,
+                SmaaWeights,
+                name,
+                &TextureDescriptor {
+                    label: Some(label),
+                    size: view_target.main_texture().size(),
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format,
+                    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                },
+            )
+        };
+        commands.entity(entity).insert(ViewSmaaWeights {
+            edges: texture("edges", "smaa_edges_texture", EDGES_TEXTURE_FORMAT),
+            blend: texture("blend", "smaa_blend_texture", BLEND_TEXTURE_FORMAT),
+            edge_detection_pipeline_id,
+            blending_weights_pipeline_id,
+        });
+    }
+}
+
+// The layout of the group 1 of the effect, the blending weights. They are read at the pixels of
+// the view, so they need no sampler
+fn blend_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "smaa_blend_bind_group_layout",
+        &BindGroupLayoutEntries::single(
+            ShaderStages::FRAGMENT,
+            texture_2d(TextureSampleType::Float { filterable: false }),
+        ),
+    )
+}
+
+// The effect skips the views without the bind group, so it doesn't read the weights until the
+// pipelines are compiled and the precomputed textures are loaded
+fn prepare_blend_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewSmaaWeights), With<SmaaSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    luts: Res<SmaaLuts>,
+    layout: Res<PostProcessBindGroupLayout<SmaaSettings>>,
+) {
+    let luts_loaded =
+        gpu_images.get(&luts.area).is_some() && gpu_images.get(&luts.search).is_some();
+    for (entity, weights) in &views {
+        if !luts_loaded
+            || pipeline_cache
+                .get_render_pipeline(weights.edge_detection_pipeline_id)
+                .is_none()
+            || pipeline_cache
+                .get_render_pipeline(weights.blending_weights_pipeline_id)
+                .is_none()
+        {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<SmaaSettings>>();
+            continue;
+        }
+
+        let bind_group = render_device.create_bind_group(
+            "smaa_blend_bind_group",
+            &layout.layout,
+            &BindGroupEntries::single(&weights.blend.default_view),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<SmaaSettings>::new(bind_group));
+    }
+}
+
+#[derive(Default)]
+struct SmaaWeightsNode;
+
+impl ViewNode for SmaaWeightsNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        // Only runs on the cameras with the antialiasing
+        &'static SmaaSettings,
+        &'static ViewSmaaWeights,
+        &'static DynamicUniformIndex<SmaaSettings>,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, _settings, weights, settings_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // The effect doesn't read the weights on the views it skips
+        let view = graph.view_entity();
+        if effects::skip_intermediate::<SmaaSettings, SmaaBlending>(world, view) {
+            return Ok(());
+        }
+
+        let weights_pipeline = world.resource::<SmaaWeightsPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let gpu_images = world.resource::<RenderAssets<GpuImage>>();
+        let luts = world.resource::<SmaaLuts>();
+        let (
+            Some(edge_detection_pipeline),
+            Some(blending_weights_pipeline),
+            Some(area_lut),
+            Some(search_lut),
+            Some(settings_binding),
+        ) = (
+            pipeline_cache.get_render_pipeline(weights.edge_detection_pipeline_id),
+            pipeline_cache.get_render_pipeline(weights.blending_weights_pipeline_id),
+            gpu_images.get(&luts.area),
+            gpu_images.get(&luts.search),
+            world
+                .resource::<ComponentUniforms<SmaaSettings>>()
+                .uniforms()
+                .binding(),
+        ) else {
+            return Ok(());
+        };
+
+        let edge_detection_bind_group = render_context.render_device().create_bind_group(
+            "smaa_edge_detection_bind_group",
+            &weights_pipeline.edge_detection_layout,
+            &BindGroupEntries::sequential((
+                view_target.main_texture_view(),
+                &weights_pipeline.screen_sampler,
+                settings_binding.clone(),
+            )),
+        );
+        let blending_weights_bind_group = render_context.render_device().create_bind_group(
+            "smaa_blending_weights_bind_group",
+            &weights_pipeline.blending_weights_layout,
+            &BindGroupEntries::sequential((
+                &weights.edges.default_view,
+                &weights_pipeline.sampler,
+                settings_binding,
+                &area_lut.texture_view,
+                &search_lut.texture_view,
+            )),
+        );
+
+        // Both passes clear their target first, the pixels without edges are discarded
+        let passes = [
+            (
+                edge_detection_pipeline,
+                &edge_detection_bind_group,
+                &weights.edges.default_view,
+            ),
+            (
+                blending_weights_pipeline,
+                &blending_weights_bind_group,
+                &weights.blend.default_view,
+            ),
+        ];
+        for (pipeline, bind_group, destination) in passes {
+            let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("smaa_weights_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: destination,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_bind_group(0, bind_group, &[settings_index.index()]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        Ok(())
+    }
+}
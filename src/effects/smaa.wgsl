@@ -0,0 +1,428 @@
+// SMAA 1x, the three passes of "SMAA: Enhanced Subpixel Morphological Antialiasing" by Jimenez
+// et al. EDGE_DETECTION selects the luma edge detection, BLENDING_WEIGHTS the blending weight
+// calculation, and neither the neighborhood blending of the effect pass.
+//
+// Ported from the reference implementation, https://github.com/iryoku/smaa, under the following
+// license:
+//
+// Copyright (C) 2013 Jorge Jimenez (jorge@iryoku.com)
+// Copyright (C) 2013 Jose I. Echevarria (joseignacioechevarria@gmail.com)
+// Copyright (C) 2013 Belen Masia (bmasia@unizar.es)
+// Copyright (C) 2013 Fernando Navarro (fernandn@microsoft.com)
+// Copyright (C) 2013 Diego Gutierrez (diegog@unizar.es)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this software
+// and associated documentation files (the "Software"), to deal in the Software without
+// restriction, including without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to permit persons to whom the
+// Software is furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all copies or
+// substantial portions of the Software. As clarification, there is no requirement that the
+// copyright notice and permission be included in binary distributions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING
+// BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+// DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::utils::luminance
+
+struct SmaaSettings {
+    threshold: f32,
+    max_search_steps: u32,
+    max_search_steps_diag: u32,
+    corner_rounding: f32,
+}
+
+#ifdef EDGE_DETECTION
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> settings: SmaaSettings;
+#else ifdef BLENDING_WEIGHTS
+@group(0) @binding(0) var edges_texture: texture_2d<f32>;
+@group(0) @binding(1) var edges_sampler: sampler;
+@group(0) @binding(2) var<uniform> settings: SmaaSettings;
+@group(0) @binding(3) var area_texture: texture_2d<f32>;
+@group(0) @binding(4) var search_texture: texture_2d<f32>;
+#else
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> settings: SmaaSettings;
+
+@group(1) @binding(0) var blend_texture: texture_2d<f32>;
+#endif
+
+// If a neighboring edge has this many times the contrast of the current one, the current edge is
+// dropped
+const LOCAL_CONTRAST_ADAPTATION_FACTOR: f32 = 2.0;
+
+const AREATEX_MAX_DISTANCE: f32 = 16.0;
+const AREATEX_MAX_DISTANCE_DIAG: f32 = 20.0;
+const AREATEX_PIXEL_SIZE: vec2<f32> = 1.0 / vec2<f32>(160.0, 560.0);
+const AREATEX_SUBTEX_SIZE: f32 = 1.0 / 7.0;
+const SEARCHTEX_SIZE: vec2<f32> = vec2(66.0, 33.0);
+const SEARCHTEX_PACKED_SIZE: vec2<f32> = vec2(64.0, 16.0);
+
+#ifdef EDGE_DETECTION
+fn luma_at(pixel: vec2<i32>, offset: vec2<i32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(screen_texture));
+    return luminance(textureLoad(screen_texture, clamp(pixel + offset, vec2(0), size - 1), 0).rgb);
+}
+
+// Marks the pixels with a luma edge on their left in red and on their top in green
+@fragment
+fn edge_detection(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(in.position.xy);
+    let luma = luma_at(pixel, vec2(0, 0));
+    let left = luma_at(pixel, vec2(-1, 0));
+    let top = luma_at(pixel, vec2(0, -1));
+
+    var delta = vec4(abs(luma - vec2(left, top)), 0.0, 0.0);
+    var edges = step(vec2(settings.threshold), delta.xy);
+    if dot(edges, vec2(1.0)) == 0.0 {
+        discard;
+    }
+
+    let right = luma_at(pixel, vec2(1, 0));
+    let bottom = luma_at(pixel, vec2(0, 1));
+    delta = vec4(delta.xy, abs(luma - vec2(right, bottom)));
+    var max_delta = max(delta.xy, delta.zw);
+
+    let left_left = luma_at(pixel, vec2(-2, 0));
+    let top_top = luma_at(pixel, vec2(0, -2));
+    delta = vec4(delta.xy, abs(vec2(left, top) - vec2(left_left, top_top)));
+    max_delta = max(max_delta.xy, delta.zw);
+
+    // Local contrast adaptation, dropping the edges next to much stronger ones
+    let final_delta = max(max_delta.x, max_delta.y);
+    edges *= step(vec2(final_delta), LOCAL_CONTRAST_ADAPTATION_FACTOR * delta.xy);
+    return vec4(edges, 0.0, 1.0);
+}
+#endif
+
+#ifdef BLENDING_WEIGHTS
+// 1 / width, 1 / height, width and height of the edges texture
+fn rt_metrics() -> vec4<f32> {
+    let size = vec2<f32>(textureDimensions(edges_texture));
+    return vec4(1.0 / size, size);
+}
+
+fn sample_edges(uv: vec2<f32>) -> vec2<f32> {
+    return textureSampleLevel(edges_texture, edges_sampler, uv, 0.0).rg;
+}
+
+// Diagonal search. Bilinear fetches tell apart which of the edges of a pair are active
+fn decode_diag_bilinear_access_2(in_e: vec2<f32>) -> vec2<f32> {
+    var e = in_e;
+    e.r = e.r * abs(5.0 * e.r - 5.0 * 0.75);
+    return round(e);
+}
+
+fn decode_diag_bilinear_access_4(e: vec4<f32>) -> vec4<f32> {
+    let e_rb = e.rb * abs(5.0 * e.rb - 5.0 * 0.75);
+    return round(vec4(e_rb.x, e.g, e_rb.y, e.a));
+}
+
+fn search_diag_1(uv: vec2<f32>, dir: vec2<f32>, e: ptr<function, vec2<f32>>) -> vec2<f32> {
+    var coord = vec4(uv, -1.0, 1.0);
+    let t = vec3(rt_metrics().xy, 1.0);
+    while coord.z < f32(settings.max_search_steps_diag) - 1.0 && coord.w > 0.9 {
+        coord = vec4(t * vec3(dir, 1.0) + coord.xyz, coord.w);
+        *e = sample_edges(coord.xy);
+        coord.w = dot(*e, vec2(0.5));
+    }
+    return coord.zw;
+}
+
+fn search_diag_2(uv: vec2<f32>, dir: vec2<f32>, e: ptr<function, vec2<f32>>) -> vec2<f32> {
+    var coord = vec4(uv, -1.0, 1.0);
+    // Offset by a quarter of a pixel, so a single bilinear fetch reads both edges
+    coord.x += 0.25 * rt_metrics().x;
+    let t = vec3(rt_metrics().xy, 1.0);
+    while coord.z < f32(settings.max_search_steps_diag) - 1.0 && coord.w > 0.9 {
+        coord = vec4(t * vec3(dir, 1.0) + coord.xyz, coord.w);
+        *e = decode_diag_bilinear_access_2(sample_edges(coord.xy));
+        coord.w = dot(*e, vec2(0.5));
+    }
+    return coord.zw;
+}
+
+// The area under the diagonal line, from the precomputed area texture
+fn area_diag(distance: vec2<f32>, e: vec2<f32>, offset: f32) -> vec2<f32> {
+    var uv = vec2(AREATEX_MAX_DISTANCE_DIAG) * e + distance;
+    uv = AREATEX_PIXEL_SIZE * uv + 0.5 * AREATEX_PIXEL_SIZE;
+    // The diagonal areas are on the right half of the texture
+    uv.x += 0.5;
+    uv.y += AREATEX_SUBTEX_SIZE * offset;
+    return textureSampleLevel(area_texture, edges_sampler, uv, 0.0).rg;
+}
+
+fn calculate_diag_weights(uv: vec2<f32>, e: vec2<f32>) -> vec2<f32> {
+    let rt = rt_metrics();
+    var weights = vec2(0.0);
+    var d = vec4(0.0);
+    var end = vec2(0.0);
+
+    // Search for the line ends going down left and up right
+    if e.r > 0.0 {
+        let d_xz = search_diag_1(uv, vec2(-1.0, 1.0), &end);
+        d = vec4(d_xz.x, d.y, d_xz.y, d.w);
+        d.x += f32(end.y > 0.9);
+    }
+    let d_yw = search_diag_1(uv, vec2(1.0, -1.0), &end);
+    d = vec4(d.x, d_yw.x, d.z, d_yw.y);
+
+    if d.x + d.y > 2.0 {
+        // Fetch the crossing edges at both ends of the line
+        let coords = vec4(-d.x + 0.25, d.x, d.y, -d.y - 0.25) * rt.xyxy + uv.xyxy;
+        var c = vec4(
+            textureSampleLevel(edges_texture, edges_sampler, coords.xy, 0.0, vec2(-1, 0)).rg,
+            textureSampleLevel(edges_texture, edges_sampler, coords.zw, 0.0, vec2(1, 0)).rg,
+        );
+        c = decode_diag_bilinear_access_4(c).yxwz;
+        var cc = vec2(2.0) * c.xz + c.yw;
+        // Lines too long for the search don't have their end taken into account
+        cc = select(cc, vec2(0.0), step(vec2(0.9), d.zw) > vec2(0.0));
+        weights += area_diag(d.xy, cc, 0.0);
+    }
+
+    // Search for the line ends going up left and down right
+    let d_xz = search_diag_2(uv, vec2(-1.0, -1.0), &end);
+    if textureSampleLevel(edges_texture, edges_sampler, uv, 0.0, vec2(1, 0)).r > 0.0 {
+        let d_yw = search_diag_2(uv, vec2(1.0, 1.0), &end);
+        d = vec4(d_xz.x, d_yw.x, d_xz.y, d_yw.y);
+        d.y += f32(end.y > 0.9);
+    } else {
+        d = vec4(d_xz.x, 0.0, d_xz.y, 0.0);
+    }
+
+    if d.x + d.y > 2.0 {
+        let coords = vec4(-d.x, -d.x, d.y, d.y) * rt.xyxy + uv.xyxy;
+        let c = vec4(
+            textureSampleLevel(edges_texture, edges_sampler, coords.xy, 0.0, vec2(-1, 0)).g,
+            textureSampleLevel(edges_texture, edges_sampler, coords.xy, 0.0, vec2(0, -1)).r,
+            textureSampleLevel(edges_texture, edges_sampler, coords.zw, 0.0, vec2(1, 0)).gr,
+        );
+        var cc = vec2(2.0) * c.xz + c.yw;
+        cc = select(cc, vec2(0.0), step(vec2(0.9), d.zw) > vec2(0.0));
+        weights += area_diag(d.xy, cc, 0.0).gr;
+    }
+
+    return weights;
+}
+
+// How far the last fetch of a search overshot the end of the line, from the search texture
+fn search_length(e: vec2<f32>, offset: f32) -> f32 {
+    var scale = SEARCHTEX_SIZE * vec2(0.5, -1.0);
+    var bias = SEARCHTEX_SIZE * vec2(offset, 1.0);
+    scale += vec2(-1.0, 1.0);
+    bias += vec2(0.5, -0.5);
+    scale *= 1.0 / SEARCHTEX_PACKED_SIZE;
+    bias *= 1.0 / SEARCHTEX_PACKED_SIZE;
+    return textureSampleLevel(search_texture, edges_sampler, scale * e + bias, 0.0).r;
+}
+
+// Horizontal and vertical searches for the ends of a line, two pixels at a time. The coordinates
+// are offset so each bilinear fetch reads four edges
+fn search_x_left(in_uv: vec2<f32>, end: f32) -> f32 {
+    var uv = in_uv;
+    var e = vec2(0.0, 1.0);
+    // Stops at a gap in the line or at a crossing edge
+    while uv.x > end && e.g > 0.8281 && e.r == 0.0 {
+        e = sample_edges(uv);
+        uv -= vec2(2.0, 0.0) * rt_metrics().xy;
+    }
+    let offset = -(255.0 / 127.0) * search_length(e, 0.0) + 3.25;
+    return rt_metrics().x * offset + uv.x;
+}
+
+fn search_x_right(in_uv: vec2<f32>, end: f32) -> f32 {
+    var uv = in_uv;
+    var e = vec2(0.0, 1.0);
+    while uv.x < end && e.g > 0.8281 && e.r == 0.0 {
+        e = sample_edges(uv);
+        uv += vec2(2.0, 0.0) * rt_metrics().xy;
+    }
+    let offset = -(255.0 / 127.0) * search_length(e, 0.5) + 3.25;
+    return -rt_metrics().x * offset + uv.x;
+}
+
+fn search_y_up(in_uv: vec2<f32>, end: f32) -> f32 {
+    var uv = in_uv;
+    var e = vec2(1.0, 0.0);
+    while uv.y > end && e.r > 0.8281 && e.g == 0.0 {
+        e = sample_edges(uv);
+        uv -= vec2(0.0, 2.0) * rt_metrics().xy;
+    }
+    let offset = -(255.0 / 127.0) * search_length(e.gr, 0.0) + 3.25;
+    return rt_metrics().y * offset + uv.y;
+}
+
+fn search_y_down(in_uv: vec2<f32>, end: f32) -> f32 {
+    var uv = in_uv;
+    var e = vec2(1.0, 0.0);
+    while uv.y < end && e.r > 0.8281 && e.g == 0.0 {
+        e = sample_edges(uv);
+        uv += vec2(0.0, 2.0) * rt_metrics().xy;
+    }
+    let offset = -(255.0 / 127.0) * search_length(e.gr, 0.5) + 3.25;
+    return -rt_metrics().y * offset + uv.y;
+}
+
+// The area under the line, from its distances to both ends and the crossing edges there
+fn area(distance: vec2<f32>, e1: f32, e2: f32, offset: f32) -> vec2<f32> {
+    var uv = AREATEX_MAX_DISTANCE * round(4.0 * vec2(e1, e2)) + distance;
+    uv = AREATEX_PIXEL_SIZE * uv + 0.5 * AREATEX_PIXEL_SIZE;
+    uv.y += AREATEX_SUBTEX_SIZE * offset;
+    return textureSampleLevel(area_texture, edges_sampler, uv, 0.0).rg;
+}
+
+// Reduces the blending at sharp corners, so they aren't rounded off completely
+fn detect_horizontal_corner_pattern(weights: vec2<f32>, uv: vec4<f32>, d: vec2<f32>) -> vec2<f32> {
+    let left_right = step(d.xy, d.yx);
+    var rounding = (1.0 - settings.corner_rounding) * left_right;
+    // Less reduction for the pixels in the middle of a line
+    rounding /= left_right.x + left_right.y;
+
+    var factor = vec2(1.0);
+    factor.x -= rounding.x
+        * textureSampleLevel(edges_texture, edges_sampler, uv.xy, 0.0, vec2(0, 1)).r;
+    factor.x -= rounding.y
+        * textureSampleLevel(edges_texture, edges_sampler, uv.zw, 0.0, vec2(1, 1)).r;
+    factor.y -= rounding.x
+        * textureSampleLevel(edges_texture, edges_sampler, uv.xy, 0.0, vec2(0, -2)).r;
+    factor.y -= rounding.y
+        * textureSampleLevel(edges_texture, edges_sampler, uv.zw, 0.0, vec2(1, -2)).r;
+    return weights * saturate(factor);
+}
+
+fn detect_vertical_corner_pattern(weights: vec2<f32>, uv: vec4<f32>, d: vec2<f32>) -> vec2<f32> {
+    let left_right = step(d.xy, d.yx);
+    var rounding = (1.0 - settings.corner_rounding) * left_right;
+    rounding /= left_right.x + left_right.y;
+
+    var factor = vec2(1.0);
+    factor.x -= rounding.x
+        * textureSampleLevel(edges_texture, edges_sampler, uv.xy, 0.0, vec2(1, 0)).g;
+    factor.x -= rounding.y
+        * textureSampleLevel(edges_texture, edges_sampler, uv.zw, 0.0, vec2(1, 1)).g;
+    factor.y -= rounding.x
+        * textureSampleLevel(edges_texture, edges_sampler, uv.xy, 0.0, vec2(-2, 0)).g;
+    factor.y -= rounding.y
+        * textureSampleLevel(edges_texture, edges_sampler, uv.zw, 0.0, vec2(-2, 1)).g;
+    return weights * saturate(factor);
+}
+
+// How much every pixel blends with its neighbors, in red and green for the top and bottom ones
+// and in blue and alpha for the left and right ones
+@fragment
+fn blending_weights(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let rt = rt_metrics();
+    let uv = in.uv;
+    let offset_0 = rt.xyxy * vec4(-0.25, -0.125, 1.25, -0.125) + uv.xyxy;
+    let offset_1 = rt.xyxy * vec4(-0.125, -0.25, -0.125, 1.25) + uv.xyxy;
+    let offset_2 = rt.xxyy * vec4(-2.0, 2.0, -2.0, 2.0) * f32(settings.max_search_steps)
+        + vec4(offset_0.xz, offset_1.yw);
+
+    var weights = vec4(0.0);
+    let e = sample_edges(uv);
+
+    // Edge at the top
+    if e.g > 0.0 {
+        if settings.max_search_steps_diag > 0u {
+            // Diagonal lines take precedence over horizontal and vertical ones
+            weights = vec4(calculate_diag_weights(uv, e), weights.ba);
+            if weights.r + weights.g != 0.0 {
+                return weights;
+            }
+        }
+
+        var d: vec2<f32>;
+        var coords: vec3<f32>;
+        coords.x = search_x_left(offset_0.xy, offset_2.x);
+        // Reads the bottom edge of the line, a quarter of a pixel below the top one
+        coords.y = offset_1.y;
+        d.x = coords.x;
+        let e1 = textureSampleLevel(edges_texture, edges_sampler, coords.xy, 0.0).r;
+
+        coords.z = search_x_right(offset_0.zw, offset_2.y);
+        d.y = coords.z;
+
+        // The distances to both ends, in pixels
+        d = abs(round(rt.zz * d - in.position.xx));
+        let sqrt_d = sqrt(d);
+        let e2 = textureSampleLevel(edges_texture, edges_sampler, coords.zy, 0.0, vec2(1, 0)).r;
+        weights = vec4(area(sqrt_d, e1, e2, 0.0), weights.ba);
+
+        coords.y = uv.y;
+        weights = vec4(detect_horizontal_corner_pattern(weights.rg, coords.xyzy, d), weights.ba);
+    }
+
+    // Edge at the left
+    if e.r > 0.0 {
+        var d: vec2<f32>;
+        var coords: vec3<f32>;
+        coords.y = search_y_up(offset_1.xy, offset_2.z);
+        coords.x = offset_0.x;
+        d.x = coords.y;
+        let e1 = textureSampleLevel(edges_texture, edges_sampler, coords.xy, 0.0).g;
+
+        coords.z = search_y_down(offset_1.zw, offset_2.w);
+        d.y = coords.z;
+
+        d = abs(round(rt.ww * d - in.position.yy));
+        let sqrt_d = sqrt(d);
+        let e2 = textureSampleLevel(edges_texture, edges_sampler, coords.xz, 0.0, vec2(0, 1)).g;
+        weights = vec4(weights.rg, area(sqrt_d, e1, e2, 0.0));
+
+        coords.x = uv.x;
+        weights = vec4(weights.rg, detect_vertical_corner_pattern(weights.ba, coords.xyxz, d));
+    }
+
+    return weights;
+}
+#endif
+
+#ifndef EDGE_DETECTION
+#ifndef BLENDING_WEIGHTS
+fn load_color(pixel: vec2<i32>) -> vec4<f32> {
+    let size = vec2<i32>(textureDimensions(screen_texture));
+    return textureLoad(screen_texture, clamp(pixel, vec2(0), size - 1), 0);
+}
+
+// The color `t` pixels from `pixel` toward `direction`, filtered by hand as not every adapter can
+// filter the view
+fn color_toward(pixel: vec2<i32>, direction: vec2<i32>, t: f32) -> vec4<f32> {
+    return mix(load_color(pixel), load_color(pixel + direction), t);
+}
+
+// Blends every pixel with the neighbors along its strongest edge, by the weights of the previous
+// pass
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(in.position.xy);
+    let size = vec2<i32>(textureDimensions(blend_texture));
+    let right = textureLoad(blend_texture, min(pixel + vec2(1, 0), size - 1), 0).a;
+    let bottom = textureLoad(blend_texture, min(pixel + vec2(0, 1), size - 1), 0).g;
+    let center = textureLoad(blend_texture, pixel, 0);
+    // Right, bottom, top and left
+    let a = vec4(right, bottom, center.zx);
+
+    if dot(a, vec4(1.0)) < 1.0e-5 {
+        return load_color(pixel);
+    }
+
+    // Blends along the strongest of the horizontal and vertical edges
+    let horizontal = max(a.x, a.z) > max(a.y, a.w);
+    var weights = select(a.yw, a.xz, horizontal);
+    weights /= dot(weights, vec2(1.0));
+    let direction = select(vec2(0, 1), vec2(1, 0), horizontal);
+    let offsets = select(vec2(a.y, a.w), vec2(a.x, a.z), horizontal);
+
+    return weights.x * color_toward(pixel, direction, offsets.x)
+        + weights.y * color_toward(pixel, -direction, offsets.y);
+}
+#endif
+#endif
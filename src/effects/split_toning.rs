@@ -0,0 +1,76 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/split_toning.wgsl";
+
+/// Split toning, tinting the shadows toward one color and the highlights toward another, run on
+/// the cameras with [`SplitToningSettings`].
+///
+/// The tints are added to the chroma of the colors in Oklab, so they don't change the lightness,
+/// and fade out toward the midtones, which keep their hue.
+#[derive(Default)]
+pub struct SplitToningPlugin {
+    /// Where the toning runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`SplitToningPlugin`] on a camera. Defaults to the teal shadows and orange
+/// highlights of blockbusters.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct SplitToningSettings {
+    /// The tint of the shadows. Only its hue and saturation are used, the more saturated the
+    /// stronger the tint.
+    pub shadows: LinearRgba,
+    /// The tint of the highlights.
+    pub highlights: LinearRgba,
+    /// Moves the split between the shadows and the highlights, from -1 tinting most of the view
+    /// like the shadows to 1 tinting most of it like the highlights.
+    pub balance: f32,
+    /// How strongly the tints are applied, 0 turning the toning off.
+    pub intensity: f32,
+}
+
+impl Default for SplitToningSettings {
+    fn default() -> Self {
+        Self {
+            shadows: LinearRgba::rgb(0.05, 0.3, 0.4),
+            highlights: LinearRgba::rgb(0.9, 0.5, 0.2),
+            balance: 0.0,
+            intensity: 0.5,
+        }
+    }
+}
+
+/// The render graph label of the [`SplitToningPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct SplitToning;
+
+impl Plugin for SplitToningPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "split_toning.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<SplitToningSettings>(
+            app,
+            &[("balance", -1.0..=1.0), ("intensity", 0.0..=1.0)],
+        );
+        app.register_type::<SplitToningSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<SplitToningSettings, _>::new(
+                SHADER_PATH,
+                SplitToning,
+                Some("split_toning"),
+                "split_toning_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view(),
+        );
+    }
+}
@@ -0,0 +1,32 @@
+// Adds the chroma of one tint to the shadows and of another to the highlights, in Oklab.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::color_space::{linear_to_oklab, oklab_to_linear}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct SplitToningSettings {
+    shadows: vec4<f32>,
+    highlights: vec4<f32>,
+    balance: f32,
+    intensity: f32,
+}
+@group(0) @binding(2) var<uniform> settings: SplitToningSettings;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    var lab = linear_to_oklab(max(color.rgb, vec3(0.0)));
+
+    // Both tints fade out at the pivot, so the midtones keep their hue
+    let pivot = clamp(0.5 - settings.balance * 0.5, 0.01, 0.99);
+    let lightness = clamp(lab.x, 0.0, 1.0);
+    let shadows = 1.0 - smoothstep(0.0, pivot, lightness);
+    let highlights = smoothstep(pivot, 1.0, lightness);
+
+    let shadows_chroma = linear_to_oklab(settings.shadows.rgb).yz;
+    let highlights_chroma = linear_to_oklab(settings.highlights.rgb).yz;
+    let tint = shadows_chroma * shadows + highlights_chroma * highlights;
+    lab = vec3(lab.x, lab.yz + tint * settings.intensity);
+
+    return vec4(max(oklab_to_linear(lab), vec3(0.0)), color.a);
+}
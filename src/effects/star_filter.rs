@@ -0,0 +1,380 @@
+use crate::{
+    effects, filtering, resolution::scaled_size, PostProcessBindGroup, PostProcessBindGroupLayout,
+    PostProcessPlacement, PostProcessPlugin, PostProcessTexturePool,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::{core_3d::graph::Core3d, FullscreenShader},
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{ComponentUniforms, DynamicUniformIndex, ExtractComponent},
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::CachedTexture,
+        view::ViewTarget,
+        Render, RenderApp, RenderSystems,
+    },
+    shader::ShaderDefVal,
+};
+use std::f32::consts::TAU;
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/star_filter.wgsl";
+
+/// The format of the highlights. It has no alpha, the effect keeps the one of the view.
+const HIGHLIGHTS_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rg11b10Ufloat;
+
+/// Star shaped rays across the highlights, like a cross screen filter in front of a lens, run on
+/// the cameras with [`StarFilterSettings`].
+///
+/// The [`StarFilterBrightPass`] node keeps the parts of the view brighter than the threshold at
+/// half resolution, then the [`StarFilter`] effect gathers them along 4, 6 or 8 rays around every
+/// pixel, fading with the distance. Turning the rays makes the highlights sparkle. Runs on the
+/// HDR colors by default, so only the real highlights shine. Placing it after the upscaling isn't
+/// supported.
+#[derive(Default)]
+pub struct StarFilterPlugin {
+    /// Where the rays are added.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`StarFilterPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct StarFilterSettings {
+    /// The color of the rays, multiplying the one of the highlights.
+    pub tint: LinearRgba,
+    /// The brightness above which the view shines.
+    pub threshold: f32,
+    /// The brightness of the rays added to the view.
+    pub intensity: f32,
+    /// How many rays every highlight has, 4, 6 or 8. Other values are rounded down to one of
+    /// them.
+    pub points: u32,
+    /// How far the rays reach, in pixels of the view.
+    pub length: f32,
+    /// How fast the brightness falls along the rays. Higher values make thinner tips.
+    pub falloff: f32,
+    /// The angle of the first ray from the right of the screen, in radians. Advanced every frame
+    /// by the `rotation_speed`.
+    pub angle: f32,
+    /// How fast the rays turn, in radians per second. Negative values turn them clockwise, 0
+    /// keeps them still.
+    pub rotation_speed: f32,
+}
+
+impl Default for StarFilterSettings {
+    fn default() -> Self {
+        Self {
+            tint: LinearRgba::WHITE,
+            threshold: 1.0,
+            intensity: 0.5,
+            points: 6,
+            length: 96.0,
+            falloff: 2.0,
+            angle: 0.3,
+            rotation_speed: 0.0,
+        }
+    }
+}
+
+/// The render graph label of the effect pass of the [`StarFilterPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct StarFilter;
+
+/// The render graph label of the node keeping the highlights of the [`StarFilterPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct StarFilterBrightPass;
+
+impl Plugin for StarFilterPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "star_filter.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<StarFilterSettings>(
+            app,
+            &[
+                ("threshold", 0.0..=10.0),
+                ("intensity", 0.0..=2.0),
+                ("length", 0.0..=256.0),
+                ("falloff", 0.0..=8.0),
+                ("rotation_speed", -1.0..=1.0),
+            ],
+        );
+        app.register_type::<StarFilterSettings>()
+            .add_systems(Update, rotate_rays);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<StarFilterSettings, _>::new(
+                SHADER_PATH,
+                StarFilter,
+                Some("star_filter"),
+                "star_filter_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view()
+            .with_bind_group(highlights_layout, prepare_highlights_bind_groups),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (before, _) = self.placement.edges();
+        render_app
+            .add_systems(Render, prepare_highlights.in_set(RenderSystems::PrepareResources))
+            .add_render_graph_node::<ViewNodeRunner<StarFilterBrightPassNode>>(
+                Core3d,
+                StarFilterBrightPass,
+            )
+            .add_render_graph_edges(Core3d, (before, StarFilterBrightPass, StarFilter));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<StarFilterBrightPassPipeline>();
+    }
+}
+
+fn rotate_rays(mut settings: Query<&mut StarFilterSettings>, time: Res<Time>) {
+    for mut settings in &mut settings {
+        if settings.rotation_speed == 0.0 {
+            continue;
+        }
+        // Wrapped to a turn, so the angle keeps its precision
+        settings.angle =
+            (settings.angle + settings.rotation_speed * time.delta_secs()).rem_euclid(TAU);
+    }
+}
+
+// The pipeline of the bright pass. It only depends on the adapter, so it has no variants
+#[derive(Resource)]
+struct StarFilterBrightPassPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    // Samples the highlights in the effect pass
+    highlights_sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for StarFilterBrightPassPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        // The bright pass reads the view, which some adapters can't filter
+        let filterable = filtering::screen_filterable(world);
+        let [source_texture, source_sampler] = filtering::screen_entries(filterable);
+        let layout = render_device.create_bind_group_layout(
+            "star_filter_bright_pass_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    source_texture,
+                    source_sampler,
+                    uniform_buffer::<StarFilterSettings>(true),
+                ),
+            ),
+        );
+        let descriptor = SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        };
+        let sampler = filtering::screen_sampler(render_device, filterable, descriptor.clone());
+        let highlights_sampler = render_device.create_sampler(&descriptor);
+
+        let mut shader_defs: Vec<ShaderDefVal> = Vec::new();
+        if !filterable {
+            shader_defs.push(filtering::SCREEN_TEXTURE_NON_FILTERABLE.into());
+        }
+        let pipeline_id =
+            world
+                .resource::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("star_filter_bright_pass_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: world.resource::<FullscreenShader>().to_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader: load_embedded_asset!(world, "star_filter.wgsl"),
+                        shader_defs,
+                        entry_point: Some("bright_pass".into()),
+                        targets: vec![Some(ColorTargetState {
+                            format: HIGHLIGHTS_TEXTURE_FORMAT,
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                    zero_initialize_workgroup_memory: false,
+                });
+
+        Self {
+            layout,
+            sampler,
+            highlights_sampler,
+            pipeline_id,
+        }
+    }
+}
+
+// The half resolution highlights of a view
+#[derive(Component)]
+struct ViewStarFilterHighlights {
+    texture: CachedTexture,
+}
+
+fn prepare_highlights(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewTarget), With<StarFilterSettings>>,
+    render_device: Res<RenderDevice>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+) {
+    for (entity, view_target) in &views {
+        let texture = texture_pool.get(
+            &render_device,
+            entity,
+            StarFilterBrightPass,
+            "highlights",
+            &TextureDescriptor {
+                label: Some("star_filter_highlights_texture"),
+                size: scaled_size(view_target.main_texture().size(), 0.5),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: HIGHLIGHTS_TEXTURE_FORMAT,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+        commands
+            .entity(entity)
+            .insert(ViewStarFilterHighlights { texture });
+    }
+}
+
+// The layout of the group 1 of the effect, the highlights and their sampler
+fn highlights_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "star_filter_highlights_bind_group_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+            ),
+        ),
+    )
+}
+
+// The effect skips the views without the bind group, so it doesn't read the highlights until the
+// bright pass is compiled
+fn prepare_highlights_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewStarFilterHighlights), With<StarFilterSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    layout: Res<PostProcessBindGroupLayout<StarFilterSettings>>,
+    bright_pass_pipeline: Res<StarFilterBrightPassPipeline>,
+) {
+    for (entity, highlights) in &views {
+        if pipeline_cache
+            .get_render_pipeline(bright_pass_pipeline.pipeline_id)
+            .is_none()
+        {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<StarFilterSettings>>();
+            continue;
+        }
+
+        let bind_group = render_device.create_bind_group(
+            "star_filter_highlights_bind_group",
+            &layout.layout,
+            &BindGroupEntries::sequential((
+                &highlights.texture.default_view,
+                &bright_pass_pipeline.highlights_sampler,
+            )),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<StarFilterSettings>::new(bind_group));
+    }
+}
+
+#[derive(Default)]
+struct StarFilterBrightPassNode;
+
+impl ViewNode for StarFilterBrightPassNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        // Only runs on the cameras with the star filter
+        &'static StarFilterSettings,
+        &'static ViewStarFilterHighlights,
+        &'static DynamicUniformIndex<StarFilterSettings>,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, _settings, highlights, settings_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // The effect doesn't read the highlights on the views it skips
+        let view = graph.view_entity();
+        if effects::skip_intermediate::<StarFilterSettings, StarFilter>(world, view) {
+            return Ok(());
+        }
+
+        let bright_pass_pipeline = world.resource::<StarFilterBrightPassPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (Some(pipeline), Some(settings_binding)) = (
+            pipeline_cache.get_render_pipeline(bright_pass_pipeline.pipeline_id),
+            world
+                .resource::<ComponentUniforms<StarFilterSettings>>()
+                .uniforms()
+                .binding(),
+        ) else {
+            return Ok(());
+        };
+
+        let bind_group = render_context.render_device().create_bind_group(
+            "star_filter_bright_pass_bind_group",
+            &bright_pass_pipeline.layout,
+            &BindGroupEntries::sequential((
+                view_target.main_texture_view(),
+                &bright_pass_pipeline.sampler,
+                settings_binding,
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("star_filter_bright_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &highlights.texture.default_view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
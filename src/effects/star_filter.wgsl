@@ -0,0 +1,84 @@
+// A cross screen filter. The bright pass keeps the highlights of the view at half resolution,
+// then the effect pass gathers them along the rays of a star around every pixel, fading with the
+// distance, and adds them to the view.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::utils::luminance
+
+// The view, read by the bright pass and the effect pass
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+struct StarFilterSettings {
+    tint: vec4<f32>,
+    threshold: f32,
+    intensity: f32,
+    points: u32,
+    length: f32,
+    falloff: f32,
+    angle: f32,
+    rotation_speed: f32,
+}
+@group(0) @binding(2) var<uniform> settings: StarFilterSettings;
+// The highlights, only bound to the effect pass
+@group(1) @binding(0) var highlights_texture: texture_2d<f32>;
+@group(1) @binding(1) var highlights_sampler: sampler;
+
+const PI: f32 = 3.14159265358979;
+// The samples along every ray
+const RAY_SAMPLES: i32 = 16;
+
+// Bilinear sampling, done manually when the adapter can't filter the view texture
+fn sample_bilinear(uv: vec2<f32>) -> vec3<f32> {
+#ifdef SCREEN_TEXTURE_NON_FILTERABLE
+    let texture_size = vec2<i32>(textureDimensions(source_texture));
+    let position = uv * vec2<f32>(texture_size) - 0.5;
+    let base = vec2<i32>(floor(position));
+    let f = fract(position);
+    let max_texel = texture_size - 1;
+    let t00 = textureLoad(source_texture, clamp(base, vec2(0), max_texel), 0).rgb;
+    let t10 = textureLoad(source_texture, clamp(base + vec2(1, 0), vec2(0), max_texel), 0).rgb;
+    let t01 = textureLoad(source_texture, clamp(base + vec2(0, 1), vec2(0), max_texel), 0).rgb;
+    let t11 = textureLoad(source_texture, clamp(base + vec2(1, 1), vec2(0), max_texel), 0).rgb;
+    return mix(mix(t00, t10, f.x), mix(t01, t11, f.x), f.y);
+#else
+    return textureSampleLevel(source_texture, source_sampler, uv, 0.0).rgb;
+#endif
+}
+
+// The part of the view brighter than the threshold, keeping its hue. Every pixel of the half
+// resolution texture covers four of the view, the bilinear sample at its center averages them
+@fragment
+fn bright_pass(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = max(sample_bilinear(in.uv), vec3(0.0));
+    let luma = luminance(color);
+    let bright = color * max(luma - settings.threshold, 0.0) / max(luma, 1.0e-5);
+    return vec4(bright, 1.0);
+}
+
+fn sample_highlights(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(highlights_texture, highlights_sampler, uv, 0.0).rgb;
+}
+
+// The effect pass. A star of 2n rays is n lines through the pixel, each gathered on both sides.
+// The highlights have no alpha, so the one of the view is kept
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureLoad(source_texture, vec2<i32>(in.position.xy), 0);
+    let texel = 1.0 / vec2<f32>(textureDimensions(source_texture));
+    let lines = clamp(settings.points, 4u, 8u) / 2u;
+    let spacing = max(settings.length, 0.0) / f32(RAY_SAMPLES);
+
+    var rays = vec3(0.0);
+    for (var line = 0u; line < lines; line++) {
+        let angle = settings.angle + f32(line) * PI / f32(lines);
+        let delta = vec2(cos(angle), -sin(angle)) * spacing * texel;
+        for (var i = 1; i <= RAY_SAMPLES; i++) {
+            let weight = pow(1.0 - f32(i - 1) / f32(RAY_SAMPLES), max(settings.falloff, 0.0));
+            let offset = delta * f32(i);
+            let forward = sample_highlights(in.uv + offset);
+            let backward = sample_highlights(in.uv - offset);
+            rays += (forward + backward) * weight;
+        }
+    }
+    let star = rays / f32(RAY_SAMPLES) * settings.tint.rgb * settings.intensity;
+    return vec4(color.rgb + star, color.a);
+}
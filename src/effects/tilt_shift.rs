@@ -0,0 +1,84 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/tilt_shift.wgsl";
+
+/// A tilt-shift blur, run on the cameras with [`TiltShiftSettings`].
+///
+/// Like the [`DepthOfFieldPlugin`](super::DepthOfFieldPlugin), but the blur grows with the
+/// distance on screen from a sharp band rather than with the depth of the scene, which makes
+/// wide shots look like miniatures. Doesn't need the depth texture.
+#[derive(Default)]
+pub struct TiltShiftPlugin {
+    /// Where the blur runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`TiltShiftPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct TiltShiftSettings {
+    /// The center of the sharp band, in UV coordinates. `(0.5, 0.5)` is the center of the view.
+    pub center: Vec2,
+    /// The rotation of the band in radians, counterclockwise. 0 is horizontal.
+    pub angle: f32,
+    /// The width of the sharp band, as a fraction of the height of the view.
+    pub band_width: f32,
+    /// How far from the band the blur reaches its largest radius, as a fraction of the height of
+    /// the view.
+    pub falloff: f32,
+    /// The largest radius of the blur, in pixels.
+    pub max_radius: f32,
+    /// How many samples are taken over the blur, capped at 64.
+    pub samples: u32,
+}
+
+impl Default for TiltShiftSettings {
+    fn default() -> Self {
+        Self {
+            center: Vec2::splat(0.5),
+            angle: 0.0,
+            band_width: 0.15,
+            falloff: 0.3,
+            max_radius: 12.0,
+            samples: 32,
+        }
+    }
+}
+
+/// The render graph label of the [`TiltShiftPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct TiltShift;
+
+impl Plugin for TiltShiftPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "tilt_shift.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<TiltShiftSettings>(
+            app,
+            &[
+                ("band_width", 0.0..=1.0),
+                ("falloff", 0.0..=1.0),
+                ("max_radius", 0.0..=32.0),
+            ],
+        );
+        app.register_type::<TiltShiftSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<TiltShiftSettings, _>::new(
+                SHADER_PATH,
+                TiltShift,
+                Some("tilt_shift"),
+                "tilt_shift_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view(),
+        );
+    }
+}
@@ -0,0 +1,46 @@
+// Blurs the view more the further a pixel is from a sharp band across it, gathering over a disk.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct TiltShiftSettings {
+    center: vec2<f32>,
+    angle: f32,
+    band_width: f32,
+    falloff: f32,
+    max_radius: f32,
+    samples: u32,
+}
+@group(0) @binding(2) var<uniform> settings: TiltShiftSettings;
+
+const GOLDEN_ANGLE: f32 = 2.39996323;
+const MAX_SAMPLES: u32 = 64u;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    // The distance across the band, in units of the height of the view so the band keeps its
+    // width whatever the aspect ratio
+    let offset = (in.uv - settings.center) * vec2(size.x / size.y, 1.0);
+    let normal = vec2(-sin(settings.angle), cos(settings.angle));
+    let distance = abs(dot(offset, normal)) - settings.band_width * 0.5;
+    let blur = smoothstep(0.0, max(settings.falloff, 1.0e-4), distance);
+    let radius = blur * settings.max_radius;
+
+    let center = textureSampleLevel(screen_texture, texture_sampler, in.uv, 0.0);
+    if radius < 0.5 {
+        return center;
+    }
+
+    // A golden angle spiral spreads the samples evenly over the disk
+    let samples = clamp(settings.samples, 1u, MAX_SAMPLES);
+    var color = vec3(0.0);
+    for (var i = 0u; i < samples; i++) {
+        let t = (f32(i) + 0.5) / f32(samples);
+        let angle = f32(i) * GOLDEN_ANGLE;
+        let sample_offset = vec2(cos(angle), sin(angle)) * radius * sqrt(t) / size;
+        let uv = in.uv + sample_offset;
+        color += textureSampleLevel(screen_texture, texture_sampler, uv, 0.0).rgb;
+    }
+    return vec4(color / f32(samples), center.a);
+}
@@ -0,0 +1,351 @@
+use crate::{
+    effects, PostProcessBindGroup, PostProcessBindGroupLayout, PostProcessPlacement,
+    PostProcessPlugin, PostProcessTexturePool,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::{
+        core_3d::graph::Core3d,
+        prepass::{NormalPrepass, ViewPrepassTextures, NORMAL_PREPASS_FORMAT},
+        FullscreenShader,
+    },
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{texture_2d, texture_2d_multisampled},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::CachedTexture,
+        view::Msaa,
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/toon_outline.wgsl";
+
+/// Toon outlines along the silhouettes and creases of the meshes, run on the cameras with
+/// [`ToonOutlineSettings`].
+///
+/// The lines follow the jumps of the depth and of the normals written by the prepass, so they
+/// ignore the textures and the lighting that fool outlines found on colors. The settings require
+/// the `NormalPrepass` of `bevy_core_pipeline`, which they add to their camera. With MSAA, the
+/// [`ToonOutlineNormals`] node copies the first sample of the normals into a texture the effect
+/// can read. Placing the outlines after the upscaling isn't supported.
+#[derive(Default)]
+pub struct ToonOutlinePlugin {
+    /// Where the outlines are drawn.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`ToonOutlinePlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+#[require(NormalPrepass)]
+pub struct ToonOutlineSettings {
+    /// The color of the lines, its alpha being their opacity.
+    pub color: LinearRgba,
+    /// The width of the lines, in pixels, up to [`ToonOutlineSettings::thickness_distance`].
+    pub thickness: f32,
+    /// The distance from the camera, in world units, beyond which the lines thin out with the
+    /// distance, so far away objects don't turn into blots of ink.
+    pub thickness_distance: f32,
+    /// The thinnest the lines get with the distance, in pixels.
+    pub min_thickness: f32,
+    /// The jump of depth drawn as a line, relative to the distance of the pixel. 0.05 draws the
+    /// silhouettes in front of what is 5% farther away.
+    pub depth_threshold: f32,
+    /// The change of normal drawn as a line, from 0 for any crease to 2 for none. 0.4 draws the
+    /// creases sharper than about 50 degrees.
+    pub normal_threshold: f32,
+}
+
+impl Default for ToonOutlineSettings {
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::BLACK,
+            thickness: 2.0,
+            thickness_distance: 10.0,
+            min_thickness: 0.5,
+            depth_threshold: 0.05,
+            normal_threshold: 0.4,
+        }
+    }
+}
+
+/// The render graph label of the effect pass of the [`ToonOutlinePlugin`], drawing the lines.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ToonOutline;
+
+/// The render graph label of the node copying the multisampled normals of the prepass for the
+/// [`ToonOutlinePlugin`], on the views using MSAA.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ToonOutlineNormals;
+
+impl Plugin for ToonOutlinePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "toon_outline.wgsl");
+        embedded_asset!(app, "toon_outline_normals.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<ToonOutlineSettings>(
+            app,
+            &[
+                ("thickness", 0.0..=8.0),
+                ("thickness_distance", 0.0..=1000.0),
+                ("min_thickness", 0.0..=8.0),
+                ("depth_threshold", 0.0..=1.0),
+                ("normal_threshold", 0.0..=2.0),
+            ],
+        );
+        app.register_type::<ToonOutlineSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<ToonOutlineSettings, _>::new(
+                SHADER_PATH,
+                ToonOutline,
+                Some("toon_outline"),
+                "toon_outline_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_depth()
+            .with_bind_group(normals_layout, prepare_normals_bind_groups),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (before, _) = self.placement.edges();
+        render_app
+            .add_systems(Render, prepare_normals.in_set(RenderSystems::PrepareResources))
+            .add_render_graph_node::<ViewNodeRunner<ToonOutlineNormalsNode>>(
+                Core3d,
+                ToonOutlineNormals,
+            )
+            .add_render_graph_edges(Core3d, (before, ToonOutlineNormals, ToonOutline));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<ToonOutlineNormalsPipeline>();
+    }
+}
+
+// The pipeline copying the first sample of the multisampled normals. Only the views using MSAA
+// need it, so it has no variants
+#[derive(Resource)]
+struct ToonOutlineNormalsPipeline {
+    layout: BindGroupLayout,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for ToonOutlineNormalsPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(
+            "toon_outline_normals_bind_group_layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::FRAGMENT,
+                texture_2d_multisampled(TextureSampleType::Float { filterable: false }),
+            ),
+        );
+
+        let pipeline_id =
+            world
+                .resource::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("toon_outline_normals_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: world.resource::<FullscreenShader>().to_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader: load_embedded_asset!(world, "toon_outline_normals.wgsl"),
+                        shader_defs: vec![],
+                        entry_point: Some("fragment".into()),
+                        targets: vec![Some(ColorTargetState {
+                            format: NORMAL_PREPASS_FORMAT,
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                    zero_initialize_workgroup_memory: false,
+                });
+
+        Self {
+            layout,
+            pipeline_id,
+        }
+    }
+}
+
+// The single sampled copy of the normals of a view using MSAA
+#[derive(Component)]
+struct ViewToonOutlineNormals {
+    texture: CachedTexture,
+}
+
+fn prepare_normals(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewPrepassTextures, &Msaa), With<ToonOutlineSettings>>,
+    render_device: Res<RenderDevice>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+) {
+    for (entity, prepass_textures, msaa) in &views {
+        // Without MSAA the effect reads the normals of the prepass directly
+        if msaa.samples() == 1 || prepass_textures.normal.is_none() {
+            commands.entity(entity).remove::<ViewToonOutlineNormals>();
+            continue;
+        }
+
+        let texture = texture_pool.get(
+            &render_device,
+            entity,
+            ToonOutlineNormals,
+            "normals",
+            &TextureDescriptor {
+                label: Some("toon_outline_normals_texture"),
+                size: prepass_textures.size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: NORMAL_PREPASS_FORMAT,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+        commands
+            .entity(entity)
+            .insert(ViewToonOutlineNormals { texture });
+    }
+}
+
+// The layout of the group 1 of the effect, the normals of the view
+fn normals_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "toon_outline_normals_result_bind_group_layout",
+        &BindGroupLayoutEntries::single(
+            ShaderStages::FRAGMENT,
+            texture_2d(TextureSampleType::Float { filterable: false }),
+        ),
+    )
+}
+
+// The effect skips the views without the bind group, so the views without normals, or whose
+// normals can't be copied yet, have no outlines
+fn prepare_normals_bind_groups(
+    mut commands: Commands,
+    views: Query<
+        (
+            Entity,
+            &ViewPrepassTextures,
+            &Msaa,
+            Option<&ViewToonOutlineNormals>,
+        ),
+        With<ToonOutlineSettings>,
+    >,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    normals_pipeline: Res<ToonOutlineNormalsPipeline>,
+    layout: Res<PostProcessBindGroupLayout<ToonOutlineSettings>>,
+) {
+    let copy_ready = pipeline_cache
+        .get_render_pipeline(normals_pipeline.pipeline_id)
+        .is_some();
+
+    for (entity, prepass_textures, msaa, copied_normals) in &views {
+        let normals = match (msaa.samples() > 1, copied_normals) {
+            (false, _) => prepass_textures.normal_view(),
+            (true, Some(copied_normals)) if copy_ready => {
+                Some(&copied_normals.texture.default_view)
+            }
+            (true, _) => None,
+        };
+        let Some(normals) = normals else {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<ToonOutlineSettings>>();
+            continue;
+        };
+
+        let bind_group = render_device.create_bind_group(
+            "toon_outline_normals_result_bind_group",
+            &layout.layout,
+            &BindGroupEntries::single(normals),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<ToonOutlineSettings>::new(bind_group));
+    }
+}
+
+#[derive(Default)]
+struct ToonOutlineNormalsNode;
+
+impl ViewNode for ToonOutlineNormalsNode {
+    type ViewQuery = (
+        // Only runs on the cameras with the outlines
+        &'static ToonOutlineSettings,
+        &'static ViewPrepassTextures,
+        &'static ViewToonOutlineNormals,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (_settings, prepass_textures, copied_normals): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // The effect doesn't read the copy on the views it skips
+        let view = graph.view_entity();
+        if effects::skip_intermediate::<ToonOutlineSettings, ToonOutline>(world, view) {
+            return Ok(());
+        }
+
+        let normals_pipeline = world.resource::<ToonOutlineNormalsPipeline>();
+        let (Some(pipeline), Some(normals)) = (
+            world
+                .resource::<PipelineCache>()
+                .get_render_pipeline(normals_pipeline.pipeline_id),
+            prepass_textures.normal_view(),
+        ) else {
+            return Ok(());
+        };
+
+        let bind_group = render_context.render_device().create_bind_group(
+            "toon_outline_normals_bind_group",
+            &normals_pipeline.layout,
+            &BindGroupEntries::single(normals),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("toon_outline_normals_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &copied_normals.texture.default_view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
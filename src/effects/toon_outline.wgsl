@@ -0,0 +1,82 @@
+// Draws lines where the depth or the normals of the prepass jump between a pixel and its
+// neighbors, the neighbors being farther apart for the close pixels so the lines thin out with
+// the distance.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_post_process::utils::linearize_depth
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct ToonOutlineSettings {
+    color: vec4<f32>,
+    thickness: f32,
+    thickness_distance: f32,
+    min_thickness: f32,
+    depth_threshold: f32,
+    normal_threshold: f32,
+}
+@group(0) @binding(2) var<uniform> settings: ToonOutlineSettings;
+@group(0) @binding(3) var<uniform> view: View;
+#ifdef MULTISAMPLED
+@group(0) @binding(4) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(4) var depth_texture: texture_depth_2d;
+#endif
+
+// The world space normals of the prepass, encoded in 0 to 1
+@group(1) @binding(0) var normals_texture: texture_2d<f32>;
+
+// The sky is cleared to a depth of 0, infinitely far away
+const FAR_DISTANCE: f32 = 1.0e6;
+
+fn distance_at(pixel: vec2<i32>) -> f32 {
+    let depth = textureLoad(depth_texture, pixel, 0);
+    if depth <= 0.0 {
+        return FAR_DISTANCE;
+    }
+    return linearize_depth(depth, view.view_from_clip);
+}
+
+fn normal_at(pixel: vec2<i32>) -> vec3<f32> {
+    return textureLoad(normals_texture, pixel, 0).xyz * 2.0 - 1.0;
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let size = vec2<i32>(textureDimensions(depth_texture));
+    let center = clamp(vec2<i32>(in.position.xy), vec2(0), size - 1);
+    let center_distance = distance_at(center);
+    let center_normal = normal_at(center);
+
+    let distance_scale = settings.thickness_distance / max(center_distance, 1.0e-4);
+    let thickness = settings.thickness * min(distance_scale, 1.0);
+    let reach = max(i32(round(max(thickness, settings.min_thickness) * 0.5)), 1);
+
+    // The jumps are measured from the closer side, so both sides of a silhouette draw the same
+    // line
+    var edge = 0.0;
+    var offsets = array(vec2(1, 0), vec2(-1, 0), vec2(0, 1), vec2(0, -1));
+    for (var i = 0u; i < 4u; i++) {
+        let neighbor = clamp(center + offsets[i] * reach, vec2(0), size - 1);
+        let neighbor_distance = distance_at(neighbor);
+        let closest = min(center_distance, neighbor_distance);
+        let depth_jump = abs(neighbor_distance - center_distance) / closest;
+        if depth_jump > settings.depth_threshold {
+            edge = 1.0;
+        }
+
+        // The normals only make creases on surfaces, not against the sky
+        if neighbor_distance < FAR_DISTANCE && center_distance < FAR_DISTANCE {
+            let normal_change = 1.0 - dot(center_normal, normal_at(neighbor));
+            if normal_change > settings.normal_threshold {
+                edge = 1.0;
+            }
+        }
+    }
+
+    // Lines thinner than a pixel fade instead of breaking up
+    let coverage = clamp(max(thickness, settings.min_thickness), 0.0, 1.0);
+    let opacity = edge * coverage * settings.color.a;
+    return vec4(mix(color.rgb, settings.color.rgb, opacity), color.a);
+}
@@ -0,0 +1,10 @@
+// Copies the first sample of the multisampled normals of the prepass, for the toon outlines of the
+// views using MSAA.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var normals: texture_multisampled_2d<f32>;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    return textureLoad(normals, vec2<i32>(in.position.xy), 0);
+}
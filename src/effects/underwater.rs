@@ -0,0 +1,127 @@
+use crate::{
+    effects, noise, PostProcessInput, PostProcessNoisePlugin, PostProcessPlacement,
+    PostProcessPlugin,
+};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/underwater.wgsl";
+
+/// The view of a camera under water, run on the cameras with [`UnderwaterSettings`].
+///
+/// The view wavers with a drifting value noise, like seen through moving water, then every
+/// channel is absorbed by the water along the distance to the pixel, red first, the water color
+/// taking over far away. Caustics scroll over the geometry close to the camera, projected from
+/// above. Insert the settings when the camera dives and remove them when it surfaces, or bypass
+/// the effect with a [`PostProcessBypass`](crate::PostProcessBypass) to keep them. Runs on the
+/// HDR colors by default. Placing it after the upscaling isn't supported.
+///
+/// Adds the [`PostProcessNoisePlugin`] when it isn't already.
+#[derive(Default)]
+pub struct UnderwaterPlugin {
+    /// Where the effect runs.
+    pub placement: PostProcessPlacement,
+    /// A tiling caustics texture, read from its red channel. Without one, the caustics are made
+    /// from two layers of the value noise of the [`noise`] module.
+    pub caustics: Option<Handle<Image>>,
+}
+
+/// The settings of the [`UnderwaterPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct UnderwaterSettings {
+    /// The color of the water, seen where the light of the scene is absorbed.
+    pub water_color: LinearRgba,
+    /// How much of the red, green and blue light the water absorbs, per world unit.
+    pub absorption: Vec3,
+    /// How far the view wavers, in pixels.
+    pub refraction_strength: f32,
+    /// The size of the waves of the wavering, in pixels.
+    pub refraction_size: f32,
+    /// How fast the wavering moves.
+    pub refraction_speed: f32,
+    /// The brightness the caustics add to the lit geometry.
+    pub caustics_intensity: f32,
+    /// The size of a tile of the caustics, in world units.
+    pub caustics_size: f32,
+    /// How fast the caustics drift, in tiles per second.
+    pub caustics_speed: f32,
+    /// The distance from the camera the caustics fade out at, in world units.
+    pub caustics_distance: f32,
+    /// The time the effect is animated with, in seconds. Written by the plugin every frame,
+    /// wrapping like [`Time::elapsed_secs_wrapped`].
+    pub time: f32,
+}
+
+impl Default for UnderwaterSettings {
+    fn default() -> Self {
+        Self {
+            water_color: LinearRgba::rgb(0.02, 0.12, 0.15),
+            absorption: Vec3::new(0.4, 0.08, 0.06),
+            refraction_strength: 4.0,
+            refraction_size: 160.0,
+            refraction_speed: 0.5,
+            caustics_intensity: 0.6,
+            caustics_size: 4.0,
+            caustics_speed: 0.05,
+            caustics_distance: 30.0,
+            time: 0.0,
+        }
+    }
+}
+
+/// The render graph label of the [`UnderwaterPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Underwater;
+
+impl Plugin for UnderwaterPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "underwater.wgsl");
+        if !app.is_plugin_added::<PostProcessNoisePlugin>() {
+            app.add_plugins(PostProcessNoisePlugin);
+        }
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<UnderwaterSettings>(
+            app,
+            &[
+                ("refraction_strength", 0.0..=16.0),
+                ("refraction_size", 4.0..=256.0),
+                ("refraction_speed", 0.0..=4.0),
+                ("caustics_intensity", 0.0..=4.0),
+                ("caustics_size", 0.5..=32.0),
+                ("caustics_speed", 0.0..=2.0),
+                ("caustics_distance", 0.0..=200.0),
+            ],
+        );
+        app.register_type::<UnderwaterSettings>()
+            .add_systems(Update, update_time);
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        let mut plugin = PostProcessPlugin::<UnderwaterSettings, _>::new(
+            SHADER_PATH,
+            Underwater,
+            Some("underwater"),
+            "underwater_bind_group_layout",
+            vertex_state,
+        )
+        .with_placement(self.placement)
+        .with_depth()
+        .with_input(PostProcessInput::Image(noise::VALUE_NOISE))
+        .with_standard_samplers();
+        if let Some(caustics) = &self.caustics {
+            plugin = plugin
+                .with_input(PostProcessInput::Image(caustics.clone()))
+                .with_shader_def("CAUSTICS_TEXTURE");
+        }
+        app.add_plugins(plugin);
+    }
+}
+
+fn update_time(mut settings: Query<&mut UnderwaterSettings>, time: Res<Time>) {
+    for mut settings in &mut settings {
+        settings.time = time.elapsed_secs_wrapped();
+    }
+}
@@ -0,0 +1,83 @@
+// The view under water. The pixels are displaced by a drifting value noise, the light of the
+// scene is absorbed along the distance to the camera, and caustics are projected from above onto
+// the geometry close to the camera.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_post_process::utils::position_world_from_depth
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct UnderwaterSettings {
+    water_color: vec4<f32>,
+    absorption: vec3<f32>,
+    refraction_strength: f32,
+    refraction_size: f32,
+    refraction_speed: f32,
+    caustics_intensity: f32,
+    caustics_size: f32,
+    caustics_speed: f32,
+    caustics_distance: f32,
+    time: f32,
+}
+@group(0) @binding(2) var<uniform> settings: UnderwaterSettings;
+@group(0) @binding(3) var<uniform> view: View;
+#ifdef MULTISAMPLED
+@group(0) @binding(4) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(0) @binding(4) var depth_texture: texture_depth_2d;
+#endif
+@group(0) @binding(7) var clamp_sampler: sampler;
+@group(0) @binding(9) var repeat_sampler: sampler;
+@group(0) @binding(16) var value_noise: texture_2d<f32>;
+#ifdef CAUSTICS_TEXTURE
+@group(0) @binding(17) var caustics_texture: texture_2d<f32>;
+#endif
+
+fn noise_at(uv: vec2<f32>) -> f32 {
+    return textureSampleLevel(value_noise, repeat_sampler, uv, 0.0).r;
+}
+
+// The brightness of the caustics at a tile coordinate
+fn caustics_at(uv: vec2<f32>) -> f32 {
+    let drift = settings.time * settings.caustics_speed;
+#ifdef CAUSTICS_TEXTURE
+    return textureSampleLevel(caustics_texture, repeat_sampler, uv + drift, 0.0).r;
+#else
+    // Two layers drifting apart, their ridges crossing into the bright lines of caustics
+    let a = 1.0 - abs(noise_at(uv + vec2(drift, drift * 0.6)) * 2.0 - 1.0);
+    let b = 1.0 - abs(noise_at(uv * 1.3 + vec2(-drift * 0.7, drift)) * 2.0 - 1.0);
+    return pow(min(a, b), 6.0) * 2.0;
+#endif
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(screen_texture));
+
+    // Two offset reads of the noise displace the pixel along both axes
+    let wave_uv = in.position.xy / max(settings.refraction_size, 1.0);
+    let wave_time = settings.time * settings.refraction_speed;
+    let wave = vec2(
+        noise_at(wave_uv + vec2(wave_time, 0.0)),
+        noise_at(wave_uv + vec2(0.43, wave_time + 0.71)),
+    ) * 2.0 - 1.0;
+    let uv = in.uv + wave * settings.refraction_strength / size;
+    let color = textureSampleLevel(screen_texture, clamp_sampler, uv, 0.0);
+
+    // The depth is read where the color comes from, so the absorption follows the wavering
+    let pixel = clamp(vec2<i32>(uv * size), vec2(0), vec2<i32>(size) - 1);
+    let depth = textureLoad(depth_texture, pixel, 0);
+    // The sky is cleared to a depth of 0, infinitely far away behind the water
+    if depth <= 0.0 {
+        return vec4(settings.water_color.rgb, color.a);
+    }
+    let position = position_world_from_depth(uv, depth, view.world_from_clip);
+    let view_distance = distance(position, view.world_position);
+
+    let fade = 1.0 - smoothstep(0.0, max(settings.caustics_distance, 1.0e-3), view_distance);
+    let caustics = caustics_at(position.xz / max(settings.caustics_size, 1.0e-3));
+    let lit = color.rgb * (1.0 + caustics * settings.caustics_intensity * fade);
+
+    let transmittance = exp(-max(settings.absorption, vec3(0.0)) * view_distance);
+    return vec4(mix(settings.water_color.rgb, lit, transmittance), color.a);
+}
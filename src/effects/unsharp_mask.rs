@@ -0,0 +1,381 @@
+use crate::{
+    effects, filtering, PostProcessBindGroup, PostProcessBindGroupLayout, PostProcessPlacement,
+    PostProcessPlugin, PostProcessTexturePool,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::{core_3d::graph::Core3d, FullscreenShader},
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{ComponentUniforms, DynamicUniformIndex, ExtractComponent},
+        render_graph::{
+            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode,
+            ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::CachedTexture,
+        view::ViewTarget,
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/unsharp_mask.wgsl";
+
+/// The format of the blurred view.
+const BLUR_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// Unsharp mask sharpening, run on the cameras with [`UnsharpMaskSettings`].
+///
+/// The [`UnsharpMaskBlur`] node blurs the view with the two passes of the
+/// [`GaussianBlurPlugin`](super::GaussianBlurPlugin), then the [`UnsharpMask`] effect adds the
+/// difference between the view and its blur back onto the view. Placing it after the upscaling
+/// isn't supported, the blur always reads the main texture of the view.
+#[derive(Default)]
+pub struct UnsharpMaskPlugin {
+    /// Where the sharpening runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`UnsharpMaskPlugin`] on a camera.
+// Starts with the fields of `GaussianBlurSettings`, so the blur passes read the same uniform
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct UnsharpMaskSettings {
+    /// The standard deviation of the blur, in pixels. Larger radii sharpen coarser details.
+    pub radius: f32,
+    /// How many pixels the blur samples on each side of a pixel, in each pass. Capped at 64,
+    /// three times the radius covers nearly all of the blur.
+    pub samples: u32,
+    /// How much of the difference with the blur is added back, 0 leaving the view unchanged.
+    pub amount: f32,
+    /// The smallest difference of luminance with the blur that is sharpened, so noise and flat
+    /// gradients aren't amplified.
+    pub threshold: f32,
+}
+
+impl Default for UnsharpMaskSettings {
+    fn default() -> Self {
+        Self {
+            radius: 1.5,
+            samples: 5,
+            amount: 0.8,
+            threshold: 0.01,
+        }
+    }
+}
+
+/// The render graph label of the effect pass of the [`UnsharpMaskPlugin`], adding the details
+/// back onto the view.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct UnsharpMask;
+
+/// The render graph label of the node blurring the view for the [`UnsharpMaskPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct UnsharpMaskBlur;
+
+impl Plugin for UnsharpMaskPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "unsharp_mask.wgsl");
+        embedded_asset!(app, "gaussian_blur.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<UnsharpMaskSettings>(
+            app,
+            &[
+                ("radius", 0.5..=16.0),
+                ("amount", 0.0..=4.0),
+                ("threshold", 0.0..=0.5),
+            ],
+        );
+        app.register_type::<UnsharpMaskSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<UnsharpMaskSettings, _>::new(
+                SHADER_PATH,
+                UnsharpMask,
+                Some("unsharp_mask"),
+                "unsharp_mask_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view()
+            .with_bind_group(blur_result_layout, prepare_blur_result_bind_groups),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let (before, _) = self.placement.edges();
+        render_app
+            .init_resource::<SpecializedRenderPipelines<UnsharpMaskBlurPipeline>>()
+            .add_systems(Render, prepare_blurs.in_set(RenderSystems::PrepareResources))
+            .add_render_graph_node::<ViewNodeRunner<UnsharpMaskBlurNode>>(Core3d, UnsharpMaskBlur)
+            .add_render_graph_edges(Core3d, (before, UnsharpMaskBlur, UnsharpMask));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<UnsharpMaskBlurPipeline>();
+    }
+}
+
+// The pipeline of the two passes of the blur, running the shader of the Gaussian blur
+#[derive(Resource)]
+struct UnsharpMaskBlurPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    filterable: bool,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+}
+
+impl FromWorld for UnsharpMaskBlurPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        // The first pass reads the view, which some adapters can't filter
+        let filterable = filtering::screen_filterable(world);
+        let [source_texture, source_sampler] = filtering::screen_entries(filterable);
+        let layout = render_device.create_bind_group_layout(
+            "unsharp_mask_blur_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    source_texture,
+                    source_sampler,
+                    uniform_buffer::<UnsharpMaskSettings>(true),
+                ),
+            ),
+        );
+
+        Self {
+            layout,
+            sampler: filtering::screen_sampler(
+                render_device,
+                filterable,
+                SamplerDescriptor {
+                    mag_filter: FilterMode::Linear,
+                    min_filter: FilterMode::Linear,
+                    ..default()
+                },
+            ),
+            filterable,
+            shader: load_embedded_asset!(world, "gaussian_blur.wgsl"),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for UnsharpMaskBlurPipeline {
+    // Whether the pass is the vertical one
+    type Key = bool;
+
+    fn specialize(&self, vertical: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if vertical {
+            shader_defs.push("VERTICAL".into());
+        }
+        if !self.filterable {
+            shader_defs.push(filtering::SCREEN_TEXTURE_NON_FILTERABLE.into());
+        }
+
+        RenderPipelineDescriptor {
+            label: Some("unsharp_mask_blur_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some("fragment".into()),
+                targets: vec![Some(ColorTargetState {
+                    format: BLUR_TEXTURE_FORMAT,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+// The textures of the horizontal and vertical passes of the blur of a view
+#[derive(Component)]
+struct ViewUnsharpMaskBlur {
+    horizontal: CachedTexture,
+    vertical: CachedTexture,
+    horizontal_pipeline_id: CachedRenderPipelineId,
+    vertical_pipeline_id: CachedRenderPipelineId,
+}
+
+fn prepare_blurs(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewTarget), With<UnsharpMaskSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    blur_pipeline: Res<UnsharpMaskBlurPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<UnsharpMaskBlurPipeline>>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+) {
+    let horizontal_pipeline_id = pipelines.specialize(&pipeline_cache, &blur_pipeline, false);
+    let vertical_pipeline_id = pipelines.specialize(&pipeline_cache, &blur_pipeline, true);
+
+    for (entity, view_target) in &views {
+        let descriptor = TextureDescriptor {
+            label: Some("unsharp_mask_blur_texture"),
+            size: view_target.main_texture().size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: BLUR_TEXTURE_FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        };
+        let mut texture = |name: &'static str| {
+            texture_pool.get(&render_device, entity, UnsharpMaskBlur, name, &descriptor)
+        };
+        commands.entity(entity).insert(ViewUnsharpMaskBlur {
+            horizontal: texture("horizontal"),
+            vertical: texture("vertical"),
+            horizontal_pipeline_id,
+            vertical_pipeline_id,
+        });
+    }
+}
+
+// The layout of the group 1 of the effect, the blurred view. It is read at the pixel of the
+// view, so it needs no sampler
+fn blur_result_layout(render_device: &RenderDevice) -> BindGroupLayout {
+    render_device.create_bind_group_layout(
+        "unsharp_mask_blur_result_bind_group_layout",
+        &BindGroupLayoutEntries::single(
+            ShaderStages::FRAGMENT,
+            texture_2d(TextureSampleType::Float { filterable: false }),
+        ),
+    )
+}
+
+// The effect skips the views without the bind group, so it doesn't read the blur until the blur
+// pipelines are compiled
+fn prepare_blur_result_bind_groups(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewUnsharpMaskBlur), With<UnsharpMaskSettings>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    layout: Res<PostProcessBindGroupLayout<UnsharpMaskSettings>>,
+) {
+    for (entity, blur) in &views {
+        if pipeline_cache
+            .get_render_pipeline(blur.horizontal_pipeline_id)
+            .is_none()
+            || pipeline_cache
+                .get_render_pipeline(blur.vertical_pipeline_id)
+                .is_none()
+        {
+            commands
+                .entity(entity)
+                .remove::<PostProcessBindGroup<UnsharpMaskSettings>>();
+            continue;
+        }
+
+        let bind_group = render_device.create_bind_group(
+            "unsharp_mask_blur_result_bind_group",
+            &layout.layout,
+            &BindGroupEntries::single(&blur.vertical.default_view),
+        );
+        commands
+            .entity(entity)
+            .insert(PostProcessBindGroup::<UnsharpMaskSettings>::new(bind_group));
+    }
+}
+
+#[derive(Default)]
+struct UnsharpMaskBlurNode;
+
+impl ViewNode for UnsharpMaskBlurNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        // Only runs on the cameras with the sharpening
+        &'static UnsharpMaskSettings,
+        &'static ViewUnsharpMaskBlur,
+        &'static DynamicUniformIndex<UnsharpMaskSettings>,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, _settings, blur, settings_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        // The effect doesn't read the blur on the views it skips
+        let view = graph.view_entity();
+        if effects::skip_intermediate::<UnsharpMaskSettings, UnsharpMask>(world, view) {
+            return Ok(());
+        }
+
+        let blur_pipeline = world.resource::<UnsharpMaskBlurPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (Some(horizontal_pipeline), Some(vertical_pipeline), Some(settings_binding)) = (
+            pipeline_cache.get_render_pipeline(blur.horizontal_pipeline_id),
+            pipeline_cache.get_render_pipeline(blur.vertical_pipeline_id),
+            world
+                .resource::<ComponentUniforms<UnsharpMaskSettings>>()
+                .uniforms()
+                .binding(),
+        ) else {
+            return Ok(());
+        };
+
+        let passes = [
+            (
+                horizontal_pipeline,
+                view_target.main_texture_view(),
+                &blur.horizontal.default_view,
+            ),
+            (
+                vertical_pipeline,
+                &blur.horizontal.default_view,
+                &blur.vertical.default_view,
+            ),
+        ];
+        for (pipeline, source, destination) in passes {
+            let bind_group = render_context.render_device().create_bind_group(
+                "unsharp_mask_blur_bind_group",
+                &blur_pipeline.layout,
+                &BindGroupEntries::sequential((
+                    source,
+                    &blur_pipeline.sampler,
+                    settings_binding.clone(),
+                )),
+            );
+
+            let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("unsharp_mask_blur_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: destination,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+// Adds the difference between the view and its blur back onto the view, above a threshold.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_post_process::utils::luminance
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct UnsharpMaskSettings {
+    radius: f32,
+    samples: u32,
+    amount: f32,
+    threshold: f32,
+}
+@group(0) @binding(2) var<uniform> settings: UnsharpMaskSettings;
+
+// The view blurred by the Gaussian blur passes, the size of the view
+@group(1) @binding(0) var blur_texture: texture_2d<f32>;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let pixel = vec2<i32>(in.position.xy);
+    let blurred = textureLoad(blur_texture, pixel, 0).rgb;
+
+    let detail = color.rgb - blurred;
+    // Fades in above the threshold rather than cutting in, which would show as speckles
+    let contrast = abs(luminance(detail));
+    let mask = smoothstep(settings.threshold, settings.threshold * 2.0 + 1.0e-4, contrast);
+    let sharpened = color.rgb + detail * settings.amount * mask;
+    return vec4(max(sharpened, vec3(0.0)), color.a);
+}
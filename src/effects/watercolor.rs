@@ -0,0 +1,107 @@
+use crate::{
+    effects, noise, PostProcessInput, PostProcessNoisePlugin, PostProcessPlacement,
+    PostProcessPlugin,
+};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/watercolor.wgsl";
+
+/// A watercolor painting, run on the cameras with [`WatercolorSettings`].
+///
+/// Wobbles the view like hand drawn shapes, bleeds the colors into their neighbors, and varies
+/// the density of the pigment with the grain of the paper and at the edges of the shapes, where
+/// watercolor pools as it dries. Runs after the tonemapping by default. Adds the
+/// [`PostProcessNoisePlugin`] when it isn't already.
+pub struct WatercolorPlugin {
+    /// Where the effect runs.
+    pub placement: PostProcessPlacement,
+    /// The grain of the paper, a tiling texture read from its red channel, 0.5 being the average
+    /// grain. Defaults to the value noise of the [`noise`] module.
+    pub paper: Handle<Image>,
+}
+
+impl Default for WatercolorPlugin {
+    fn default() -> Self {
+        Self {
+            placement: PostProcessPlacement::AfterTonemapping,
+            paper: noise::VALUE_NOISE,
+        }
+    }
+}
+
+/// The settings of the [`WatercolorPlugin`] on a camera.
+#[derive(Component, Reflect, Clone, Copy, Debug, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct WatercolorSettings {
+    /// How far the shapes wobble, in pixels.
+    pub wobble: f32,
+    /// The size of the wobbles, in pixels.
+    pub wobble_size: f32,
+    /// How far the colors bleed into their neighbors, in pixels.
+    pub bleed: f32,
+    /// How much the pigment darkens at the edges of the shapes.
+    pub edge_darkening: f32,
+    /// How much the grain of the paper varies the density of the pigment.
+    pub paper_intensity: f32,
+    /// The size of a tile of the paper texture, in pixels.
+    pub paper_size: f32,
+}
+
+impl Default for WatercolorSettings {
+    fn default() -> Self {
+        Self {
+            wobble: 2.0,
+            wobble_size: 96.0,
+            bleed: 3.0,
+            edge_darkening: 2.0,
+            paper_intensity: 0.4,
+            paper_size: 256.0,
+        }
+    }
+}
+
+/// The render graph label of the [`WatercolorPlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct Watercolor;
+
+impl Plugin for WatercolorPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "watercolor.wgsl");
+        if !app.is_plugin_added::<PostProcessNoisePlugin>() {
+            app.add_plugins(PostProcessNoisePlugin);
+        }
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<WatercolorSettings>(
+            app,
+            &[
+                ("wobble", 0.0..=8.0),
+                ("wobble_size", 4.0..=128.0),
+                ("bleed", 0.0..=8.0),
+                ("edge_darkening", 0.0..=2.0),
+                ("paper_intensity", 0.0..=1.0),
+                ("paper_size", 16.0..=1024.0),
+            ],
+        );
+        app.register_type::<WatercolorSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<WatercolorSettings, _>::new(
+                SHADER_PATH,
+                Watercolor,
+                Some("watercolor"),
+                "watercolor_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .with_input(PostProcessInput::Image(noise::VALUE_NOISE))
+            .with_input(PostProcessInput::Image(self.paper.clone()))
+            .with_standard_samplers()
+            .without_view(),
+        );
+    }
+}
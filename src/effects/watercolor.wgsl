@@ -0,0 +1,64 @@
+// Wobbles and bleeds the colors, then varies the density of the pigment with the paper grain and
+// the edges, with the pigment model of Bousseau et al., "Interactive watercolor rendering with
+// temporal coherence and abstraction".
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct WatercolorSettings {
+    wobble: f32,
+    wobble_size: f32,
+    bleed: f32,
+    edge_darkening: f32,
+    paper_intensity: f32,
+    paper_size: f32,
+}
+@group(0) @binding(2) var<uniform> settings: WatercolorSettings;
+@group(0) @binding(7) var linear_sampler: sampler;
+@group(0) @binding(9) var repeat_sampler: sampler;
+@group(0) @binding(16) var value_noise: texture_2d<f32>;
+@group(0) @binding(17) var paper: texture_2d<f32>;
+
+const BLEED_TAPS: u32 = 8u;
+const TAU: f32 = 6.28318531;
+
+fn sample_color(uv: vec2<f32>) -> vec3<f32> {
+    let clamped = clamp(uv, vec2(0.0), vec2(1.0));
+    return textureSampleLevel(screen_texture, linear_sampler, clamped, 0.0).rgb;
+}
+
+// Darkens or lightens a color as if its pigment were `density` times as dense
+fn apply_density(color: vec3<f32>, density: f32) -> vec3<f32> {
+    return color - (color - color * color) * (density - 1.0);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(screen_texture));
+    let alpha = textureSampleLevel(screen_texture, linear_sampler, in.uv, 0.0).a;
+
+    // Two decorrelated reads of the noise make the offset
+    let noise_uv = in.position.xy / max(settings.wobble_size, 1.0);
+    let noise = vec2(
+        textureSampleLevel(value_noise, repeat_sampler, noise_uv, 0.0).r,
+        textureSampleLevel(value_noise, repeat_sampler, noise_uv + vec2(0.37, 0.61), 0.0).r,
+    );
+    let uv = in.uv + (noise * 2.0 - 1.0) * settings.wobble / size;
+
+    let center = sample_color(uv);
+    var bled = center;
+    for (var i = 0u; i < BLEED_TAPS; i++) {
+        let angle = f32(i) / f32(BLEED_TAPS) * TAU;
+        bled += sample_color(uv + vec2(cos(angle), sin(angle)) * settings.bleed / size);
+    }
+    bled /= f32(BLEED_TAPS + 1u);
+
+    // The pigment pools where the bled color differs from the pixel, at the edges of the shapes
+    let edge = length(center - bled);
+    let paper_uv = in.position.xy / max(settings.paper_size, 1.0);
+    let grain = textureSampleLevel(paper, repeat_sampler, paper_uv, 0.0).r;
+    let density = 1.0 + settings.edge_darkening * edge + settings.paper_intensity * (grain - 0.5);
+
+    let color = clamp(mix(center, bled, 0.5), vec3(0.0), vec3(1.0));
+    return vec4(apply_density(color, density), alpha);
+}
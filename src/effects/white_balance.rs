@@ -0,0 +1,59 @@
+use crate::{effects, PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{extract_component::ExtractComponent, render_graph::RenderLabel, render_resource::*},
+};
+
+const SHADER_PATH: &str = "embedded://bevy_post_process_util/effects/white_balance.wgsl";
+
+/// A white balance correction, run on the cameras with [`WhiteBalanceSettings`].
+///
+/// The colors are adapted to a shifted white point in the LMS space of CAT02, like a camera
+/// adapts to the light of a scene, which keeps neutrals neutral where scaling the RGB channels
+/// would tint the whole view.
+#[derive(Default)]
+pub struct WhiteBalancePlugin {
+    /// Where the correction runs.
+    pub placement: PostProcessPlacement,
+}
+
+/// The settings of the [`WhiteBalancePlugin`] on a camera. Both default to 0, leaving the colors
+/// unchanged.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, ExtractComponent, ShaderType)]
+#[reflect(Component, Default)]
+pub struct WhiteBalanceSettings {
+    /// The color temperature, from -1 for a cool, blue view to 1 for a warm, yellow one.
+    pub temperature: f32,
+    /// The tint, from -1 for a green view to 1 for a magenta one.
+    pub tint: f32,
+}
+
+/// The render graph label of the [`WhiteBalancePlugin`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct WhiteBalance;
+
+impl Plugin for WhiteBalancePlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "white_balance.wgsl");
+        #[cfg(feature = "inspector")]
+        effects::register_inspector_ranges::<WhiteBalanceSettings>(
+            app,
+            &[("temperature", -1.0..=1.0), ("tint", -1.0..=1.0)],
+        );
+        app.register_type::<WhiteBalanceSettings>();
+
+        let vertex_state = effects::fullscreen_vertex_state(app);
+        app.add_plugins(
+            PostProcessPlugin::<WhiteBalanceSettings, _>::new(
+                SHADER_PATH,
+                WhiteBalance,
+                Some("white_balance"),
+                "white_balance_bind_group_layout",
+                vertex_state,
+            )
+            .with_placement(self.placement)
+            .without_view(),
+        );
+    }
+}
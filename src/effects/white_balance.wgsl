@@ -0,0 +1,53 @@
+// Adapts the colors from a white point shifted by the temperature and tint to D65, with a von
+// Kries transform in the LMS space of CAT02.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+struct WhiteBalanceSettings {
+    temperature: f32,
+    tint: f32,
+}
+@group(0) @binding(2) var<uniform> settings: WhiteBalanceSettings;
+
+// The rows of the matrices are written as columns, so `color * matrix` applies them
+const LINEAR_TO_LMS: mat3x3<f32> = mat3x3<f32>(
+    0.390405, 0.549941, 0.00892632,
+    0.0708416, 0.963172, 0.00135775,
+    0.0231082, 0.128021, 0.936245,
+);
+const LMS_TO_LINEAR: mat3x3<f32> = mat3x3<f32>(
+    2.85847, -1.62879, -0.024891,
+    -0.210182, 1.1582, 0.000324281,
+    -0.041812, -0.118169, 1.06867,
+);
+// D65 in the LMS space of CAT02
+const D65_LMS: vec3<f32> = vec3(0.949237, 1.03542, 1.08728);
+
+// A white point given by its CIE xy chromaticity, in the LMS space of CAT02
+fn xy_to_lms(x: f32, y: f32) -> vec3<f32> {
+    let xyz = vec3(x / y, 1.0, (1.0 - x - y) / y);
+    return vec3(
+        dot(xyz, vec3(0.7328, 0.4296, -0.1624)),
+        dot(xyz, vec3(-0.7036, 1.6975, 0.0061)),
+        dot(xyz, vec3(0.003, 0.0136, 0.9834)),
+    );
+}
+
+// The scale of every LMS channel adapting the shifted white point to D65. The white point moves
+// along the daylight locus with the temperature and across it with the tint
+fn adaptation() -> vec3<f32> {
+    let temperature = settings.temperature * 1.54;
+    let tint = settings.tint * 1.54;
+    let x = 0.31271 - temperature * select(0.05, 0.1, temperature < 0.0);
+    let daylight_y = 2.87 * x - 3.0 * x * x - 0.27509507;
+    let y = daylight_y + tint * 0.05;
+    return D65_LMS / xy_to_lms(x, y);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let lms = color.rgb * LINEAR_TO_LMS * adaptation();
+    return vec4(max(lms * LMS_TO_LINEAR, vec3(0.0)), color.a);
+}
@@ -0,0 +1,64 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{
+            binding_types::{sampler, texture_2d},
+            BindGroupLayoutEntryBuilder, FilterMode, Sampler, SamplerBindingType,
+            SamplerDescriptor, TextureFormatFeatureFlags, TextureSampleType,
+        },
+        renderer::{RenderAdapter, RenderDevice},
+        view::ViewTarget,
+    },
+};
+
+/// The shader def set when the screen texture can't be sampled with a filtering sampler.
+///
+/// The screen texture is then bound as a non-filterable texture with a nearest sampler, and
+/// shaders must use `textureLoad` or filter manually instead of relying on bilinear sampling.
+pub const SCREEN_TEXTURE_NON_FILTERABLE: &str = "SCREEN_TEXTURE_NON_FILTERABLE";
+
+/// Whether the HDR view targets can be sampled with a filtering sampler.
+///
+/// Some adapters, like downlevel WebGPU or some mobile GPUs, can't filter `Rgba16Float`. The
+/// SDR formats are always filterable, so the HDR format decides for every view.
+pub(crate) fn screen_filterable(world: &World) -> bool {
+    world.get_resource::<RenderAdapter>().is_none_or(|adapter| {
+        adapter
+            .get_texture_format_features(ViewTarget::TEXTURE_FORMAT_HDR)
+            .flags
+            .contains(TextureFormatFeatureFlags::FILTERABLE)
+    })
+}
+
+/// The layout entries of the screen texture and of its sampler.
+pub(crate) fn screen_entries(filterable: bool) -> [BindGroupLayoutEntryBuilder; 2] {
+    if filterable {
+        [
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            sampler(SamplerBindingType::Filtering),
+        ]
+    } else {
+        [
+            texture_2d(TextureSampleType::Float { filterable: false }),
+            sampler(SamplerBindingType::NonFiltering),
+        ]
+    }
+}
+
+/// Create the sampler of the screen texture, falling back to nearest when it isn't filterable.
+pub(crate) fn screen_sampler(
+    render_device: &RenderDevice,
+    filterable: bool,
+    descriptor: SamplerDescriptor,
+) -> Sampler {
+    if filterable {
+        render_device.create_sampler(&descriptor)
+    } else {
+        render_device.create_sampler(&SamplerDescriptor {
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+            mipmap_filter: FilterMode::Nearest,
+            ..descriptor
+        })
+    }
+}
@@ -0,0 +1,677 @@
+use crate::{
+    bindings,
+    condition::{self, RunCondition},
+    filtering,
+    output_pass::{self, ViewOutputChain},
+    state::SharedPipelineReadiness,
+    stats::{self, RenderedEffects},
+    uniform::SettingsUniform,
+    PostProcessBypass, PostProcessEffectInfo, PostProcessLayers, PostProcessPlacement,
+    PostProcessRegistry, PostProcessSharedPlugin,
+};
+use bevy::{
+    core_pipeline::{core_3d::graph::Core3d, FullscreenShader},
+    ecs::{
+        query::QueryItem,
+        system::{BoxedReadOnlySystem, ReadOnlySystem},
+    },
+    prelude::*,
+    render::{
+        diagnostic::RecordDiagnostics,
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_graph::{
+            InternedRenderSubGraph, NodeRunError, RenderGraphContext, RenderGraphExt,
+            RenderLabel, RenderSubGraph, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{encase::internal::WriteInto, *},
+        renderer::{RenderContext, RenderDevice},
+        view::ViewTarget,
+        Render, RenderApp, RenderSystems,
+    },
+    shader::ShaderDefVal,
+};
+use std::fmt::{Debug, Write};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+/// Runs several lightweight effects in a single fullscreen pass.
+///
+/// Every effect is a WGSL module with an `apply` function taking the color of the pixel and its
+/// UV, and returning the new color. The modules are chained in the order they were added by a
+/// generated shader, so the stack only reads and writes the view target once instead of once per
+/// effect. An effect runs on the cameras with its settings component, and is compiled out of the
+/// pipeline of the cameras without it.
+///
+/// The settings of an effect are bound in group 0 at the index given by the shader def named after
+/// its import path, `my_game::vignette` becoming `MY_GAME_VIGNETTE_SETTINGS_BINDING`. Bindings 0
+/// and 1 are the screen texture and its sampler, which are owned by the generated shader, so the
+/// modules shouldn't declare any other binding.
+///
+/// ```wgsl
+/// #define_import_path my_game::vignette
+///
+/// struct VignetteSettings {
+///     intensity: f32,
+/// }
+/// @group(0) @binding(#{MY_GAME_VIGNETTE_SETTINGS_BINDING}) var<uniform> settings: VignetteSettings;
+///
+/// fn apply(color: vec4<f32>, uv: vec2<f32>) -> vec4<f32> {
+///     let falloff = 1.0 - settings.intensity * length(uv - 0.5);
+///     return vec4(color.rgb * falloff, color.a);
+/// }
+/// ```
+///
+/// ```ignore
+/// app.add_plugins(
+///     PostProcessFusedPlugin::new(ColorStackLabel)
+///         .with_effect::<VignetteSettings>("shaders/vignette.wgsl", "my_game::vignette")
+///         .with_effect::<GrainSettings>("shaders/grain.wgsl", "my_game::grain"),
+/// );
+/// ```
+///
+/// The stack is filtered like a single effect: its layers, its run condition and a
+/// [`PostProcessBypass`] of its label apply to all of its effects.
+pub struct PostProcessFusedPlugin<R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel> {
+    label: R,
+    placement: PostProcessPlacement,
+    graph: InternedRenderSubGraph,
+    layers: PostProcessLayers,
+    effects: Vec<FusedEffect>,
+    // Taken by the plugin when it finishes, since systems can't be cloned
+    run_condition: Mutex<Option<BoxedReadOnlySystem<In<Entity>, bool>>>,
+}
+
+// An effect of the stack, type erased so the stack can hold effects with different settings
+#[derive(Clone)]
+struct FusedEffect {
+    shader_path: &'static str,
+    import_path: &'static str,
+    uniform: SettingsUniform,
+    // Registers the extraction of the settings, unless a standalone effect already did
+    add_plugins: fn(&mut App),
+    // The size of the settings, for the buffer bound on the views without them
+    min_size: u64,
+}
+
+impl FusedEffect {
+    // The name of the shader def holding the binding index of the settings
+    fn binding_def(&self) -> String {
+        format!(
+            "{}_SETTINGS_BINDING",
+            self.import_path.replace("::", "_").to_uppercase()
+        )
+    }
+}
+
+impl<R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel> PostProcessFusedPlugin<R> {
+    /// The maximum number of effects in a stack.
+    pub const MAX_EFFECTS: usize = u32::BITS as usize;
+
+    pub fn new(label: R) -> Self {
+        Self {
+            label,
+            placement: PostProcessPlacement::default(),
+            graph: Core3d.intern(),
+            layers: PostProcessLayers::default(),
+            effects: Vec::new(),
+            run_condition: Mutex::new(None),
+        }
+    }
+
+    /// Add an effect to the stack, running after the effects added before it.
+    ///
+    /// `shader_path` is the asset path of the module, loaded by the stack, and `import_path` the
+    /// path it declares with `#define_import_path`.
+    pub fn with_effect<U>(mut self, shader_path: &'static str, import_path: &'static str) -> Self
+    where
+        U: Component + ShaderType + WriteInto + Clone + ExtractComponent,
+    {
+        assert!(
+            self.effects.len() < Self::MAX_EFFECTS,
+            "too many effects in a fused stack"
+        );
+        self.effects.push(FusedEffect {
+            shader_path,
+            import_path,
+            uniform: SettingsUniform::of::<U>(),
+            add_plugins: |app| {
+                if !app.is_plugin_added::<ExtractComponentPlugin<U>>() {
+                    app.add_plugins(ExtractComponentPlugin::<U>::default());
+                }
+            },
+            min_size: U::min_size().get(),
+        });
+        self
+    }
+
+    /// Set where the stack runs relative to Bevy's built-in post processing nodes.
+    pub fn with_placement(mut self, placement: PostProcessPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Set the render graph the stack node is added to. Defaults to [`Core3d`].
+    pub fn in_graph(mut self, graph: impl RenderSubGraph) -> Self {
+        self.graph = graph.intern();
+        self
+    }
+
+    /// Only run the stack on the cameras on any of these layers.
+    pub fn with_layers(mut self, layers: PostProcessLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Only run the stack on the cameras for which `condition` returns `true`, see
+    /// [`PostProcessPlugin::with_run_condition`](crate::PostProcessPlugin::with_run_condition).
+    pub fn with_run_condition<M, C>(self, condition: C) -> Self
+    where
+        C: IntoSystem<In<Entity>, bool, M>,
+        C::System: ReadOnlySystem,
+    {
+        *self.run_condition.lock().unwrap() = Some(condition::boxed_condition(condition));
+        self
+    }
+
+    // The bindings of the generated shader, the settings named after the module of their effect
+    fn bindings(&self) -> Vec<(u32, String)> {
+        [
+            (bindings::SCREEN_TEXTURE, "screen_texture".to_string()),
+            (bindings::SAMPLER, "sampler".to_string()),
+        ]
+        .into_iter()
+        .chain(
+            self.effects.iter().enumerate().map(|(index, effect)| {
+                (2 + index as u32, format!("{} settings", effect.import_path))
+            }),
+        )
+        .collect()
+    }
+
+    // The shader chaining the `apply` functions of the effects
+    fn shader_source(&self) -> String {
+        let mut source = String::from(
+            "#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput\n",
+        );
+        for effect in &self.effects {
+            let _ = writeln!(source, "#import {}", effect.import_path);
+        }
+        source.push_str(
+            "
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    var color = textureSample(screen_texture, texture_sampler, in.uv);
+",
+        );
+        for (index, effect) in self.effects.iter().enumerate() {
+            let _ = write!(
+                source,
+                "#ifdef FUSED_EFFECT_{index}\n    color = {}::apply(color, in.uv);\n#endif\n",
+                effect.import_path
+            );
+        }
+        source.push_str("    return color;\n}\n");
+        source
+    }
+}
+
+impl<R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel> Plugin for PostProcessFusedPlugin<R> {
+    fn build(&self, app: &mut App) {
+        for effect in &self.effects {
+            (effect.add_plugins)(app);
+            effect.uniform.add_plugin(app);
+        }
+
+        // The modules import the shader libraries of the crate, and the stack is filtered with
+        // the shared components
+        if !app.is_plugin_added::<PostProcessSharedPlugin>() {
+            app.add_plugins(PostProcessSharedPlugin);
+        }
+        app.world_mut()
+            .resource_mut::<PostProcessRegistry>()
+            .register(PostProcessEffectInfo {
+                label: self.label.intern(),
+                graph: self.graph,
+                placement: self.placement,
+                settings_type: std::any::type_name::<Self>(),
+                settings_type_id: std::any::TypeId::of::<Self>(),
+                bindings: self.bindings(),
+            });
+
+        // The modules are kept loaded for the generated shader to import them
+        let modules = self
+            .effects
+            .iter()
+            .map(|effect| app.world().resource::<AssetServer>().load(effect.shader_path))
+            .collect();
+        let shader = app.world_mut().resource_mut::<Assets<Shader>>().add(Shader::from_wgsl(
+            self.shader_source(),
+            format!("post_process_fused/{:?}.wgsl", self.label),
+        ));
+        app.insert_resource(FusedShaders::<R> {
+            _modules: modules,
+            shader: shader.clone(),
+            _marker: PhantomData,
+        });
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        let (before, after) = self.placement.edges();
+        if self.placement.writes_output() {
+            output_pass::add_output_pass(render_app, self.graph);
+        }
+        render_app
+            .insert_resource(FusedShaders::<R> {
+                _modules: Vec::new(),
+                shader,
+                _marker: PhantomData,
+            })
+            .insert_resource(FusedStack::<R> {
+                label: self.label.clone(),
+                layers: self.layers,
+                effects: self.effects.clone(),
+                writes_output: self.placement.writes_output(),
+            })
+            .add_systems(
+                Render,
+                (
+                    prepare_fused_pipelines::<R>.in_set(RenderSystems::Prepare),
+                    update_fused_readiness::<R>.in_set(RenderSystems::Cleanup),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<FusedNode<R>>>(self.graph, self.label.clone())
+            .add_render_graph_edges(self.graph, (before, self.label.clone(), after));
+    }
+
+    fn finish(&self, app: &mut App) {
+        // The condition is initialized on the main world it runs on
+        let run_condition = self
+            .run_condition
+            .lock()
+            .unwrap()
+            .take()
+            .map(|condition| RunCondition::<(), R>::new(condition, app.world_mut()));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        if let Some(run_condition) = run_condition {
+            render_app.insert_resource(run_condition).add_systems(
+                ExtractSchedule,
+                condition::extract_stack_run_conditions::<R>,
+            );
+        }
+
+        render_app
+            .init_resource::<FusedPipeline<R>>()
+            .init_resource::<SpecializedRenderPipelines<FusedPipeline<R>>>();
+    }
+}
+
+#[derive(Resource)]
+struct FusedShaders<R> {
+    _modules: Vec<Handle<Shader>>,
+    shader: Handle<Shader>,
+    _marker: PhantomData<R>,
+}
+
+#[derive(Resource)]
+struct FusedStack<R> {
+    label: R,
+    layers: PostProcessLayers,
+    effects: Vec<FusedEffect>,
+    // Whether the stack runs after the upscaling, see [`PostProcessPlacement::AfterUpscaling`]
+    writes_output: bool,
+}
+
+#[derive(Resource)]
+struct FusedPipeline<R> {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    // Bound instead of the settings of the effects a view doesn't run
+    fallback_buffers: Vec<Buffer>,
+    binding_defs: Vec<String>,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+    _marker: PhantomData<R>,
+}
+
+impl<R: Send + Sync + 'static> FromWorld for FusedPipeline<R> {
+    fn from_world(world: &mut World) -> Self {
+        let stack = world.resource::<FusedStack<R>>();
+        let render_device = world.resource::<RenderDevice>();
+
+        // Like the effects it fuses, the stack falls back to a non-filterable screen texture when
+        // the HDR format can't be filtered
+        let filterable = filtering::screen_filterable(world);
+        let [screen_texture, screen_sampler] = filtering::screen_entries(filterable);
+        let visibility = ShaderStages::FRAGMENT;
+        let entries: Vec<_> = [
+            screen_texture.build(0, visibility),
+            screen_sampler.build(1, visibility),
+        ]
+        .into_iter()
+        .chain(stack.effects.iter().enumerate().map(|(index, effect)| {
+            effect
+                .uniform
+                .layout_entry()
+                .build(2 + index as u32, visibility)
+        }))
+        .collect();
+        let layout = render_device.create_bind_group_layout("post_process_fused_layout", &entries);
+
+        let fallback_buffers = stack
+            .effects
+            .iter()
+            .map(|effect| {
+                render_device.create_buffer(&BufferDescriptor {
+                    label: Some("post_process_fused_fallback_settings"),
+                    size: effect.min_size,
+                    usage: BufferUsages::UNIFORM,
+                    mapped_at_creation: false,
+                })
+            })
+            .collect();
+
+        Self {
+            layout,
+            sampler: filtering::screen_sampler(
+                render_device,
+                filterable,
+                SamplerDescriptor::default(),
+            ),
+            fallback_buffers,
+            binding_defs: stack.effects.iter().map(FusedEffect::binding_def).collect(),
+            shader: world.resource::<FusedShaders<R>>().shader.clone(),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct FusedPipelineKey {
+    texture_format: TextureFormat,
+    // One bit per effect of the stack the view runs
+    effects: u32,
+}
+
+impl<R: Send + Sync + 'static> SpecializedRenderPipeline for FusedPipeline<R> {
+    type Key = FusedPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let shader_defs = self
+            .binding_defs
+            .iter()
+            .enumerate()
+            .flat_map(|(index, binding_def)| {
+                let binding = ShaderDefVal::UInt(binding_def.clone(), 2 + index as u32);
+                let enabled = (key.effects & (1 << index) != 0)
+                    .then(|| ShaderDefVal::from(format!("FUSED_EFFECT_{index}")));
+                std::iter::once(binding).chain(enabled)
+            })
+            .collect();
+
+        RenderPipelineDescriptor {
+            label: Some("post_process_fused_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some("fragment".into()),
+                targets: vec![Some(ColorTargetState {
+                    format: key.texture_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+/// The pipeline of the stack specialized for the effects a view runs.
+#[derive(Component)]
+struct ViewFusedPipeline<R: Send + Sync + 'static> {
+    pipeline_id: CachedRenderPipelineId,
+    effects: u32,
+    _marker: PhantomData<R>,
+}
+
+/// Specialize the pipeline of the stack for the effects of every view.
+///
+/// The settings types are erased, so the views are checked with the world directly.
+fn prepare_fused_pipelines<R: Send + Sync + 'static>(world: &mut World) {
+    let mut views = world.query::<(Entity, &ViewTarget)>();
+    let stack = world.resource::<FusedStack<R>>();
+    let keys: Vec<_> = views
+        .iter(world)
+        .map(|(entity, view_target)| {
+            let effects = stack
+                .effects
+                .iter()
+                .enumerate()
+                .filter(|(_, effect)| effect.uniform.index(world, entity).is_some())
+                .fold(0, |effects, (index, _)| effects | (1 << index));
+            let key = FusedPipelineKey {
+                texture_format: match stack.writes_output {
+                    true => view_target.out_texture_format(),
+                    false => view_target.main_texture_format(),
+                },
+                effects,
+            };
+            (entity, key)
+        })
+        .collect();
+
+    world.resource_scope(
+        |world, mut pipelines: Mut<SpecializedRenderPipelines<FusedPipeline<R>>>| {
+            for (entity, key) in keys {
+                // Views without any effect of the stack skip the pass
+                if key.effects == 0 {
+                    world.entity_mut(entity).remove::<ViewFusedPipeline<R>>();
+                    continue;
+                }
+                let pipeline_id = pipelines.specialize(
+                    world.resource::<PipelineCache>(),
+                    world.resource::<FusedPipeline<R>>(),
+                    key,
+                );
+                world.entity_mut(entity).insert(ViewFusedPipeline::<R> {
+                    pipeline_id,
+                    effects: key.effects,
+                    _marker: PhantomData,
+                });
+            }
+        },
+    );
+}
+
+/// Track whether the pipeline of the stack is compiled for any of the views running it.
+fn update_fused_readiness<R: Send + Sync + 'static + Clone + RenderLabel>(
+    stack: Res<FusedStack<R>>,
+    pipeline_cache: Res<PipelineCache>,
+    readiness: Res<SharedPipelineReadiness>,
+    views: Query<&ViewFusedPipeline<R>>,
+) {
+    // Like the other effects, the stack is only tracked once a view runs it
+    if views.is_empty() {
+        return;
+    }
+    let ready = views.iter().any(|view_pipeline| {
+        pipeline_cache
+            .get_render_pipeline(view_pipeline.pipeline_id)
+            .is_some()
+    });
+    readiness.track(stack.label.intern(), ready);
+}
+
+struct FusedNode<R>(PhantomData<R>);
+
+impl<R> FromWorld for FusedNode<R> {
+    fn from_world(_world: &mut World) -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<R: Send + Sync + 'static + Clone + RenderLabel> ViewNode for FusedNode<R> {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewFusedPipeline<R>,
+        Option<&'static PostProcessLayers>,
+        Option<&'static PostProcessBypass>,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, view_pipeline, view_layers, bypass): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let stack = world.resource::<FusedStack<R>>();
+
+        // The same filtering as a single effect, for the whole stack
+        if !stack
+            .layers
+            .intersects(view_layers.unwrap_or(&PostProcessLayers::default()))
+        {
+            return Ok(());
+        }
+        if bypass.is_some_and(|bypass| bypass.is_bypassed(stack.label.clone())) {
+            return Ok(());
+        }
+        if let Some(run_condition) = world.get_resource::<RunCondition<(), R>>()
+            && !run_condition.passes(graph.view_entity())
+        {
+            return Ok(());
+        }
+
+        let fused_pipeline = world.resource::<FusedPipeline<R>>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(view_pipeline.pipeline_id) else {
+            return Ok(());
+        };
+
+        // The effects the view doesn't run are compiled out, but their binding is still part of
+        // the layout
+        let mut settings = Vec::with_capacity(stack.effects.len());
+        for (index, effect) in stack.effects.iter().enumerate() {
+            let binding = if view_pipeline.effects & (1 << index) != 0 {
+                let (Some(binding), Some(settings_index)) = (
+                    effect.uniform.binding(world),
+                    effect.uniform.index(world, graph.view_entity()),
+                ) else {
+                    return Ok(());
+                };
+                (binding, settings_index)
+            } else {
+                let fallback = BindingResource::Buffer(BufferBinding {
+                    buffer: &fused_pipeline.fallback_buffers[index],
+                    offset: 0,
+                    size: BufferSize::new(effect.min_size),
+                });
+                (fallback, 0)
+            };
+            settings.push(binding);
+        }
+
+        // After the upscaling, the stack renders into the output textures of the view
+        let output_chain = match stack.writes_output {
+            true => {
+                let Some(output_chain) = world.get::<ViewOutputChain>(graph.view_entity()) else {
+                    return Ok(());
+                };
+                let Some(blit_pipeline) =
+                    pipeline_cache.get_render_pipeline(output_chain.blit_pipeline_id)
+                else {
+                    return Ok(());
+                };
+                Some((output_chain, blit_pipeline))
+            }
+            false => None,
+        };
+        let (source, destination) = match output_chain {
+            Some((output_chain, _)) => output_chain.write(view_target),
+            None => {
+                let post_process = view_target.post_process_write();
+                (post_process.source, post_process.destination)
+            }
+        };
+
+        let entries: Vec<_> = [
+            BindGroupEntry {
+                binding: 0,
+                resource: source.into_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: fused_pipeline.sampler.into_binding(),
+            },
+        ]
+        .into_iter()
+        .chain(
+            settings
+                .iter()
+                .enumerate()
+                .map(|(index, (binding, _))| BindGroupEntry {
+                    binding: 2 + index as u32,
+                    resource: binding.clone(),
+                }),
+        )
+        .collect();
+        let bind_group = render_context.render_device().create_bind_group(
+            "post_process_fused_bind_group",
+            &fused_pipeline.layout,
+            &entries,
+        );
+
+        let diagnostics = render_context.diagnostic_recorder();
+        let time_span = diagnostics.time_span(
+            render_context.command_encoder(),
+            stats::span_name(stack.label.clone()),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("post_process_fused_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: destination,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        // The dynamic offsets are ordered by binding index, which follows the order of the effects
+        let dynamic_offsets: Vec<_> = settings.iter().map(|(_, offset)| *offset).collect();
+        render_pass.set_bind_group(0, &bind_group, &dynamic_offsets);
+        render_pass.draw(0..3, 0..1);
+        drop(render_pass);
+
+        if let Some((output_chain, blit_pipeline)) = output_chain {
+            output_chain.blit(render_context, world, view_target, blit_pipeline, destination);
+        }
+
+        time_span.end(render_context.command_encoder());
+        if let Some(rendered) = world.get_resource::<RenderedEffects>() {
+            rendered.insert(stack.label.intern());
+        }
+
+        Ok(())
+    }
+}
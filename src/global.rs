@@ -0,0 +1,79 @@
+use crate::mask::MaskCamera;
+use bevy::{
+    prelude::*,
+    render::{sync_world::RenderEntity, Extract, ExtractSchedule, RenderApp},
+};
+use std::marker::PhantomData;
+
+/// Present when the effect with the settings `U` can run on every camera.
+#[derive(Resource)]
+pub(crate) struct GlobalEffect<U> {
+    // Whether the settings resource exists, updated at the start of every frame
+    active: bool,
+    _marker: PhantomData<U>,
+}
+
+/// The cameras an effect can run on, leaving out the mask cameras.
+pub(crate) type EffectCameraFilter = (With<Camera>, Without<MaskCamera>);
+
+// The cameras without settings of their own
+type CamerasWithoutSettings<U> = (With<Camera>, Without<U>, Without<MaskCamera>);
+
+/// Whether a camera runs the effect, either with its own settings or with the global ones.
+pub(crate) fn runs_effect<U>(has_settings: bool, global: &Option<Res<GlobalEffect<U>>>) -> bool
+where
+    U: Send + Sync + 'static,
+{
+    has_settings || global.as_ref().is_some_and(|global| global.active)
+}
+
+// Makes the effect run on every camera with the settings of the `U` resource
+pub(crate) fn add_global_settings<U: Component + Resource + Clone>(app: &mut App) {
+    app.insert_resource(GlobalEffect::<U> {
+        active: false,
+        _marker: PhantomData,
+    })
+    .add_systems(First, track_global_settings::<U>);
+    if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+        render_app.add_systems(ExtractSchedule, extract_global_settings::<U>);
+    }
+}
+
+/// Follow the settings resource, so the effect stops running on the cameras without settings of
+/// their own when it is removed.
+fn track_global_settings<U: Resource>(
+    mut global: ResMut<GlobalEffect<U>>,
+    settings: Option<Res<U>>,
+) {
+    let active = settings.is_some();
+    // Only written on changes, to keep the change detection of the resource quiet
+    if global.active != active {
+        global.active = active;
+    }
+}
+
+/// Copy the settings resource to every camera that doesn't have its own settings.
+fn extract_global_settings<U: Component + Resource + Clone>(
+    mut commands: Commands,
+    settings: Extract<Option<Res<U>>>,
+    cameras: Extract<Query<RenderEntity, CamerasWithoutSettings<U>>>,
+    // Whether the settings were copied last frame
+    mut copied: Local<bool>,
+) {
+    match settings.as_deref() {
+        Some(settings) => {
+            for render_entity in &cameras {
+                commands.entity(render_entity).insert(settings.clone());
+            }
+            *copied = true;
+        }
+        // The effect stops when the resource is removed
+        None if *copied => {
+            for render_entity in &cameras {
+                commands.entity(render_entity).remove::<U>();
+            }
+            *copied = false;
+        }
+        None => {}
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{PostProcessPlacement, PostProcessPlugin};
+use bevy::{
+    app::PluginGroupBuilder,
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        render_graph::{InternedRenderLabel, RenderLabel},
+    },
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+type AddEffect = Box<dyn FnOnce(PluginGroupBuilder) -> PluginGroupBuilder + Send + Sync>;
+
+/// Registers a stack of effects at once, running them in the order they were added.
+///
+/// The first effect keeps its [`PostProcessPlacement`], and every following effect is placed right
+/// after the previous one, so the placement of the following effects is ignored.
+///
+/// ```ignore
+/// app.add_plugins(
+///     PostProcessPlugins::default()
+///         .with(PostProcessPlugin::<BlurSettings, BlurLabel>::new(/* ... */))
+///         .with(PostProcessPlugin::<GrainSettings, GrainLabel>::new(/* ... */)),
+/// );
+/// ```
+#[derive(Default)]
+pub struct PostProcessPlugins {
+    effects: Vec<AddEffect>,
+    // The label of the last effect and the node it runs before
+    last: Option<(InternedRenderLabel, InternedRenderLabel)>,
+}
+
+impl PostProcessPlugins {
+    /// Add an effect running after the effects added before it.
+    pub fn with<
+        U: Component + Clone + ExtractComponent,
+        R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+    >(
+        mut self,
+        mut plugin: PostProcessPlugin<U, R>,
+    ) -> Self {
+        let settings = &mut plugin.post_process_plugin_settings;
+        if let Some((previous, after)) = self.last {
+            settings.placement = PostProcessPlacement::Between(previous, after);
+        }
+        let (_, after) = settings.placement.edges();
+        self.last = Some((settings.label.intern(), after));
+
+        self.effects
+            .push(Box::new(move |group: PluginGroupBuilder| group.add(plugin)));
+        self
+    }
+}
+
+impl PluginGroup for PostProcessPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        self.effects
+            .into_iter()
+            .fold(PluginGroupBuilder::start::<Self>(), |group, add_effect| {
+                add_effect(group)
+            })
+    }
+}
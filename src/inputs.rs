@@ -0,0 +1,83 @@
+use crate::PostProcessPluginSettings;
+use bevy::{
+    camera::RenderTarget,
+    prelude::*,
+    render::{render_graph::RenderLabel, Extract},
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// An additional texture bound to the effect shader.
+///
+/// Inputs are declared with [`PostProcessPlugin::with_input`](crate::PostProcessPlugin::with_input)
+/// and bound starting at [`bindings::FIRST_INPUT`](crate::bindings::FIRST_INPUT),
+/// in the order they were declared. They are sampled with the screen sampler.
+/// The same sources can be used for the mask of an effect, see
+/// [`PostProcessPlugin::with_mask`](crate::PostProcessPlugin::with_mask).
+///
+/// While an input isn't available yet, like an image that is still loading,
+/// a white fallback texture is bound instead.
+#[derive(Clone, Debug)]
+pub enum PostProcessInput {
+    /// An image asset.
+    Image(Handle<Image>),
+    /// The image render target of another camera, like a security camera feed or a portal view.
+    ///
+    /// The camera must target a [`RenderTarget::Image`] and render before the cameras running
+    /// the effect, which means it needs a lower [`Camera::order`].
+    CameraTarget(Entity),
+}
+
+// The images of the inputs of an effect, resolved every frame in the render world.
+#[derive(Resource)]
+pub(crate) struct ResolvedInputs<U, R> {
+    pub(crate) images: Vec<Option<AssetId<Image>>>,
+    pub(crate) mask: Option<AssetId<Image>>,
+    _marker: PhantomData<(U, R)>,
+}
+
+impl<U, R> Default for ResolvedInputs<U, R> {
+    fn default() -> Self {
+        Self {
+            images: Vec::new(),
+            mask: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Resolve the images of the inputs of an effect, following the render targets of cameras.
+pub(crate) fn extract_inputs<
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    plugin_settings: Res<PostProcessPluginSettings<U, R>>,
+    mut resolved_inputs: ResMut<ResolvedInputs<U, R>>,
+    cameras: Extract<Query<&Camera>>,
+) {
+    resolved_inputs.images.clear();
+    resolved_inputs.images.extend(
+        plugin_settings
+            .inputs
+            .iter()
+            .map(|input| resolve_input(input, &cameras)),
+    );
+    resolved_inputs.mask = plugin_settings
+        .mask
+        .as_ref()
+        .and_then(|mask| resolve_input(mask, &cameras));
+}
+
+fn resolve_input(input: &PostProcessInput, cameras: &Query<&Camera>) -> Option<AssetId<Image>> {
+    match input {
+        PostProcessInput::Image(image) => Some(image.id()),
+        PostProcessInput::CameraTarget(entity) => match cameras.get(*entity) {
+            Ok(Camera {
+                target: RenderTarget::Image(image_target),
+                ..
+            }) => Some(image_target.handle.id()),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,102 @@
+use crate::PostProcessRegistry;
+use bevy::{
+    ecs::reflect::AppTypeRegistry,
+    prelude::*,
+    reflect::{GetTypeRegistration, TypeInfo},
+};
+use bevy_egui::egui;
+use bevy_inspector_egui::{
+    inspector_options::{std_options::NumberOptions, ReflectInspectorOptions, Target},
+    reflect_inspector::ui_for_value,
+};
+use std::any::TypeId;
+use std::ops::RangeInclusive;
+
+/// Registers the inspector ranges of the fields of effect settings.
+pub trait PostProcessInspectorAppExt {
+    /// Limit a numeric field of the settings `U` to `range` in the inspector.
+    ///
+    /// Unlike `#[derive(InspectorOptions)]`, this doesn't require the settings type to depend on
+    /// bevy-inspector-egui, so the ranges can be registered next to the effect plugin.
+    fn register_post_process_range<U: Component + GetTypeRegistration>(
+        &mut self,
+        field: &str,
+        range: RangeInclusive<f32>,
+    ) -> &mut Self;
+}
+
+impl PostProcessInspectorAppExt for App {
+    fn register_post_process_range<U: Component + GetTypeRegistration>(
+        &mut self,
+        field: &str,
+        range: RangeInclusive<f32>,
+    ) -> &mut Self {
+        self.register_type::<U>();
+        let type_registry = self.world().resource::<AppTypeRegistry>().clone();
+        let mut type_registry = type_registry.write();
+        let registration = type_registry.get_mut(TypeId::of::<U>()).unwrap();
+
+        let index = match registration.type_info() {
+            TypeInfo::Struct(info) => info.index_of(field),
+            _ => None,
+        };
+        let Some(index) = index else {
+            warn!("{} has no field named {field}", std::any::type_name::<U>());
+            return self;
+        };
+
+        let mut options = registration
+            .data::<ReflectInspectorOptions>()
+            .map(|options| options.0.clone())
+            .unwrap_or_default();
+        options.insert(
+            Target::Field(index),
+            NumberOptions::between(*range.start(), *range.end()),
+        );
+        registration.insert(ReflectInspectorOptions(options));
+        self
+    }
+}
+
+/// Show the settings of every effect on `camera` in a single "Post processing" section.
+///
+/// Meant to be called from a custom inspector window, instead of scrolling through the effect
+/// components among the other components of the camera. Settings that aren't registered with
+/// `#[reflect(Component)]` are skipped.
+pub fn ui_for_post_process(world: &mut World, camera: Entity, ui: &mut egui::Ui) {
+    let effects: Vec<_> = world
+        .resource::<PostProcessRegistry>()
+        .effects()
+        .cloned()
+        .collect();
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+
+    egui::CollapsingHeader::new("Post processing")
+        .id_salt(("post_process", camera))
+        .show(ui, |ui| {
+            for effect in effects {
+                let Some(reflect_component) =
+                    type_registry.get_type_data::<ReflectComponent>(effect.settings_type_id)
+                else {
+                    continue;
+                };
+                let mut entity = world.entity_mut(camera);
+                let Some(mut settings) = reflect_component.reflect_mut(&mut entity) else {
+                    continue;
+                };
+
+                ui.label(format!("{:?}", effect.label));
+                ui.push_id(effect.label, |ui| {
+                    // Only trigger change detection when a field was actually edited
+                    if ui_for_value(
+                        settings.bypass_change_detection().as_partial_reflect_mut(),
+                        ui,
+                        &type_registry,
+                    ) {
+                        settings.set_changed();
+                    }
+                });
+            }
+        });
+}
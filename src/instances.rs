@@ -0,0 +1,116 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{
+            binding_types::{storage_buffer_read_only, uniform_buffer},
+            encase::internal::WriteInto,
+            BindGroupLayoutEntryBuilder, BindingResource, ShaderSize, ShaderType, StorageBuffer,
+            UniformBuffer,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        Extract, ExtractSchedule, Render, RenderApp, RenderSystems,
+    },
+};
+
+/// The instances of an effect, gathered from every entity with the instance component.
+///
+/// Type erased so the bounds of the instance component are only needed by the builder.
+#[derive(Clone, Copy)]
+pub(crate) struct EffectInstances {
+    add_systems: fn(&mut App),
+    layout_entries: fn() -> [BindGroupLayoutEntryBuilder; 2],
+    bindings: for<'w> fn(&'w World) -> Option<[BindingResource<'w>; 2]>,
+}
+
+impl EffectInstances {
+    pub(crate) fn of<I>() -> Self
+    where
+        I: Component + ShaderType + ShaderSize + WriteInto + Clone + Default,
+    {
+        Self {
+            add_systems: |app| {
+                let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+                    return;
+                };
+                // The instances are shared by the effects using the same component
+                if render_app.world().contains_resource::<InstanceBuffers<I>>() {
+                    return;
+                }
+                render_app
+                    .init_resource::<InstanceBuffers<I>>()
+                    .add_systems(ExtractSchedule, extract_instances::<I>)
+                    .add_systems(
+                        Render,
+                        prepare_instances::<I>.in_set(RenderSystems::PrepareResources),
+                    );
+            },
+            layout_entries: || {
+                [
+                    storage_buffer_read_only::<I>(false),
+                    uniform_buffer::<u32>(false),
+                ]
+            },
+            bindings: |world| {
+                let buffers = world.get_resource::<InstanceBuffers<I>>()?;
+                Some([buffers.instances.binding()?, buffers.count.binding()?])
+            },
+        }
+    }
+
+    pub(crate) fn add_systems(&self, app: &mut App) {
+        (self.add_systems)(app);
+    }
+
+    /// The layout entries of the instances array and of their count.
+    pub(crate) fn layout_entries(&self) -> [BindGroupLayoutEntryBuilder; 2] {
+        (self.layout_entries)()
+    }
+
+    /// The bindings of the instances array and of their count.
+    pub(crate) fn bindings<'w>(&self, world: &'w World) -> Option<[BindingResource<'w>; 2]> {
+        (self.bindings)(world)
+    }
+}
+
+#[derive(Resource)]
+struct InstanceBuffers<I: ShaderType + ShaderSize + WriteInto> {
+    instances: StorageBuffer<Vec<I>>,
+    count: UniformBuffer<u32>,
+}
+
+impl<I: ShaderType + ShaderSize + WriteInto> Default for InstanceBuffers<I> {
+    fn default() -> Self {
+        let mut instances = StorageBuffer::default();
+        instances.set_label(Some("post_process_instances"));
+        Self {
+            instances,
+            count: UniformBuffer::default(),
+        }
+    }
+}
+
+/// Gather the instances of every entity with the instance component.
+fn extract_instances<I: Component + ShaderType + ShaderSize + WriteInto + Clone + Default>(
+    mut buffers: ResMut<InstanceBuffers<I>>,
+    instances: Extract<Query<&I>>,
+) {
+    let buffers = &mut *buffers;
+    let values = buffers.instances.get_mut();
+    values.clear();
+    values.extend(instances.iter().cloned());
+    buffers.count.set(values.len() as u32);
+    // Empty buffers can't be bound, the count tells the shader there are no instances
+    if values.is_empty() {
+        values.push(I::default());
+    }
+}
+
+fn prepare_instances<I: ShaderType + ShaderSize + WriteInto + Send + Sync + 'static>(
+    mut buffers: ResMut<InstanceBuffers<I>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let buffers = &mut *buffers;
+    buffers.instances.write_buffer(&render_device, &render_queue);
+    buffers.count.write_buffer(&render_device, &render_queue);
+}
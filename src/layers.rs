@@ -0,0 +1,59 @@
+use bevy::{prelude::*, render::extract_component::ExtractComponent};
+
+/// A bit mask describing which post process layers a camera belongs to.
+///
+/// This works like `RenderLayers`, but for post processing effects instead of meshes.
+/// An effect only runs on a camera when the layers of the effect (see
+/// [`PostProcessPlugin::with_layers`](crate::PostProcessPlugin::with_layers))
+/// intersect with the layers of the camera.
+///
+/// Cameras without this component are considered to be on layer 0 only,
+/// which is also the default layer of every effect.
+#[derive(Component, ExtractComponent, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PostProcessLayers(u32);
+
+impl PostProcessLayers {
+    /// The total number of layers supported.
+    pub const TOTAL_LAYERS: usize = u32::BITS as usize;
+
+    /// Create a new mask belonging to the given layer only.
+    pub const fn layer(layer: usize) -> Self {
+        Self(0).with(layer)
+    }
+
+    /// Create a new mask belonging to every layer.
+    pub const fn all() -> Self {
+        Self(u32::MAX)
+    }
+
+    /// Create a new mask belonging to no layer.
+    /// An effect or camera with this mask will never match anything.
+    pub const fn none() -> Self {
+        Self(0)
+    }
+
+    /// Add the given layer to the mask.
+    pub const fn with(mut self, layer: usize) -> Self {
+        assert!(layer < Self::TOTAL_LAYERS, "post process layer out of range");
+        self.0 |= 1 << layer;
+        self
+    }
+
+    /// Remove the given layer from the mask.
+    pub const fn without(mut self, layer: usize) -> Self {
+        assert!(layer < Self::TOTAL_LAYERS, "post process layer out of range");
+        self.0 &= !(1 << layer);
+        self
+    }
+
+    /// Returns true if the two masks share at least one layer.
+    pub const fn intersects(&self, other: &PostProcessLayers) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl Default for PostProcessLayers {
+    fn default() -> Self {
+        Self::layer(0)
+    }
+}
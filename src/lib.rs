@@ -1,42 +1,238 @@
 use bevy::render::render_resource::encase::internal::WriteInto;
-use bevy::render::view::{ViewUniform, ViewUniformOffset, ViewUniforms};
+use bevy::render::view::{ViewUniformOffset, ViewUniforms};
 use bevy::{
-    core_pipeline::core_3d::graph::{Core3d, Node3d},
-    ecs::query::QueryItem,
+    asset::embedded_asset,
+    core_pipeline::{core_3d::graph::Core3d, tonemapping::Tonemapping},
+    ecs::{
+        query::QueryItem,
+        system::{BoxedReadOnlySystem, ReadOnlySystem, ScheduleSystem},
+    },
     prelude::*,
     render::{
-        extract_component::{
-            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
-            UniformComponentPlugin,
-        },
+        diagnostic::RecordDiagnostics,
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
         render_graph::{
-            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode, ViewNodeRunner,
-        },
-        render_resource::{
-            binding_types::{sampler, texture_2d, uniform_buffer},
-            *,
+            InternedRenderSubGraph, NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel,
+            RenderSubGraph, ViewNode, ViewNodeRunner,
         },
+        render_asset::RenderAssets,
+        render_phase::TrackedRenderPass,
+        render_resource::*,
         renderer::{RenderContext, RenderDevice},
-        view::ViewTarget,
-        RenderApp,
+        texture::{FallbackImage, FallbackImageZero, GpuImage},
+        view::{Msaa, ViewDepthTexture, ViewTarget},
+        ExtractSchedule, Render, RenderApp, RenderSystems,
     },
+    shader::{load_shader_library, ShaderDefVal},
 };
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::Mutex;
+
+pub mod bindings;
+pub mod noise;
+#[cfg(feature = "effects")]
+pub mod effects;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+mod activity;
+mod adaptive;
+#[cfg(feature = "animation")]
+mod animation;
+#[cfg(feature = "audio")]
+mod audio;
+mod bind_group;
+mod bypass;
+mod color_space;
+mod condition;
+mod depth;
+mod filtering;
+mod fused;
+mod global;
+mod group;
+mod inputs;
+mod instances;
+#[cfg(feature = "inspector")]
+mod inspector;
+mod layers;
+mod mask;
+mod output_pass;
+mod outputs;
+#[cfg(feature = "egui")]
+mod panel;
+mod params;
+mod pipeline;
+mod placement;
+mod prewarm;
+mod quality;
+mod queue;
+mod registry;
+mod resolution;
+mod sequence;
+mod snapshot;
+mod state;
+mod stats;
+mod stencil;
+mod texture_pool;
+mod tonemapping;
+mod uniform;
+mod view_data;
+mod view_info;
+
+pub use activity::{PostProcessActivated, PostProcessDeactivated};
+pub use adaptive::{PostProcessAdaptiveQuality, PostProcessAdaptiveQualityPlugin};
+#[cfg(feature = "animation")]
+pub use animation::PostProcessEnabled;
+#[cfg(feature = "audio")]
+pub use audio::{PostProcessAudio, PostProcessAudioPlugin, AUDIO_BANDS};
+pub use bind_group::{PostProcessBindGroup, PostProcessBindGroupLayout};
+pub use bypass::PostProcessBypass;
+pub use color_space::PostProcessColorSpace;
+pub use filtering::SCREEN_TEXTURE_NON_FILTERABLE;
+pub use fused::PostProcessFusedPlugin;
+pub use group::PostProcessPlugins;
+pub use inputs::PostProcessInput;
+#[cfg(feature = "inspector")]
+pub use inspector::{ui_for_post_process, PostProcessInspectorAppExt};
+pub use layers::PostProcessLayers;
+pub use mask::PostProcessMask;
+pub use noise::PostProcessNoisePlugin;
+pub use outputs::{PostProcessDestination, PostProcessOutputs};
+#[cfg(feature = "egui")]
+pub use panel::PostProcessEguiPlugin;
+pub use params::{PostProcessParam, PostProcessParams, MAX_PARAMS};
+pub use pipeline::{PostProcessPipelineKey, PostProcessShaderOverride};
+pub use placement::PostProcessPlacement;
+pub use prewarm::PostProcessPrewarm;
+pub use quality::{PostProcessQuality, PostProcessQualityTier};
+pub use registry::{
+    PostProcessDebugDump, PostProcessEffectDump, PostProcessEffectInfo, PostProcessRegistry,
+};
+pub use resolution::PostProcessUpscaleFilter;
+pub use sequence::{
+    PostProcessSequence, PostProcessSequenceFinished, PostProcessSequencePlayer,
+    PostProcessSequenceValue,
+};
+pub use snapshot::PostProcessSnapshot;
+pub use state::{PostProcessReady, PostProcessState};
+pub use stats::{PostProcessEffectStats, PostProcessStats, PostProcessStatsPlugin};
+pub use stencil::{PostProcessStencil, PostProcessStencilTest};
+pub use texture_pool::PostProcessTexturePool;
+pub use view_data::PostProcessViewData;
+
+use activity::ActiveCameras;
+use condition::RunCondition;
+use depth::{
+    DepthRangePipeline, DepthRangeUniform, PostProcessDepthRangePlugin, ViewPostProcessDepthRange,
+};
+use inputs::ResolvedInputs;
+use instances::EffectInstances;
+use pipeline::{PostProcessPipeline, ViewPostProcessPipeline};
+use output_pass::ViewOutputChain;
+use queue::ViewQueuedBindGroups;
+use resolution::{CompositePipeline, ViewPostProcessIntermediate};
+use mask::{MaskCameraPlugin, ViewMask};
+use state::SharedPipelineReadiness;
+use stats::RenderedEffects;
+use stencil::{PostProcessStencilPlugin, StencilWritePipeline, ViewPostProcessStencil};
+use uniform::SettingsUniform;
+
+type AddPrepareSystems = Box<dyn FnOnce(&mut SubApp) + Send + Sync>;
 
 /// It is generally encouraged to set up post processing effects as a plugin
+///
+/// The effect runs on every camera with its settings, whatever the camera renders to: the primary
+/// window, another window or an image. The pipeline and textures of the effect are specialized and
+/// allocated per camera, following the format and size of its target.
 pub struct PostProcessPlugin<U: Clone, R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel> {
     post_process_plugin_settings: PostProcessPluginSettings<U, R>,
+    // Taken by the plugin when it finishes, since systems can't be cloned
+    run_condition: Mutex<Option<BoxedReadOnlySystem<In<Entity>, bool>>>,
+    // Adds the prepare systems of the user bind group to the render app
+    prepare_bind_group: Mutex<Option<AddPrepareSystems>>,
 }
 
-impl<U: Clone, R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel> PostProcessPlugin<U, R> {
+impl<
+        U: Component + ShaderType + WriteInto + Clone,
+        R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+    > PostProcessPlugin<U, R>
+{
     pub fn new(
         shader_path: &'static str,
         label: R,
         debug_label: Option<&'static str>,
         bind_group_layout_label: &'static str,
         vertex_state: VertexState,
+    ) -> Self {
+        Self::from_parts(
+            shader_path,
+            label,
+            debug_label,
+            bind_group_layout_label,
+            vertex_state,
+            Some(SettingsUniform::of::<U>()),
+        )
+    }
+}
+
+impl<M: Send + Sync + 'static, R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel>
+    PostProcessPlugin<PostProcessParams<M>, R>
+{
+    /// Create an effect whose settings are named parameters.
+    ///
+    /// See [`PostProcessParams`]. The pipeline is specialized with the index of every parameter,
+    /// so cameras declaring other parameters get their own pipeline.
+    pub fn new_params(
+        shader_path: &'static str,
+        label: R,
+        debug_label: Option<&'static str>,
+        bind_group_layout_label: &'static str,
+        vertex_state: VertexState,
+    ) -> Self {
+        Self::from_parts(
+            shader_path,
+            label,
+            debug_label,
+            bind_group_layout_label,
+            vertex_state,
+            Some(SettingsUniform::params::<M>()),
+        )
+        .with_pipeline_key()
+    }
+}
+
+impl<U: Clone, R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel> PostProcessPlugin<U, R> {
+    /// Create an effect without settings uniform, for effects without parameters.
+    ///
+    /// The settings are a marker component that only selects the cameras running the effect,
+    /// and nothing is bound at [`bindings::SETTINGS`].
+    pub fn new_marker(
+        shader_path: &'static str,
+        label: R,
+        debug_label: Option<&'static str>,
+        bind_group_layout_label: &'static str,
+        vertex_state: VertexState,
+    ) -> Self {
+        Self::from_parts(
+            shader_path,
+            label,
+            debug_label,
+            bind_group_layout_label,
+            vertex_state,
+            None,
+        )
+    }
+
+    fn from_parts(
+        shader_path: &'static str,
+        label: R,
+        debug_label: Option<&'static str>,
+        bind_group_layout_label: &'static str,
+        vertex_state: VertexState,
+        uniform: Option<SettingsUniform>,
     ) -> Self {
         Self {
             post_process_plugin_settings: PostProcessPluginSettings::<U, R> {
@@ -46,36 +242,502 @@ impl<U: Clone, R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel> PostProce
                 bind_group_layout_label,
                 phantom_data: PhantomData,
                 vertex_state,
+                layers: PostProcessLayers::default(),
+                placement: PostProcessPlacement::default(),
+                graph: Core3d.intern(),
+                extra_outputs: Vec::new(),
+                destination: PostProcessDestination::default(),
+                resolution_scale: 1.0,
+                base_resolution_scale: 1.0,
+                frame_interval: 1,
+                upscale_filter: PostProcessUpscaleFilter::default(),
+                inputs: Vec::new(),
+                stencil: PostProcessStencilTest::default(),
+                depth: false,
+                depth_range: None,
+                mask: None,
+                entity_mask: false,
+                shader_defs: None,
+                global_settings: None,
+                uniform,
+                view: true,
+                standard_samplers: false,
+                bind_group_layout: None,
+                view_data: None,
+                instances: None,
+                audio: false,
+                view_info: false,
+                tonemapping_lut: false,
+                idle_release: None,
+                quality: None,
+                color_space: PostProcessColorSpace::default(),
+                constant_shader_defs: Vec::new(),
             },
+            run_condition: Mutex::new(None),
+            prepare_bind_group: Mutex::new(None),
         }
     }
+
+    /// Set the layers this effect runs on.
+    ///
+    /// The effect will only run on cameras whose [`PostProcessLayers`] intersect with these layers.
+    /// By default effects run on layer 0, which is also the layer of cameras without the component.
+    pub fn with_layers(mut self, layers: PostProcessLayers) -> Self {
+        self.post_process_plugin_settings.layers = layers;
+        self
+    }
+
+    /// Set where the effect runs relative to Bevy's built-in post processing nodes.
+    pub fn with_placement(mut self, placement: PostProcessPlacement) -> Self {
+        self.post_process_plugin_settings.placement = placement;
+        self
+    }
+
+    /// Set the render graph the effect node is added to. Defaults to [`Core3d`].
+    ///
+    /// Any graph running on views with a [`ViewTarget`] works.
+    /// When using a custom graph, the placement usually needs to be set with
+    /// [`PostProcessPlacement::between`] as the built-in presets reference [`Core3d`] nodes.
+    pub fn in_graph(mut self, graph: impl RenderSubGraph) -> Self {
+        self.post_process_plugin_settings.graph = graph.intern();
+        self
+    }
+
+    /// Add an extra color output to the effect pass.
+    ///
+    /// The first call adds a texture written at `@location(1)` in the fragment shader,
+    /// the second one at `@location(2)` and so on. The textures are allocated by the crate
+    /// with the size of the view and can be read by later passes from [`PostProcessOutputs`].
+    pub fn with_extra_output(mut self, format: TextureFormat) -> Self {
+        self.post_process_plugin_settings.extra_outputs.push(format);
+        self
+    }
+
+    /// Set where the main output of the effect is written.
+    ///
+    /// By default the effect writes back into the view target.
+    /// With [`PostProcessDestination::Image`] the view target is left untouched, and the bind
+    /// group of the effect is created once per frame before the render graph runs rather than by
+    /// the node.
+    pub fn with_destination(mut self, destination: PostProcessDestination) -> Self {
+        self.post_process_plugin_settings.destination = destination;
+        self
+    }
+
+    /// Render the effect at a fraction of the view resolution.
+    ///
+    /// The effect still samples the full resolution screen texture, but renders into an intermediate
+    /// texture that is `scale` times the size of the view. A built-in composite pass then upsamples it
+    /// back onto the view. Use this for expensive effects, a scale of `0.5` renders a quarter of the pixels.
+    /// The scale is multiplied by the one of the tier of the effect when a [`PostProcessQuality`]
+    /// resource exists.
+    pub fn with_resolution_scale(mut self, scale: f32) -> Self {
+        self.post_process_plugin_settings.resolution_scale = scale.clamp(0.0, 1.0);
+        self.post_process_plugin_settings.base_resolution_scale = scale.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the filter used to upsample the effect when rendering at a reduced resolution.
+    pub fn with_upscale_filter(mut self, filter: PostProcessUpscaleFilter) -> Self {
+        self.post_process_plugin_settings.upscale_filter = filter;
+        self
+    }
+
+    /// Run the effect pass every `frames` frames, and composite its last result in between.
+    ///
+    /// Meant for slowly changing effects, like a heavy stylization over a paused background. The
+    /// result is kept in the same intermediate texture as [`with_resolution_scale`] uses, so the
+    /// scene changes and the settings changes only show up when the pass runs again. Extra outputs
+    /// also keep their last content. Cameras are staggered so they don't all run the pass on the
+    /// same frame.
+    ///
+    /// [`with_resolution_scale`]: PostProcessPlugin::with_resolution_scale
+    pub fn with_frame_interval(mut self, frames: u32) -> Self {
+        self.post_process_plugin_settings.frame_interval = frames.max(1);
+        self
+    }
+
+    /// Set the color space the effect shader works in, see [`PostProcessColorSpace`].
+    ///
+    /// The shader converts the colors with `bevy_post_process::color_space`, which the crate
+    /// selects the conversions of with shader defs.
+    pub fn with_color_space(mut self, color_space: PostProcessColorSpace) -> Self {
+        self.post_process_plugin_settings.color_space = color_space;
+        self
+    }
+
+    /// Bind an additional texture to the effect shader.
+    ///
+    /// The first input is bound at [`bindings::FIRST_INPUT`], the next one right after it and so on.
+    pub fn with_input(mut self, input: PostProcessInput) -> Self {
+        self.post_process_plugin_settings.inputs.push(input);
+        self
+    }
+
+    /// Restrict the effect using the stencil written by meshes marked with [`PostProcessStencil`].
+    ///
+    /// The pixels the effect skips keep the color they had before the effect.
+    pub fn with_stencil(mut self, test: PostProcessStencilTest) -> Self {
+        self.post_process_plugin_settings.stencil = test;
+        self
+    }
+
+    /// Bind the depth texture of the view to the effect shader at [`bindings::DEPTH`].
+    ///
+    /// The depth textures of the cameras running the effect are made bindable automatically.
+    pub fn with_depth(mut self) -> Self {
+        self.post_process_plugin_settings.depth = true;
+        self
+    }
+
+    /// Only apply the effect to the pixels whose distance from the camera is within `range`.
+    ///
+    /// The distance is measured in world units along the view direction, and the background is
+    /// infinitely far away. Use `50.0..f32::INFINITY` to only affect what is beyond 50 meters.
+    /// The test is done by the crate, the effect shader doesn't need to bind the depth.
+    /// The pixels outside of the range keep the color they had before the effect.
+    pub fn with_depth_range(mut self, range: Range<f32>) -> Self {
+        self.post_process_plugin_settings.depth_range = Some(range);
+        self
+    }
+
+    /// Bind a screen-space mask texture to the effect shader at [`bindings::MASK`].
+    ///
+    /// By convention the red channel of the mask is the weight of the effect, so the shader blends
+    /// its result with `mix(source, effect, textureSample(mask_texture, screen_sampler, in.uv).r)`.
+    /// The mask can be an image, or the render target of a dedicated mask camera rendering only the
+    /// shapes of the mask on its own [`RenderLayers`](bevy::camera::visibility::RenderLayers).
+    /// While the mask isn't available a white texture is bound, so the effect applies everywhere.
+    pub fn with_mask(mut self, mask: PostProcessInput) -> Self {
+        self.post_process_plugin_settings.mask = Some(mask);
+        self
+    }
+
+    /// Bind the entity mask of the view to the effect shader at [`bindings::ENTITY_MASK`].
+    ///
+    /// Every mesh with a [`PostProcessMask`] is rendered into the mask with its color, over a black
+    /// background. This is the base of selection outlines, hover glows and x-ray highlights.
+    /// Until the mask of a view has been rendered a black texture is bound.
+    pub fn with_entity_mask(mut self) -> Self {
+        self.post_process_plugin_settings.entity_mask = true;
+        self
+    }
+
+    /// Specialize the pipeline of the effect with the shader defs of its settings.
+    ///
+    /// See [`PostProcessPipelineKey`]. Changing a setting that maps to other shader defs switches
+    /// the camera to another pipeline, which is compiled the first time it is used.
+    pub fn with_pipeline_key(mut self) -> Self
+    where
+        U: PostProcessPipelineKey,
+    {
+        self.post_process_plugin_settings.shader_defs = Some(U::shader_defs);
+        self
+    }
+
+    /// Compile the effect shader with this shader def on every view.
+    ///
+    /// Effects made of several passes can share a shader this way, like the horizontal and
+    /// vertical passes of a blur, each pass being its own effect.
+    pub fn with_shader_def(mut self, shader_def: impl Into<ShaderDefVal>) -> Self {
+        self.post_process_plugin_settings
+            .constant_shader_defs
+            .push(shader_def.into());
+        self
+    }
+
+    /// Leave the view uniform out of the layout of the effect.
+    ///
+    /// Simple color filters don't need it, so their shader doesn't have to declare it at
+    /// [`bindings::VIEW`], and the pass sets one dynamic offset less.
+    pub fn without_view(mut self) -> Self {
+        self.post_process_plugin_settings.view = false;
+        self
+    }
+
+    /// Bind the size and the exposure of the view to the effect shader, at
+    /// [`bindings::VIEW_INFO`]:
+    ///
+    /// ```wgsl
+    /// struct PostProcessViewInfo {
+    ///     // The physical size of the screen texture, in pixels
+    ///     resolution: vec2<f32>,
+    ///     // The size of a texel in UV space, `1.0 / resolution`
+    ///     texel_size: vec2<f32>,
+    ///     // The top left corner of the viewport of the camera in the screen texture, in pixels
+    ///     viewport_offset: vec2<f32>,
+    ///     // The physical size of the viewport of the camera, in pixels
+    ///     viewport_size: vec2<f32>,
+    ///     // The width of the viewport divided by its height
+    ///     aspect_ratio: f32,
+    ///     // The exposure of the camera, the factor applied to the light before tonemapping
+    ///     exposure: f32,
+    ///     // The same exposure as an EV100 value
+    ///     ev100: f32,
+    /// }
+    /// ```
+    ///
+    /// The resolution is the one of the screen texture sampled by the effect, so UV offsets
+    /// don't have to be computed from the settings each frame. The texture covers the whole
+    /// render target, so split screen and viewport cameras use the viewport for their aspect
+    /// ratio and to tell where they render. Effects placed after the upscaling get the size and
+    /// viewport of the output instead, which they render at. HDR effects can scale their thresholds and intensities with the exposure. The
+    /// correction applied by auto exposure isn't included, it is only known to its own pipeline.
+    pub fn with_view_info(mut self) -> Self {
+        self.post_process_plugin_settings.view_info = true;
+        self
+    }
+
+    /// Bind the tonemapping LUT of the view at [`bindings::TONEMAPPING_LUT`] and
+    /// [`bindings::TONEMAPPING_LUT_SAMPLER`], and compile the shader with the `TONEMAP_METHOD_*`
+    /// shader def of the tonemapping of the view.
+    ///
+    /// Effects running before tonemapping can then preview the final colors, and effects
+    /// distorting the tonemapped image can apply it again, with Bevy's own functions:
+    ///
+    /// ```wgsl
+    /// #import bevy_core_pipeline::tonemapping::tone_mapping
+    ///
+    /// let display = tone_mapping(color, view.color_grading);
+    /// ```
+    pub fn with_tonemapping_lut(mut self) -> Self {
+        self.post_process_plugin_settings.tonemapping_lut = true;
+        self
+    }
+
+    /// Bind a linear clamp, a nearest clamp and a linear repeat sampler to the effect shader, at
+    /// [`bindings::LINEAR_CLAMP_SAMPLER`], [`bindings::NEAREST_CLAMP_SAMPLER`] and
+    /// [`bindings::LINEAR_REPEAT_SAMPLER`].
+    ///
+    /// Each texture can then be sampled the way it needs, like point sampling the screen while
+    /// tiling a noise texture. Like the screen sampler, they fall back to nearest filtering when
+    /// the screen texture isn't filterable, see [`SCREEN_TEXTURE_NON_FILTERABLE`].
+    pub fn with_standard_samplers(mut self) -> Self {
+        self.post_process_plugin_settings.standard_samplers = true;
+        self
+    }
+
+    /// Set a bind group of your own at index 1 of the effect shader.
+    ///
+    /// `layout` creates the layout of the group once, which is then available in the render world
+    /// as a [`PostProcessBindGroupLayout<U>`] resource. `prepare` runs in the render world in
+    /// [`RenderSystems::PrepareBindGroups`] and inserts a [`PostProcessBindGroup<U>`] on the views
+    /// running the effect. This is an escape hatch for bindings the crate doesn't model, like
+    /// storage buffers.
+    pub fn with_bind_group<M: 'static>(
+        mut self,
+        layout: fn(&RenderDevice) -> BindGroupLayout,
+        prepare: impl IntoScheduleConfigs<ScheduleSystem, M> + Send + Sync + 'static,
+    ) -> Self {
+        self.post_process_plugin_settings.bind_group_layout = Some(layout);
+        // The schedule configs aren't Send, so they are only made when the systems are added
+        *self.prepare_bind_group.get_mut().unwrap() = Some(Box::new(|render_app: &mut SubApp| {
+            render_app.add_systems(Render, prepare.in_set(RenderSystems::PrepareBindGroups));
+        }));
+        self
+    }
+
+    /// Fetch the view data of `D` and hand it to the effect pass before it draws.
+    ///
+    /// See [`PostProcessViewData`].
+    pub fn with_view_data<D: PostProcessViewData>(mut self) -> Self {
+        self.post_process_plugin_settings.view_data = Some(view_data::prepare_pass::<D>);
+        self
+    }
+
+    /// Gather the `I` component of every entity into an array bound to the effect shader.
+    ///
+    /// The array is a read-only storage buffer at [`bindings::INSTANCES`], and the number of
+    /// instances is bound at [`bindings::INSTANCE_COUNT`]. This lets an effect render any number
+    /// of simultaneous instances, like the shockwaves of several explosions, in a single pass.
+    pub fn with_instances<I>(mut self) -> Self
+    where
+        I: Component + ShaderType + ShaderSize + WriteInto + Clone + Default,
+    {
+        self.post_process_plugin_settings.instances = Some(EffectInstances::of::<I>());
+        self
+    }
+
+    /// Bind the audio feed of the [`PostProcessAudio`] resource at [`bindings::AUDIO`].
+    ///
+    /// Adds the [`PostProcessAudioPlugin`] if needed.
+    #[cfg(feature = "audio")]
+    pub fn with_audio(mut self) -> Self {
+        self.post_process_plugin_settings.audio = true;
+        self
+    }
+
+    /// Release the pipeline of the effect after `frames` frames without any camera running it.
+    ///
+    /// The pipeline of an effect is only created once a camera runs it, so unused effects don't
+    /// cost any shader compilation. Effects that are only used for a moment, like a transition,
+    /// can release it afterwards. It is compiled again the next time a camera runs the effect,
+    /// which takes a few frames during which the effect doesn't render.
+    pub fn with_idle_release(mut self, frames: u32) -> Self {
+        self.post_process_plugin_settings.idle_release = Some(frames.max(1));
+        self
+    }
+
+    /// Run the effect on every camera, with the settings of the `U` resource.
+    ///
+    /// Meant for global effects like gamma correction or colorblindness filters, which must also
+    /// apply to the cameras spawned by other plugins. Cameras with their own settings component
+    /// keep using it. The effect stops running when the resource is removed.
+    pub fn on_all_cameras(mut self) -> Self
+    where
+        U: Component + Resource,
+    {
+        self.post_process_plugin_settings.global_settings =
+            Some(global::add_global_settings::<U>);
+        self
+    }
+
+    /// Only run the effect on the cameras for which `condition` returns `true`.
+    ///
+    /// The condition is a read-only system taking the camera entity as input. It is evaluated on the
+    /// main world every frame, for every camera running the effect, like:
+    ///
+    /// ```ignore
+    /// |In(camera): In<Entity>, underwater: Res<Underwater>, players: Query<(), With<Player>>| {
+    ///     underwater.0 && players.contains(camera)
+    /// }
+    /// ```
+    ///
+    /// This avoids adding and removing the settings component of state driven effects.
+    pub fn with_run_condition<M, C>(self, condition: C) -> Self
+    where
+        C: IntoSystem<In<Entity>, bool, M>,
+        C::System: ReadOnlySystem,
+    {
+        *self.run_condition.lock().unwrap() = Some(condition::boxed_condition(condition));
+        self
+    }
 }
 
 impl<
-        U: WriteInto + Component + ShaderType + Clone + ExtractComponent,
+        U: Component + Clone + ExtractComponent,
         R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
     > Plugin for PostProcessPlugin<U, R>
 {
     fn build(&self, app: &mut App) {
-        app.add_plugins((
-            // The settings will be a component that lives in the main world but will
-            // be extracted to the render world every frame.
-            // This makes it possible to control the effect from the main world.
-            // This plugin will take care of extracting it automatically.
-            // It's important to derive [`ExtractComponent`] on the `ShaderUniform`
-            // for this plugin to work correctly.
-            ExtractComponentPlugin::<U>::default(),
-            // The settings will also be the data used in the shader.
-            // This plugin will prepare the component for the GPU by creating a uniform buffer
-            // and writing the data to that buffer every frame.
-            UniformComponentPlugin::<U>::default(),
-        ));
+        // The settings will be a component that lives in the main world but will
+        // be extracted to the render world every frame.
+        // This makes it possible to control the effect from the main world.
+        // This plugin will take care of extracting it automatically.
+        // It's important to derive [`ExtractComponent`] on the `ShaderUniform`
+        // for this plugin to work correctly.
+        // A fused stack running the same settings may have added it already.
+        if !app.is_plugin_added::<ExtractComponentPlugin<U>>() {
+            app.add_plugins(ExtractComponentPlugin::<U>::default());
+        }
+        // Cameras can replace the shader of the effect. The passes of an effect made of several
+        // share its settings
+        if !app.is_plugin_added::<ExtractComponentPlugin<PostProcessShaderOverride<U>>>() {
+            app.add_plugins(ExtractComponentPlugin::<PostProcessShaderOverride<U>>::default());
+        }
+
+        // The settings will also be the data used in the shader, unless they are a marker.
+        // This will prepare the component for the GPU by creating a uniform buffer
+        // and writing the data to that buffer every frame.
+        if let Some(uniform) = &self.post_process_plugin_settings.uniform {
+            uniform.add_plugin(app);
+        }
+
+        // Everything shared by the effects is only registered by the first plugin
+        if !app.is_plugin_added::<PostProcessSharedPlugin>() {
+            app.add_plugins(PostProcessSharedPlugin);
+        }
+
+        let settings = &self.post_process_plugin_settings;
+        app.world_mut()
+            .resource_mut::<PostProcessRegistry>()
+            .register(PostProcessEffectInfo {
+                label: settings.label.intern(),
+                graph: settings.graph,
+                placement: settings.placement,
+                settings_type: std::any::type_name::<U>(),
+                settings_type_id: std::any::TypeId::of::<U>(),
+                bindings: settings.bindings(),
+            });
+
+        // The cameras the effect becomes active or inactive on are reported to the main world
+        app.insert_resource(ActiveCameras::<U, R>::new(
+            settings.label.clone(),
+            settings.layers,
+        ))
+        .add_systems(PostUpdate, activity::track_active_cameras::<U, R>);
+
+        // Animations turning the effect on or off are applied before the activity is tracked
+        #[cfg(feature = "animation")]
+        app.add_systems(
+            PostUpdate,
+            animation::sync_enabled::<U, R>
+                .after(bevy::app::AnimationSystems)
+                .before(activity::track_active_cameras::<U, R>),
+        );
+
+        if self.post_process_plugin_settings.stencil != PostProcessStencilTest::Disabled {
+            if !app.is_plugin_added::<PostProcessStencilPlugin>() {
+                app.add_plugins(PostProcessStencilPlugin);
+            }
+            app.add_systems(
+                PostUpdate,
+                mask::collect_mask_users::<U, PostProcessStencil>
+                    .before(mask::update_mask_cameras::<PostProcessStencil>),
+            );
+        }
+
+        if self.post_process_plugin_settings.entity_mask {
+            if !app.is_plugin_added::<MaskCameraPlugin<PostProcessMask>>() {
+                app.add_plugins(MaskCameraPlugin::<PostProcessMask>::default());
+            }
+            app.add_systems(
+                PostUpdate,
+                mask::collect_mask_users::<U, PostProcessMask>
+                    .before(mask::update_mask_cameras::<PostProcessMask>),
+            );
+        }
+
+        if self.post_process_plugin_settings.depth_range.is_some()
+            && !app.is_plugin_added::<PostProcessDepthRangePlugin>()
+        {
+            app.add_plugins(PostProcessDepthRangePlugin);
+        }
+
+        if self.post_process_plugin_settings.reads_depth() {
+            app.add_systems(PostUpdate, depth::configure_depth_texture_usages::<U>);
+        }
+
+        if let Some(add_global_settings) = self.post_process_plugin_settings.global_settings {
+            add_global_settings(app);
+        }
+
+        if let Some(instances) = &self.post_process_plugin_settings.instances {
+            instances.add_systems(app);
+        }
+
+        if self.post_process_plugin_settings.view_info {
+            view_info::add_systems(app);
+        }
+
+        #[cfg(feature = "audio")]
+        if self.post_process_plugin_settings.audio
+            && !app.is_plugin_added::<PostProcessAudioPlugin>()
+        {
+            app.add_plugins(PostProcessAudioPlugin);
+        }
 
         // We need to get the render app from the main app
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
         };
 
+        let graph = self.post_process_plugin_settings.graph;
+        let (before, after) = self.post_process_plugin_settings.placement.edges();
+        if self.post_process_plugin_settings.placement.writes_output() {
+            output_pass::add_output_pass(render_app, graph);
+        }
+
         render_app
             // Bevy's renderer uses a render graph which is a collection of nodes in a directed acyclic graph.
             // It currently runs on each view/camera and executes each node in the specified order.
@@ -91,34 +753,174 @@ impl<
             // The [`ViewNodeRunner`] is a special [`Node`] that will automatically run the node for each view
             // matching the [`ViewQuery`]
             .add_render_graph_node::<ViewNodeRunner<PipelineNode<U, R>>>(
-                // Specify the label of the graph, in this case we want the graph for 3d
-                Core3d,
+                // Specify the label of the graph, this is the graph for 3d unless configured otherwise
+                graph,
                 // It also needs the label of the node
                 self.post_process_plugin_settings.label.clone(),
             )
             .add_render_graph_edges(
-                Core3d,
+                graph,
                 // Specify the node ordering.
                 // This will automatically create all required node edges to enforce the given ordering.
                 (
-                    Node3d::EndMainPass,
+                    before,
                     self.post_process_plugin_settings.label.clone(),
-                    Node3d::EndMainPassPostProcessing,
+                    after,
                 ),
             );
     }
 
     fn finish(&self, app: &mut App) {
+        // The condition is initialized on the main world it runs on
+        let run_condition = self
+            .run_condition
+            .lock()
+            .unwrap()
+            .take()
+            .map(|condition| RunCondition::<U, R>::new(condition, app.world_mut()));
+
         // We need to get the render app from the main app
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
         };
 
-        render_app.insert_resource(self.post_process_plugin_settings.clone());
+        if let Some(run_condition) = run_condition {
+            render_app
+                .insert_resource(run_condition)
+                .add_systems(ExtractSchedule, condition::extract_run_conditions::<U, R>);
+        }
+
+        // The layout of the user bind group is needed by the pipeline
+        if let Some(bind_group_layout) = self.post_process_plugin_settings.bind_group_layout {
+            let layout = bind_group_layout(render_app.world().resource::<RenderDevice>());
+            render_app.insert_resource(PostProcessBindGroupLayout::<U>::new(layout));
+        }
+        if let Some(add_prepare_bind_group) = self.prepare_bind_group.lock().unwrap().take() {
+            add_prepare_bind_group(render_app);
+        }
+
+        render_app
+            .insert_resource(self.post_process_plugin_settings.clone())
+            .init_resource::<ResolvedInputs<U, R>>()
+            .add_systems(
+                ExtractSchedule,
+                (inputs::extract_inputs::<U, R>, quality::extract_quality::<U, R>),
+            )
+            .add_systems(
+                Render,
+                (
+                    // The pipeline is only created once a view runs the effect
+                    (
+                        pipeline::init_pipeline::<U, R>.run_if(
+                            not(resource_exists::<PostProcessPipeline<U, R>>)
+                                .and(any_with_component::<U>),
+                        ),
+                        pipeline::prepare_view_pipelines::<U, R>
+                            .run_if(resource_exists::<PostProcessPipeline<U, R>>),
+                    )
+                        .chain()
+                        .in_set(RenderSystems::Prepare),
+                    // Compiled again when the quality tier changes the shader defs
+                    prewarm::prewarm_pipelines::<U, R>
+                        .run_if(
+                            resource_exists::<PostProcessPrewarm>.and(
+                                resource_changed::<PostProcessPrewarm>
+                                    .or(resource_changed::<PostProcessPluginSettings<U, R>>),
+                            ),
+                        )
+                        .in_set(RenderSystems::Prepare),
+                    (
+                        outputs::prepare_extra_outputs::<U, R>,
+                        resolution::prepare_intermediate_textures::<U, R>,
+                        stencil::prepare_stencil_textures::<U, R>,
+                    )
+                        .in_set(RenderSystems::PrepareResources),
+                    state::update_pipeline_readiness::<U, R>.in_set(RenderSystems::Cleanup),
+                ),
+            );
+
+        // Effects rendering into an image don't flip the view target, so their bind groups can be
+        // created ahead of the node
+        if matches!(
+            self.post_process_plugin_settings.destination,
+            PostProcessDestination::Image(_)
+        ) {
+            render_app.add_systems(
+                Render,
+                queue::queue_bind_groups::<U, R>.in_set(RenderSystems::PrepareBindGroups),
+            );
+        }
+
+        if self.post_process_plugin_settings.depth_range.is_some() {
+            render_app
+                .init_resource::<DepthRangeUniform<U, R>>()
+                .add_systems(
+                    Render,
+                    depth::prepare_depth_range_pipelines::<U, R>.in_set(RenderSystems::Prepare),
+                );
+        }
+
+        if self.post_process_plugin_settings.idle_release.is_some() {
+            render_app.add_systems(
+                Render,
+                pipeline::release_idle_pipeline::<U, R>.in_set(RenderSystems::Cleanup),
+            );
+        }
+
+        render_app
+            // The pipeline is specialized per view, depending on its texture format and MSAA
+            .init_resource::<SpecializedRenderPipelines<PostProcessPipeline<U, R>>>();
+    }
+}
+
+// Registers everything shared by all the effects. Added once by the first [`PostProcessPlugin`].
+struct PostProcessSharedPlugin;
+
+impl Plugin for PostProcessSharedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ExtractComponentPlugin::<PostProcessLayers>::default(),
+            ExtractComponentPlugin::<PostProcessBypass>::default(),
+        ));
+
+        embedded_asset!(app, "composite.wgsl");
+        // The WGSL modules effect shaders can import
+        load_shader_library!(app, "color_space.wgsl");
+        load_shader_library!(app, "utils.wgsl");
+
+        texture_pool::add_texture_pool(app);
+        sequence::add_sequences(app);
+
+        // The readiness of the pipelines is shared between the render world and the main world
+        let readiness = SharedPipelineReadiness::default();
+        app.insert_resource(readiness.clone())
+            .insert_resource(PostProcessRegistry::new(readiness.clone()))
+            .init_resource::<PostProcessState>()
+            .init_resource::<PostProcessPrewarm>()
+            .add_message::<PostProcessReady>()
+            .add_message::<PostProcessActivated>()
+            .add_message::<PostProcessDeactivated>()
+            .add_systems(PreUpdate, state::sync_post_process_state);
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .insert_resource(readiness)
+                .add_systems(
+                    ExtractSchedule,
+                    (registry::extract_effect_order, prewarm::extract_prewarm),
+                );
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
 
         render_app
-            // Initialize the pipeline
-            .init_resource::<PostProcessPipeline<U, R>>();
+            .init_resource::<PostProcessOutputs>()
+            .init_resource::<CompositePipeline>()
+            .init_resource::<SpecializedRenderPipelines<CompositePipeline>>();
     }
 }
 
@@ -135,6 +937,164 @@ where
     bind_group_layout_label: &'static str,
     phantom_data: PhantomData<U>,
     vertex_state: VertexState,
+    /// Layers of the cameras this effect runs on
+    layers: PostProcessLayers,
+    /// Where the node is inserted in the render graph
+    placement: PostProcessPlacement,
+    /// The render graph the node is added to
+    graph: InternedRenderSubGraph,
+    /// Formats of the additional color attachments written by the pass
+    extra_outputs: Vec<TextureFormat>,
+    /// Where the main output of the pass is written
+    destination: PostProcessDestination,
+    /// Fraction of the view resolution the effect is rendered at
+    resolution_scale: f32,
+    /// Resolution scale set on the plugin, before the quality tier is applied
+    base_resolution_scale: f32,
+    /// Number of frames between two runs of the effect pass
+    frame_interval: u32,
+    /// Filter used to composite the reduced resolution output onto the view
+    upscale_filter: PostProcessUpscaleFilter,
+    /// Additional textures bound to the shader
+    inputs: Vec<PostProcessInput>,
+    /// How the effect tests the stencil written by marked meshes
+    stencil: PostProcessStencilTest,
+    /// Whether the depth texture of the view is bound to the shader
+    depth: bool,
+    /// Distances from the camera the effect is restricted to
+    depth_range: Option<Range<f32>>,
+    /// Screen-space mask weighting the effect
+    mask: Option<PostProcessInput>,
+    /// Whether the entity mask of the view is bound to the shader
+    entity_mask: bool,
+    /// Derives shader defs from the settings of a view
+    shader_defs: Option<fn(&U) -> Vec<ShaderDefVal>>,
+    /// Copies the settings resource to every camera
+    global_settings: Option<fn(&mut App)>,
+    /// The settings uniform, absent when the settings are a marker component
+    uniform: Option<SettingsUniform>,
+    /// Whether the view uniform is bound to the shader
+    view: bool,
+    /// Whether the standard samplers are bound to the shader
+    standard_samplers: bool,
+    /// Creates the layout of the bind group set by the user at index 1
+    bind_group_layout: Option<fn(&RenderDevice) -> BindGroupLayout>,
+    /// Hands the view data to the pass before it draws
+    view_data: Option<fn(&World, Entity, &mut TrackedRenderPass)>,
+    /// Gathers the instances of the effect into an array
+    instances: Option<EffectInstances>,
+    /// Whether the audio feed is bound to the shader
+    audio: bool,
+    /// Whether the size and the exposure of the view are bound to the shader
+    view_info: bool,
+    /// Whether the tonemapping LUT of the view is bound to the shader
+    tonemapping_lut: bool,
+    /// Number of frames without any view running the effect after which its pipeline is released
+    idle_release: Option<u32>,
+    /// Quality tier of the effect, while a [`PostProcessQuality`] resource exists
+    quality: Option<PostProcessQualityTier>,
+    /// The color space the effect shader works in
+    color_space: PostProcessColorSpace,
+    /// Shader defs the effect is compiled with on every view
+    constant_shader_defs: Vec<ShaderDefVal>,
+}
+
+impl<U: Clone, R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel>
+    PostProcessPluginSettings<U, R>
+{
+    /// Whether the effect pass is restricted by a stencil attachment
+    fn uses_stencil(&self) -> bool {
+        !self.writes_output()
+            && (self.stencil != PostProcessStencilTest::Disabled || self.depth_range.is_some())
+    }
+
+    /// Whether the effect renders into an intermediate texture composited onto the destination
+    fn uses_intermediate(&self) -> bool {
+        !self.writes_output() && (self.resolution_scale < 1.0 || self.frame_interval > 1)
+    }
+
+    /// Whether the effect runs after the upscaling and writes the output texture of the view
+    fn writes_output(&self) -> bool {
+        matches!(self.destination, PostProcessDestination::ViewTarget)
+            && self.placement.writes_output()
+    }
+
+    /// Whether the depth texture of the view must be bindable
+    fn reads_depth(&self) -> bool {
+        self.depth || self.depth_range.is_some()
+    }
+
+    /// The shader defs of the pipeline on a view, appended to the ones of its settings
+    ///
+    /// Shared by the views and the prewarm, so the variants compiled ahead of time are the ones
+    /// the views use
+    fn view_shader_defs(
+        &self,
+        mut shader_defs: Vec<ShaderDefVal>,
+        tonemapping: Option<&Tonemapping>,
+    ) -> Vec<ShaderDefVal> {
+        if self.tonemapping_lut {
+            shader_defs.extend(tonemapping::shader_defs(tonemapping));
+        }
+        if let Some(quality) = self.quality {
+            shader_defs.extend(quality.shader_defs());
+        }
+        shader_defs.extend(self.color_space.shader_defs());
+        shader_defs
+    }
+
+    /// The bindings of the effect shader, with their index
+    fn bindings(&self) -> Vec<(u32, String)> {
+        let mut entries = vec![
+            (bindings::SCREEN_TEXTURE, "screen_texture".to_string()),
+            (bindings::SAMPLER, "sampler".to_string()),
+        ];
+        if self.uniform.is_some() {
+            entries.push((bindings::SETTINGS, std::any::type_name::<U>().to_string()));
+        }
+        if self.view {
+            entries.push((bindings::VIEW, "view".to_string()));
+        }
+        if self.instances.is_some() {
+            entries.extend([
+                (bindings::INSTANCES, "instances".to_string()),
+                (bindings::INSTANCE_COUNT, "instance_count".to_string()),
+            ]);
+        }
+        if self.audio {
+            entries.push((bindings::AUDIO, "audio".to_string()));
+        }
+        if self.view_info {
+            entries.push((bindings::VIEW_INFO, "view_info".to_string()));
+        }
+        if self.tonemapping_lut {
+            entries.extend([
+                (bindings::TONEMAPPING_LUT, "tonemapping_lut".to_string()),
+                (bindings::TONEMAPPING_LUT_SAMPLER, "tonemapping_lut_sampler".to_string()),
+            ]);
+        }
+        if self.standard_samplers {
+            entries.extend([
+                (bindings::LINEAR_CLAMP_SAMPLER, "linear_clamp_sampler".to_string()),
+                (bindings::NEAREST_CLAMP_SAMPLER, "nearest_clamp_sampler".to_string()),
+                (bindings::LINEAR_REPEAT_SAMPLER, "linear_repeat_sampler".to_string()),
+            ]);
+        }
+        if self.depth {
+            entries.push((bindings::DEPTH, "depth".to_string()));
+        }
+        if self.mask.is_some() {
+            entries.push((bindings::MASK, "mask".to_string()));
+        }
+        if self.entity_mask {
+            entries.push((bindings::ENTITY_MASK, "entity_mask".to_string()));
+        }
+        entries.extend(
+            (0..self.inputs.len() as u32)
+                .map(|index| (bindings::FIRST_INPUT + index, format!("input {index}"))),
+        );
+        entries
+    }
 }
 
 // The post process node used for the render graph
@@ -147,10 +1107,8 @@ impl<U, R> FromWorld for PipelineNode<U, R> {
 }
 
 // The ViewNode trait is required by the ViewNodeRunner
-impl<
-        U: Component + ShaderType + WriteInto + Clone,
-        R: Send + Sync + 'static + Hash + Eq + Clone + RenderLabel,
-    > ViewNode for PipelineNode<U, R>
+impl<U: Component + Clone, R: Send + Sync + 'static + Hash + Eq + Clone + RenderLabel> ViewNode
+    for PipelineNode<U, R>
 {
     // The node needs a query to gather data from the ECS in order to do its rendering,
     // but it's not a normal system so we need to define it manually.
@@ -161,9 +1119,22 @@ impl<
         // This makes sure the node only runs on cameras with the SkyPipelineSettings component
         &'static U,
         &'static ViewUniformOffset,
-        // As there could be multiple post processing components sent to the GPU (one per camera),
-        // we need to get the index of the one that is associated with the current view.
-        &'static DynamicUniformIndex<U>,
+        // Cameras without layers are on the default layer
+        Option<&'static PostProcessLayers>,
+        // The pipeline specialized for this view
+        &'static ViewPostProcessPipeline<U, R>,
+        // Only present when the effect renders at a reduced resolution
+        Option<&'static ViewPostProcessIntermediate<U, R>>,
+        // Only present when the effect uses the stencil
+        Option<&'static ViewPostProcessStencil<U, R>>,
+        Option<&'static ViewMask<PostProcessStencil>>,
+        // Only present when the effect is restricted to a depth range
+        Option<&'static ViewPostProcessDepthRange<U, R>>,
+        Option<&'static ViewDepthTexture>,
+        &'static Msaa,
+        Option<&'static PostProcessBypass>,
+        // Only present when the effect has a user bind group
+        Option<&'static PostProcessBindGroup<U>>,
     );
 
     // Runs the node logic
@@ -175,19 +1146,54 @@ impl<
     // to identify which camera(s) should run the effect.
     fn run(
         &self,
-        _graph: &mut RenderGraphContext,
+        graph: &mut RenderGraphContext,
         render_context: &mut RenderContext,
         (
             view_target,
             _post_process_settings,
             view_uniform_offset,
-            settings_index,
+            view_layers,
+            view_pipeline,
+            intermediate,
+            view_stencil,
+            stencil_mask,
+            view_depth_range,
+            view_depth,
+            msaa,
+            bypass,
+            user_bind_group,
         ): QueryItem<Self::ViewQuery>,
         world: &World,
     ) -> Result<(), NodeRunError> {
+        let plugin_settings = world
+            .get_resource::<PostProcessPluginSettings<U, R>>()
+            .unwrap();
+
+        // Skip cameras that are not on any of the layers of this effect
+        if !plugin_settings
+            .layers
+            .intersects(view_layers.unwrap_or(&PostProcessLayers::default()))
+        {
+            return Ok(());
+        }
+
+        // Skip cameras the effect is turned off on
+        if bypass.is_some_and(|bypass| bypass.is_bypassed(plugin_settings.label.clone())) {
+            return Ok(());
+        }
+
+        // Skip cameras the run condition of the effect failed on
+        if let Some(run_condition) = world.get_resource::<RunCondition<U, R>>()
+            && !run_condition.passes(graph.view_entity())
+        {
+            return Ok(());
+        }
+
         // Get the pipeline resource that contains the global data we need
-        // to create the render pipeline
-        let post_process_pipeline = world.resource::<PostProcessPipeline<U, R>>();
+        // to create the render pipeline. It only exists once a view ran the effect
+        let Some(post_process_pipeline) = world.get_resource::<PostProcessPipeline<U, R>>() else {
+            return Ok(());
+        };
 
         // The pipeline cache is a cache of all previously created pipelines.
         // It is required to avoid creating a new pipeline each frame,
@@ -195,15 +1201,22 @@ impl<
         let pipeline_cache = world.resource::<PipelineCache>();
 
         // Get the pipeline from the cache
-        let Some(pipeline) = pipeline_cache.get_render_pipeline(post_process_pipeline.pipeline_id)
-        else {
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(view_pipeline.pipeline_id) else {
             return Ok(());
         };
 
-        // Get the settings uniform binding
-        let settings_uniforms = world.resource::<ComponentUniforms<U>>();
-        let Some(settings_binding) = settings_uniforms.uniforms().binding() else {
-            return Ok(());
+        // Get the settings uniform binding and the offset of the settings of this view
+        let settings = match &plugin_settings.uniform {
+            Some(uniform) => {
+                let (Some(settings_binding), Some(settings_index)) = (
+                    uniform.binding(world),
+                    uniform.index(world, graph.view_entity()),
+                ) else {
+                    return Ok(());
+                };
+                Some((settings_binding, settings_index))
+            }
+            None => None,
         };
 
         let view_uniforms = world.resource::<ViewUniforms>();
@@ -212,53 +1225,323 @@ impl<
             return Ok(());
         };
 
-        // This will start a new "post process write", obtaining two texture
-        // views from the view target - a `source` and a `destination`.
-        // `source` is the "current" main texture and you _must_ write into
-        // `destination` because calling `post_process_write()` on the
-        // [`ViewTarget`] will internally flip the [`ViewTarget`]'s main
-        // texture to the `destination` texture. Failing to do so will cause
-        // the current main texture information to be lost.
-        let post_process = view_target.post_process_write();
+        // The extra outputs are allocated during the prepare phase
+        let extra_outputs = world
+            .resource::<PostProcessOutputs>()
+            .get(graph.view_entity(), plugin_settings.label.clone())
+            .unwrap_or_default();
+        if extra_outputs.len() != plugin_settings.extra_outputs.len() {
+            return Ok(());
+        }
 
-        let plugin_settings = world
-            .get_resource::<PostProcessPluginSettings<U, R>>()
-            .unwrap();
+        // When rendering at a reduced resolution the effect renders into the intermediate texture,
+        // which then gets upsampled onto the destination by the composite pipeline
+        let composite = match intermediate {
+            Some(intermediate) => {
+                let Some(composite_render_pipeline) =
+                    pipeline_cache.get_render_pipeline(intermediate.composite_pipeline_id)
+                else {
+                    return Ok(());
+                };
+                Some((intermediate, composite_render_pipeline))
+            }
+            None if plugin_settings.uses_intermediate() => return Ok(()),
+            None => None,
+        };
+
+        // The size and the exposure of the view are written during the prepare phase
+        let view_info = match plugin_settings.view_info {
+            true => {
+                let Some(view_info) =
+                    view_info::binding(world, graph.view_entity(), plugin_settings.writes_output())
+                else {
+                    return Ok(());
+                };
+                Some(view_info)
+            }
+            false => None,
+        };
+
+        // The user bind group is prepared for every view
+        if plugin_settings.bind_group_layout.is_some() && user_bind_group.is_none() {
+            return Ok(());
+        }
+
+        // The depth texture can only be bound after the main pass created it
+        let depth_view = match view_depth {
+            Some(view_depth) => Some(view_depth.view()),
+            None if plugin_settings.reads_depth() => return Ok(()),
+            None => None,
+        };
+
+        // The stencil is written from the mask rendered by the stencil camera of the view
+        // and from the depth range of the effect
+        let stencil = if plugin_settings.uses_stencil() {
+            let Some(view_stencil) = view_stencil else {
+                return Ok(());
+            };
+            let Some(copy_pipeline) =
+                pipeline_cache.get_render_pipeline(view_stencil.copy_pipeline_id)
+            else {
+                return Ok(());
+            };
+            let marked = match (view_stencil.write_pipeline_id, stencil_mask) {
+                (Some(write_pipeline_id), Some(stencil_mask)) => {
+                    let (Some(write_pipeline), Some(mask)) = (
+                        pipeline_cache.get_render_pipeline(write_pipeline_id),
+                        world
+                            .resource::<RenderAssets<GpuImage>>()
+                            .get(&stencil_mask.image),
+                    ) else {
+                        return Ok(());
+                    };
+                    Some((write_pipeline, mask))
+                }
+                _ => None,
+            };
+            let depth_range = match view_depth_range {
+                Some(view_depth_range) => {
+                    let (Some(depth_range_pipeline), Some(depth_range_binding)) = (
+                        pipeline_cache.get_render_pipeline(view_depth_range.pipeline_id),
+                        world
+                            .resource::<DepthRangeUniform<U, R>>()
+                            .buffer
+                            .binding(),
+                    ) else {
+                        return Ok(());
+                    };
+                    Some((depth_range_pipeline, depth_range_binding))
+                }
+                None if plugin_settings.depth_range.is_some() => return Ok(()),
+                None => None,
+            };
+            Some((view_stencil, copy_pipeline, marked, depth_range))
+        } else {
+            None
+        };
+
+        // Effects running after the upscaling render into the output textures of the view,
+        // which are prepared once for every effect placed there
+        let output_chain = match plugin_settings.writes_output() {
+            true => {
+                let Some(output_chain) = world.get::<ViewOutputChain>(graph.view_entity()) else {
+                    return Ok(());
+                };
+                let Some(blit_pipeline) =
+                    pipeline_cache.get_render_pipeline(output_chain.blit_pipeline_id)
+                else {
+                    return Ok(());
+                };
+                Some((output_chain, blit_pipeline))
+            }
+            false => None,
+        };
 
-        // The bind_group gets created each frame.
+        // The bind group is created every frame, as the texture the effect reads changes with
+        // the effects that ran before it. Passes that don't flip the view target have theirs
+        // created in the queue phase instead, for both textures they may read.
         //
-        // Normally, you would create a bind_group in the Queue set,
-        // but this doesn't work with the post_process_write().
-        // The reason it doesn't work is because each post_process_write will alternate the source/destination.
-        // The only way to have the correct source/destination for the bind_group
-        // is to make sure you get it during the node execution.
-        let bind_group = render_context.render_device().create_bind_group(
-            plugin_settings.bind_group_layout_label,
-            &post_process_pipeline.layout,
-            // It's important for this to match the BindGroupLayout defined in the SkyPipelinePipeline
-            &BindGroupEntries::sequential((
-                // Make sure to use the source view
-                post_process.source,
-                // Use the sampler created for the pipeline
-                &post_process_pipeline.sampler,
-                // Set the settings binding
-                settings_binding.clone(),
-                view_binding.clone(),
-            )),
+        // The entries are gathered before flipping the view target, so that nothing is flipped
+        // when a bound resource isn't ready
+        let source = match output_chain {
+            Some((output_chain, _)) => output_chain.source(view_target),
+            None => view_target.main_texture_view(),
+        };
+        let queued_bind_group = world
+            .get::<ViewQueuedBindGroups<U, R>>(graph.view_entity())
+            .and_then(|queued| queued.get(source));
+        let entries = match queued_bind_group {
+            Some(_) => Vec::new(),
+            None => {
+                let Some(entries) = bind_group_entries::<U, R>(world, graph.view_entity(), source)
+                else {
+                    return Ok(());
+                };
+                entries
+            }
+        };
+
+        let (source, destination) = match (&plugin_settings.destination, output_chain) {
+            (PostProcessDestination::ViewTarget, Some((output_chain, _))) => {
+                output_chain.write(view_target)
+            }
+            (PostProcessDestination::ViewTarget, None) => {
+                // This will start a new "post process write", obtaining two texture
+                // views from the view target - a `source` and a `destination`.
+                // `source` is the "current" main texture and you _must_ write into
+                // `destination` because calling `post_process_write()` on the
+                // [`ViewTarget`] will internally flip the [`ViewTarget`]'s main
+                // texture to the `destination` texture. Failing to do so will cause
+                // the current main texture information to be lost.
+                let post_process = view_target.post_process_write();
+                (post_process.source, post_process.destination)
+            }
+            (PostProcessDestination::Image(image), _) => {
+                let Some(gpu_image) = world.resource::<RenderAssets<GpuImage>>().get(image) else {
+                    return Ok(());
+                };
+                // The view target is only read from, so there is no need to flip it
+                (view_target.main_texture_view(), &gpu_image.texture_view)
+            }
+        };
+
+        // Effects running every few frames composite their last result in between
+        if let Some((intermediate, composite_render_pipeline)) = composite
+            && intermediate.reuses_result()
+        {
+            let diagnostics = render_context.diagnostic_recorder();
+            let time_span = diagnostics.time_span(
+                render_context.command_encoder(),
+                stats::span_name(plugin_settings.label.clone()),
+            );
+            resolution::composite(
+                render_context,
+                world,
+                intermediate,
+                composite_render_pipeline,
+                destination,
+            );
+            time_span.end(render_context.command_encoder());
+            if let Some(rendered) = world.get_resource::<RenderedEffects>() {
+                rendered.insert(plugin_settings.label.intern());
+            }
+            return Ok(());
+        }
+
+        let bind_group = match queued_bind_group {
+            Some(bind_group) => bind_group.clone(),
+            None => render_context.render_device().create_bind_group(
+                plugin_settings.bind_group_layout_label,
+                post_process_pipeline.layout(msaa.samples() > 1),
+                &entries,
+            ),
+        };
+
+        // Time the passes of the effect, when render diagnostics are enabled
+        let diagnostics = render_context.diagnostic_recorder();
+        let time_span = diagnostics.time_span(
+            render_context.command_encoder(),
+            stats::span_name(plugin_settings.label.clone()),
         );
 
+        let effect_target = match composite {
+            Some((intermediate, _)) => &intermediate.texture.default_view,
+            None => destination,
+        };
+
+        if let Some((view_stencil, copy_pipeline, marked, depth_range)) = &stencil {
+            let composite_pipeline = world.resource::<CompositePipeline>();
+            let copy_bind_group = render_context.render_device().create_bind_group(
+                "post_process_stencil_copy_bind_group",
+                &composite_pipeline.layout,
+                &BindGroupEntries::sequential((source, &composite_pipeline.sampler)),
+            );
+            // The bind groups are created before the pass, which borrows the render context
+            let write_bind_group = marked.map(|(write_pipeline, mask)| {
+                let write_pipeline_resource = world.resource::<StencilWritePipeline>();
+                let write_bind_group = render_context.render_device().create_bind_group(
+                    "post_process_stencil_write_bind_group",
+                    &write_pipeline_resource.layout,
+                    &BindGroupEntries::sequential((
+                        &mask.texture_view,
+                        &write_pipeline_resource.sampler,
+                    )),
+                );
+                (write_pipeline, write_bind_group)
+            });
+            let depth_range_bind_group = match (depth_range, depth_view) {
+                (Some((depth_range_pipeline, depth_range_binding)), Some(depth_view)) => {
+                    let depth_range_layout = world
+                        .resource::<DepthRangePipeline>()
+                        .layout(msaa.samples() > 1);
+                    let depth_range_bind_group = render_context.render_device().create_bind_group(
+                        "post_process_depth_range_bind_group",
+                        depth_range_layout,
+                        &BindGroupEntries::sequential((
+                            view_binding.clone(),
+                            depth_view,
+                            depth_range_binding.clone(),
+                        )),
+                    );
+                    Some((*depth_range_pipeline, depth_range_bind_group))
+                }
+                _ => None,
+            };
+
+            // Copy the source so the pixels skipped by the effect keep their color,
+            // then write the stencil of the marked meshes and of the depth range
+            let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("post_process_stencil_write_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: effect_target,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })],
+                depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                    view: &view_stencil.texture.default_view,
+                    depth_ops: None,
+                    stencil_ops: Some(Operations {
+                        load: LoadOp::Clear(plugin_settings.stencil.clear_value()),
+                        store: StoreOp::Store,
+                    }),
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_render_pipeline(copy_pipeline);
+            render_pass.set_bind_group(0, &copy_bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+
+            if let Some((write_pipeline, write_bind_group)) = &write_bind_group {
+                render_pass.set_render_pipeline(write_pipeline);
+                render_pass.set_bind_group(0, write_bind_group, &[]);
+                render_pass.set_stencil_reference(plugin_settings.stencil.marked_reference());
+                render_pass.draw(0..3, 0..1);
+            }
+
+            if let Some((depth_range_pipeline, depth_range_bind_group)) = &depth_range_bind_group {
+                // The pixels outside of the range aren't affected
+                render_pass.set_render_pipeline(depth_range_pipeline);
+                render_pass.set_bind_group(
+                    0,
+                    depth_range_bind_group,
+                    &[view_uniform_offset.offset],
+                );
+                render_pass.set_stencil_reference(0);
+                render_pass.draw(0..3, 0..1);
+            }
+        }
+
+        let color_attachments: Vec<_> = std::iter::once(effect_target)
+            // The extra outputs follow the main output, in the order they were declared
+            .chain(extra_outputs.iter().map(|texture| &texture.default_view))
+            .map(|view| {
+                Some(RenderPassColorAttachment {
+                    // We need to specify the post process destination view here
+                    // to make sure we write to the appropriate texture.
+                    view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })
+            })
+            .collect();
+
         // Begin the render pass
         let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
             label: plugin_settings.debug_label,
-            color_attachments: &[Some(RenderPassColorAttachment {
-                // We need to specify the post process destination view here
-                // to make sure we write to the appropriate texture.
-                view: post_process.destination,
-                depth_slice: None,
-                resolve_target: None,
-                ops: Operations::default(),
-            })],
-            depth_stencil_attachment: None,
+            color_attachments: &color_attachments,
+            depth_stencil_attachment: stencil.as_ref().map(|(view_stencil, ..)| {
+                RenderPassDepthStencilAttachment {
+                    view: &view_stencil.texture.default_view,
+                    depth_ops: None,
+                    stencil_ops: Some(Operations {
+                        load: LoadOp::Load,
+                        store: StoreOp::Discard,
+                    }),
+                }
+            }),
             timestamp_writes: None,
             occlusion_query_set: None,
         });
@@ -269,96 +1552,212 @@ impl<
         // By passing in the index of the post process settings on this view, we ensure
         // that in the event that multiple settings were sent to the GPU (as would be the
         // case with multiple cameras), we use the correct one.
-        render_pass.set_bind_group(
-            0,
-            &bind_group,
-            &[settings_index.index(), view_uniform_offset.offset],
-        );
+        // The dynamic offsets are ordered by binding index.
+        let dynamic_offsets: Vec<_> = settings
+            .iter()
+            .map(|(_, settings_index)| *settings_index)
+            .chain(plugin_settings.view.then_some(view_uniform_offset.offset))
+            .chain(view_info.iter().map(|(_, view_info_offset)| *view_info_offset))
+            .collect();
+        render_pass.set_bind_group(0, &bind_group, &dynamic_offsets);
+        if let Some(user_bind_group) = user_bind_group {
+            render_pass.set_bind_group(
+                1,
+                &user_bind_group.bind_group,
+                &user_bind_group.dynamic_offsets,
+            );
+        }
+        if stencil.is_some() {
+            render_pass.set_stencil_reference(1);
+        }
+        if let Some(prepare_pass) = plugin_settings.view_data {
+            prepare_pass(world, graph.view_entity(), &mut render_pass);
+        }
         render_pass.draw(0..3, 0..1);
+        drop(render_pass);
+
+        if let Some((intermediate, composite_render_pipeline)) = composite {
+            intermediate.set_rendered();
+            // Upsample the reduced resolution output onto the destination
+            resolution::composite(
+                render_context,
+                world,
+                intermediate,
+                composite_render_pipeline,
+                destination,
+            );
+        }
+
+        if let Some((output_chain, blit_pipeline)) = output_chain {
+            output_chain.blit(render_context, world, view_target, blit_pipeline, destination);
+        }
+
+        time_span.end(render_context.command_encoder());
+        if let Some(rendered) = world.get_resource::<RenderedEffects>() {
+            rendered.insert(plugin_settings.label.intern());
+        }
 
         Ok(())
     }
 }
 
-// This contains global data used by the render pipeline. This will be created once on startup.
-#[derive(Resource)]
-struct PostProcessPipeline<U, R> {
-    layout: BindGroupLayout,
-    sampler: Sampler,
-    pipeline_id: CachedRenderPipelineId,
-    _uniform: PhantomData<U>,
-    _render_label: PhantomData<R>,
-}
+/// The entries of the bind group of the effect on a view, with `source` as the screen texture.
+///
+/// Returns `None` while one of the bound resources isn't ready. It's important for this to match
+/// the BindGroupLayout defined in the PostProcessPipeline.
+fn bind_group_entries<
+    'w,
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    world: &'w World,
+    view_entity: Entity,
+    source: &'w TextureView,
+) -> Option<Vec<BindGroupEntry<'w>>> {
+    let plugin_settings = world.resource::<PostProcessPluginSettings<U, R>>();
+    let post_process_pipeline = world.get_resource::<PostProcessPipeline<U, R>>()?;
 
-impl<U: Clone + Send + Sync + ShaderType + 'static, R: Hash + Eq + Clone + RenderLabel> FromWorld
-    for PostProcessPipeline<U, R>
-{
-    fn from_world(world: &mut World) -> Self {
-        let plugin_settings = world
-            .get_resource::<PostProcessPluginSettings<U, R>>()
-            .unwrap()
-            .clone();
-        let render_device = world.resource::<RenderDevice>();
-        // We need to define the bind group layout used for our pipeline
-        let layout = render_device.create_bind_group_layout(
-            plugin_settings.bind_group_layout_label,
-            &BindGroupLayoutEntries::sequential(
-                // The layout entries will only be visible in the fragment stage
-                ShaderStages::VERTEX_FRAGMENT,
-                (
-                    // The screen texture
-                    texture_2d(TextureSampleType::Float { filterable: true }),
-                    // The sampler that will be used to sample the screen texture
-                    sampler(SamplerBindingType::Filtering),
-                    // The settings uniform that will control the effect
-                    uniform_buffer::<U>(true),
-                    // The view uniform
-                    uniform_buffer::<ViewUniform>(true),
-                ),
-            ),
-        );
+    let mut entries = vec![
+        BindGroupEntry {
+            binding: bindings::SCREEN_TEXTURE,
+            // Make sure to use the source view
+            resource: source.into_binding(),
+        },
+        BindGroupEntry {
+            binding: bindings::SAMPLER,
+            // Use the sampler created for the pipeline
+            resource: post_process_pipeline.sampler.into_binding(),
+        },
+    ];
 
-        // We can create the sampler here since it won't change at runtime and doesn't depend on the view
-        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
-
-        // Get the shader handle
-        let shader = world.load_asset(plugin_settings.shader_path);
-        
-        let pipeline_id = world
-            .resource::<PipelineCache>()
-            // This will add the pipeline to the cache and queue its creation
-            .queue_render_pipeline(RenderPipelineDescriptor {
-                label: plugin_settings.debug_label.map(Into::into),
-                layout: vec![layout.clone()],
-                // This will setup a fullscreen triangle for the vertex state
-                vertex: plugin_settings.vertex_state,
-                fragment: Some(FragmentState {
-                    shader,
-                    shader_defs: vec![],
-                    // Make sure this matches the entry point of your shader.
-                    // It can be anything as long as it matches here and in the shader.
-                    entry_point: Some("fragment".into()),
-                    targets: vec![Some(ColorTargetState {
-                        format: TextureFormat::bevy_default(),
-                        blend: None,
-                        write_mask: ColorWrites::ALL,
-                    })],
-                }),
-                // All the following properties are not important for this effect so just use the default values.
-                // This struct doesn't have the Default trait implemented because not all fields can have a default value.
-                primitive: PrimitiveState::default(),
-                depth_stencil: None,
-                multisample: MultisampleState::default(),
-                push_constant_ranges: vec![],
-                zero_initialize_workgroup_memory: false,
-            });
+    if plugin_settings.view {
+        entries.push(BindGroupEntry {
+            binding: bindings::VIEW,
+            resource: world.resource::<ViewUniforms>().uniforms.binding()?,
+        });
+    }
 
-        PostProcessPipeline::<U, R> {
-            layout,
-            sampler,
-            pipeline_id,
-            _uniform: Default::default(),
-            _render_label: Default::default(),
-        }
+    if let Some([linear_clamp, nearest_clamp, linear_repeat]) =
+        &post_process_pipeline.standard_samplers
+    {
+        entries.extend([
+            BindGroupEntry {
+                binding: bindings::LINEAR_CLAMP_SAMPLER,
+                resource: linear_clamp.into_binding(),
+            },
+            BindGroupEntry {
+                binding: bindings::NEAREST_CLAMP_SAMPLER,
+                resource: nearest_clamp.into_binding(),
+            },
+            BindGroupEntry {
+                binding: bindings::LINEAR_REPEAT_SAMPLER,
+                resource: linear_repeat.into_binding(),
+            },
+        ]);
+    }
+
+    if let Some(uniform) = &plugin_settings.uniform {
+        entries.push(BindGroupEntry {
+            binding: bindings::SETTINGS,
+            resource: uniform.binding(world)?,
+        });
+    }
+
+    // The depth texture can only be bound after the main pass created it
+    if plugin_settings.depth {
+        entries.push(BindGroupEntry {
+            binding: bindings::DEPTH,
+            resource: world
+                .get::<ViewDepthTexture>(view_entity)?
+                .view()
+                .into_binding(),
+        });
     }
+
+    // The instances are written during the prepare phase
+    if let Some(instances) = &plugin_settings.instances {
+        let [instances, instance_count] = instances.bindings(world)?;
+        entries.extend([
+            BindGroupEntry {
+                binding: bindings::INSTANCES,
+                resource: instances,
+            },
+            BindGroupEntry {
+                binding: bindings::INSTANCE_COUNT,
+                resource: instance_count,
+            },
+        ]);
+    }
+
+    // The audio uniform is written during the prepare phase
+    #[cfg(feature = "audio")]
+    if plugin_settings.audio {
+        entries.push(BindGroupEntry {
+            binding: bindings::AUDIO,
+            resource: audio::binding(world)?,
+        });
+    }
+
+    // The size and the exposure of the view are written during the prepare phase
+    if plugin_settings.view_info {
+        let (view_info_binding, _) =
+            view_info::binding(world, view_entity, plugin_settings.writes_output())?;
+        entries.push(BindGroupEntry {
+            binding: bindings::VIEW_INFO,
+            resource: view_info_binding,
+        });
+    }
+
+    // The LUTs are loaded by the tonemapping plugin
+    if plugin_settings.tonemapping_lut {
+        entries.extend(tonemapping::bind_group_entries(world, view_entity)?);
+    }
+
+    // Inputs that aren't available yet are replaced by the fallback image
+    let gpu_images = world.resource::<RenderAssets<GpuImage>>();
+    let fallback_image = world.resource::<FallbackImage>();
+    let resolved_inputs = world.resource::<ResolvedInputs<U, R>>();
+    entries.extend(
+        resolved_inputs
+            .images
+            .iter()
+            .enumerate()
+            .map(|(index, image)| BindGroupEntry {
+                binding: bindings::FIRST_INPUT + index as u32,
+                resource: image
+                    .and_then(|image| gpu_images.get(image))
+                    .map_or(&fallback_image.d2.texture_view, |gpu_image| {
+                        &gpu_image.texture_view
+                    })
+                    .into_binding(),
+            }),
+    );
+    if plugin_settings.mask.is_some() {
+        entries.push(BindGroupEntry {
+            binding: bindings::MASK,
+            resource: resolved_inputs
+                .mask
+                .and_then(|image| gpu_images.get(image))
+                .map_or(&fallback_image.d2.texture_view, |gpu_image| {
+                    &gpu_image.texture_view
+                })
+                .into_binding(),
+        });
+    }
+    if plugin_settings.entity_mask {
+        // Nothing is masked until the mask camera of the view rendered
+        let fallback_image_zero = world.resource::<FallbackImageZero>();
+        entries.push(BindGroupEntry {
+            binding: bindings::ENTITY_MASK,
+            resource: world
+                .get::<ViewMask<PostProcessMask>>(view_entity)
+                .and_then(|entity_mask| gpu_images.get(&entity_mask.image))
+                .map_or(&fallback_image_zero.texture_view, |gpu_image| {
+                    &gpu_image.texture_view
+                })
+                .into_binding(),
+        });
+    }
+
+    Some(entries)
 }
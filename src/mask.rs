@@ -0,0 +1,409 @@
+use crate::global::{self, EffectCameraFilter, GlobalEffect};
+use bevy::{
+    asset::RenderAssetUsages,
+    camera::{visibility::RenderLayers, CameraUpdateSystems},
+    color::ColorToComponents,
+    core_pipeline::tonemapping::Tonemapping,
+    ecs::query::QueryItem,
+    light::NotShadowCaster,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+    },
+};
+use std::marker::PhantomData;
+
+/// Marks a mesh as part of the entity mask bound to effects using
+/// [`PostProcessPlugin::with_entity_mask`](crate::PostProcessPlugin::with_entity_mask).
+///
+/// The mesh is rendered into the mask of every view with its color, over a black background.
+/// By convention the red channel is the weight of the effect, while the other channels can
+/// carry an id or a color, like the outline color of a selected object.
+///
+/// The marked meshes are rendered by a crate-managed camera on [`PostProcessMask::RENDER_LAYER`],
+/// without testing against the depth of the scene, so hidden meshes are still visible in the mask.
+/// This is what x-ray highlights need, and effects can compare with the depth binding otherwise.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PostProcessMask {
+    /// The color the mesh is rendered with in the mask.
+    pub color: Color,
+}
+
+impl PostProcessMask {
+    /// The render layer used to render the marked meshes. Avoid using it for anything else.
+    pub const RENDER_LAYER: usize = 30;
+}
+
+impl Default for PostProcessMask {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+        }
+    }
+}
+
+/// A component marking meshes rendered into a per-view mask by a crate-managed camera.
+pub(crate) trait MaskSource: Component {
+    /// The render layer the copies of the marked meshes are rendered on
+    const RENDER_LAYER: usize;
+
+    /// The color the mesh is rendered with in the mask
+    fn mask_color(&self) -> Color;
+}
+
+impl MaskSource for PostProcessMask {
+    const RENDER_LAYER: usize = PostProcessMask::RENDER_LAYER;
+
+    fn mask_color(&self) -> Color {
+        self.color
+    }
+}
+
+// Renders the meshes marked with `M` into a mask for every view that needs it.
+// Added once per kind of mask, the effects using it mark the cameras that need it.
+pub(crate) struct MaskCameraPlugin<M>(PhantomData<M>);
+
+impl<M> Default for MaskCameraPlugin<M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<M: MaskSource> Plugin for MaskCameraPlugin<M> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MaskMaterials>()
+            .init_resource::<MaskUsers<M>>()
+            .add_plugins(ExtractComponentPlugin::<ViewMask<M>>::default())
+            .add_systems(
+                PostUpdate,
+                (
+                    mirror_meshes::<M>,
+                    (update_mask_cameras::<M>, sync_mask_cameras::<M>).chain(),
+                )
+                    .before(CameraUpdateSystems),
+            );
+    }
+}
+
+// The unlit materials of the copies of the marked meshes, one per color
+#[derive(Resource, Default)]
+struct MaskMaterials(HashMap<[u32; 4], Handle<StandardMaterial>>);
+
+impl MaskMaterials {
+    fn get(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+        color: Color,
+    ) -> Handle<StandardMaterial> {
+        let key = color.to_linear().to_f32_array().map(f32::to_bits);
+        self.0
+            .entry(key)
+            .or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color: color,
+                    unlit: true,
+                    ..default()
+                })
+            })
+            .clone()
+    }
+}
+
+// Points from a marked mesh to its copy rendered on the mask layer
+#[derive(Component)]
+struct MaskMirror<M> {
+    entity: Entity,
+    _marker: PhantomData<M>,
+}
+
+/// Marks the cameras rendering a mask, which never run effects themselves.
+#[derive(Component)]
+pub(crate) struct MaskCamera;
+
+/// The mask rendered for a view by its mask camera.
+#[derive(Component)]
+pub(crate) struct ViewMask<M> {
+    pub(crate) image: Handle<Image>,
+    camera: Entity,
+    _marker: PhantomData<M>,
+}
+
+impl<M> Clone for ViewMask<M> {
+    fn clone(&self) -> Self {
+        Self {
+            image: self.image.clone(),
+            camera: self.camera,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<M: MaskSource> ExtractComponent for ViewMask<M> {
+    type QueryData = &'static Self;
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(item: QueryItem<'_, '_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+/// The cameras running an effect that needs the mask `M` this frame.
+#[derive(Resource)]
+pub(crate) struct MaskUsers<M> {
+    cameras: HashSet<Entity>,
+    _marker: PhantomData<M>,
+}
+
+impl<M> Default for MaskUsers<M> {
+    fn default() -> Self {
+        Self {
+            cameras: HashSet::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Mark the cameras running the effect as needing the mask `M`.
+///
+/// Added by every effect using the mask, before [`update_mask_cameras`] spawns or despawns the
+/// mask cameras.
+pub(crate) fn collect_mask_users<U: Component, M: MaskSource>(
+    mut users: ResMut<MaskUsers<M>>,
+    cameras: Query<(Entity, Has<U>), EffectCameraFilter>,
+    global: Option<Res<GlobalEffect<U>>>,
+) {
+    let cameras = cameras
+        .iter()
+        .filter(|(_, has_settings)| global::runs_effect(*has_settings, &global))
+        .map(|(entity, _)| entity);
+    users.cameras.extend(cameras);
+}
+
+// The cameras that may need a mask, with the mask they have
+type MaskCameraUsers<M> = (
+    Entity,
+    &'static Camera,
+    &'static Projection,
+    Option<&'static ViewMask<M>>,
+);
+
+/// Spawn a mask camera for every camera running an effect that needs the mask, and despawn it once
+/// none does.
+pub(crate) fn update_mask_cameras<M: MaskSource>(
+    mut commands: Commands,
+    mut users: ResMut<MaskUsers<M>>,
+    cameras: Query<MaskCameraUsers<M>, EffectCameraFilter>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (entity, camera, projection, mask) in &cameras {
+        match (users.cameras.contains(&entity), mask) {
+            (true, None) => {
+                let mask =
+                    spawn_mask_camera::<M>(&mut commands, &mut images, entity, camera, projection);
+                commands.entity(entity).insert(mask);
+            }
+            // No effect needs the mask anymore, the image is freed with the last handle
+            (false, Some(mask)) => {
+                commands.entity(mask.camera).try_despawn();
+                commands.entity(entity).remove::<ViewMask<M>>();
+            }
+            _ => {}
+        }
+    }
+    users.cameras.clear();
+}
+
+// Spawn the camera rendering the mask of a camera, as its child
+fn spawn_mask_camera<M: MaskSource>(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    entity: Entity,
+    camera: &Camera,
+    projection: &Projection,
+) -> ViewMask<M> {
+    let size = camera.physical_target_size().unwrap_or(UVec2::ONE);
+    let image = images.add(mask_image(size));
+
+    let mask_camera = commands
+        .spawn((
+            Camera3d::default(),
+            Camera {
+                // Render the mask before the camera using it
+                order: camera.order - 1,
+                target: image.clone().into(),
+                clear_color: ClearColorConfig::Custom(Color::BLACK),
+                // The mask covers the same part of its image as the camera of its target
+                viewport: camera.viewport.clone(),
+                ..default()
+            },
+            projection.clone(),
+            RenderLayers::layer(M::RENDER_LAYER),
+            Msaa::Off,
+            Tonemapping::None,
+            Transform::IDENTITY,
+            ChildOf(entity),
+            MaskCamera,
+        ))
+        .id();
+
+    ViewMask {
+        image,
+        camera: mask_camera,
+        _marker: PhantomData,
+    }
+}
+
+fn mask_image(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x.max(1),
+            height: size.y.max(1),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Rgba8Unorm,
+        // Kept in the main world so it can be resized with the camera
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_DST;
+    image
+}
+
+// The cameras whose mask camera must follow them
+type ChangedCameras = Or<(Changed<Camera>, Changed<Projection>)>;
+
+/// Keep the mask cameras in sync with the camera they render the mask for.
+fn sync_mask_cameras<M: MaskSource>(
+    cameras: Query<(&Camera, &Projection, &ViewMask<M>), ChangedCameras>,
+    mut mask_cameras: Query<(&mut Camera, &mut Projection), Without<ViewMask<M>>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (camera, projection, mask) in &cameras {
+        let Ok((mut mask_camera, mut mask_projection)) = mask_cameras.get_mut(mask.camera) else {
+            continue;
+        };
+        mask_camera.is_active = camera.is_active;
+        mask_camera.order = camera.order - 1;
+        mask_camera.viewport = camera.viewport.clone();
+        *mask_projection = projection.clone();
+
+        // Follow the size of the render target of the camera
+        if let Some(size) = camera.physical_target_size()
+            && let Some(image) = images.get(&mask.image)
+            && image.size() != size
+        {
+            images.insert(&mask.image, mask_image(size)).ok();
+        }
+    }
+}
+
+// The marked meshes whose copy must follow them
+type ChangedSources<M> = Or<(Changed<Mesh3d>, Changed<M>)>;
+
+/// Render a copy of every marked mesh on the mask layer.
+fn mirror_meshes<M: MaskSource>(
+    mut commands: Commands,
+    mut mask_materials: ResMut<MaskMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    added: Query<(Entity, &Mesh3d, &M), Without<MaskMirror<M>>>,
+    changed: Query<(&Mesh3d, &M, &MaskMirror<M>), ChangedSources<M>>,
+    mut removed: RemovedComponents<M>,
+    mirrors: Query<&MaskMirror<M>>,
+) {
+    for (entity, mesh, source) in &added {
+        let mirror = commands
+            .spawn((
+                mesh.clone(),
+                MeshMaterial3d(mask_materials.get(&mut materials, source.mask_color())),
+                RenderLayers::layer(M::RENDER_LAYER),
+                NotShadowCaster,
+                Transform::IDENTITY,
+                ChildOf(entity),
+            ))
+            .id();
+        commands.entity(entity).insert(MaskMirror::<M> {
+            entity: mirror,
+            _marker: PhantomData,
+        });
+    }
+
+    for (mesh, source, mirror) in &changed {
+        commands.entity(mirror.entity).try_insert((
+            mesh.clone(),
+            MeshMaterial3d(mask_materials.get(&mut materials, source.mask_color())),
+        ));
+    }
+
+    for entity in removed.read() {
+        if let Ok(mirror) = mirrors.get(entity) {
+            commands.entity(mirror.entity).try_despawn();
+            commands.entity(entity).try_remove::<MaskMirror<M>>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct FirstSettings;
+
+    #[derive(Component)]
+    struct SecondSettings;
+
+    fn mask_cameras(app: &mut App) -> usize {
+        app.world_mut()
+            .query_filtered::<(), With<MaskCamera>>()
+            .iter(app.world())
+            .count()
+    }
+
+    #[test]
+    fn effects_sharing_a_mask_share_its_camera() {
+        let mut app = App::new();
+        app.init_resource::<Assets<Image>>()
+            .init_resource::<MaskUsers<PostProcessMask>>()
+            .add_systems(
+                Update,
+                (
+                    collect_mask_users::<FirstSettings, PostProcessMask>,
+                    collect_mask_users::<SecondSettings, PostProcessMask>,
+                    update_mask_cameras::<PostProcessMask>,
+                )
+                    .chain(),
+            );
+        let camera = app
+            .world_mut()
+            .spawn((
+                Camera::default(),
+                Projection::default(),
+                FirstSettings,
+                SecondSettings,
+            ))
+            .id();
+
+        app.update();
+        assert_eq!(mask_cameras(&mut app), 1);
+
+        app.world_mut().entity_mut(camera).remove::<FirstSettings>();
+        app.update();
+        assert_eq!(mask_cameras(&mut app), 1);
+
+        app.world_mut()
+            .entity_mut(camera)
+            .remove::<SecondSettings>();
+        app.update();
+        assert_eq!(mask_cameras(&mut app), 0);
+        assert!(
+            !app.world()
+                .entity(camera)
+                .contains::<ViewMask<PostProcessMask>>()
+        );
+    }
+}
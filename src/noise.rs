@@ -0,0 +1,271 @@
+//! Procedurally generated textures commonly needed by dithering, grain and dissolve effects.
+//!
+//! Add [`PostProcessNoisePlugin`] to generate them, then bind them to an effect with
+//! [`PostProcessInput::Image`](crate::PostProcessInput::Image) and one of the handles of this module.
+//!
+//! All the textures tile and are single channel `R8Unorm` images. They are meant to be read with
+//! `textureLoad` and wrapped pixel coordinates, like
+//! `textureLoad(blue_noise, vec2<u32>(in.position.xy) % textureDimensions(blue_noise), 0).r`.
+
+use bevy::{
+    asset::{uuid_handle, RenderAssetUsages},
+    image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+/// A 64x64 tiling blue noise texture, generated with the void and cluster method.
+/// Every value from 0 to 1 appears the same number of times.
+pub const BLUE_NOISE: Handle<Image> = uuid_handle!("7c4bd1e6-5f0e-4c0a-9a0c-1b6f3f9b4e21");
+/// A 64x64 white noise texture.
+pub const WHITE_NOISE: Handle<Image> = uuid_handle!("0f8e6a57-2d7b-4f55-8f2e-6a3c1d9e7b42");
+/// A 128x128 tiling value noise texture with a few octaves.
+pub const VALUE_NOISE: Handle<Image> = uuid_handle!("c21a9f3e-8b4d-4e6f-a1c7-3d5e9f2b8a63");
+/// The 2x2 Bayer matrix used for ordered dithering.
+pub const BAYER_2X2: Handle<Image> = uuid_handle!("5e3d7a1b-9c2f-4b8e-b6d4-2f1a8c7e9d84");
+/// The 4x4 Bayer matrix used for ordered dithering.
+pub const BAYER_4X4: Handle<Image> = uuid_handle!("a8f2c6d4-1e7b-4a3c-9d5f-7b2e4c1a6f95");
+/// The 8x8 Bayer matrix used for ordered dithering.
+pub const BAYER_8X8: Handle<Image> = uuid_handle!("3b9e5f2a-7d1c-4e8b-8a6f-9c4d2e7b1a06");
+
+/// Generates the noise textures of this module and registers them at their constant handles.
+pub struct PostProcessNoisePlugin;
+
+impl Plugin for PostProcessNoisePlugin {
+    fn build(&self, app: &mut App) {
+        let mut images = app.world_mut().resource_mut::<Assets<Image>>();
+
+        for (handle, size, data) in [
+            (BLUE_NOISE, 64, blue_noise(64)),
+            (WHITE_NOISE, 64, white_noise(64)),
+            (VALUE_NOISE, 128, value_noise(128, 8)),
+            (BAYER_2X2, 2, bayer(2)),
+            (BAYER_4X4, 4, bayer(4)),
+            (BAYER_8X8, 8, bayer(8)),
+        ] {
+            images
+                .insert(&handle, noise_image(size, data))
+                .expect("the noise handles are uuid handles");
+        }
+    }
+}
+
+fn noise_image(size: u32, data: Vec<u8>) -> Image {
+    let mut image = Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::nearest()
+    });
+    image
+}
+
+/// Map a rank in `0..count` to the full `u8` range.
+fn rank_to_u8(rank: usize, count: usize) -> u8 {
+    ((rank as f32 + 0.5) / count as f32 * 255.0) as u8
+}
+
+/// A small integer hash used as a deterministic random number generator.
+fn hash(mut x: u32) -> u32 {
+    // PCG hash
+    x = x.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((x >> ((x >> 28) + 4)) ^ x).wrapping_mul(277803737);
+    (word >> 22) ^ word
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn bayer(size: usize) -> Vec<u8> {
+    // Build the matrix recursively: M(2n) = [4M + 0, 4M + 2; 4M + 3, 4M + 1]
+    let mut matrix = vec![0usize];
+    let mut n = 1;
+    while n < size {
+        let mut next = vec![0; 4 * n * n];
+        for y in 0..n {
+            for x in 0..n {
+                let value = 4 * matrix[y * n + x];
+                next[y * 2 * n + x] = value;
+                next[y * 2 * n + x + n] = value + 2;
+                next[(y + n) * 2 * n + x] = value + 3;
+                next[(y + n) * 2 * n + x + n] = value + 1;
+            }
+        }
+        matrix = next;
+        n *= 2;
+    }
+
+    matrix
+        .into_iter()
+        .map(|rank| rank_to_u8(rank, size * size))
+        .collect()
+}
+
+fn white_noise(size: usize) -> Vec<u8> {
+    (0..size * size)
+        .map(|index| (hash(index as u32) & 0xff) as u8)
+        .collect()
+}
+
+fn value_noise(size: usize, base_cells: usize) -> Vec<u8> {
+    let mut values = vec![0.0f32; size * size];
+    let mut amplitude = 0.5;
+    let mut total_amplitude = 0.0;
+    let mut cells = base_cells;
+    let mut octave = 0;
+
+    while cells <= size / 2 {
+        let lattice = |x: usize, y: usize| {
+            let index = (y % cells) * cells + (x % cells);
+            (hash(index as u32 ^ hash(octave)) & 0xffff) as f32 / 65535.0
+        };
+        let cell_size = (size / cells) as f32;
+
+        for y in 0..size {
+            for x in 0..size {
+                let fx = x as f32 / cell_size;
+                let fy = y as f32 / cell_size;
+                let (x0, y0) = (fx as usize, fy as usize);
+                // Smoothstep between the lattice points
+                let tx = fx.fract() * fx.fract() * (3.0 - 2.0 * fx.fract());
+                let ty = fy.fract() * fy.fract() * (3.0 - 2.0 * fy.fract());
+                let top = lerp(lattice(x0, y0), lattice(x0 + 1, y0), tx);
+                let bottom = lerp(lattice(x0, y0 + 1), lattice(x0 + 1, y0 + 1), tx);
+                values[y * size + x] += lerp(top, bottom, ty) * amplitude;
+            }
+        }
+
+        total_amplitude += amplitude;
+        amplitude *= 0.5;
+        cells *= 2;
+        octave += 1;
+    }
+
+    values
+        .into_iter()
+        .map(|value| (value / total_amplitude * 255.0) as u8)
+        .collect()
+}
+
+/// Generate tiling blue noise with the void and cluster method.
+fn blue_noise(size: usize) -> Vec<u8> {
+    const SIGMA: f32 = 1.5;
+    let count = size * size;
+
+    // The gaussian energy contributed by a pixel to every offset, wrapping around the edges
+    let energy_lut: Vec<f32> = (0..count)
+        .map(|index| {
+            let wrap = |d: usize| d.min(size - d) as f32;
+            let (dx, dy) = (wrap(index % size), wrap(index / size));
+            (-(dx * dx + dy * dy) / (2.0 * SIGMA * SIGMA)).exp()
+        })
+        .collect();
+
+    struct Pattern<'a> {
+        size: usize,
+        lut: &'a [f32],
+        ones: Vec<bool>,
+        energy: Vec<f32>,
+    }
+
+    impl Pattern<'_> {
+        fn set(&mut self, index: usize, value: bool) {
+            self.ones[index] = value;
+            let sign = if value { 1.0 } else { -1.0 };
+            let (x, y) = (index % self.size, index / self.size);
+            for (other, energy) in self.energy.iter_mut().enumerate() {
+                let dx = (other % self.size + self.size - x) % self.size;
+                let dy = (other / self.size + self.size - y) % self.size;
+                *energy += sign * self.lut[dy * self.size + dx];
+            }
+        }
+
+        fn tightest_cluster(&self) -> usize {
+            self.extreme(true, |a, b| a > b)
+        }
+
+        fn largest_void(&self) -> usize {
+            self.extreme(false, |a, b| a < b)
+        }
+
+        fn extreme(&self, of: bool, better: impl Fn(f32, f32) -> bool) -> usize {
+            let mut best = None;
+            for (index, &one) in self.ones.iter().enumerate() {
+                if one == of && best.is_none_or(|best| better(self.energy[index], self.energy[best]))
+                {
+                    best = Some(index);
+                }
+            }
+            best.unwrap()
+        }
+    }
+
+    let mut pattern = Pattern {
+        size,
+        lut: &energy_lut,
+        ones: vec![false; count],
+        energy: vec![0.0; count],
+    };
+
+    // Start with about 10% of random pixels set
+    let initial = count / 10;
+    let mut placed = 0;
+    let mut seed = 0;
+    while placed < initial {
+        let index = hash(seed) as usize % count;
+        seed += 1;
+        if !pattern.ones[index] {
+            pattern.set(index, true);
+            placed += 1;
+        }
+    }
+
+    // Spread the initial pattern out by moving the tightest cluster into the largest void
+    loop {
+        let cluster = pattern.tightest_cluster();
+        pattern.set(cluster, false);
+        let void = pattern.largest_void();
+        pattern.set(void, true);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0; count];
+
+    // Rank the initial pixels by removing the tightest clusters one by one
+    let mut removal = Pattern {
+        size,
+        lut: &energy_lut,
+        ones: pattern.ones.clone(),
+        energy: pattern.energy.clone(),
+    };
+    for rank in (0..initial).rev() {
+        let cluster = removal.tightest_cluster();
+        removal.set(cluster, false);
+        ranks[cluster] = rank;
+    }
+
+    // Rank the remaining pixels by filling the largest voids one by one
+    for rank in initial..count {
+        let void = pattern.largest_void();
+        pattern.set(void, true);
+        ranks[void] = rank;
+    }
+
+    ranks
+        .into_iter()
+        .map(|rank| rank_to_u8(rank, count))
+        .collect()
+}
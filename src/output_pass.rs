@@ -0,0 +1,193 @@
+use crate::{
+    resolution::{CompositePipeline, CompositePipelineKey},
+    PostProcessTexturePool, PostProcessUpscaleFilter,
+};
+use bevy::{
+    camera::Viewport,
+    core_pipeline::core_3d::graph::Node3d,
+    prelude::*,
+    render::{
+        camera::ExtractedCamera,
+        render_graph::{
+            EmptyNode, InternedRenderSubGraph, RenderGraph, RenderGraphExt, RenderLabel,
+        },
+        render_resource::*,
+        renderer::{RenderContext, RenderDevice},
+        texture::CachedTexture,
+        view::ViewTarget,
+        Render, RenderSystems,
+    },
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The node ending the passes running after the upscaling, at the resolution of the output.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub(crate) struct EndOutputPass;
+
+// Marks the prepare system as added, it is shared by every graph
+#[derive(Resource)]
+struct OutputPassAdded;
+
+/// Add the [`EndOutputPass`] node after the upscaling of `graph`, along with the textures of the
+/// effects placed before it. Does nothing when it was already added.
+pub(crate) fn add_output_pass(render_app: &mut SubApp, graph: InternedRenderSubGraph) {
+    if !render_app.world().contains_resource::<OutputPassAdded>() {
+        render_app.insert_resource(OutputPassAdded).add_systems(
+            Render,
+            prepare_output_chains.in_set(RenderSystems::PrepareResources),
+        );
+    }
+
+    let added = render_app
+        .world()
+        .resource::<RenderGraph>()
+        .get_sub_graph(graph)
+        .is_some_and(|sub_graph| sub_graph.get_node_state(EndOutputPass).is_ok());
+    if !added {
+        render_app
+            .add_render_graph_node::<EmptyNode>(graph, EndOutputPass)
+            .add_render_graph_edge(graph, Node3d::Upscaling, EndOutputPass);
+    }
+}
+
+/// The textures the effects running after the upscaling render into, in turns.
+///
+/// The upscaling already wrote the output texture of the view, which can't be read while it is
+/// written. So the first effect reads the main texture, at the resolution of the scene, and each
+/// effect renders into one of these output sized textures before copying it to the output.
+#[derive(Component)]
+pub(crate) struct ViewOutputChain {
+    textures: [CachedTexture; 2],
+    pub(crate) blit_pipeline_id: CachedRenderPipelineId,
+    viewport: Option<Viewport>,
+    // 0 until an effect renders this frame, then 1 + the index of the texture written last
+    written: AtomicUsize,
+}
+
+impl ViewOutputChain {
+    /// The texture the next output pass reads.
+    pub(crate) fn source<'a>(&'a self, view_target: &'a ViewTarget) -> &'a TextureView {
+        match self.written.load(Ordering::SeqCst) {
+            0 => view_target.main_texture_view(),
+            written => &self.textures[written - 1].default_view,
+        }
+    }
+
+    /// Start an output pass, returning the texture to read and the texture to render into.
+    ///
+    /// Like [`ViewTarget::post_process_write`], the effect must render into the destination.
+    pub(crate) fn write<'a>(
+        &'a self,
+        view_target: &'a ViewTarget,
+    ) -> (&'a TextureView, &'a TextureView) {
+        let source = self.source(view_target);
+        let written = self.written.load(Ordering::SeqCst);
+        let next = if written == 1 { 1 } else { 0 };
+        self.written.store(next + 1, Ordering::SeqCst);
+        (source, &self.textures[next].default_view)
+    }
+
+    /// Copy the texture an effect rendered into onto the output texture of the view.
+    pub(crate) fn blit(
+        &self,
+        render_context: &mut RenderContext,
+        world: &World,
+        view_target: &ViewTarget,
+        blit_pipeline: &RenderPipeline,
+        source: &TextureView,
+    ) {
+        let composite_pipeline = world.resource::<CompositePipeline>();
+        let bind_group = render_context.render_device().create_bind_group(
+            "post_process_output_blit_bind_group",
+            &composite_pipeline.layout,
+            &BindGroupEntries::sequential((source, &composite_pipeline.sampler)),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("post_process_output_blit_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: view_target.out_texture(),
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        // Cameras sharing a target only overwrite their own viewport. The textures cover the
+        // whole target, so the viewport is cut out rather than mapped onto
+        if let Some(viewport) = &self.viewport {
+            render_pass.set_scissor_rect(
+                viewport.physical_position.x,
+                viewport.physical_position.y,
+                viewport.physical_size.x,
+                viewport.physical_size.y,
+            );
+        }
+        render_pass.set_render_pipeline(blit_pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+/// Allocate the output sized textures of every view, once per frame.
+fn prepare_output_chains(
+    mut commands: Commands,
+    views: Query<(Entity, &ViewTarget, &ExtractedCamera)>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    composite_pipeline: Res<CompositePipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<CompositePipeline>>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+) {
+    for (entity, view_target, camera) in &views {
+        let Some(size) = camera.physical_target_size else {
+            continue;
+        };
+        let texture_format = view_target.out_texture_format();
+        let descriptor = TextureDescriptor {
+            label: Some("post_process_output_texture"),
+            size: Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: texture_format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        };
+        let textures = [0, 1].map(|index| {
+            texture_pool.get_indexed(
+                &render_device,
+                entity,
+                EndOutputPass,
+                "output",
+                index,
+                &descriptor,
+            )
+        });
+
+        // The textures have the size and format of the output, so the blit is a plain copy
+        let blit_pipeline_id = pipelines.specialize(
+            &pipeline_cache,
+            &composite_pipeline,
+            CompositePipelineKey {
+                texture_format,
+                filter: PostProcessUpscaleFilter::Bilinear,
+                stencil: false,
+            },
+        );
+
+        // Inserted again every frame, which resets the chain to the main texture
+        commands.entity(entity).insert(ViewOutputChain {
+            textures,
+            blit_pipeline_id,
+            viewport: camera.viewport.clone(),
+            written: AtomicUsize::new(0),
+        });
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{resolution::scaled_size, PostProcessPluginSettings, PostProcessTexturePool};
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    render::{
+        render_graph::{InternedRenderLabel, RenderLabel},
+        render_resource::*,
+        renderer::RenderDevice,
+        texture::CachedTexture,
+        view::ViewTarget,
+    },
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Where the main output of an effect is written.
+#[derive(Clone, Debug, Default)]
+pub enum PostProcessDestination {
+    /// Write back into the view target, so the effect is visible on screen.
+    #[default]
+    ViewTarget,
+    /// Write into the given image and leave the view target untouched.
+    ///
+    /// This is useful to generate a processed copy of the frame, like a blurred snapshot
+    /// used as a pause menu background. The image must have the `RENDER_ATTACHMENT` usage.
+    Image(Handle<Image>),
+}
+
+/// The additional color outputs written by effects, per view.
+///
+/// Effects declare extra outputs with
+/// [`PostProcessPlugin::with_extra_output`](crate::PostProcessPlugin::with_extra_output).
+/// They have the same size as the effect output, which is smaller than the view
+/// when the effect renders at a reduced resolution.
+/// The shader writes to them at `@location(1)`, `@location(2)`, ... in the order they were declared.
+/// The textures live in the render world and are kept by the [`PostProcessTexturePool`], so they
+/// still hold the previous frame until the effect renders again.
+#[derive(Resource, Default)]
+pub struct PostProcessOutputs {
+    textures: HashMap<(Entity, InternedRenderLabel), Vec<CachedTexture>>,
+}
+
+impl PostProcessOutputs {
+    /// Get the extra outputs of the effect with the given label for the given view.
+    pub fn get(&self, view: Entity, label: impl RenderLabel) -> Option<&[CachedTexture]> {
+        self.textures
+            .get(&(view, label.intern()))
+            .map(Vec::as_slice)
+    }
+
+    /// Get a single extra output of the effect with the given label for the given view.
+    pub fn get_output(
+        &self,
+        view: Entity,
+        label: impl RenderLabel,
+        index: usize,
+    ) -> Option<&CachedTexture> {
+        self.get(view, label)?.get(index)
+    }
+}
+
+/// Allocate the extra outputs of an effect for every view it runs on.
+pub(crate) fn prepare_extra_outputs<
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    plugin_settings: Res<PostProcessPluginSettings<U, R>>,
+    views: Query<(Entity, &ViewTarget), With<U>>,
+    render_device: Res<RenderDevice>,
+    mut texture_pool: ResMut<PostProcessTexturePool>,
+    mut outputs: ResMut<PostProcessOutputs>,
+) {
+    let label = plugin_settings.label.intern();
+    outputs.textures.retain(|(_, l), _| *l != label);
+
+    if plugin_settings.extra_outputs.is_empty() {
+        return;
+    }
+
+    for (entity, view_target) in &views {
+        // The outputs always match the size of the main output they are rendered alongside
+        let size = scaled_size(
+            view_target.main_texture().size(),
+            plugin_settings.resolution_scale,
+        );
+        let textures = plugin_settings
+            .extra_outputs
+            .iter()
+            .enumerate()
+            .map(|(index, format)| {
+                texture_pool.get_indexed(
+                    &render_device,
+                    entity,
+                    label,
+                    "extra_output",
+                    index as u32,
+                    &TextureDescriptor {
+                        label: Some("post_process_extra_output"),
+                        size,
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: TextureDimension::D2,
+                        format: *format,
+                        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                        view_formats: &[],
+                    },
+                )
+            })
+            .collect();
+        outputs.textures.insert((entity, label), textures);
+    }
+}
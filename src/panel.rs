@@ -0,0 +1,190 @@
+use crate::{PostProcessBypass, PostProcessEffectInfo, PostProcessRegistry};
+use bevy::{
+    ecs::reflect::AppTypeRegistry,
+    prelude::*,
+    reflect::{PartialReflect, ReflectMut, TypeRegistry},
+};
+use bevy_egui::{egui, EguiContext, EguiPlugin, EguiPrimaryContextPass, PrimaryEguiContext};
+
+/// A debug window listing the effects of every camera, to tune them at runtime.
+///
+/// Effects can be turned on and off with a [`PostProcessBypass`], and moved up and down within
+/// the effects placed between the same nodes. The fields of the settings are editable when the
+/// settings component derives [`Reflect`] and is registered with `#[reflect(Component)]`.
+pub struct PostProcessEguiPlugin;
+
+impl Plugin for PostProcessEguiPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+        app.add_systems(EguiPrimaryContextPass, post_process_panel);
+    }
+}
+
+fn post_process_panel(world: &mut World) {
+    let Ok(mut egui_context) = world
+        .query_filtered::<&mut EguiContext, With<PrimaryEguiContext>>()
+        .single_mut(world)
+    else {
+        return;
+    };
+    let ctx = egui_context.get_mut().clone();
+
+    let effects: Vec<_> = world
+        .resource::<PostProcessRegistry>()
+        .effects()
+        .cloned()
+        .collect();
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let cameras: Vec<_> = world
+        .query_filtered::<(Entity, Option<&Name>), With<Camera>>()
+        .iter(world)
+        .map(|(entity, name)| {
+            let name = name.map_or_else(|| format!("Camera {entity}"), ToString::to_string);
+            (entity, name)
+        })
+        .collect();
+
+    egui::Window::new("Post processing").show(&ctx, |ui| {
+        let type_registry = type_registry.read();
+        for (camera, name) in cameras {
+            let camera_effects: Vec<_> = effects
+                .iter()
+                .filter(|effect| {
+                    world
+                        .entity(camera)
+                        .contains_type_id(effect.settings_type_id)
+                })
+                .collect();
+            if camera_effects.is_empty() {
+                continue;
+            }
+
+            egui::CollapsingHeader::new(name)
+                .id_salt(camera)
+                .default_open(true)
+                .show(ui, |ui| {
+                    for effect in camera_effects {
+                        effect_ui(ui, world, &type_registry, camera, effect);
+                    }
+                });
+        }
+    });
+}
+
+fn effect_ui(
+    ui: &mut egui::Ui,
+    world: &mut World,
+    type_registry: &TypeRegistry,
+    camera: Entity,
+    effect: &PostProcessEffectInfo,
+) {
+    ui.horizontal(|ui| {
+        let bypassed = world
+            .get::<PostProcessBypass>(camera)
+            .is_some_and(|bypass| bypass.is_bypassed(effect.label));
+        let mut enabled = !bypassed;
+        if ui
+            .checkbox(&mut enabled, format!("{:?}", effect.label))
+            .changed()
+        {
+            let mut bypass = world
+                .get::<PostProcessBypass>(camera)
+                .cloned()
+                .unwrap_or_default();
+            if enabled {
+                bypass.enable(effect.label);
+            } else {
+                bypass.bypass(effect.label);
+            }
+            world.entity_mut(camera).insert(bypass);
+        }
+
+        let mut registry = world.resource_mut::<PostProcessRegistry>();
+        if ui.small_button("⏶").on_hover_text("Run earlier").clicked() {
+            registry.move_up(effect.label);
+        }
+        if ui.small_button("⏷").on_hover_text("Run later").clicked() {
+            registry.move_down(effect.label);
+        }
+    });
+
+    ui.indent(effect.label, |ui| {
+        let Some(reflect_component) =
+            type_registry.get_type_data::<ReflectComponent>(effect.settings_type_id)
+        else {
+            ui.label(format!(
+                "{} isn't registered with #[reflect(Component)]",
+                effect.settings_type
+            ));
+            return;
+        };
+        let mut entity = world.entity_mut(camera);
+        let Some(mut settings) = reflect_component.reflect_mut(&mut entity) else {
+            return;
+        };
+        // Only trigger change detection when a field was actually edited
+        if reflect_ui(ui, settings.bypass_change_detection().as_partial_reflect_mut()) {
+            settings.set_changed();
+        }
+    });
+}
+
+/// Edit a reflected value, returning whether it changed.
+fn reflect_ui(ui: &mut egui::Ui, value: &mut dyn PartialReflect) -> bool {
+    if let Some(value) = value.try_downcast_mut::<f32>() {
+        return ui.add(egui::DragValue::new(value).speed(0.01)).changed();
+    }
+    if let Some(value) = value.try_downcast_mut::<u32>() {
+        return ui.add(egui::DragValue::new(value)).changed();
+    }
+    if let Some(value) = value.try_downcast_mut::<i32>() {
+        return ui.add(egui::DragValue::new(value)).changed();
+    }
+    if let Some(value) = value.try_downcast_mut::<bool>() {
+        return ui.checkbox(value, "").changed();
+    }
+
+    let type_path = value.reflect_type_path().to_owned();
+    let mut changed = false;
+    match value.reflect_mut() {
+        ReflectMut::Struct(value) => {
+            for index in 0..value.field_len() {
+                let name = value.name_at(index).unwrap_or_default().to_owned();
+                if let Some(field) = value.field_at_mut(index) {
+                    ui.horizontal(|ui| {
+                        ui.label(name);
+                        changed |= reflect_ui(ui, field);
+                    });
+                }
+            }
+        }
+        ReflectMut::TupleStruct(value) => {
+            for index in 0..value.field_len() {
+                if let Some(field) = value.field_mut(index) {
+                    changed |= reflect_ui(ui, field);
+                }
+            }
+        }
+        ReflectMut::Enum(value) => {
+            ui.label(value.variant_name().to_owned());
+            for index in 0..value.field_len() {
+                let name = value.name_at(index).map(ToOwned::to_owned);
+                if let Some(field) = value.field_at_mut(index) {
+                    ui.horizontal(|ui| {
+                        if let Some(name) = name {
+                            ui.label(name);
+                        }
+                        changed |= reflect_ui(ui, field);
+                    });
+                }
+            }
+        }
+        // Lists, maps and opaque values are shown but not editable
+        _ => {
+            ui.label(type_path);
+        }
+    }
+    changed
+}
@@ -0,0 +1,242 @@
+use crate::PostProcessPipelineKey;
+use bevy::{
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        render_resource::{
+            binding_types::uniform_buffer, BindGroupLayoutEntryBuilder, BindingResource,
+            DynamicUniformBuffer, ShaderType,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        Render, RenderApp, RenderSystems,
+    },
+    shader::ShaderDefVal,
+};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// The maximum number of parameters of a [`PostProcessParams`].
+pub const MAX_PARAMS: usize = 32;
+
+/// Settings made of named parameters, for effects tweaked by scripts, consoles, data files or mods.
+///
+/// Used with [`PostProcessPlugin::new_params`](crate::PostProcessPlugin::new_params). The
+/// parameters are bound as an array of `vec4<f32>` at
+/// [`bindings::SETTINGS`](crate::bindings::SETTINGS), and the shader is compiled with a
+/// `PARAM_<NAME>` shader def holding the index of every parameter, so the uniform struct never
+/// changes:
+///
+/// ```wgsl
+/// struct PostProcessParams {
+///     values: array<vec4<f32>, 32>,
+/// }
+/// @group(0) @binding(2) var<uniform> params: PostProcessParams;
+///
+/// let intensity = params.values[#{PARAM_INTENSITY}].x;
+/// let tint = params.values[#{PARAM_TINT}];
+/// ```
+///
+/// `M` is a marker telling apart the parameters of different effects on the same camera.
+#[derive(Component)]
+pub struct PostProcessParams<M> {
+    names: Arc<[String]>,
+    values: [Vec4; MAX_PARAMS],
+    _marker: PhantomData<M>,
+}
+
+impl<M> PostProcessParams<M> {
+    /// Declare the parameters, in the order of their index. They all start at zero.
+    ///
+    /// Names are turned into shader defs by upper casing them and replacing anything that isn't
+    /// alphanumeric by `_`, so `bloom.intensity` becomes `PARAM_BLOOM_INTENSITY`.
+    pub fn new(names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let names: Arc<[String]> = names.into_iter().map(Into::into).collect();
+        assert!(names.len() <= MAX_PARAMS, "too many post process parameters");
+        Self {
+            names,
+            values: [Vec4::ZERO; MAX_PARAMS],
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set a parameter, returning the parameters for chaining.
+    pub fn with(mut self, name: &str, value: impl Into<PostProcessParam>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Set a parameter. Returns false when there is no parameter with this name.
+    pub fn set(&mut self, name: &str, value: impl Into<PostProcessParam>) -> bool {
+        let Some(index) = self.index(name) else {
+            return false;
+        };
+        self.values[index] = value.into().0;
+        true
+    }
+
+    /// Get the value of a parameter. Scalars are stored in the `x` component.
+    pub fn get(&self, name: &str) -> Option<Vec4> {
+        self.index(name).map(|index| self.values[index])
+    }
+
+    /// The index of a parameter in the array bound to the shader.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|param| param == name)
+    }
+
+    /// The names of the parameters, in the order of their index.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+}
+
+impl<M> Clone for PostProcessParams<M> {
+    fn clone(&self) -> Self {
+        Self {
+            names: self.names.clone(),
+            values: self.values,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<M: Send + Sync + 'static> PostProcessPipelineKey for PostProcessParams<M> {
+    /// The `PARAM_<NAME>` shader defs holding the index of every parameter.
+    fn shader_defs(&self) -> Vec<ShaderDefVal> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let name: String = name
+                    .chars()
+                    .map(|c| match c.is_ascii_alphanumeric() {
+                        true => c.to_ascii_uppercase(),
+                        false => '_',
+                    })
+                    .collect();
+                ShaderDefVal::UInt(format!("PARAM_{name}"), index as u32)
+            })
+            .collect()
+    }
+}
+
+impl<M: Send + Sync + 'static> ExtractComponent for PostProcessParams<M> {
+    type QueryData = &'static Self;
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(item: QueryItem<'_, '_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+/// The value of a parameter, a scalar or a vector.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PostProcessParam(pub Vec4);
+
+impl From<f32> for PostProcessParam {
+    fn from(value: f32) -> Self {
+        Self(Vec4::new(value, 0.0, 0.0, 0.0))
+    }
+}
+
+impl From<Vec2> for PostProcessParam {
+    fn from(value: Vec2) -> Self {
+        Self(value.extend(0.0).extend(0.0))
+    }
+}
+
+impl From<Vec3> for PostProcessParam {
+    fn from(value: Vec3) -> Self {
+        Self(value.extend(0.0))
+    }
+}
+
+impl From<Vec4> for PostProcessParam {
+    fn from(value: Vec4) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Color> for PostProcessParam {
+    fn from(value: Color) -> Self {
+        Self(LinearRgba::from(value).to_vec4())
+    }
+}
+
+#[derive(Clone, Copy, ShaderType)]
+struct ParamsUniform {
+    values: [Vec4; MAX_PARAMS],
+}
+
+#[derive(Resource)]
+struct ParamsUniforms<M> {
+    uniforms: DynamicUniformBuffer<ParamsUniform>,
+    _marker: PhantomData<M>,
+}
+
+impl<M> Default for ParamsUniforms<M> {
+    fn default() -> Self {
+        Self {
+            uniforms: DynamicUniformBuffer::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Component)]
+struct ParamsUniformOffset<M> {
+    offset: u32,
+    _marker: PhantomData<M>,
+}
+
+/// Write the parameters of every view to the uniform buffer.
+pub(crate) fn add_systems<M: Send + Sync + 'static>(app: &mut App) {
+    let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    // The buffer is shared by the effects using the same parameters
+    if render_app.world().contains_resource::<ParamsUniforms<M>>() {
+        return;
+    }
+    render_app.init_resource::<ParamsUniforms<M>>().add_systems(
+        Render,
+        prepare_params_uniforms::<M>.in_set(RenderSystems::PrepareResources),
+    );
+}
+
+fn prepare_params_uniforms<M: Send + Sync + 'static>(
+    mut commands: Commands,
+    mut params_uniforms: ResMut<ParamsUniforms<M>>,
+    views: Query<(Entity, &PostProcessParams<M>)>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let uniforms = &mut params_uniforms.uniforms;
+    uniforms.clear();
+    for (entity, params) in &views {
+        let offset = uniforms.push(&ParamsUniform {
+            values: params.values,
+        });
+        commands.entity(entity).insert(ParamsUniformOffset::<M> {
+            offset,
+            _marker: PhantomData,
+        });
+    }
+    uniforms.write_buffer(&render_device, &render_queue);
+}
+
+pub(crate) fn layout_entry() -> BindGroupLayoutEntryBuilder {
+    uniform_buffer::<ParamsUniform>(true)
+}
+
+pub(crate) fn binding<M: Send + Sync + 'static>(world: &World) -> Option<BindingResource<'_>> {
+    world.get_resource::<ParamsUniforms<M>>()?.uniforms.binding()
+}
+
+pub(crate) fn index<M: Send + Sync + 'static>(world: &World, view: Entity) -> Option<u32> {
+    world
+        .get::<ParamsUniformOffset<M>>(view)
+        .map(|offset| offset.offset)
+}
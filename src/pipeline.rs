@@ -0,0 +1,441 @@
+use crate::{
+    bindings, depth::depth_texture_entry, filtering, prewarm::PrewarmedPipelines, tonemapping,
+    view_info, PostProcessBindGroupLayout, PostProcessDestination, PostProcessPluginSettings,
+    PostProcessStencilTest,
+};
+use bevy::{
+    core_pipeline::tonemapping::Tonemapping,
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::ExtractComponent,
+        render_asset::RenderAssets,
+        render_graph::RenderLabel,
+        render_resource::{
+            binding_types::{texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::RenderDevice,
+        texture::GpuImage,
+        view::{Msaa, ViewTarget, ViewUniform},
+    },
+    shader::ShaderDefVal,
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// Replaces the shader of an effect on the camera it is added to.
+///
+/// `U` is the settings component of the effect. The override uses the same bindings as the shader
+/// of the effect, so it can for example be a simplified variant for a VR spectator camera.
+#[derive(Component)]
+pub struct PostProcessShaderOverride<U> {
+    /// The fragment shader used instead of the one of the effect.
+    pub shader: Handle<Shader>,
+    _marker: PhantomData<U>,
+}
+
+impl<U> PostProcessShaderOverride<U> {
+    pub fn new(shader: Handle<Shader>) -> Self {
+        Self {
+            shader,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<U> Clone for PostProcessShaderOverride<U> {
+    fn clone(&self) -> Self {
+        Self::new(self.shader.clone())
+    }
+}
+
+impl<U: Send + Sync + 'static> ExtractComponent for PostProcessShaderOverride<U> {
+    type QueryData = &'static Self;
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(item: QueryItem<'_, '_, Self::QueryData>) -> Option<Self::Out> {
+        Some(item.clone())
+    }
+}
+
+/// Maps the settings of an effect to shader defs, so the pipeline is specialized for them.
+///
+/// Enabled with [`PostProcessPlugin::with_pipeline_key`](crate::PostProcessPlugin::with_pipeline_key).
+/// Settings that change the structure of the shader, like a quality tier deciding the number of
+/// samples or the size of a kernel, are better compiled in than branched on at runtime. A pipeline
+/// is specialized for every combination of shader defs used by a camera, and reused afterwards.
+///
+/// ```ignore
+/// impl PostProcessPipelineKey for BlurSettings {
+///     fn shader_defs(&self) -> Vec<ShaderDefVal> {
+///         vec![ShaderDefVal::UInt("SAMPLE_COUNT".into(), self.quality.sample_count())]
+///     }
+/// }
+/// ```
+pub trait PostProcessPipelineKey: Component {
+    /// The shader defs the effect shader is compiled with for these settings.
+    fn shader_defs(&self) -> Vec<ShaderDefVal>;
+}
+
+// This contains global data used by the render pipeline. This will be created once on startup.
+#[derive(Resource)]
+pub(crate) struct PostProcessPipeline<U, R> {
+    layout: BindGroupLayout,
+    // Only differs from the layout when the depth texture is bound
+    multisampled_layout: BindGroupLayout,
+    pub(crate) sampler: Sampler,
+    // The linear clamp, nearest clamp and linear repeat samplers, when the effect binds them
+    pub(crate) standard_samplers: Option<[Sampler; 3]>,
+    // The layout of the bind group set by the user at index 1
+    user_layout: Option<BindGroupLayout>,
+    // Whether the screen texture is bound as filterable
+    filterable: bool,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+    debug_label: Option<&'static str>,
+    extra_outputs: Vec<TextureFormat>,
+    // The shader defs set with `with_shader_def`
+    shader_defs: Vec<ShaderDefVal>,
+    _uniform: PhantomData<U>,
+    _render_label: PhantomData<R>,
+}
+
+impl<U: Clone + Send + Sync + 'static, R: Hash + Eq + Clone + RenderLabel> FromWorld
+    for PostProcessPipeline<U, R>
+{
+    fn from_world(world: &mut World) -> Self {
+        let plugin_settings = world
+            .get_resource::<PostProcessPluginSettings<U, R>>()
+            .unwrap()
+            .clone();
+        let render_device = world.resource::<RenderDevice>();
+        let filterable = filtering::screen_filterable(world);
+
+        // The layout entries will be visible in the vertex and fragment stages
+        let visibility = ShaderStages::VERTEX_FRAGMENT;
+        let entries = |multisampled| {
+            let [screen_texture, screen_sampler] = filtering::screen_entries(filterable);
+            let mut entries = vec![
+                // The screen texture
+                screen_texture.build(bindings::SCREEN_TEXTURE, visibility),
+                // The sampler that will be used to sample the screen texture
+                screen_sampler.build(bindings::SAMPLER, visibility),
+            ];
+            // The settings uniform that will control the effect, unless the settings are a marker
+            if let Some(uniform) = &plugin_settings.uniform {
+                entries.push(uniform.layout_entry().build(bindings::SETTINGS, visibility));
+            }
+            // The view uniform
+            if plugin_settings.view {
+                entries.push(uniform_buffer::<ViewUniform>(true).build(bindings::VIEW, visibility));
+            }
+            // The standard samplers, which also sample the screen texture, so they fall back to
+            // non-filtering with it
+            if plugin_settings.standard_samplers {
+                entries.extend(
+                    [
+                        bindings::LINEAR_CLAMP_SAMPLER,
+                        bindings::NEAREST_CLAMP_SAMPLER,
+                        bindings::LINEAR_REPEAT_SAMPLER,
+                    ]
+                    .map(|binding| screen_sampler.build(binding, visibility)),
+                );
+            }
+            // The instances of the effect and their count
+            if let Some(instances) = &plugin_settings.instances {
+                let [instances, count] = instances.layout_entries();
+                entries.push(instances.build(bindings::INSTANCES, visibility));
+                entries.push(count.build(bindings::INSTANCE_COUNT, visibility));
+            }
+            // The audio feed
+            #[cfg(feature = "audio")]
+            if plugin_settings.audio {
+                entries.push(crate::audio::layout_entry().build(bindings::AUDIO, visibility));
+            }
+            // The size and the exposure of the view
+            if plugin_settings.view_info {
+                entries.push(view_info::layout_entry().build(bindings::VIEW_INFO, visibility));
+            }
+            // The tonemapping LUT of the view
+            if plugin_settings.tonemapping_lut {
+                let [lut, lut_sampler] = tonemapping::layout_entries();
+                entries.push(lut.build(bindings::TONEMAPPING_LUT, visibility));
+                entries.push(lut_sampler.build(bindings::TONEMAPPING_LUT_SAMPLER, visibility));
+            }
+            // The additional input textures
+            entries.extend((0..plugin_settings.inputs.len() as u32).map(|index| {
+                texture_2d(TextureSampleType::Float { filterable: true })
+                    .build(bindings::FIRST_INPUT + index, visibility)
+            }));
+            // The mask texture, sampled with the screen sampler
+            if plugin_settings.mask.is_some() {
+                entries.push(
+                    texture_2d(TextureSampleType::Float { filterable: true })
+                        .build(bindings::MASK, visibility),
+                );
+            }
+            // The entity mask of the view
+            if plugin_settings.entity_mask {
+                entries.push(
+                    texture_2d(TextureSampleType::Float { filterable: true })
+                        .build(bindings::ENTITY_MASK, visibility),
+                );
+            }
+            // The depth texture of the view, multisampled when the view uses MSAA
+            if plugin_settings.depth {
+                entries.push(depth_texture_entry(multisampled).build(bindings::DEPTH, visibility));
+            }
+            entries
+        };
+
+        // We need to define the bind group layout used for our pipeline
+        let layout = render_device
+            .create_bind_group_layout(plugin_settings.bind_group_layout_label, &entries(false));
+        let multisampled_layout = if plugin_settings.depth {
+            render_device
+                .create_bind_group_layout(plugin_settings.bind_group_layout_label, &entries(true))
+        } else {
+            layout.clone()
+        };
+
+        // We can create the sampler here since it won't change at runtime and doesn't depend on the view
+        let sampler =
+            filtering::screen_sampler(render_device, filterable, SamplerDescriptor::default());
+        let standard_samplers = plugin_settings.standard_samplers.then(|| {
+            let linear = SamplerDescriptor {
+                mag_filter: FilterMode::Linear,
+                min_filter: FilterMode::Linear,
+                mipmap_filter: FilterMode::Linear,
+                ..default()
+            };
+            let repeat = SamplerDescriptor {
+                address_mode_u: AddressMode::Repeat,
+                address_mode_v: AddressMode::Repeat,
+                address_mode_w: AddressMode::Repeat,
+                ..linear.clone()
+            };
+            [linear, SamplerDescriptor::default(), repeat]
+                .map(|descriptor| filtering::screen_sampler(render_device, filterable, descriptor))
+        });
+
+        let user_layout = world
+            .get_resource::<PostProcessBindGroupLayout<U>>()
+            .map(|user_layout| user_layout.layout.clone());
+
+        // Get the shader handle
+        let shader = world.load_asset(plugin_settings.shader_path);
+
+        PostProcessPipeline::<U, R> {
+            layout,
+            multisampled_layout,
+            sampler,
+            standard_samplers,
+            user_layout,
+            filterable,
+            shader,
+            vertex_state: plugin_settings.vertex_state,
+            debug_label: plugin_settings.debug_label,
+            extra_outputs: plugin_settings.extra_outputs,
+            shader_defs: plugin_settings.constant_shader_defs,
+            _uniform: Default::default(),
+            _render_label: Default::default(),
+        }
+    }
+}
+
+impl<U, R> PostProcessPipeline<U, R> {
+    pub(crate) fn layout(&self, multisampled: bool) -> &BindGroupLayout {
+        if multisampled {
+            &self.multisampled_layout
+        } else {
+            &self.layout
+        }
+    }
+}
+
+/// The parts of the pipeline that depend on the view the effect runs on.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct ViewPipelineKey {
+    /// Format of the texture the effect writes its main output to.
+    /// This is an HDR format for HDR cameras.
+    pub(crate) texture_format: TextureFormat,
+    /// Number of MSAA samples of the view.
+    pub(crate) samples: u32,
+    /// Whether the effect only affects the pixels where the stencil is set.
+    pub(crate) stencil: bool,
+    /// The shader overriding the one of the effect on this view.
+    pub(crate) shader_override: Option<Handle<Shader>>,
+    /// The shader defs derived from the settings and the tonemapping of the view.
+    pub(crate) shader_defs: Vec<ShaderDefVal>,
+}
+
+impl<U: Send + Sync + 'static, R: Send + Sync + 'static> SpecializedRenderPipeline
+    for PostProcessPipeline<U, R>
+{
+    type Key = ViewPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        // The textures written by a post process are always resolved, but the depth and prepass
+        // textures of the view are multisampled and must be declared accordingly in the shader.
+        if key.samples > 1 {
+            shader_defs.push("MULTISAMPLED".into());
+        }
+        if !self.filterable {
+            shader_defs.push(filtering::SCREEN_TEXTURE_NON_FILTERABLE.into());
+        }
+        shader_defs.extend(self.shader_defs.iter().cloned());
+        shader_defs.extend(key.shader_defs);
+
+        RenderPipelineDescriptor {
+            label: self.debug_label.map(Into::into),
+            layout: std::iter::once(self.layout(key.samples > 1))
+                .chain(&self.user_layout)
+                .cloned()
+                .collect(),
+            // This will setup a fullscreen triangle for the vertex state
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: key.shader_override.unwrap_or_else(|| self.shader.clone()),
+                shader_defs,
+                // Make sure this matches the entry point of your shader.
+                // It can be anything as long as it matches here and in the shader.
+                entry_point: Some("fragment".into()),
+                targets: std::iter::once(key.texture_format)
+                    .chain(self.extra_outputs.iter().copied())
+                    .map(|format| {
+                        Some(ColorTargetState {
+                            format,
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })
+                    })
+                    .collect(),
+            }),
+            // All the following properties are not important for this effect so just use the default values.
+            // This struct doesn't have the Default trait implemented because not all fields can have a default value.
+            primitive: PrimitiveState::default(),
+            // Only the pixels where the stencil is set are affected
+            depth_stencil: key
+                .stencil
+                .then(|| PostProcessStencilTest::depth_stencil_state(CompareFunction::Equal)),
+            // The pass only ever renders into resolved textures, so it is never multisampled itself
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+/// The pipeline specialized for a view, inserted on every view the effect runs on.
+#[derive(Component)]
+pub(crate) struct ViewPostProcessPipeline<U: Send + Sync + 'static, R: Send + Sync + 'static> {
+    pub(crate) pipeline_id: CachedRenderPipelineId,
+    _marker: PhantomData<(U, R)>,
+}
+
+/// Create the pipeline of an effect, once a view runs it.
+pub(crate) fn init_pipeline<U, R>(world: &mut World)
+where
+    U: Clone + Send + Sync + 'static,
+    R: Hash + Eq + Clone + RenderLabel,
+{
+    world.init_resource::<PostProcessPipeline<U, R>>();
+}
+
+/// Release the pipeline of an effect once no view ran it for the number of frames of its settings.
+///
+/// Prewarmed effects keep their pipeline, which was compiled to be ready when a view needs it.
+pub(crate) fn release_idle_pipeline<
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    mut commands: Commands,
+    plugin_settings: Res<PostProcessPluginSettings<U, R>>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<PostProcessPipeline<U, R>>>,
+    views: Query<(), With<U>>,
+    prewarmed: Option<Res<PrewarmedPipelines<U, R>>>,
+    // The frames since a view last ran the effect, none before a view ran it or once released
+    mut idle_frames: Local<Option<u32>>,
+) {
+    if prewarmed.is_some() {
+        return;
+    }
+    if !views.is_empty() {
+        *idle_frames = Some(0);
+        return;
+    }
+    let Some(frames) = idle_frames.as_mut() else {
+        return;
+    };
+    *frames += 1;
+    if Some(*frames) == plugin_settings.idle_release {
+        commands.remove_resource::<PostProcessPipeline<U, R>>();
+        // The specialized pipelines reference the layout of the released pipeline
+        *pipelines = SpecializedRenderPipelines::default();
+        *idle_frames = None;
+    }
+}
+
+// The views a pipeline is specialized for, with what its key depends on
+type PipelineViews<U> = (
+    Entity,
+    &'static ViewTarget,
+    &'static Msaa,
+    &'static U,
+    Option<&'static PostProcessShaderOverride<U>>,
+    Option<&'static Tonemapping>,
+);
+
+/// Specialize the pipeline of an effect for every view it runs on.
+pub(crate) fn prepare_view_pipelines<
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<PostProcessPipeline<U, R>>>,
+    post_process_pipeline: Res<PostProcessPipeline<U, R>>,
+    plugin_settings: Res<PostProcessPluginSettings<U, R>>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    views: Query<PipelineViews<U>>,
+) {
+    for (entity, view_target, msaa, settings, shader_override, tonemapping) in &views {
+        let texture_format = match &plugin_settings.destination {
+            // Effects running after the upscaling render in the format of the output
+            PostProcessDestination::ViewTarget if plugin_settings.writes_output() => {
+                view_target.out_texture_format()
+            }
+            PostProcessDestination::ViewTarget => view_target.main_texture_format(),
+            PostProcessDestination::Image(image) => {
+                // The image might still be loading
+                let Some(gpu_image) = gpu_images.get(image) else {
+                    continue;
+                };
+                gpu_image.texture_format
+            }
+        };
+
+        let settings_defs = plugin_settings
+            .shader_defs
+            .map_or_else(Vec::new, |shader_defs| shader_defs(settings));
+        let shader_defs = plugin_settings.view_shader_defs(settings_defs, tonemapping);
+
+        let key = ViewPipelineKey {
+            texture_format,
+            samples: msaa.samples(),
+            stencil: plugin_settings.uses_stencil(),
+            shader_override: shader_override.map(|shader_override| shader_override.shader.clone()),
+            shader_defs,
+        };
+        let pipeline_id = pipelines.specialize(&pipeline_cache, &post_process_pipeline, key);
+
+        commands.entity(entity).insert(ViewPostProcessPipeline::<U, R> {
+            pipeline_id,
+            _marker: PhantomData,
+        });
+    }
+}
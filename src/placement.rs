@@ -0,0 +1,91 @@
+use crate::output_pass::EndOutputPass;
+use bevy::{
+    core_pipeline::core_3d::graph::Node3d,
+    render::render_graph::{InternedRenderLabel, RenderLabel},
+    ui_render::graph::NodeUi,
+};
+
+/// Where the effect node is inserted in the render graph, relative to Bevy's built-in nodes.
+///
+/// The presets that reference an optional Bevy node (bloom, FXAA, UI) require the matching plugin
+/// to be added, which is the case with `DefaultPlugins`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PostProcessPlacement {
+    /// Runs after the main pass and before the end of post processing.
+    /// The order relative to Bevy's own post processing nodes is not specified.
+    #[default]
+    EndMainPass,
+    /// Runs before [`Node3d::Bloom`], on the HDR scene color.
+    /// Useful for distortions that should also affect the bloom.
+    BeforeBloom,
+    /// Runs after [`Node3d::Bloom`] but before [`Node3d::Tonemapping`].
+    AfterBloom,
+    /// Runs after [`Node3d::Tonemapping`] but before [`Node3d::Fxaa`].
+    /// Color grading usually wants to run here.
+    AfterTonemapping,
+    /// Runs after [`Node3d::Fxaa`], at the very end of post processing.
+    AfterFxaa,
+    /// Runs after the UI has been rendered on top of the scene but before [`Node3d::Upscaling`].
+    /// The composited UI is part of the effect input, so the effect also distorts or filters the UI.
+    AfterUi,
+    /// Runs after [`Node3d::Upscaling`], at the resolution of the output rather than the one of
+    /// the scene.
+    ///
+    /// Screen space patterns like film grain, scanlines or CRT masks stay crisp when the scene
+    /// renders at a lower resolution. The effect reads the final image, UI included, and renders
+    /// in the format of the output, so it doesn't see HDR values.
+    ///
+    /// Each effect placed here renders into an output sized texture that is then copied onto the
+    /// output, which costs an extra pass. The resolution scale, the frame interval and the
+    /// stencil of the effect are ignored here.
+    AfterUpscaling,
+    /// Runs between two arbitrary nodes.
+    ///
+    /// Use this together with [`PostProcessPlugin::in_graph`](crate::PostProcessPlugin::in_graph)
+    /// to insert the effect into a custom render graph.
+    Between(InternedRenderLabel, InternedRenderLabel),
+}
+
+impl PostProcessPlacement {
+    /// Run the effect after `before` and before `after`.
+    pub fn between(before: impl RenderLabel, after: impl RenderLabel) -> Self {
+        PostProcessPlacement::Between(before.intern(), after.intern())
+    }
+
+    /// The nodes that must run before and after the effect node.
+    pub(crate) fn edges(&self) -> (InternedRenderLabel, InternedRenderLabel) {
+        match self {
+            PostProcessPlacement::EndMainPass => (
+                Node3d::EndMainPass.intern(),
+                Node3d::EndMainPassPostProcessing.intern(),
+            ),
+            PostProcessPlacement::BeforeBloom => (
+                Node3d::StartMainPassPostProcessing.intern(),
+                Node3d::Bloom.intern(),
+            ),
+            PostProcessPlacement::AfterBloom => {
+                (Node3d::Bloom.intern(), Node3d::Tonemapping.intern())
+            }
+            PostProcessPlacement::AfterTonemapping => {
+                (Node3d::Tonemapping.intern(), Node3d::Fxaa.intern())
+            }
+            PostProcessPlacement::AfterFxaa => (
+                Node3d::Fxaa.intern(),
+                Node3d::EndMainPassPostProcessing.intern(),
+            ),
+            PostProcessPlacement::AfterUi => (NodeUi::UiPass.intern(), Node3d::Upscaling.intern()),
+            PostProcessPlacement::AfterUpscaling => {
+                (Node3d::Upscaling.intern(), EndOutputPass.intern())
+            }
+            PostProcessPlacement::Between(before, after) => (*before, *after),
+        }
+    }
+
+    /// Whether the effect runs after the upscaling and writes the output texture of the view.
+    ///
+    /// This also holds for the effects a group places between the first one and the end of the
+    /// output passes.
+    pub(crate) fn writes_output(&self) -> bool {
+        self.edges().1 == EndOutputPass.intern()
+    }
+}
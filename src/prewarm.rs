@@ -0,0 +1,180 @@
+use crate::{
+    pipeline::{PostProcessPipeline, ViewPipelineKey},
+    PostProcessPipelineKey, PostProcessPluginSettings,
+};
+use bevy::{
+    core_pipeline::tonemapping::Tonemapping,
+    image::BevyDefault,
+    prelude::*,
+    render::{
+        render_graph::{InternedRenderLabel, RenderLabel},
+        render_resource::{
+            CachedRenderPipelineId, PipelineCache, SpecializedRenderPipelines, TextureFormat,
+        },
+        view::ViewTarget,
+        Extract,
+    },
+    shader::ShaderDefVal,
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// Compiles the pipelines of effects before any camera runs them.
+///
+/// Effects compile their pipelines the first time a camera runs them, which takes a few frames
+/// during which they don't render. Request the effects during a loading screen, and wait for them
+/// with [`PostProcessState`](crate::PostProcessState) or the
+/// [`PostProcessReady`](crate::PostProcessReady) messages. An effect becomes ready once all of
+/// its requested variants are compiled.
+///
+/// Only the main pipeline of the effect is compiled ahead of time. The stencil, depth range and
+/// composite pipelines are still compiled with the first camera using them. Effects binding the
+/// tonemapping LUT are compiled for the default [`Tonemapping`] unless a variant names another,
+/// and the variants are compiled again when the [`PostProcessQuality`](crate::PostProcessQuality)
+/// tier of the effect changes.
+///
+/// ```ignore
+/// fn prewarm(mut prewarm: ResMut<PostProcessPrewarm>) {
+///     prewarm
+///         .effect(UnderwaterLabel)
+///         .effect_with_settings(BlurLabel, &BlurSettings::high());
+/// }
+/// ```
+#[derive(Resource, Clone, Debug, Default)]
+pub struct PostProcessPrewarm {
+    variants: Vec<PrewarmVariant>,
+}
+
+#[derive(Clone, Debug)]
+struct PrewarmVariant {
+    label: InternedRenderLabel,
+    texture_format: TextureFormat,
+    samples: u32,
+    tonemapping: Tonemapping,
+    shader_defs: Vec<ShaderDefVal>,
+}
+
+impl PostProcessPrewarm {
+    /// Compile the effect with this label for HDR and non-HDR cameras without MSAA, with the
+    /// default tonemapping.
+    pub fn effect(&mut self, label: impl RenderLabel) -> &mut Self {
+        self.hdr_and_sdr(label.intern(), Vec::new())
+    }
+
+    /// Compile the effect with this label for HDR and non-HDR cameras without MSAA and with the
+    /// default tonemapping, with the shader defs of these settings.
+    ///
+    /// See [`PostProcessPlugin::with_pipeline_key`](crate::PostProcessPlugin::with_pipeline_key).
+    pub fn effect_with_settings<U: PostProcessPipelineKey>(
+        &mut self,
+        label: impl RenderLabel,
+        settings: &U,
+    ) -> &mut Self {
+        self.hdr_and_sdr(label.intern(), settings.shader_defs())
+    }
+
+    /// Compile a single variant of the effect with this label.
+    ///
+    /// `texture_format` is the format of the texture the effect writes to, `samples` the number of
+    /// MSAA samples of the camera and `tonemapping` its tonemapping, which only matters for the
+    /// effects binding the tonemapping LUT.
+    pub fn variant(
+        &mut self,
+        label: impl RenderLabel,
+        texture_format: TextureFormat,
+        samples: u32,
+        tonemapping: Tonemapping,
+        shader_defs: Vec<ShaderDefVal>,
+    ) -> &mut Self {
+        self.variants.push(PrewarmVariant {
+            label: label.intern(),
+            texture_format,
+            samples,
+            tonemapping,
+            shader_defs,
+        });
+        self
+    }
+
+    fn hdr_and_sdr(
+        &mut self,
+        label: InternedRenderLabel,
+        shader_defs: Vec<ShaderDefVal>,
+    ) -> &mut Self {
+        for texture_format in [ViewTarget::TEXTURE_FORMAT_HDR, TextureFormat::bevy_default()] {
+            self.variant(
+                label,
+                texture_format,
+                1,
+                Tonemapping::default(),
+                shader_defs.clone(),
+            );
+        }
+        self
+    }
+}
+
+// The pipelines compiled ahead of time for an effect
+#[derive(Resource)]
+pub(crate) struct PrewarmedPipelines<U, R> {
+    pub(crate) pipeline_ids: Vec<CachedRenderPipelineId>,
+    _marker: PhantomData<(U, R)>,
+}
+
+/// Copy the requests to the render world when they change.
+pub(crate) fn extract_prewarm(
+    mut commands: Commands,
+    prewarm: Extract<Option<Res<PostProcessPrewarm>>>,
+) {
+    if let Some(prewarm) = prewarm.as_ref()
+        && prewarm.is_changed()
+    {
+        commands.insert_resource(PostProcessPrewarm::clone(prewarm));
+    }
+}
+
+/// Create the pipeline of the effect and specialize it for every requested variant, when the
+/// requests or the quality tier of the effect change.
+pub(crate) fn prewarm_pipelines<U, R>(world: &mut World)
+where
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+{
+    let plugin_settings = world.resource::<PostProcessPluginSettings<U, R>>();
+    let label = plugin_settings.label.intern();
+    let stencil = plugin_settings.uses_stencil();
+    let keys: Vec<_> = world
+        .resource::<PostProcessPrewarm>()
+        .variants
+        .iter()
+        .filter(|variant| variant.label == label)
+        .map(|variant| ViewPipelineKey {
+            texture_format: variant.texture_format,
+            samples: variant.samples,
+            stencil,
+            shader_override: None,
+            // Assembled like `prepare_view_pipelines` does, so the keys match
+            shader_defs: plugin_settings
+                .view_shader_defs(variant.shader_defs.clone(), Some(&variant.tonemapping)),
+        })
+        .collect();
+    if keys.is_empty() {
+        return;
+    }
+
+    world.init_resource::<PostProcessPipeline<U, R>>();
+    let pipeline_ids = world.resource_scope(
+        |world, mut pipelines: Mut<SpecializedRenderPipelines<PostProcessPipeline<U, R>>>| {
+            let pipeline_cache = world.resource::<PipelineCache>();
+            let post_process_pipeline = world.resource::<PostProcessPipeline<U, R>>();
+            keys.into_iter()
+                .map(|key| pipelines.specialize(pipeline_cache, post_process_pipeline, key))
+                .collect()
+        },
+    );
+    world.insert_resource(PrewarmedPipelines::<U, R> {
+        pipeline_ids,
+        _marker: PhantomData,
+    });
+}
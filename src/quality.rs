@@ -0,0 +1,163 @@
+use crate::PostProcessPluginSettings;
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    render::{
+        render_graph::{InternedRenderLabel, RenderLabel},
+        Extract,
+    },
+    shader::ShaderDefVal,
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// A quality tier of the effects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PostProcessQualityTier {
+    /// Mobile and low end hardware.
+    Low,
+    /// Integrated GPUs and handhelds.
+    Medium,
+    /// Desktop GPUs.
+    #[default]
+    High,
+    /// High end GPUs, for effects that are only worth it there.
+    Ultra,
+}
+
+impl PostProcessQualityTier {
+    /// The tiers from the lowest to the highest.
+    pub const ALL: [Self; 4] = [Self::Low, Self::Medium, Self::High, Self::Ultra];
+
+    /// The shader defs the effects are compiled with for this tier.
+    ///
+    /// `QUALITY_LEVEL` holds the index of the tier, from 0 for [`Low`](Self::Low) to 3 for
+    /// [`Ultra`](Self::Ultra), and one of `QUALITY_LOW`, `QUALITY_MEDIUM`, `QUALITY_HIGH` or
+    /// `QUALITY_ULTRA` is set.
+    pub fn shader_defs(self) -> Vec<ShaderDefVal> {
+        let name = match self {
+            Self::Low => "QUALITY_LOW",
+            Self::Medium => "QUALITY_MEDIUM",
+            Self::High => "QUALITY_HIGH",
+            Self::Ultra => "QUALITY_ULTRA",
+        };
+        vec![
+            name.into(),
+            ShaderDefVal::UInt("QUALITY_LEVEL".into(), self as u32),
+        ]
+    }
+
+    /// The tier below this one.
+    pub fn lower(self) -> Option<Self> {
+        (self as usize).checked_sub(1).map(|index| Self::ALL[index])
+    }
+
+    /// The tier above this one.
+    pub fn higher(self) -> Option<Self> {
+        Self::ALL.get(self as usize + 1).copied()
+    }
+}
+
+/// The quality of every effect, for shipping the same effects on desktop and mobile.
+///
+/// While the resource exists, the effects are compiled with the shader defs of their tier, see
+/// [`PostProcessQualityTier::shader_defs`], and render at the resolution scale of their tier
+/// times the one set with
+/// [`PostProcessPlugin::with_resolution_scale`](crate::PostProcessPlugin::with_resolution_scale).
+///
+/// ```ignore
+/// app.insert_resource(
+///     PostProcessQuality::new(PostProcessQualityTier::Low)
+///         .with_resolution_scale(PostProcessQualityTier::Low, 0.5)
+///         // The color grading is cheap and noticeable
+///         .with_override(ColorGradingLabel, PostProcessQualityTier::High),
+/// );
+/// ```
+#[derive(Resource, Clone, Debug)]
+pub struct PostProcessQuality {
+    /// The tier of the effects without an override.
+    pub tier: PostProcessQualityTier,
+    overrides: HashMap<InternedRenderLabel, PostProcessQualityTier>,
+    // Indexed by tier
+    resolution_scales: [f32; 4],
+}
+
+impl Default for PostProcessQuality {
+    fn default() -> Self {
+        Self::new(PostProcessQualityTier::default())
+    }
+}
+
+impl PostProcessQuality {
+    /// Run every effect at this tier. No tier lowers the resolution until
+    /// [`PostProcessQuality::with_resolution_scale`] is used.
+    pub fn new(tier: PostProcessQualityTier) -> Self {
+        Self {
+            tier,
+            overrides: HashMap::default(),
+            resolution_scales: [1.0; 4],
+        }
+    }
+
+    /// Run the effect with this label at another tier.
+    pub fn with_override(mut self, label: impl RenderLabel, tier: PostProcessQualityTier) -> Self {
+        self.overrides.insert(label.intern(), tier);
+        self
+    }
+
+    /// Render the effects of a tier at a fraction of their resolution.
+    pub fn with_resolution_scale(mut self, tier: PostProcessQualityTier, scale: f32) -> Self {
+        self.resolution_scales[tier as usize] = scale.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Run the effect with this label at another tier, or at the default tier with `None`.
+    pub fn set_override(&mut self, label: impl RenderLabel, tier: Option<PostProcessQualityTier>) {
+        match tier {
+            Some(tier) => self.overrides.insert(label.intern(), tier),
+            None => self.overrides.remove(&label.intern()),
+        };
+    }
+
+    /// The tier of the effect with this label.
+    pub fn tier_of(&self, label: impl RenderLabel) -> PostProcessQualityTier {
+        self.overrides
+            .get(&label.intern())
+            .copied()
+            .unwrap_or(self.tier)
+    }
+
+    /// The tier the effect with this label was set to, if it doesn't run at the default tier.
+    pub fn override_of(&self, label: impl RenderLabel) -> Option<PostProcessQualityTier> {
+        self.overrides.get(&label.intern()).copied()
+    }
+
+    /// The resolution scale of a tier.
+    pub fn resolution_scale(&self, tier: PostProcessQualityTier) -> f32 {
+        self.resolution_scales[tier as usize]
+    }
+}
+
+/// Apply the tier of the effect to its settings in the render world.
+pub(crate) fn extract_quality<
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    mut plugin_settings: ResMut<PostProcessPluginSettings<U, R>>,
+    quality: Extract<Option<Res<PostProcessQuality>>>,
+) {
+    let tier = quality
+        .as_ref()
+        .map(|quality| quality.tier_of(plugin_settings.label.clone()));
+    let scale = match (quality.as_ref(), tier) {
+        (Some(quality), Some(tier)) => quality.resolution_scale(tier),
+        _ => 1.0,
+    };
+    let resolution_scale = plugin_settings.base_resolution_scale * scale;
+    // Leave the settings untouched when nothing changed, they are read by every system
+    if plugin_settings.quality == tier && plugin_settings.resolution_scale == resolution_scale {
+        return;
+    }
+    plugin_settings.resolution_scale = resolution_scale;
+    plugin_settings.quality = tier;
+}
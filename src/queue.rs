@@ -0,0 +1,182 @@
+use crate::{
+    bind_group_entries,
+    pipeline::{PostProcessPipeline, ViewPostProcessPipeline},
+    PostProcessPluginSettings,
+};
+use bevy::{
+    prelude::*,
+    render::{
+        render_graph::RenderLabel,
+        render_resource::{
+            BindGroup, BindGroupEntry, BindGroupLayoutId, BindingResource, Buffer, BufferAddress,
+            BufferSize, TextureView, TextureViewId, WgpuSampler, WgpuTextureView,
+        },
+        renderer::RenderDevice,
+        view::{Msaa, ViewTarget},
+    },
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+// The buffer type of the bind group entries, which isn't re-exported by Bevy
+type WgpuBuffer = <Buffer as Deref>::Target;
+
+/// The bind groups of an effect that doesn't flip the view target, created ahead of the node.
+///
+/// The effect reads the current main texture of the view, which is one or the other depending on
+/// the effects that ran before it, so there is a bind group for each. They are kept across frames
+/// and only created again when a resource they bind changes.
+#[derive(Component)]
+pub(crate) struct ViewQueuedBindGroups<U: Send + Sync + 'static, R: Send + Sync + 'static> {
+    bind_groups: Vec<QueuedBindGroup>,
+    _marker: PhantomData<(U, R)>,
+}
+
+impl<U: Send + Sync + 'static, R: Send + Sync + 'static> ViewQueuedBindGroups<U, R> {
+    /// The bind group reading `source`, if it was created.
+    pub(crate) fn get(&self, source: &TextureView) -> Option<&BindGroup> {
+        self.find(source.id())
+            .map(|queued_bind_group| &queued_bind_group.bind_group)
+    }
+
+    fn find(&self, source: TextureViewId) -> Option<&QueuedBindGroup> {
+        self.bind_groups
+            .iter()
+            .find(|queued_bind_group| queued_bind_group.source == source)
+    }
+}
+
+// A bind group with what it was created from, to tell when it must be created again
+#[derive(Clone)]
+struct QueuedBindGroup {
+    source: TextureViewId,
+    layout: BindGroupLayoutId,
+    // None when a resource can't be compared, so the bind group is created every frame
+    resources: Option<Vec<BoundResource>>,
+    bind_group: BindGroup,
+}
+
+impl QueuedBindGroup {
+    fn is_current(
+        &self,
+        layout: BindGroupLayoutId,
+        resources: &Option<Vec<BoundResource>>,
+    ) -> bool {
+        self.layout == layout && self.resources.is_some() && self.resources == *resources
+    }
+}
+
+// A resource bound by a bind group, compared by identity. Keeping it doesn't keep anything alive
+// longer, as the bind group holds it too.
+#[derive(Clone, PartialEq, Eq)]
+enum BoundResource {
+    Buffer(WgpuBuffer, BufferAddress, Option<BufferSize>),
+    Sampler(WgpuSampler),
+    TextureView(WgpuTextureView),
+}
+
+// The resources of the entries, None if one of them is an array
+fn bound_resources(entries: &[BindGroupEntry]) -> Option<Vec<BoundResource>> {
+    entries
+        .iter()
+        .map(|entry| match &entry.resource {
+            BindingResource::Buffer(binding) => Some(BoundResource::Buffer(
+                binding.buffer.clone(),
+                binding.offset,
+                binding.size,
+            )),
+            BindingResource::Sampler(sampler) => Some(BoundResource::Sampler((*sampler).clone())),
+            BindingResource::TextureView(texture_view) => {
+                Some(BoundResource::TextureView((*texture_view).clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Create the bind groups of an effect rendering into an image for every view, before the render
+/// graph runs.
+///
+/// The bind groups are only created again when the texture they read, their layout or one of the
+/// buffers, textures and samplers they bind changes, like the settings buffer growing or an input
+/// finishing loading. Static effects don't create any bind group per frame.
+///
+/// Effects writing the view target flip it, so their bind group is created by the node instead.
+/// The crate has no overlay passes blending into the view target without flipping it, so only
+/// the effects rendering into an image take this path. The views whose resources aren't ready get
+/// no bind group, and the node creates it itself.
+pub(crate) fn queue_bind_groups<
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    world: &mut World,
+) {
+    let views: Vec<_> = world
+        .query_filtered::<Entity, (With<U>, With<ViewPostProcessPipeline<U, R>>)>()
+        .iter(world)
+        .collect();
+
+    let world_ref = &*world;
+    let Some(post_process_pipeline) = world_ref.get_resource::<PostProcessPipeline<U, R>>()
+    else {
+        return;
+    };
+    let plugin_settings = world_ref.resource::<PostProcessPluginSettings<U, R>>();
+    let render_device = world_ref.resource::<RenderDevice>();
+    let mut updated = Vec::new();
+    for view in views {
+        let Some(view_target) = world_ref.get::<ViewTarget>(view) else {
+            continue;
+        };
+        let Some(msaa) = world_ref.get::<Msaa>(view) else {
+            continue;
+        };
+        let layout = post_process_pipeline.layout(msaa.samples() > 1);
+        let cached = world_ref.get::<ViewQueuedBindGroups<U, R>>(view);
+
+        let mut changed = false;
+        let mut bind_groups = Vec::new();
+        for source in [
+            view_target.main_texture_view(),
+            view_target.main_texture_other_view(),
+        ] {
+            let Some(entries) = bind_group_entries::<U, R>(world_ref, view, source) else {
+                continue;
+            };
+            let resources = bound_resources(&entries);
+            let current = cached
+                .and_then(|cached| cached.find(source.id()))
+                .filter(|cached| cached.is_current(layout.id(), &resources));
+            match current {
+                Some(current) => bind_groups.push(current.clone()),
+                None => {
+                    changed = true;
+                    bind_groups.push(QueuedBindGroup {
+                        source: source.id(),
+                        layout: layout.id(),
+                        resources,
+                        bind_group: render_device.create_bind_group(
+                            plugin_settings.bind_group_layout_label,
+                            layout,
+                            &entries,
+                        ),
+                    });
+                }
+            }
+        }
+
+        // A bind group whose resources stopped being ready is dropped too
+        if changed || cached.is_none_or(|cached| cached.bind_groups.len() != bind_groups.len()) {
+            updated.push((view, bind_groups));
+        }
+    }
+
+    for (view, bind_groups) in updated {
+        world.entity_mut(view).insert(ViewQueuedBindGroups::<U, R> {
+            bind_groups,
+            _marker: PhantomData,
+        });
+    }
+}
@@ -0,0 +1,275 @@
+use crate::{state::SharedPipelineReadiness, PostProcessPlacement};
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    render::{
+        render_graph::{InternedRenderLabel, InternedRenderSubGraph, RenderGraph},
+        Extract,
+    },
+};
+use std::any::TypeId;
+use std::fmt::{self, Display, Write};
+
+/// An effect added to the app with a [`PostProcessPlugin`](crate::PostProcessPlugin).
+#[derive(Clone, Debug)]
+pub struct PostProcessEffectInfo {
+    /// The label of the effect node.
+    pub label: InternedRenderLabel,
+    /// The render graph the effect node was added to.
+    pub graph: InternedRenderSubGraph,
+    /// Where the effect node was inserted in the render graph.
+    pub placement: PostProcessPlacement,
+    /// The type name of the settings component of the effect.
+    pub settings_type: &'static str,
+    /// The type id of the settings component of the effect.
+    pub settings_type_id: TypeId,
+    /// The bindings of the effect shader, with their index.
+    pub bindings: Vec<(u32, String)>,
+}
+
+/// Lists the effects added to the app, in the order they run in.
+///
+/// Effects placed between the same nodes, including the effects of a
+/// [`PostProcessPlugins`](crate::PostProcessPlugins) stack, share a slot of the render graph.
+/// Their order within the slot can be changed at runtime with [`PostProcessRegistry::move_up`]
+/// and [`PostProcessRegistry::move_down`].
+#[derive(Resource)]
+pub struct PostProcessRegistry {
+    effects: Vec<PostProcessEffectInfo>,
+    // Whether the order of the effects changed since it was registered
+    reordered: bool,
+    readiness: SharedPipelineReadiness,
+}
+
+impl PostProcessRegistry {
+    pub(crate) fn new(readiness: SharedPipelineReadiness) -> Self {
+        Self {
+            effects: Vec::new(),
+            reordered: false,
+            readiness,
+        }
+    }
+
+    pub(crate) fn register(&mut self, effect: PostProcessEffectInfo) {
+        self.effects.push(effect);
+    }
+
+    /// The registered effects, ordered within their slot.
+    pub fn effects(&self) -> impl Iterator<Item = &PostProcessEffectInfo> {
+        self.effects.iter()
+    }
+
+    /// The effect with this label.
+    pub fn get(&self, label: InternedRenderLabel) -> Option<&PostProcessEffectInfo> {
+        self.effects.iter().find(|effect| effect.label == label)
+    }
+
+    /// Run the effect before the previous effect of its slot. Returns whether it moved.
+    pub fn move_up(&mut self, label: InternedRenderLabel) -> bool {
+        let Some(index) = self.position(label) else {
+            return false;
+        };
+        let slot = self.slot(&self.effects[index]);
+        let Some(previous) = (0..index)
+            .rev()
+            .find(|&previous| self.slot(&self.effects[previous]) == slot)
+        else {
+            return false;
+        };
+        self.effects.swap(index, previous);
+        self.reordered = true;
+        true
+    }
+
+    /// Run the effect after the next effect of its slot. Returns whether it moved.
+    pub fn move_down(&mut self, label: InternedRenderLabel) -> bool {
+        let Some(index) = self.position(label) else {
+            return false;
+        };
+        let slot = self.slot(&self.effects[index]);
+        let Some(next) = (index + 1..self.effects.len())
+            .find(|&next| self.slot(&self.effects[next]) == slot)
+        else {
+            return false;
+        };
+        self.move_up(self.effects[next].label)
+    }
+
+    /// Describe the effect nodes the crate wired into the render graphs.
+    ///
+    /// The dump implements [`Display`] for logs, and can be rendered with Graphviz with
+    /// [`PostProcessDebugDump::to_graphviz`].
+    pub fn debug_dump(&self) -> PostProcessDebugDump {
+        let effects = self
+            .effects
+            .iter()
+            .enumerate()
+            .map(|(index, effect)| {
+                let (before, after) = self.edges(index);
+                PostProcessEffectDump {
+                    label: format!("{:?}", effect.label),
+                    graph: format!("{:?}", effect.graph),
+                    before: format!("{before:?}"),
+                    after: format!("{after:?}"),
+                    settings_type: effect.settings_type,
+                    bindings: effect.bindings.clone(),
+                    ready: self.readiness.is_ready(effect.label),
+                }
+            })
+            .collect();
+        PostProcessDebugDump { effects }
+    }
+
+    // The nodes the effect is wired between, following the order of its slot once reordered
+    fn edges(&self, index: usize) -> (InternedRenderLabel, InternedRenderLabel) {
+        let effect = &self.effects[index];
+        let (before, after) = effect.placement.edges();
+        if !self.reordered {
+            return (before, after);
+        }
+        let slot = self.slot(effect);
+        let (_, slot_before, slot_after) = slot;
+        let previous = self.effects[..index]
+            .iter()
+            .rev()
+            .find(|previous| self.slot(previous) == slot);
+        let next = self.effects[index + 1..]
+            .iter()
+            .find(|next| self.slot(next) == slot);
+        (
+            previous.map_or(slot_before, |previous| previous.label),
+            next.map_or(slot_after, |next| next.label),
+        )
+    }
+
+    fn position(&self, label: InternedRenderLabel) -> Option<usize> {
+        self.effects.iter().position(|effect| effect.label == label)
+    }
+
+    // The graph and the built-in nodes the effect runs between. Effects placed right after another
+    // effect share its slot.
+    fn slot(
+        &self,
+        effect: &PostProcessEffectInfo,
+    ) -> (InternedRenderSubGraph, InternedRenderLabel, InternedRenderLabel) {
+        let (mut before, after) = effect.placement.edges();
+        while let Some(previous) = self
+            .effects
+            .iter()
+            .find(|previous| previous.label == before && previous.graph == effect.graph)
+        {
+            (before, _) = previous.placement.edges();
+        }
+        (effect.graph, before, after)
+    }
+}
+
+/// A description of the registered effects, returned by [`PostProcessRegistry::debug_dump`].
+#[derive(Clone, Debug)]
+pub struct PostProcessDebugDump {
+    /// The effects, ordered within their slot.
+    pub effects: Vec<PostProcessEffectDump>,
+}
+
+/// A registered effect, as wired by the crate.
+#[derive(Clone, Debug)]
+pub struct PostProcessEffectDump {
+    /// The label of the effect node.
+    pub label: String,
+    /// The render graph the effect node is in.
+    pub graph: String,
+    /// The node the effect runs after.
+    pub before: String,
+    /// The node the effect runs before.
+    pub after: String,
+    /// The type name of the settings component.
+    pub settings_type: &'static str,
+    /// The bindings of the effect shader, with their index.
+    pub bindings: Vec<(u32, String)>,
+    /// Whether the pipelines of the effect are compiled.
+    pub ready: bool,
+}
+
+impl PostProcessDebugDump {
+    /// The effects and the nodes they are wired between, as a Graphviz digraph.
+    ///
+    /// The edges are labeled with the render graph they belong to.
+    pub fn to_graphviz(&self) -> String {
+        let mut graphviz = String::from("digraph post_process {\n");
+        for effect in &self.effects {
+            let _ = writeln!(
+                graphviz,
+                "    \"{}\" [shape=box, color={}, tooltip=\"{}\"];",
+                effect.label,
+                if effect.ready { "green" } else { "red" },
+                effect.settings_type,
+            );
+            let _ = writeln!(
+                graphviz,
+                "    \"{}\" -> \"{}\" -> \"{}\" [label=\"{}\"];",
+                effect.before, effect.label, effect.after, effect.graph,
+            );
+        }
+        graphviz.push('}');
+        graphviz
+    }
+}
+
+impl Display for PostProcessDebugDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for effect in &self.effects {
+            writeln!(
+                f,
+                "{} ({}) in {}: {} -> {} -> {}, {}",
+                effect.label,
+                effect.settings_type,
+                effect.graph,
+                effect.before,
+                effect.label,
+                effect.after,
+                if effect.ready { "ready" } else { "compiling" },
+            )?;
+            for (index, name) in &effect.bindings {
+                writeln!(f, "    @binding({index}) {name}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rewire the effect nodes of every slot in the order of the registry.
+pub(crate) fn extract_effect_order(
+    registry: Extract<Res<PostProcessRegistry>>,
+    mut render_graph: ResMut<RenderGraph>,
+) {
+    if !registry.reordered || !registry.is_changed() {
+        return;
+    }
+
+    let mut slots = HashMap::<_, Vec<InternedRenderLabel>>::default();
+    for effect in registry.effects() {
+        slots
+            .entry(registry.slot(effect))
+            .or_default()
+            .push(effect.label);
+    }
+
+    for ((graph, before, after), labels) in slots {
+        let Some(sub_graph) = render_graph.get_sub_graph_mut(graph) else {
+            continue;
+        };
+        // Drop the edges between the effects of the slot, then chain them in order
+        for &output in &labels {
+            for &input in &labels {
+                sub_graph.remove_node_edge(output, input).ok();
+            }
+        }
+        for &label in &labels {
+            sub_graph.try_add_node_edge(before, label).ok();
+            sub_graph.try_add_node_edge(label, after).ok();
+        }
+        for pair in labels.windows(2) {
+            sub_graph.try_add_node_edge(pair[0], pair[1]).ok();
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use crate::{
+    filtering, PostProcessDestination, PostProcessPluginSettings, PostProcessStencilTest,
+    PostProcessTexturePool,
+};
+use bevy::{
+    asset::load_embedded_asset,
+    core_pipeline::FullscreenShader,
+    diagnostic::FrameCount,
+    ecs::system::SystemParam,
+    prelude::*,
+    render::{
+        render_asset::RenderAssets,
+        render_graph::RenderLabel,
+        render_resource::*,
+        renderer::{RenderContext, RenderDevice},
+        texture::{CachedTexture, GpuImage},
+        view::ViewTarget,
+    },
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// The filter used to upsample an effect rendered at a reduced resolution back onto the view.
+///
+/// See [`PostProcessPlugin::with_resolution_scale`](crate::PostProcessPlugin::with_resolution_scale).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PostProcessUpscaleFilter {
+    /// A single bilinear sample. Cheap, but a bit blurry at low scales.
+    #[default]
+    Bilinear,
+    /// A Catmull-Rom bicubic filter made of 9 bilinear samples. Sharper than bilinear.
+    Bicubic,
+}
+
+/// Scale a texture size, making sure it never goes below a single texel.
+pub(crate) fn scaled_size(size: Extent3d, scale: f32) -> Extent3d {
+    Extent3d {
+        width: ((size.width as f32 * scale) as u32).max(1),
+        height: ((size.height as f32 * scale) as u32).max(1),
+        depth_or_array_layers: 1,
+    }
+}
+
+// The pipeline used to upsample the reduced resolution output onto the view target.
+// It is shared by every effect.
+#[derive(Resource)]
+pub(crate) struct CompositePipeline {
+    pub(crate) layout: BindGroupLayout,
+    pub(crate) sampler: Sampler,
+    filterable: bool,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+}
+
+impl FromWorld for CompositePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let filterable = filtering::screen_filterable(world);
+        // The reduced resolution output of the effect and its sampler
+        let [source_texture, source_sampler] = filtering::screen_entries(filterable);
+        let layout = render_device.create_bind_group_layout(
+            "post_process_composite_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (source_texture, source_sampler),
+            ),
+        );
+
+        // The upsampling relies on the hardware bilinear filtering, unless the texture can't be
+        // filtered and the shader filters it manually
+        let sampler = filtering::screen_sampler(
+            render_device,
+            filterable,
+            SamplerDescriptor {
+                mag_filter: FilterMode::Linear,
+                min_filter: FilterMode::Linear,
+                ..default()
+            },
+        );
+
+        Self {
+            layout,
+            sampler,
+            filterable,
+            shader: load_embedded_asset!(world, "composite.wgsl"),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct CompositePipelineKey {
+    pub(crate) texture_format: TextureFormat,
+    pub(crate) filter: PostProcessUpscaleFilter,
+    /// Whether the pipeline runs in a pass with a stencil attachment, without testing it
+    pub(crate) stencil: bool,
+}
+
+impl SpecializedRenderPipeline for CompositePipeline {
+    type Key = CompositePipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if !self.filterable {
+            shader_defs.push(filtering::SCREEN_TEXTURE_NON_FILTERABLE.into());
+        }
+        if key.filter == PostProcessUpscaleFilter::Bicubic {
+            shader_defs.push("BICUBIC".into());
+        }
+
+        RenderPipelineDescriptor {
+            label: Some("post_process_composite_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs,
+                entry_point: Some("fragment".into()),
+                targets: vec![Some(ColorTargetState {
+                    format: key.texture_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: key
+                .stencil
+                .then(|| PostProcessStencilTest::depth_stencil_state(CompareFunction::Always)),
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+/// The reduced resolution texture an effect renders into before being composited onto the view.
+///
+/// It is also used by effects running every few frames, which composite the texture they rendered
+/// last on the frames in between.
+#[derive(Component)]
+pub(crate) struct ViewPostProcessIntermediate<U: Send + Sync + 'static, R: Send + Sync + 'static> {
+    pub(crate) texture: CachedTexture,
+    pub(crate) composite_pipeline_id: CachedRenderPipelineId,
+    /// Whether the effect pass is skipped this frame in favor of the last result
+    skip: bool,
+    // Whether the texture holds a result, set by the node once the effect rendered into it
+    rendered: Arc<AtomicBool>,
+    _marker: PhantomData<(U, R)>,
+}
+
+impl<U: Send + Sync + 'static, R: Send + Sync + 'static> ViewPostProcessIntermediate<U, R> {
+    /// Whether the last result can be composited instead of running the effect this frame.
+    pub(crate) fn reuses_result(&self) -> bool {
+        self.skip && self.rendered.load(Ordering::Relaxed)
+    }
+
+    /// Record that the effect rendered into the texture.
+    pub(crate) fn set_rendered(&self) {
+        self.rendered.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The resources the intermediate textures are allocated and composited with, and the frame count
+/// deciding which frames reuse them.
+#[derive(SystemParam)]
+pub(crate) struct IntermediateTextureParams<'w> {
+    frame_count: Res<'w, FrameCount>,
+    render_device: Res<'w, RenderDevice>,
+    pipeline_cache: Res<'w, PipelineCache>,
+    composite_pipeline: Res<'w, CompositePipeline>,
+    pipelines: ResMut<'w, SpecializedRenderPipelines<CompositePipeline>>,
+    texture_pool: ResMut<'w, PostProcessTexturePool>,
+    gpu_images: Res<'w, RenderAssets<GpuImage>>,
+}
+
+// The views running the effect, with the texture they were given last frame
+type IntermediateViews<U, R> = (
+    Entity,
+    &'static ViewTarget,
+    Option<&'static ViewPostProcessIntermediate<U, R>>,
+);
+
+/// Allocate the reduced resolution texture of an effect for every view it runs on.
+pub(crate) fn prepare_intermediate_textures<
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    mut commands: Commands,
+    plugin_settings: Res<PostProcessPluginSettings<U, R>>,
+    views: Query<IntermediateViews<U, R>, With<U>>,
+    intermediates: Query<Entity, With<ViewPostProcessIntermediate<U, R>>>,
+    mut params: IntermediateTextureParams,
+) {
+    let IntermediateTextureParams {
+        frame_count,
+        render_device,
+        pipeline_cache,
+        composite_pipeline,
+        pipelines,
+        texture_pool,
+        gpu_images,
+    } = &mut params;
+
+    if !plugin_settings.uses_intermediate() {
+        // The quality tier may have brought the effect back to full resolution
+        for entity in &intermediates {
+            commands
+                .entity(entity)
+                .remove::<ViewPostProcessIntermediate<U, R>>();
+        }
+        return;
+    }
+
+    for (entity, view_target, previous) in &views {
+        let texture_format = match &plugin_settings.destination {
+            PostProcessDestination::ViewTarget => view_target.main_texture_format(),
+            PostProcessDestination::Image(image) => {
+                let Some(gpu_image) = gpu_images.get(image) else {
+                    continue;
+                };
+                gpu_image.texture_format
+            }
+        };
+
+        let texture = texture_pool.get(
+            render_device,
+            entity,
+            plugin_settings.label.clone(),
+            "intermediate",
+            &TextureDescriptor {
+                label: Some("post_process_intermediate_texture"),
+                size: scaled_size(
+                    view_target.main_texture().size(),
+                    plugin_settings.resolution_scale,
+                ),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: texture_format,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+
+        let composite_pipeline_id = pipelines.specialize(
+            pipeline_cache,
+            composite_pipeline,
+            CompositePipelineKey {
+                texture_format,
+                filter: plugin_settings.upscale_filter,
+                stencil: false,
+            },
+        );
+
+        // A texture that was just allocated doesn't hold a result to reuse yet
+        let rendered = match previous {
+            Some(previous) if previous.texture.texture.id() == texture.texture.id() => {
+                previous.rendered.clone()
+            }
+            _ => Arc::default(),
+        };
+        // The views are staggered so they don't all run the effect on the same frame
+        let frame = frame_count.0.wrapping_add(entity.index());
+        let skip = frame % plugin_settings.frame_interval != 0;
+
+        commands
+            .entity(entity)
+            .insert(ViewPostProcessIntermediate::<U, R> {
+                texture,
+                composite_pipeline_id,
+                skip,
+                rendered,
+                _marker: PhantomData,
+            });
+    }
+}
+
+/// Composite the intermediate texture of an effect onto its destination.
+pub(crate) fn composite<U: Send + Sync + 'static, R: Send + Sync + 'static>(
+    render_context: &mut RenderContext,
+    world: &World,
+    intermediate: &ViewPostProcessIntermediate<U, R>,
+    composite_render_pipeline: &RenderPipeline,
+    destination: &TextureView,
+) {
+    let composite_pipeline = world.resource::<CompositePipeline>();
+    let bind_group = render_context.render_device().create_bind_group(
+        "post_process_composite_bind_group",
+        &composite_pipeline.layout,
+        &BindGroupEntries::sequential((
+            &intermediate.texture.default_view,
+            &composite_pipeline.sampler,
+        )),
+    );
+
+    let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+        label: Some("post_process_composite_pass"),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view: destination,
+            depth_slice: None,
+            resolve_target: None,
+            ops: Operations::default(),
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    render_pass.set_render_pipeline(composite_render_pipeline);
+    render_pass.set_bind_group(0, &bind_group, &[]);
+    render_pass.draw(0..3, 0..1);
+}
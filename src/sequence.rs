@@ -0,0 +1,392 @@
+use crate::{PostProcessBypass, PostProcessRegistry};
+use bevy::{
+    color::Mix,
+    math::curve::{Curve, EaseFunction},
+    prelude::*,
+    reflect::GetPath,
+    render::render_graph::{InternedRenderLabel, RenderLabel},
+};
+
+/// A timeline of keyframed settings and enabled states for the effects of a camera.
+///
+/// Play it on a camera with a [`PostProcessSequencePlayer`]. Every frame the tracks are evaluated
+/// at the time of the player and written into the settings components of the camera, so cutscenes
+/// can be authored once and played by any camera.
+///
+/// Settings fields are reached by reflection, like in the debug window, so the settings component
+/// must derive [`Reflect`] and be registered with `#[reflect(Component)]`. Fields are addressed
+/// with a reflection path such as `"intensity"` or `"tint.x"`.
+///
+/// ```ignore
+/// let sequence = PostProcessSequence::new(5.0)
+///     .with_keyframe(FadeLabel, "amount", 0.0, 0.0, EaseFunction::Linear)
+///     .with_keyframe(FadeLabel, "amount", 2.0, 1.0, EaseFunction::CubicIn)
+///     .with_enabled(GrainLabel, 0.0, false)
+///     .with_enabled(GrainLabel, 3.5, true)
+///     .with_keyframe(GrainLabel, "strength", 3.5, 0.0, EaseFunction::Linear)
+///     .with_keyframe(GrainLabel, "strength", 5.0, 0.4, EaseFunction::SmoothStep);
+/// let player = PostProcessSequencePlayer::new(sequences.add(sequence));
+/// commands.spawn((Camera3d::default(), player));
+/// ```
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct PostProcessSequence {
+    duration: f32,
+    values: Vec<ValueTrack>,
+    enabled: Vec<EnabledTrack>,
+}
+
+#[derive(Clone, Debug)]
+struct ValueTrack {
+    effect: InternedRenderLabel,
+    field: String,
+    // Sorted by time
+    keyframes: Vec<ValueKeyframe>,
+}
+
+#[derive(Clone, Debug)]
+struct ValueKeyframe {
+    time: f32,
+    value: PostProcessSequenceValue,
+    // The easing from the previous keyframe to this one
+    easing: EaseFunction,
+}
+
+#[derive(Clone, Debug)]
+struct EnabledTrack {
+    effect: InternedRenderLabel,
+    // Sorted by time
+    keyframes: Vec<(f32, bool)>,
+}
+
+/// A value keyframed by a [`PostProcessSequence`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PostProcessSequenceValue {
+    Float(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    Color(Color),
+}
+
+impl PostProcessSequenceValue {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        match (self, other) {
+            (Self::Float(a), Self::Float(b)) => Self::Float(a.lerp(b, t)),
+            (Self::Vec2(a), Self::Vec2(b)) => Self::Vec2(a.lerp(b, t)),
+            (Self::Vec3(a), Self::Vec3(b)) => Self::Vec3(a.lerp(b, t)),
+            (Self::Vec4(a), Self::Vec4(b)) => Self::Vec4(a.lerp(b, t)),
+            (Self::Color(a), Self::Color(b)) => Self::Color(a.mix(&b, t)),
+            // Values of different types can't be blended, so they switch at the next keyframe
+            _ if t < 1.0 => self,
+            _ => other,
+        }
+    }
+
+    /// Write the value into a reflected field of the same type, returning whether it changed.
+    ///
+    /// Colors can also be written into [`LinearRgba`] and [`Srgba`] fields.
+    fn apply(self, field: &mut dyn PartialReflect) -> Option<bool> {
+        fn write<T>(field: &mut dyn PartialReflect, value: T) -> Option<bool>
+        where
+            T: Reflect + PartialEq,
+        {
+            let field = field.try_downcast_mut::<T>()?;
+            let changed = *field != value;
+            *field = value;
+            Some(changed)
+        }
+        match self {
+            Self::Float(value) => write(field, value),
+            Self::Vec2(value) => write(field, value),
+            Self::Vec3(value) => write(field, value),
+            Self::Vec4(value) => write(field, value),
+            Self::Color(value) => write(field, value)
+                .or_else(|| write(field, value.to_linear()))
+                .or_else(|| write(field, value.to_srgba())),
+        }
+    }
+}
+
+impl From<f32> for PostProcessSequenceValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<Vec2> for PostProcessSequenceValue {
+    fn from(value: Vec2) -> Self {
+        Self::Vec2(value)
+    }
+}
+
+impl From<Vec3> for PostProcessSequenceValue {
+    fn from(value: Vec3) -> Self {
+        Self::Vec3(value)
+    }
+}
+
+impl From<Vec4> for PostProcessSequenceValue {
+    fn from(value: Vec4) -> Self {
+        Self::Vec4(value)
+    }
+}
+
+impl From<Color> for PostProcessSequenceValue {
+    fn from(value: Color) -> Self {
+        Self::Color(value)
+    }
+}
+
+impl PostProcessSequence {
+    /// Create an empty sequence lasting `duration` seconds.
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            values: Vec::new(),
+            enabled: Vec::new(),
+        }
+    }
+
+    /// The duration of the sequence in seconds.
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Set the `field` of the settings of an effect to `value` at `time`.
+    ///
+    /// The value is eased from the previous keyframe of the field with `easing`. It is held
+    /// before the first keyframe and after the last one.
+    pub fn with_keyframe(
+        mut self,
+        effect: impl RenderLabel,
+        field: impl Into<String>,
+        time: f32,
+        value: impl Into<PostProcessSequenceValue>,
+        easing: EaseFunction,
+    ) -> Self {
+        let effect = effect.intern();
+        let field = field.into();
+        let keyframe = ValueKeyframe {
+            time,
+            value: value.into(),
+            easing,
+        };
+        match self
+            .values
+            .iter_mut()
+            .find(|track| track.effect == effect && track.field == field)
+        {
+            Some(track) => {
+                let index = track.keyframes.partition_point(|keyframe| keyframe.time <= time);
+                track.keyframes.insert(index, keyframe);
+            }
+            None => self.values.push(ValueTrack {
+                effect,
+                field,
+                keyframes: vec![keyframe],
+            }),
+        }
+        self
+    }
+
+    /// Turn an effect on or off at `time`, with [`PostProcessBypass`].
+    ///
+    /// The state is held until the next keyframe of the effect. Effects without keyframes
+    /// aren't touched.
+    pub fn with_enabled(mut self, effect: impl RenderLabel, time: f32, enabled: bool) -> Self {
+        let effect = effect.intern();
+        let track = match self.enabled.iter().position(|track| track.effect == effect) {
+            Some(index) => &mut self.enabled[index],
+            None => {
+                self.enabled.push(EnabledTrack {
+                    effect,
+                    keyframes: Vec::new(),
+                });
+                self.enabled.last_mut().unwrap()
+            }
+        };
+        let index = track.keyframes.partition_point(|&(keyframe, _)| keyframe <= time);
+        track.keyframes.insert(index, (time, enabled));
+        self
+    }
+}
+
+impl ValueTrack {
+    fn sample(&self, time: f32) -> Option<PostProcessSequenceValue> {
+        let next = self.keyframes.partition_point(|keyframe| keyframe.time <= time);
+        let Some(previous) = next.checked_sub(1).map(|index| &self.keyframes[index]) else {
+            return self.keyframes.first().map(|keyframe| keyframe.value);
+        };
+        let Some(next) = self.keyframes.get(next) else {
+            return Some(previous.value);
+        };
+        let t = (time - previous.time) / (next.time - previous.time);
+        let t = next.easing.sample_clamped(t);
+        Some(previous.value.lerp(next.value, t))
+    }
+}
+
+impl EnabledTrack {
+    fn sample(&self, time: f32) -> Option<bool> {
+        let next = self.keyframes.partition_point(|&(keyframe, _)| keyframe <= time);
+        let index = next.saturating_sub(1);
+        self.keyframes.get(index).map(|&(_, enabled)| enabled)
+    }
+}
+
+/// Plays a [`PostProcessSequence`] on the camera it is added to.
+#[derive(Component, Clone, Debug)]
+pub struct PostProcessSequencePlayer {
+    /// The sequence being played.
+    pub sequence: Handle<PostProcessSequence>,
+    /// The current time in the sequence, in seconds.
+    pub time: f32,
+    /// How fast the sequence plays, 1.0 being real time.
+    pub speed: f32,
+    /// Whether the time is held.
+    pub paused: bool,
+    /// Whether the sequence starts over once it reached its end.
+    pub looping: bool,
+    finished: bool,
+}
+
+impl PostProcessSequencePlayer {
+    /// Play the sequence from the start.
+    pub fn new(sequence: Handle<PostProcessSequence>) -> Self {
+        Self {
+            sequence,
+            time: 0.0,
+            speed: 1.0,
+            paused: false,
+            looping: false,
+            finished: false,
+        }
+    }
+
+    /// Start the sequence over once it reached its end.
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Set how fast the sequence plays.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Jump to a time in the sequence.
+    pub fn seek(&mut self, time: f32) {
+        self.time = time;
+        self.finished = false;
+    }
+
+    /// Whether the sequence reached its end. Looping sequences never finish.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Written when a sequence that doesn't loop reached its end.
+#[derive(Message, Clone, Debug)]
+pub struct PostProcessSequenceFinished {
+    /// The camera playing the sequence.
+    pub camera: Entity,
+}
+
+pub(crate) fn add_sequences(app: &mut App) {
+    app.init_asset::<PostProcessSequence>()
+        .add_message::<PostProcessSequenceFinished>()
+        .add_systems(Update, play_sequences);
+}
+
+/// Advance the players and write the sampled tracks into the settings of their camera.
+fn play_sequences(world: &mut World) {
+    let delta = world.resource::<Time>().delta_secs();
+    let mut values = Vec::new();
+    let mut enabled = Vec::new();
+    let mut finished = Vec::new();
+
+    world.resource_scope(|world, sequences: Mut<Assets<PostProcessSequence>>| {
+        let mut players = world.query::<(Entity, &mut PostProcessSequencePlayer)>();
+        for (camera, mut player) in players.iter_mut(world) {
+            let Some(sequence) = sequences.get(&player.sequence) else {
+                continue;
+            };
+            if !player.paused && !player.finished {
+                player.time += delta * player.speed;
+                if player.looping && sequence.duration > 0.0 {
+                    player.time = player.time.rem_euclid(sequence.duration);
+                } else if player.time >= sequence.duration {
+                    player.time = sequence.duration;
+                    player.finished = true;
+                    finished.push(camera);
+                }
+            }
+
+            let time = player.time;
+            values.extend(sequence.values.iter().filter_map(|track| {
+                let value = track.sample(time)?;
+                Some((camera, track.effect, track.field.clone(), value))
+            }));
+            enabled.extend(sequence.enabled.iter().filter_map(|track| {
+                Some((camera, track.effect, track.sample(time)?))
+            }));
+        }
+    });
+
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+    for (camera, effect, field, value) in values {
+        let Some(settings_type_id) = world
+            .resource::<PostProcessRegistry>()
+            .get(effect)
+            .map(|effect| effect.settings_type_id)
+        else {
+            continue;
+        };
+        let Some(reflect_component) =
+            type_registry.get_type_data::<ReflectComponent>(settings_type_id)
+        else {
+            warn_once!("{effect:?} settings aren't registered with #[reflect(Component)]");
+            continue;
+        };
+        let Ok(mut entity) = world.get_entity_mut(camera) else {
+            continue;
+        };
+        let Some(mut settings) = reflect_component.reflect_mut(&mut entity) else {
+            continue;
+        };
+        let Ok(target) = settings.bypass_change_detection().reflect_path_mut(field.as_str())
+        else {
+            warn_once!("{effect:?} settings don't have a field at {field:?}");
+            continue;
+        };
+        // Only trigger change detection when the value actually changed
+        match value.apply(target) {
+            Some(true) => settings.set_changed(),
+            Some(false) => {}
+            None => warn_once!("{effect:?} settings field {field:?} has another type"),
+        }
+    }
+
+    for (camera, effect, enabled) in enabled {
+        let Ok(mut entity) = world.get_entity_mut(camera) else {
+            continue;
+        };
+        let mut bypass = entity.get::<PostProcessBypass>().cloned().unwrap_or_default();
+        if bypass.is_bypassed(effect) != enabled {
+            continue;
+        }
+        if enabled {
+            bypass.enable(effect);
+        } else {
+            bypass.bypass(effect);
+        }
+        entity.insert(bypass);
+    }
+
+    for camera in finished {
+        world.write_message(PostProcessSequenceFinished { camera });
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{mask::MaskCamera, PostProcessBypass, PostProcessRegistry};
+use bevy::{
+    ecs::reflect::AppTypeRegistry,
+    prelude::*,
+    reflect::{PartialReflect, TypeRegistry},
+};
+use std::any::TypeId;
+
+/// The settings and enabled state of the effects of every camera, captured at one point in time.
+///
+/// Restore it to go back to the captured look, like when leaving a photo mode, undoing an edit or
+/// seeking in a replay. Cameras spawned after the capture are left untouched by
+/// [`PostProcessSnapshot::apply`], as are the cameras despawned since.
+///
+/// Settings are captured by reflection, like in the debug window, so the settings components
+/// must derive [`Reflect`] and be registered with `#[reflect(Component)]`. The settings of
+/// effects that aren't registered are neither captured nor restored, but whether those effects
+/// are bypassed is.
+#[derive(Default)]
+pub struct PostProcessSnapshot {
+    cameras: Vec<CameraSnapshot>,
+}
+
+struct CameraSnapshot {
+    camera: Entity,
+    // The settings of every registered effect, `None` when the camera didn't have them
+    settings: Vec<(TypeId, Option<Box<dyn PartialReflect>>)>,
+    bypass: Option<PostProcessBypass>,
+}
+
+impl PostProcessSnapshot {
+    /// Capture the effects of every camera.
+    pub fn capture(world: &mut World) -> Self {
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        let type_registry = type_registry.read();
+        let settings_types = settings_types(world, &type_registry);
+
+        let cameras = world
+            .query_filtered::<Entity, (With<Camera>, Without<MaskCamera>)>()
+            .iter(world)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|camera| {
+                let entity = world.entity(camera);
+                let settings = settings_types
+                    .iter()
+                    .map(|&(type_id, reflect_component)| {
+                        let settings = reflect_component
+                            .reflect(entity)
+                            .map(|settings| clone_value(settings.as_partial_reflect()));
+                        (type_id, settings)
+                    })
+                    .collect();
+                CameraSnapshot {
+                    camera,
+                    settings,
+                    bypass: entity.get::<PostProcessBypass>().cloned(),
+                }
+            })
+            .collect();
+        Self { cameras }
+    }
+
+    /// Restore the captured effects, inserting or removing settings components as needed.
+    pub fn apply(&self, world: &mut World) {
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        let type_registry = type_registry.read();
+
+        for snapshot in &self.cameras {
+            let Ok(mut entity) = world.get_entity_mut(snapshot.camera) else {
+                continue;
+            };
+            for (type_id, settings) in &snapshot.settings {
+                let Some(reflect_component) =
+                    type_registry.get_type_data::<ReflectComponent>(*type_id)
+                else {
+                    continue;
+                };
+                match settings {
+                    Some(settings) if entity.contains_type_id(*type_id) => {
+                        reflect_component.apply(&mut entity, settings.as_ref());
+                    }
+                    Some(settings) => {
+                        reflect_component.insert(&mut entity, settings.as_ref(), &type_registry);
+                    }
+                    None => reflect_component.remove(&mut entity),
+                }
+            }
+            match &snapshot.bypass {
+                Some(bypass) => {
+                    entity.insert(bypass.clone());
+                }
+                None => {
+                    entity.remove::<PostProcessBypass>();
+                }
+            }
+        }
+    }
+
+    /// The cameras captured by the snapshot.
+    pub fn cameras(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.cameras.iter().map(|snapshot| snapshot.camera)
+    }
+}
+
+impl Clone for PostProcessSnapshot {
+    fn clone(&self) -> Self {
+        let cameras = self
+            .cameras
+            .iter()
+            .map(|snapshot| CameraSnapshot {
+                camera: snapshot.camera,
+                settings: snapshot
+                    .settings
+                    .iter()
+                    .map(|(type_id, settings)| {
+                        (*type_id, settings.as_deref().map(clone_value))
+                    })
+                    .collect(),
+                bypass: snapshot.bypass.clone(),
+            })
+            .collect();
+        Self { cameras }
+    }
+}
+
+// The settings types of the registered effects that can be reflected, once each
+fn settings_types<'a>(
+    world: &World,
+    type_registry: &'a TypeRegistry,
+) -> Vec<(TypeId, &'a ReflectComponent)> {
+    let mut settings_types: Vec<(TypeId, &ReflectComponent)> = Vec::new();
+    for effect in world.resource::<PostProcessRegistry>().effects() {
+        let type_id = effect.settings_type_id;
+        if settings_types.iter().any(|&(captured, _)| captured == type_id) {
+            continue;
+        }
+        if let Some(reflect_component) = type_registry.get_type_data::<ReflectComponent>(type_id) {
+            settings_types.push((type_id, reflect_component));
+        }
+    }
+    settings_types
+}
+
+// Keep the concrete type when possible, so the value can be inserted back as a component
+fn clone_value(value: &dyn PartialReflect) -> Box<dyn PartialReflect> {
+    match value.reflect_clone() {
+        Ok(value) => value.into_partial_reflect(),
+        Err(_) => value.to_dynamic(),
+    }
+}
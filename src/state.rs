@@ -0,0 +1,138 @@
+use crate::{
+    depth::ViewPostProcessDepthRange, pipeline::ViewPostProcessPipeline,
+    prewarm::PrewarmedPipelines, resolution::ViewPostProcessIntermediate,
+    stencil::ViewPostProcessStencil, PostProcessPluginSettings,
+};
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+    render::{
+        render_graph::{InternedRenderLabel, RenderLabel},
+        render_resource::{CachedRenderPipelineId, PipelineCache},
+    },
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+/// Tracks which effects have finished compiling their pipelines.
+///
+/// An effect is ready once the pipelines it needs for at least one of the views it runs on have been
+/// compiled. Use it to hold a loading screen until the effects can render, so the first gameplay frame
+/// neither hitches nor renders without them. A [`PostProcessReady`] message is written when an effect
+/// becomes ready. Effects requested with [`PostProcessPrewarm`](crate::PostProcessPrewarm) also
+/// become ready once their requested variants are compiled, without any view.
+///
+/// Pipelines are only compiled for the effects a camera runs or that are prewarmed, so only those
+/// are tracked. An effect is tracked from the first frame a camera runs it or its prewarm request
+/// reaches the render world, so spawn the cameras or request the prewarm before waiting.
+#[derive(Resource, Default, Debug)]
+pub struct PostProcessState {
+    ready: HashSet<InternedRenderLabel>,
+    effects: HashSet<InternedRenderLabel>,
+}
+
+impl PostProcessState {
+    /// Whether the effect with this label is ready to render.
+    pub fn ready(&self, label: impl RenderLabel) -> bool {
+        self.ready.contains(&label.intern())
+    }
+
+    /// Whether every tracked effect is ready to render, those run by a camera or prewarmed.
+    ///
+    /// Also true when no effect is tracked yet, see [`PostProcessState::tracks_any`].
+    pub fn all_ready(&self) -> bool {
+        self.effects.is_subset(&self.ready)
+    }
+
+    /// Whether any effect is tracked, because a camera runs it or it is prewarmed.
+    pub fn tracks_any(&self) -> bool {
+        !self.effects.is_empty()
+    }
+}
+
+/// Written when the pipelines of an effect finished compiling and it is ready to render.
+#[derive(Message, Clone, Debug)]
+pub struct PostProcessReady {
+    /// The label of the effect.
+    pub label: InternedRenderLabel,
+}
+
+// Readiness of the effects, written by the render world and read by the main world
+#[derive(Resource, Clone, Default)]
+pub(crate) struct SharedPipelineReadiness(Arc<Mutex<HashMap<InternedRenderLabel, bool>>>);
+
+impl SharedPipelineReadiness {
+    /// Track an effect in use, which stays ready once it was.
+    pub(crate) fn track(&self, label: InternedRenderLabel, ready: bool) {
+        *self.0.lock().unwrap().entry(label).or_insert(false) |= ready;
+    }
+
+    pub(crate) fn is_ready(&self, label: InternedRenderLabel) -> bool {
+        self.0.lock().unwrap().get(&label).copied().unwrap_or(false)
+    }
+}
+
+// The pipelines an effect needs on a view
+type ViewPipelines<U, R> = (
+    &'static ViewPostProcessPipeline<U, R>,
+    Option<&'static ViewPostProcessIntermediate<U, R>>,
+    Option<&'static ViewPostProcessStencil<U, R>>,
+    Option<&'static ViewPostProcessDepthRange<U, R>>,
+);
+
+/// Check whether the pipelines of an effect are compiled for any of the views it runs on.
+///
+/// The effects no view runs and that aren't prewarmed have no pipeline to wait for, so they aren't
+/// tracked.
+pub(crate) fn update_pipeline_readiness<
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    plugin_settings: Res<PostProcessPluginSettings<U, R>>,
+    pipeline_cache: Res<PipelineCache>,
+    readiness: Res<SharedPipelineReadiness>,
+    views: Query<ViewPipelines<U, R>>,
+    prewarmed: Option<Res<PrewarmedPipelines<U, R>>>,
+) {
+    if views.is_empty() && prewarmed.is_none() {
+        return;
+    }
+    let compiled =
+        |id: CachedRenderPipelineId| pipeline_cache.get_render_pipeline(id).is_some();
+
+    let ready = views
+        .iter()
+        .any(|(view_pipeline, intermediate, stencil, depth_range)| {
+            compiled(view_pipeline.pipeline_id)
+                && intermediate.is_none_or(|intermediate| {
+                    compiled(intermediate.composite_pipeline_id)
+                })
+                && stencil.is_none_or(|stencil| {
+                    compiled(stencil.copy_pipeline_id)
+                        && stencil.write_pipeline_id.is_none_or(compiled)
+                })
+                && depth_range.is_none_or(|depth_range| compiled(depth_range.pipeline_id))
+        })
+        // The variants compiled ahead of time don't need any view
+        || prewarmed.is_some_and(|prewarmed| {
+            prewarmed.pipeline_ids.iter().copied().all(compiled)
+        });
+
+    // Once ready an effect stays ready, even if it doesn't run on any view for a while
+    readiness.track(plugin_settings.label.intern(), ready);
+}
+
+/// Copy the readiness written by the render world and notify the effects that became ready.
+pub(crate) fn sync_post_process_state(
+    readiness: Res<SharedPipelineReadiness>,
+    mut state: ResMut<PostProcessState>,
+    mut ready_messages: MessageWriter<PostProcessReady>,
+) {
+    for (&label, &ready) in readiness.0.lock().unwrap().iter() {
+        state.effects.insert(label);
+        if ready && state.ready.insert(label) {
+            ready_messages.write(PostProcessReady { label });
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::PostProcessRegistry;
+use bevy::{
+    diagnostic::{DiagnosticPath, DiagnosticsStore},
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+    render::{
+        diagnostic::RenderDiagnosticsPlugin,
+        render_graph::{InternedRenderLabel, RenderLabel},
+        Render, RenderApp, RenderSystems,
+    },
+};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Collects the cost of every effect into [`PostProcessStats`].
+///
+/// The GPU and CPU times are measured with Bevy's render diagnostics, which are added by the plugin
+/// if needed, and are also available in the [`DiagnosticsStore`] under
+/// `render/post_process/<label>/elapsed_gpu`. GPU timings are only supported on Vulkan and DX12.
+#[derive(Default)]
+pub struct PostProcessStatsPlugin {
+    /// Show the stats in a text overlay in the top right corner of the window.
+    pub overlay: bool,
+}
+
+impl Plugin for PostProcessStatsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderDiagnosticsPlugin>() {
+            app.add_plugins(RenderDiagnosticsPlugin);
+        }
+
+        let rendered = RenderedEffects::default();
+        app.insert_resource(rendered.clone())
+            .init_resource::<PostProcessStats>()
+            .add_systems(PostUpdate, update_post_process_stats);
+
+        if self.overlay {
+            app.add_systems(Startup, spawn_stats_overlay)
+                .add_systems(PostUpdate, update_stats_overlay.after(update_post_process_stats));
+        }
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .insert_resource(rendered)
+                .add_systems(Render, publish_rendered_effects.in_set(RenderSystems::Cleanup));
+        }
+    }
+}
+
+/// The cost of an effect, averaged over the last frames.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PostProcessEffectStats {
+    /// Time spent by the GPU on the passes of the effect, in milliseconds.
+    pub gpu_ms: Option<f64>,
+    /// Time spent encoding the passes of the effect, in milliseconds.
+    pub cpu_ms: Option<f64>,
+    /// Whether the effect didn't render on any view during the last rendered frame, because it was
+    /// bypassed, its run condition failed, no camera has its settings or it isn't ready yet.
+    pub bypassed: bool,
+}
+
+/// The cost of every effect, collected by the [`PostProcessStatsPlugin`].
+#[derive(Resource, Default, Debug)]
+pub struct PostProcessStats {
+    effects: HashMap<InternedRenderLabel, PostProcessEffectStats>,
+}
+
+impl PostProcessStats {
+    /// The stats of the effect with this label.
+    pub fn get(&self, label: impl RenderLabel) -> Option<&PostProcessEffectStats> {
+        self.effects.get(&label.intern())
+    }
+
+    /// The stats of every effect.
+    pub fn iter(&self) -> impl Iterator<Item = (InternedRenderLabel, &PostProcessEffectStats)> {
+        self.effects.iter().map(|(label, stats)| (*label, stats))
+    }
+}
+
+// The effects that rendered on at least one view, written by the render world
+#[derive(Resource, Clone, Default)]
+pub(crate) struct RenderedEffects(Arc<Mutex<RenderedEffectsFrames>>);
+
+#[derive(Default)]
+struct RenderedEffectsFrames {
+    current: HashSet<InternedRenderLabel>,
+    last: HashSet<InternedRenderLabel>,
+}
+
+impl RenderedEffects {
+    pub(crate) fn insert(&self, label: InternedRenderLabel) {
+        self.0.lock().unwrap().current.insert(label);
+    }
+}
+
+/// The name of the diagnostics span of an effect.
+pub(crate) fn span_name(label: impl RenderLabel) -> String {
+    format!("post_process/{:?}", label.intern())
+}
+
+// Only publish full frames, since the main world reads them while the next frame renders
+fn publish_rendered_effects(rendered: Res<RenderedEffects>) {
+    let frames = &mut *rendered.0.lock().unwrap();
+    frames.last = std::mem::take(&mut frames.current);
+}
+
+fn update_post_process_stats(
+    registry: Res<PostProcessRegistry>,
+    rendered: Res<RenderedEffects>,
+    diagnostics: Option<Res<DiagnosticsStore>>,
+    mut stats: ResMut<PostProcessStats>,
+) {
+    let rendered = rendered.0.lock().unwrap();
+    let measurement = |label: InternedRenderLabel, field: &str| {
+        let path = DiagnosticPath::from_components(
+            ["render"]
+                .into_iter()
+                .chain(span_name(label).split('/'))
+                .chain([field]),
+        );
+        diagnostics
+            .as_ref()
+            .and_then(|diagnostics| diagnostics.get(&path))
+            .and_then(|diagnostic| diagnostic.smoothed())
+    };
+
+    for effect in registry.effects() {
+        stats.effects.insert(
+            effect.label,
+            PostProcessEffectStats {
+                gpu_ms: measurement(effect.label, "elapsed_gpu"),
+                cpu_ms: measurement(effect.label, "elapsed_cpu"),
+                bypassed: !rendered.last.contains(&effect.label),
+            },
+        );
+    }
+}
+
+#[derive(Component)]
+struct PostProcessStatsOverlay;
+
+fn spawn_stats_overlay(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            right: Val::Px(8.0),
+            ..default()
+        },
+        PostProcessStatsOverlay,
+    ));
+}
+
+fn update_stats_overlay(
+    registry: Res<PostProcessRegistry>,
+    stats: Res<PostProcessStats>,
+    mut overlays: Query<&mut Text, With<PostProcessStatsOverlay>>,
+) {
+    let mut text = String::new();
+    // The effects are listed in the order they run in
+    for effect in registry.effects() {
+        let Some(effect_stats) = stats.get(effect.label) else {
+            continue;
+        };
+        let _ = write!(text, "{:?}: ", effect.label);
+        let _ = match (effect_stats.bypassed, effect_stats.gpu_ms) {
+            (true, _) => writeln!(text, "bypassed"),
+            (false, Some(gpu_ms)) => writeln!(text, "{gpu_ms:.3} ms"),
+            (false, None) => writeln!(text, "-"),
+        };
+    }
+
+    for mut overlay in &mut overlays {
+        if overlay.0 != text {
+            overlay.0.clone_from(&text);
+        }
+    }
+}
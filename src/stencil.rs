@@ -0,0 +1,305 @@
+use crate::{
+    mask::{MaskCameraPlugin, MaskSource, ViewMask},
+    resolution::{scaled_size, CompositePipeline, CompositePipelineKey},
+    PostProcessDestination, PostProcessPluginSettings, PostProcessUpscaleFilter,
+};
+use bevy::{
+    asset::{embedded_asset, load_embedded_asset},
+    core_pipeline::FullscreenShader,
+    prelude::*,
+    render::{
+        render_asset::RenderAssets,
+        render_graph::RenderLabel,
+        render_resource::{
+            binding_types::{sampler, texture_2d},
+            *,
+        },
+        renderer::RenderDevice,
+        texture::{CachedTexture, GpuImage, TextureCache},
+        view::ViewTarget,
+        RenderApp,
+    },
+};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// The format of the stencil attachment of the effect passes.
+pub(crate) const STENCIL_FORMAT: TextureFormat = TextureFormat::Stencil8;
+
+/// Marks a mesh as writing the post process stencil bit.
+///
+/// Effects using [`PostProcessStencilTest`] can then skip or only affect the pixels covered by
+/// the marked meshes, like a first person view model that shouldn't get motion blur.
+///
+/// The marked meshes are rendered by a crate-managed camera on [`PostProcessStencil::RENDER_LAYER`],
+/// without testing against the depth of the scene, so a marked mesh hidden behind another object
+/// still writes the stencil.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct PostProcessStencil;
+
+impl PostProcessStencil {
+    /// The render layer used to render the marked meshes. Avoid using it for anything else.
+    pub const RENDER_LAYER: usize = 31;
+}
+
+impl MaskSource for PostProcessStencil {
+    const RENDER_LAYER: usize = PostProcessStencil::RENDER_LAYER;
+
+    fn mask_color(&self) -> Color {
+        Color::WHITE
+    }
+}
+
+/// How an effect uses the stencil written by meshes marked with [`PostProcessStencil`].
+///
+/// See [`PostProcessPlugin::with_stencil`](crate::PostProcessPlugin::with_stencil).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PostProcessStencilTest {
+    /// The effect affects every pixel.
+    #[default]
+    Disabled,
+    /// The effect doesn't affect the pixels covered by marked meshes.
+    ExcludeMarked,
+    /// The effect only affects the pixels covered by marked meshes.
+    OnlyMarked,
+}
+
+// The stencil is 1 for the pixels affected by the effect. It is cleared to the value of the pixels
+// that aren't covered by marked meshes, then the marked meshes and the depth range are written on top.
+impl PostProcessStencilTest {
+    /// The stencil value of the pixels that aren't covered by marked meshes.
+    pub(crate) fn clear_value(&self) -> u32 {
+        match self {
+            PostProcessStencilTest::OnlyMarked => 0,
+            _ => 1,
+        }
+    }
+
+    /// The stencil value of the pixels covered by marked meshes.
+    pub(crate) fn marked_reference(&self) -> u32 {
+        match self {
+            PostProcessStencilTest::OnlyMarked => 1,
+            _ => 0,
+        }
+    }
+
+    /// The depth stencil state of a pipeline running in a stencil tested pass.
+    pub(crate) fn depth_stencil_state(compare: CompareFunction) -> DepthStencilState {
+        Self::stencil_state(compare, StencilOperation::Keep, 0)
+    }
+
+    /// The depth stencil state of a pipeline writing the stencil reference where it isn't discarded.
+    pub(crate) fn stencil_write_state() -> DepthStencilState {
+        Self::stencil_state(CompareFunction::Always, StencilOperation::Replace, 0xff)
+    }
+
+    fn stencil_state(
+        compare: CompareFunction,
+        pass_op: StencilOperation,
+        write_mask: u32,
+    ) -> DepthStencilState {
+        let face = StencilFaceState {
+            compare,
+            fail_op: StencilOperation::Keep,
+            depth_fail_op: StencilOperation::Keep,
+            pass_op,
+        };
+        DepthStencilState {
+            format: STENCIL_FORMAT,
+            depth_write_enabled: false,
+            depth_compare: CompareFunction::Always,
+            stencil: StencilState {
+                front: face,
+                back: face,
+                read_mask: 0xff,
+                write_mask,
+            },
+            bias: DepthBiasState::default(),
+        }
+    }
+}
+
+// Registers everything needed by effects using the stencil. Added once by the first effect using it.
+pub(crate) struct PostProcessStencilPlugin;
+
+impl Plugin for PostProcessStencilPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "stencil.wgsl");
+
+        app.add_plugins(MaskCameraPlugin::<PostProcessStencil>::default());
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<StencilWritePipeline>()
+            .init_resource::<SpecializedRenderPipelines<StencilWritePipeline>>();
+    }
+}
+
+// Writes the stencil from the mask rendered by the stencil camera
+#[derive(Resource)]
+pub(crate) struct StencilWritePipeline {
+    pub(crate) layout: BindGroupLayout,
+    pub(crate) sampler: Sampler,
+    shader: Handle<Shader>,
+    vertex_state: VertexState,
+}
+
+impl FromWorld for StencilWritePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(
+            "post_process_stencil_write_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    // The mask rendered by the stencil camera
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                ),
+            ),
+        );
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+        Self {
+            layout,
+            sampler,
+            shader: load_embedded_asset!(world, "stencil.wgsl"),
+            vertex_state: world.resource::<FullscreenShader>().to_vertex_state(),
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for StencilWritePipeline {
+    // The format of the color attachment of the pass, which must match even if it isn't written
+    type Key = TextureFormat;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: Some("post_process_stencil_write_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: self.vertex_state.clone(),
+            fragment: Some(FragmentState {
+                shader: self.shader.clone(),
+                shader_defs: vec![],
+                entry_point: Some("fragment".into()),
+                // Only the stencil is written
+                targets: vec![Some(ColorTargetState {
+                    format: key,
+                    blend: None,
+                    write_mask: ColorWrites::empty(),
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: Some(PostProcessStencilTest::stencil_write_state()),
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+/// The stencil attachment of the effect pass of a view, and the pipelines writing it.
+#[derive(Component)]
+pub(crate) struct ViewPostProcessStencil<U: Send + Sync + 'static, R: Send + Sync + 'static> {
+    pub(crate) texture: CachedTexture,
+    /// Only present when the effect tests the marked meshes
+    pub(crate) write_pipeline_id: Option<CachedRenderPipelineId>,
+    /// Copies the source onto the target so the pixels skipped by the effect keep their color
+    pub(crate) copy_pipeline_id: CachedRenderPipelineId,
+    _marker: PhantomData<(U, R)>,
+}
+
+// The views running the effect, and whether they have the mask of the marked meshes
+type StencilViews = (Entity, &'static ViewTarget, Has<ViewMask<PostProcessStencil>>);
+
+/// Allocate the stencil attachment of an effect for every view it runs on.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn prepare_stencil_textures<
+    U: Component + Clone,
+    R: Debug + Hash + PartialEq + Eq + Clone + RenderLabel,
+>(
+    mut commands: Commands,
+    plugin_settings: Res<PostProcessPluginSettings<U, R>>,
+    views: Query<StencilViews, With<U>>,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    // Only present when an effect tests the marked meshes
+    write_pipeline: Option<Res<StencilWritePipeline>>,
+    mut write_pipelines: Option<ResMut<SpecializedRenderPipelines<StencilWritePipeline>>>,
+    composite_pipeline: Res<CompositePipeline>,
+    mut composite_pipelines: ResMut<SpecializedRenderPipelines<CompositePipeline>>,
+    mut texture_cache: ResMut<TextureCache>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+) {
+    if !plugin_settings.uses_stencil() {
+        return;
+    }
+
+    for (entity, view_target, has_mask) in &views {
+        // The stencil camera is spawned by the main world after the first frame
+        if plugin_settings.stencil != PostProcessStencilTest::Disabled && !has_mask {
+            continue;
+        }
+
+        let texture_format = match &plugin_settings.destination {
+            PostProcessDestination::ViewTarget => view_target.main_texture_format(),
+            PostProcessDestination::Image(image) => {
+                let Some(gpu_image) = gpu_images.get(image) else {
+                    continue;
+                };
+                gpu_image.texture_format
+            }
+        };
+
+        let texture = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some("post_process_stencil_texture"),
+                // The attachment must match the size of the texture the effect renders into
+                size: scaled_size(
+                    view_target.main_texture().size(),
+                    plugin_settings.resolution_scale,
+                ),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: STENCIL_FORMAT,
+                usage: TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            },
+        );
+
+        let write_pipeline_id = match (&write_pipeline, &mut write_pipelines) {
+            (Some(write_pipeline), Some(write_pipelines))
+                if plugin_settings.stencil != PostProcessStencilTest::Disabled =>
+            {
+                Some(write_pipelines.specialize(&pipeline_cache, write_pipeline, texture_format))
+            }
+            _ => None,
+        };
+        let copy_pipeline_id = composite_pipelines.specialize(
+            &pipeline_cache,
+            &composite_pipeline,
+            CompositePipelineKey {
+                texture_format,
+                filter: PostProcessUpscaleFilter::Bilinear,
+                stencil: true,
+            },
+        );
+
+        commands
+            .entity(entity)
+            .insert(ViewPostProcessStencil::<U, R> {
+                texture,
+                write_pipeline_id,
+                copy_pipeline_id,
+                _marker: PhantomData,
+            });
+    }
+}
@@ -0,0 +1,15 @@
+// Writes the stencil where the mask rendered by the stencil camera is set.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var mask_texture: texture_2d<f32>;
+@group(0) @binding(1) var mask_sampler: sampler;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // Only the fragments that aren't discarded write the stencil reference.
+    // The color output is masked out by the pipeline.
+    if (textureSample(mask_texture, mask_sampler, in.uv).r < 0.5) {
+        discard;
+    }
+    return vec4(0.0);
+}
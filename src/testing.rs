@@ -0,0 +1,379 @@
+//! Golden image tests for effects.
+//!
+//! [`PostProcessTestHarness`] renders an effect over a known input in a headless app and reads
+//! the result back, and [`compare_images`] checks it against a reference image:
+//!
+//! ```ignore
+//! #[test]
+//! fn vignette_matches_reference() {
+//!     let output = PostProcessTestHarness::new(UVec2::new(64, 64))
+//!         .add_plugins(VignettePlugin)
+//!         .run(VignetteSettings::default())
+//!         .unwrap();
+//!     let reference = reference_image(include_bytes!("references/vignette.png"));
+//!     compare_images(&output, &reference, 2).unwrap();
+//! }
+//! ```
+//!
+//! The harness needs a GPU adapter, software adapters like lavapipe or WARP work in CI. To create
+//! or update a reference, save the output with `output.try_into_dynamic()?.save(path)` and check
+//! it by eye.
+
+use crate::PostProcessState;
+use bevy::{
+    app::Plugins,
+    asset::RenderAssetUsages,
+    camera::{RenderTarget, ScalingMode},
+    core_pipeline::tonemapping::{DebandDither, Tonemapping},
+    image::{CompressedImageFormats, ImageSampler, ImageType},
+    prelude::*,
+    render::{
+        gpu_readback::{Readback, ReadbackComplete},
+        pipelined_rendering::PipelinedRenderingPlugin,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        renderer::RenderDevice,
+    },
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use std::fmt::{self, Display};
+use std::sync::{Arc, Mutex};
+
+// The number of frames to wait for the pipelines to compile and the readback to complete
+const MAX_FRAMES: u32 = 600;
+// The frames rendered with the effect before reading back, so the readback isn't a stale one
+const SETTLE_FRAMES: u32 = 3;
+
+/// Renders effects over a known input in a headless app and reads the result back.
+///
+/// The input is drawn unlit on a quad filling the view of a camera without HDR, MSAA,
+/// tonemapping or dithering, so the effects see it unchanged. Without an input, a gradient
+/// going from black to red horizontally and to green vertically is drawn.
+pub struct PostProcessTestHarness {
+    app: App,
+    size: UVec2,
+    input: Option<Image>,
+}
+
+/// The errors of the [`PostProcessTestHarness`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostProcessTestError {
+    /// The effects weren't ready or the output wasn't read back in time.
+    Timeout,
+}
+
+impl Display for PostProcessTestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "the effects didn't render within {MAX_FRAMES} frames"),
+        }
+    }
+}
+
+impl std::error::Error for PostProcessTestError {}
+
+// The last texture read back, written by the readback observer
+#[derive(Clone, Default)]
+struct ReadbackData(Arc<Mutex<Option<Vec<u8>>>>);
+
+impl PostProcessTestHarness {
+    /// Create a headless app rendering at this size.
+    pub fn new(size: UVec2) -> Self {
+        let mut app = App::new();
+        app.add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    ..default()
+                })
+                .disable::<WinitPlugin>()
+                // Render on the main thread, so every update renders the frame it prepared
+                .disable::<PipelinedRenderingPlugin>(),
+        );
+        Self {
+            app,
+            size,
+            input: None,
+        }
+    }
+
+    /// Add the plugins of the effects under test.
+    pub fn add_plugins<M>(mut self, plugins: impl Plugins<M>) -> Self {
+        self.app.add_plugins(plugins);
+        self
+    }
+
+    /// Draw this image under the effects instead of the gradient.
+    pub fn with_input(mut self, input: Image) -> Self {
+        self.input = Some(input);
+        self
+    }
+
+    /// Access the app, to insert resources or spawn extra entities before running.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Render the effects with these settings on the camera and read back the output.
+    pub fn run(mut self, settings: impl Bundle) -> Result<Image, PostProcessTestError> {
+        let size = self.size;
+        let input = self.input.take().unwrap_or_else(|| gradient(size));
+        let app = &mut self.app;
+        app.finish();
+        app.cleanup();
+
+        let world = app.world_mut();
+        let mut target = Image::new_target_texture(size.x, size.y, TextureFormat::Rgba8UnormSrgb);
+        target.texture_descriptor.usage |= TextureUsages::COPY_SRC;
+        let target = world.resource_mut::<Assets<Image>>().add(target);
+        let input = world.resource_mut::<Assets<Image>>().add(input);
+        let quad = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Rectangle::new(1.0, 1.0));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color_texture: Some(input),
+                unlit: true,
+                ..default()
+            });
+
+        world.spawn((Mesh3d(quad), MeshMaterial3d(material)));
+        world.spawn((
+            Camera3d::default(),
+            Camera {
+                target: RenderTarget::Image(target.clone().into()),
+                ..default()
+            },
+            Projection::Orthographic(OrthographicProjection {
+                scaling_mode: ScalingMode::Fixed {
+                    width: 1.0,
+                    height: 1.0,
+                },
+                ..OrthographicProjection::default_3d()
+            }),
+            Transform::from_xyz(0.0, 0.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+            Msaa::Off,
+            Tonemapping::None,
+            DebandDither::Disabled,
+            settings,
+        ));
+
+        let readback = ReadbackData::default();
+        let captured = readback.clone();
+        world.spawn(Readback::texture(target)).observe(
+            move |readback: On<ReadbackComplete>| {
+                *captured.0.lock().unwrap() = Some(readback.data.clone());
+            },
+        );
+
+        let mut ready_frames = 0;
+        for _ in 0..MAX_FRAMES {
+            app.update();
+            let state = app.world().resource::<PostProcessState>();
+            if !state.tracks_any() || !state.all_ready() {
+                continue;
+            }
+            ready_frames += 1;
+            if ready_frames == SETTLE_FRAMES {
+                // Drop the readbacks of the frames rendered before the effects were ready
+                readback.0.lock().unwrap().take();
+            }
+            if ready_frames > SETTLE_FRAMES
+                && let Some(data) = readback.0.lock().unwrap().take()
+            {
+                return Ok(output_image(size, &data));
+            }
+        }
+        Err(PostProcessTestError::Timeout)
+    }
+}
+
+// Remove the padding of the rows of the texture read back
+fn output_image(size: UVec2, data: &[u8]) -> Image {
+    let row_bytes = size.x as usize * 4;
+    let padded_row_bytes = RenderDevice::align_copy_bytes_per_row(row_bytes);
+    let data = data
+        .chunks(padded_row_bytes)
+        .take(size.y as usize)
+        .flat_map(|row| &row[..row_bytes])
+        .copied()
+        .collect();
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+fn gradient(size: UVec2) -> Image {
+    let data = (0..size.y)
+        .flat_map(|y| {
+            (0..size.x).flat_map(move |x| {
+                let red = (x * 255 / (size.x - 1).max(1)) as u8;
+                let green = (y * 255 / (size.y - 1).max(1)) as u8;
+                [red, green, 0, 255]
+            })
+        })
+        .collect();
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Decode a PNG reference image, usually embedded with `include_bytes!`.
+pub fn reference_image(png: &[u8]) -> Image {
+    Image::from_buffer(
+        png,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::default(),
+    )
+    .expect("invalid reference image")
+}
+
+/// How an output differs from its reference image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageMismatch {
+    /// The images don't have the same size or format.
+    Layout,
+    /// Some channels differ by more than the tolerance.
+    Pixels {
+        /// The number of pixels with a channel over the tolerance.
+        differing_pixels: usize,
+        /// The largest difference of a channel.
+        max_difference: u8,
+    },
+}
+
+impl Display for ImageMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Layout => write!(f, "the images don't have the same size or format"),
+            Self::Pixels {
+                differing_pixels,
+                max_difference,
+            } => write!(
+                f,
+                "{differing_pixels} pixels differ, by up to {max_difference} in a channel"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImageMismatch {}
+
+/// Compare two 8-bit images, allowing each channel to differ by `tolerance`.
+///
+/// GPUs don't all round the same way, so a small tolerance avoids failures on other machines.
+pub fn compare_images(
+    output: &Image,
+    reference: &Image,
+    tolerance: u8,
+) -> Result<(), ImageMismatch> {
+    let (Some(output_data), Some(reference_data)) = (&output.data, &reference.data) else {
+        return Err(ImageMismatch::Layout);
+    };
+    if output.size() != reference.size() || output_data.len() != reference_data.len() {
+        return Err(ImageMismatch::Layout);
+    }
+
+    let mut differing_pixels = 0;
+    let mut max_difference = 0;
+    for (output, reference) in output_data.chunks(4).zip(reference_data.chunks(4)) {
+        let difference = output
+            .iter()
+            .zip(reference)
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0);
+        max_difference = max_difference.max(difference);
+        if difference > tolerance {
+            differing_pixels += 1;
+        }
+    }
+
+    match differing_pixels {
+        0 => Ok(()),
+        _ => Err(ImageMismatch::Pixels {
+            differing_pixels,
+            max_difference,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(size: UVec2, color: [u8; 4]) -> Image {
+        Image::new_fill(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &color,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        )
+    }
+
+    #[test]
+    fn identical_images_match() {
+        let image = gradient(UVec2::new(8, 8));
+        assert_eq!(compare_images(&image, &image, 0), Ok(()));
+    }
+
+    #[test]
+    fn differences_within_the_tolerance_match() {
+        let output = solid(UVec2::new(4, 4), [100, 100, 100, 255]);
+        let reference = solid(UVec2::new(4, 4), [102, 98, 100, 255]);
+        assert_eq!(compare_images(&output, &reference, 2), Ok(()));
+    }
+
+    #[test]
+    fn differences_over_the_tolerance_are_counted() {
+        let output = solid(UVec2::new(4, 4), [100, 100, 100, 255]);
+        let mut reference = output.clone();
+        let data = reference.data.as_mut().unwrap();
+        // One pixel just over the tolerance and one far over it, on different channels
+        data[0] = 103;
+        data[6] = 140;
+        assert_eq!(
+            compare_images(&output, &reference, 2),
+            Err(ImageMismatch::Pixels {
+                differing_pixels: 2,
+                max_difference: 40,
+            })
+        );
+    }
+
+    #[test]
+    fn different_sizes_dont_match() {
+        let output = solid(UVec2::new(4, 4), [0, 0, 0, 255]);
+        let reference = solid(UVec2::new(4, 2), [0, 0, 0, 255]);
+        assert_eq!(
+            compare_images(&output, &reference, 255),
+            Err(ImageMismatch::Layout)
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    render::{
+        render_graph::{InternedRenderLabel, RenderLabel},
+        render_resource::{TextureDescriptor, TextureDimension, TextureFormat, TextureUsages},
+        renderer::RenderDevice,
+        texture::CachedTexture,
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+/// The number of frames a texture is kept after it was last requested.
+///
+/// Released textures can be picked up by any request with the same descriptor in the meantime,
+/// so toggling an effect or a camera doesn't reallocate its textures.
+const RELEASE_FRAMES: u32 = 3;
+
+/// The textures of the effects, kept per view from one frame to the next.
+///
+/// Unlike the [`TextureCache`](bevy::render::texture::TextureCache), which hands out any texture
+/// with a matching descriptor, a texture of the pool is owned by a single view, effect and name,
+/// so it keeps its content between frames. This is what history buffers and mip chains need.
+///
+/// The texture is reallocated when its descriptor changes, like when the window is resized, and
+/// released a few frames after it was last requested, like when the camera is despawned.
+///
+/// This is a render world resource. Request the textures in
+/// [`RenderSystems::PrepareResources`] and read them in the render graph.
+#[derive(Resource, Default)]
+pub struct PostProcessTexturePool {
+    textures: HashMap<TextureKey, PooledTexture>,
+    // Released textures, reused by the next request with the same shape
+    released: Vec<PooledTexture>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct TextureKey {
+    view: Entity,
+    owner: InternedRenderLabel,
+    name: &'static str,
+    index: u32,
+}
+
+// The parts of the descriptor that decide whether a texture can be reused
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct TextureShape {
+    size: (u32, u32, u32),
+    mip_level_count: u32,
+    sample_count: u32,
+    dimension: TextureDimension,
+    format: TextureFormat,
+    usage: TextureUsages,
+}
+
+impl TextureShape {
+    fn of(descriptor: &TextureDescriptor) -> Self {
+        Self {
+            size: (
+                descriptor.size.width,
+                descriptor.size.height,
+                descriptor.size.depth_or_array_layers,
+            ),
+            mip_level_count: descriptor.mip_level_count,
+            sample_count: descriptor.sample_count,
+            dimension: descriptor.dimension,
+            format: descriptor.format,
+            usage: descriptor.usage,
+        }
+    }
+}
+
+struct PooledTexture {
+    texture: CachedTexture,
+    shape: TextureShape,
+    // Frames since the texture was last requested
+    unused_frames: u32,
+}
+
+impl PostProcessTexturePool {
+    /// Get the texture named `name` of the effect `owner` for `view`, allocating it if needed.
+    ///
+    /// The same texture is returned every frame as long as `descriptor` doesn't change.
+    pub fn get(
+        &mut self,
+        render_device: &RenderDevice,
+        view: Entity,
+        owner: impl RenderLabel,
+        name: &'static str,
+        descriptor: &TextureDescriptor,
+    ) -> CachedTexture {
+        self.get_indexed(render_device, view, owner, name, 0, descriptor)
+    }
+
+    /// Get one of several textures named `name`, like the levels of a mip chain or the frames of
+    /// a history. See [`PostProcessTexturePool::get`].
+    pub fn get_indexed(
+        &mut self,
+        render_device: &RenderDevice,
+        view: Entity,
+        owner: impl RenderLabel,
+        name: &'static str,
+        index: u32,
+        descriptor: &TextureDescriptor,
+    ) -> CachedTexture {
+        let key = TextureKey {
+            view,
+            owner: owner.intern(),
+            name,
+            index,
+        };
+        let shape = TextureShape::of(descriptor);
+
+        if let Some(pooled) = self.textures.get_mut(&key) {
+            if pooled.shape == shape {
+                pooled.unused_frames = 0;
+                return pooled.texture.clone();
+            }
+            // The texture was resized, the previous one may still be reused by another request
+            if let Some(mut previous) = self.textures.remove(&key) {
+                previous.unused_frames = 0;
+                self.released.push(previous);
+            }
+        }
+
+        let pooled = match self
+            .released
+            .iter()
+            .position(|released| released.shape == shape)
+        {
+            Some(index) => self.released.swap_remove(index),
+            None => {
+                let texture = render_device.create_texture(descriptor);
+                PooledTexture {
+                    texture: CachedTexture {
+                        default_view: texture.create_view(&default()),
+                        texture,
+                    },
+                    shape,
+                    unused_frames: 0,
+                }
+            }
+        };
+        let texture = pooled.texture.clone();
+        self.textures.insert(key, PooledTexture {
+            unused_frames: 0,
+            ..pooled
+        });
+        texture
+    }
+
+    /// Get the texture named `name` of the effect `owner` for `view`, if it was requested.
+    pub fn get_existing(
+        &self,
+        view: Entity,
+        owner: impl RenderLabel,
+        name: &'static str,
+        index: u32,
+    ) -> Option<&CachedTexture> {
+        let key = TextureKey {
+            view,
+            owner: owner.intern(),
+            name,
+            index,
+        };
+        self.textures.get(&key).map(|pooled| &pooled.texture)
+    }
+
+    /// The number of textures allocated by the pool, including the released ones.
+    pub fn len(&self) -> usize {
+        self.textures.len() + self.released.len()
+    }
+
+    /// Whether the pool doesn't hold any texture.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub(crate) fn add_texture_pool(app: &mut App) {
+    let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    render_app.init_resource::<PostProcessTexturePool>().add_systems(
+        Render,
+        release_unused_textures.in_set(RenderSystems::Cleanup),
+    );
+}
+
+/// Release the textures that weren't requested for a few frames, and free those that weren't
+/// reused in the meantime.
+fn release_unused_textures(mut pool: ResMut<PostProcessTexturePool>) {
+    let pool = &mut *pool;
+    pool.released.retain_mut(|released| {
+        released.unused_frames += 1;
+        released.unused_frames <= RELEASE_FRAMES
+    });
+    pool.textures.retain(|_, pooled| {
+        pooled.unused_frames += 1;
+        if pooled.unused_frames <= RELEASE_FRAMES {
+            return true;
+        }
+        pool.released.push(PooledTexture {
+            texture: pooled.texture.clone(),
+            shape: pooled.shape,
+            unused_frames: 0,
+        });
+        false
+    });
+}
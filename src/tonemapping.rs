@@ -0,0 +1,70 @@
+use crate::bindings;
+use bevy::{
+    core_pipeline::tonemapping::{
+        get_lut_bind_group_layout_entries, get_lut_bindings, Tonemapping, TonemappingLuts,
+    },
+    prelude::*,
+    render::{
+        render_asset::RenderAssets,
+        render_resource::{BindGroupEntry, BindGroupLayoutEntryBuilder, IntoBinding},
+        texture::{FallbackImage, GpuImage},
+    },
+    shader::ShaderDefVal,
+};
+
+/// The shader defs selecting the tonemapping method of the view and the bindings of its LUT, so
+/// `bevy_core_pipeline::tonemapping` can be imported by the effect shader.
+pub(crate) fn shader_defs(tonemapping: Option<&Tonemapping>) -> Vec<ShaderDefVal> {
+    let method = match tonemapping.copied().unwrap_or(Tonemapping::None) {
+        Tonemapping::None => "TONEMAP_METHOD_NONE",
+        Tonemapping::Reinhard => "TONEMAP_METHOD_REINHARD",
+        Tonemapping::ReinhardLuminance => "TONEMAP_METHOD_REINHARD_LUMINANCE",
+        Tonemapping::AcesFitted => "TONEMAP_METHOD_ACES_FITTED",
+        Tonemapping::AgX => "TONEMAP_METHOD_AGX",
+        Tonemapping::SomewhatBoringDisplayTransform => {
+            "TONEMAP_METHOD_SOMEWHAT_BORING_DISPLAY_TRANSFORM"
+        }
+        Tonemapping::TonyMcMapface => "TONEMAP_METHOD_TONY_MC_MAPFACE",
+        Tonemapping::BlenderFilmic => "TONEMAP_METHOD_BLENDER_FILMIC",
+    };
+    vec![
+        method.into(),
+        ShaderDefVal::UInt(
+            "TONEMAPPING_LUT_TEXTURE_BINDING_INDEX".into(),
+            bindings::TONEMAPPING_LUT,
+        ),
+        ShaderDefVal::UInt(
+            "TONEMAPPING_LUT_SAMPLER_BINDING_INDEX".into(),
+            bindings::TONEMAPPING_LUT_SAMPLER,
+        ),
+    ]
+}
+
+/// The layout entries of the LUT texture and of its sampler.
+pub(crate) fn layout_entries() -> [BindGroupLayoutEntryBuilder; 2] {
+    get_lut_bind_group_layout_entries()
+}
+
+/// The bind group entries of the LUT used by the tonemapping method of the view.
+pub(crate) fn bind_group_entries<'w>(
+    world: &'w World,
+    view: Entity,
+) -> Option<[BindGroupEntry<'w>; 2]> {
+    let tonemapping = world.get::<Tonemapping>(view).unwrap_or(&Tonemapping::None);
+    let (texture_view, sampler) = get_lut_bindings(
+        world.resource::<RenderAssets<GpuImage>>(),
+        world.get_resource::<TonemappingLuts>()?,
+        tonemapping,
+        world.resource::<FallbackImage>(),
+    );
+    Some([
+        BindGroupEntry {
+            binding: bindings::TONEMAPPING_LUT,
+            resource: texture_view.into_binding(),
+        },
+        BindGroupEntry {
+            binding: bindings::TONEMAPPING_LUT_SAMPLER,
+            resource: sampler.into_binding(),
+        },
+    ])
+}
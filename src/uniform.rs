@@ -0,0 +1,74 @@
+use crate::params;
+use bevy::{
+    prelude::*,
+    render::{
+        extract_component::{ComponentUniforms, DynamicUniformIndex, UniformComponentPlugin},
+        render_resource::{
+            binding_types::uniform_buffer, encase::internal::WriteInto,
+            BindGroupLayoutEntryBuilder, BindingResource, ShaderType,
+        },
+    },
+};
+
+/// The settings uniform of an effect.
+///
+/// Effects whose settings are a marker component don't have one, so it is type erased to keep
+/// the [`ShaderType`] bound on the constructor of the plugin only.
+#[derive(Clone, Copy)]
+pub(crate) struct SettingsUniform {
+    add_plugin: fn(&mut App),
+    layout_entry: fn() -> BindGroupLayoutEntryBuilder,
+    binding: for<'w> fn(&'w World) -> Option<BindingResource<'w>>,
+    index: fn(&World, Entity) -> Option<u32>,
+}
+
+impl SettingsUniform {
+    pub(crate) fn of<U: Component + ShaderType + WriteInto + Clone>() -> Self {
+        Self {
+            // The settings are written to a uniform buffer every frame, the buffer is shared by
+            // the effects using the same settings
+            add_plugin: |app| {
+                if !app.is_plugin_added::<UniformComponentPlugin<U>>() {
+                    app.add_plugins(UniformComponentPlugin::<U>::default());
+                }
+            },
+            layout_entry: || uniform_buffer::<U>(true),
+            binding: |world| world.resource::<ComponentUniforms<U>>().uniforms().binding(),
+            // As there could be multiple settings sent to the GPU (one per camera),
+            // we need to get the index of the one that is associated with the view.
+            index: |world, view| {
+                world
+                    .get::<DynamicUniformIndex<U>>(view)
+                    .map(DynamicUniformIndex::index)
+            },
+        }
+    }
+
+    /// The uniform of [`PostProcessParams`](crate::PostProcessParams), written by the crate
+    /// since the component itself isn't a [`ShaderType`].
+    pub(crate) fn params<M: Send + Sync + 'static>() -> Self {
+        Self {
+            add_plugin: params::add_systems::<M>,
+            layout_entry: params::layout_entry,
+            binding: params::binding::<M>,
+            index: params::index::<M>,
+        }
+    }
+
+    pub(crate) fn add_plugin(&self, app: &mut App) {
+        (self.add_plugin)(app);
+    }
+
+    pub(crate) fn layout_entry(&self) -> BindGroupLayoutEntryBuilder {
+        (self.layout_entry)()
+    }
+
+    pub(crate) fn binding<'w>(&self, world: &'w World) -> Option<BindingResource<'w>> {
+        (self.binding)(world)
+    }
+
+    /// The dynamic offset of the settings of the view.
+    pub(crate) fn index(&self, world: &World, view: Entity) -> Option<u32> {
+        (self.index)(world, view)
+    }
+}
@@ -0,0 +1,86 @@
+#define_import_path bevy_post_process::utils
+
+// Helpers shared by effect shaders. The functions taking a matrix expect the one of the same name
+// in `bevy_render::view::View`, bound at `bindings::VIEW`. The conversions between linear, sRGB
+// and Oklab colors are in `bevy_post_process::color_space`.
+
+// Rec. 709 weights, for linear colors
+const LUMINANCE_WEIGHTS: vec3<f32> = vec3(0.2126, 0.7152, 0.0722);
+
+// The UV of the center of a pixel
+fn pixel_to_uv(pixel: vec2<f32>, size: vec2<f32>) -> vec2<f32> {
+    return (pixel + 0.5) / size;
+}
+
+// The pixel a UV falls in
+fn uv_to_pixel(uv: vec2<f32>, size: vec2<f32>) -> vec2<i32> {
+    return vec2<i32>(floor(uv * size));
+}
+
+// The size of a texel of a texture in UV units
+fn texel_size(texture: texture_2d<f32>) -> vec2<f32> {
+    return 1.0 / vec2<f32>(textureDimensions(texture));
+}
+
+// From a UV, with y going down, to normalized device coordinates, with y going up
+fn uv_to_ndc(uv: vec2<f32>) -> vec2<f32> {
+    return vec2(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+}
+
+fn ndc_to_uv(ndc: vec2<f32>) -> vec2<f32> {
+    return vec2(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+}
+
+// The view space position of the pixel at this UV, from the value of the depth texture. Works for
+// perspective and orthographic projections
+fn position_view_from_depth(
+    uv: vec2<f32>,
+    depth: f32,
+    view_from_clip: mat4x4<f32>,
+) -> vec3<f32> {
+    let view = view_from_clip * vec4(uv_to_ndc(uv), depth, 1.0);
+    return view.xyz / view.w;
+}
+
+// The world space position of the pixel at this UV, from the value of the depth texture
+fn position_world_from_depth(
+    uv: vec2<f32>,
+    depth: f32,
+    world_from_clip: mat4x4<f32>,
+) -> vec3<f32> {
+    let world = world_from_clip * vec4(uv_to_ndc(uv), depth, 1.0);
+    return world.xyz / world.w;
+}
+
+// The distance from the camera plane to the surface, positive in front of the camera. Bevy uses a
+// reversed depth, so the raw value is 1 at the near plane and 0 at infinity
+fn linearize_depth(depth: f32, view_from_clip: mat4x4<f32>) -> f32 {
+    let view = view_from_clip * vec4(0.0, 0.0, depth, 1.0);
+    return -view.z / view.w;
+}
+
+// The relative luminance of a linear color
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, LUMINANCE_WEIGHTS);
+}
+
+// Scale the saturation of a linear color around its luminance, 0 being grayscale
+fn saturate_color(color: vec3<f32>, saturation: f32) -> vec3<f32> {
+    return mix(vec3(luminance(color)), color, saturation);
+}
+
+// Hue, saturation and value, each in 0 to 1
+fn rgb_to_hsv(color: vec3<f32>) -> vec3<f32> {
+    let k = vec4(0.0, -1.0 / 3.0, 2.0 / 3.0, -1.0);
+    let p = select(vec4(color.gb, k.xy), vec4(color.bg, k.wz), color.g < color.b);
+    let q = select(vec4(p.xyw, color.r), vec4(color.r, p.yzx), p.x <= color.r);
+    let d = q.x - min(q.w, q.y);
+    let e = 1.0e-10;
+    return vec3(abs(q.z + (q.w - q.y) / (6.0 * d + e)), d / (q.x + e), q.x);
+}
+
+fn hsv_to_rgb(color: vec3<f32>) -> vec3<f32> {
+    let k = vec4(1.0, 2.0 / 3.0, 1.0 / 3.0, 3.0);
+    let p = abs(fract(color.xxx + k.xyz) * 6.0 - k.www);
+    return color.z * mix(k.xxx, clamp(p - k.xxx, vec3(0.0), vec3(1.0)), color.y);
+}
@@ -0,0 +1,46 @@
+use bevy::{
+    ecs::query::{QueryItem, ReadOnlyQueryData, ReleaseStateQueryData},
+    prelude::*,
+    render::render_phase::TrackedRenderPass,
+};
+
+/// Per-view data fetched by the effect node and handed to the effect right before it draws.
+///
+/// Enabled with [`PostProcessPlugin::with_view_data`](crate::PostProcessPlugin::with_view_data).
+/// This gives access to the render world components of the view inside the pass, to set push
+/// constants, stencil references or additional bind groups, without reimplementing the node.
+///
+/// ```ignore
+/// struct ExposureData;
+///
+/// impl PostProcessViewData for ExposureData {
+///     type ViewQuery = &'static ExtractedExposure;
+///
+///     fn prepare_pass(exposure: &ExtractedExposure, _world: &World, pass: &mut TrackedRenderPass) {
+///         pass.set_push_constants(ShaderStages::FRAGMENT, 0, bytemuck::bytes_of(&exposure.ev100));
+///     }
+/// }
+/// ```
+pub trait PostProcessViewData: Send + Sync + 'static {
+    /// The components fetched from the view. Views without them are drawn without calling
+    /// [`PostProcessViewData::prepare_pass`].
+    type ViewQuery: ReadOnlyQueryData + ReleaseStateQueryData;
+
+    /// Called after the pipeline and the bind groups of the effect are set, before it draws.
+    fn prepare_pass(
+        item: QueryItem<'_, 'static, Self::ViewQuery>,
+        world: &World,
+        render_pass: &mut TrackedRenderPass,
+    );
+}
+
+/// Fetch the view data of `D` and hand it to the pass.
+pub(crate) fn prepare_pass<D: PostProcessViewData>(
+    world: &World,
+    view: Entity,
+    render_pass: &mut TrackedRenderPass,
+) {
+    if let Some(item) = world.entity(view).get_components::<D::ViewQuery>() {
+        D::prepare_pass(item, world, render_pass);
+    }
+}
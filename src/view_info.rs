@@ -0,0 +1,150 @@
+use bevy::{
+    camera::Exposure,
+    prelude::*,
+    render::{
+        render_resource::{
+            binding_types::uniform_buffer, BindGroupLayoutEntryBuilder, BindingResource,
+            DynamicUniformBuffer, ShaderType,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        camera::ExtractedCamera,
+        view::{ExtractedView, ViewTarget},
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+// The size is the one of the texture sampled by the effect, which covers the whole target, while
+// the aspect ratio is the one of the viewport of the camera
+#[derive(Clone, Copy, Default, ShaderType)]
+struct ViewInfoUniform {
+    resolution: Vec2,
+    texel_size: Vec2,
+    viewport_offset: Vec2,
+    viewport_size: Vec2,
+    aspect_ratio: f32,
+    exposure: f32,
+    ev100: f32,
+}
+
+#[derive(Resource, Default)]
+struct ViewInfoUniforms {
+    uniforms: DynamicUniformBuffer<ViewInfoUniform>,
+}
+
+#[derive(Component)]
+struct ViewInfoUniformOffset {
+    offset: u32,
+    // The info of the output texture, for the effects running after the upscaling
+    output_offset: u32,
+}
+
+impl ViewInfoUniform {
+    fn new(resolution: UVec2, viewport_offset: UVec2, viewport_size: UVec2, exposure: f32) -> Self {
+        let resolution = resolution.as_vec2().max(Vec2::ONE);
+        let viewport_size = viewport_size.as_vec2().max(Vec2::ONE);
+        Self {
+            resolution,
+            texel_size: resolution.recip(),
+            viewport_offset: viewport_offset.as_vec2(),
+            viewport_size,
+            aspect_ratio: viewport_size.x / viewport_size.y,
+            exposure,
+            // The exposure is derived from the EV100 with `2^-ev100 / 1.2`
+            ev100: -(exposure * 1.2).log2(),
+        }
+    }
+}
+
+/// Write the size and the exposure of every view. Shared by the effects binding it.
+pub(crate) fn add_systems(app: &mut App) {
+    let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+        return;
+    };
+    if render_app.world().contains_resource::<ViewInfoUniforms>() {
+        return;
+    }
+    render_app.init_resource::<ViewInfoUniforms>().add_systems(
+        Render,
+        prepare_view_info_uniforms.in_set(RenderSystems::PrepareResources),
+    );
+}
+
+// The views the info is written for, with their camera
+type InfoViews = (
+    Entity,
+    &'static ViewTarget,
+    &'static ExtractedView,
+    Option<&'static ExtractedCamera>,
+);
+
+fn prepare_view_info_uniforms(
+    mut commands: Commands,
+    mut view_info_uniforms: ResMut<ViewInfoUniforms>,
+    views: Query<InfoViews>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let uniforms = &mut view_info_uniforms.uniforms;
+    uniforms.clear();
+    for (entity, view_target, view, camera) in &views {
+        let size = view_target.main_texture().size();
+        let exposure = camera.map_or_else(
+            || Exposure::default().exposure(),
+            |camera| camera.exposure,
+        );
+        // Split screen and viewport cameras only cover a part of the texture
+        let offset = uniforms.push(&ViewInfoUniform::new(
+            UVec2::new(size.width, size.height),
+            view.viewport.xy(),
+            view.viewport.zw(),
+            exposure,
+        ));
+
+        // The effects running after the upscaling render at the size of the output
+        let output = camera.and_then(|camera| {
+            let size = camera.physical_target_size?;
+            let (viewport_offset, viewport_size) = camera
+                .viewport
+                .as_ref()
+                .map_or((UVec2::ZERO, size), |viewport| {
+                    (viewport.physical_position, viewport.physical_size)
+                });
+            Some(ViewInfoUniform::new(
+                size,
+                viewport_offset,
+                viewport_size,
+                exposure,
+            ))
+        });
+        let output_offset = output.map_or(offset, |output| uniforms.push(&output));
+
+        commands.entity(entity).insert(ViewInfoUniformOffset {
+            offset,
+            output_offset,
+        });
+    }
+    uniforms.write_buffer(&render_device, &render_queue);
+}
+
+/// The layout entry of the view info uniform.
+pub(crate) fn layout_entry() -> BindGroupLayoutEntryBuilder {
+    uniform_buffer::<ViewInfoUniform>(true)
+}
+
+/// The binding of the view info uniforms and the dynamic offset of the info of the view.
+///
+/// Effects writing the output of the view get the info of the output texture, which differs from
+/// the main texture when the scene renders at a lower resolution.
+pub(crate) fn binding(
+    world: &World,
+    view: Entity,
+    writes_output: bool,
+) -> Option<(BindingResource<'_>, u32)> {
+    let binding = world.get_resource::<ViewInfoUniforms>()?.uniforms.binding()?;
+    let offsets = world.get::<ViewInfoUniformOffset>(view)?;
+    let offset = match writes_output {
+        true => offsets.output_offset,
+        false => offsets.offset,
+    };
+    Some((binding, offset))
+}
@@ -0,0 +1,22 @@
+//! Golden image tests of the built-in effects, rendered over the gradient of the harness.
+//!
+//! The harness needs a GPU adapter, software adapters like lavapipe or WARP work in CI.
+
+#![cfg(all(feature = "testing", feature = "effects"))]
+
+use bevy::prelude::*;
+use bevy_post_process_util::{
+    effects::{PosterizePlugin, PosterizeSettings},
+    testing::{compare_images, reference_image, PostProcessTestHarness},
+};
+
+#[test]
+fn posterize_matches_reference() {
+    // 6 levels per channel, so the gradient snaps to multiples of 51
+    let output = PostProcessTestHarness::new(UVec2::new(64, 64))
+        .add_plugins(PosterizePlugin::default())
+        .run(PosterizeSettings::default())
+        .unwrap();
+    let reference = reference_image(include_bytes!("references/posterize.png"));
+    compare_images(&output, &reference, 2).unwrap();
+}